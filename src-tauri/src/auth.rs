@@ -1,35 +1,29 @@
 use keyring::Entry;
 
-const SERVICE_NAME: &str = "com.techbruwh.nobraindev";
+pub(crate) const SERVICE_NAME: &str = "com.techbruwh.nobraindev";
 
 /// Store a user token securely in the OS keychain
 #[tauri::command]
 pub async fn store_user_token(key: String, token: String) -> Result<(), String> {
-    let entry = Entry::new(SERVICE_NAME, &key)
-        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
-    entry
-        .set_password(&token)
-        .map_err(|e| format!("Failed to store token: {}", e))?;
-    
-    Ok(())
+    set_token(&key, &token)
 }
 
 /// Get a user token from the OS keychain
 #[tauri::command]
 pub async fn get_user_token(key: String) -> Result<String, String> {
-    let entry = Entry::new(SERVICE_NAME, &key)
-        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
-    entry
-        .get_password()
-        .map_err(|e| format!("Failed to retrieve token: {}", e))
+    get_token(&key)
 }
 
 /// Clear all user tokens from the OS keychain
 #[tauri::command]
 pub async fn clear_user_tokens() -> Result<(), String> {
-    let keys = vec!["access_token", "refresh_token", "user_email"];
+    let keys = vec![
+        "access_token",
+        "refresh_token",
+        "user_email",
+        crate::sync_crypto::BUNDLE_TOKEN,
+        crate::sync_crypto::KEYS_TOKEN,
+    ];
     
     for key in keys {
         if let Ok(entry) = Entry::new(SERVICE_NAME, key) {
@@ -37,10 +31,29 @@ pub async fn clear_user_tokens() -> Result<(), String> {
             let _ = entry.delete_credential();
         }
     }
+    crate::sync_crypto::forget_keys();
     
     Ok(())
 }
 
+pub(crate) fn set_token(key: &str, token: &str) -> Result<(), String> {
+    let entry = Entry::new(SERVICE_NAME, key)
+        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
+    
+    entry
+        .set_password(token)
+        .map_err(|e| format!("Failed to store token: {}", e))
+}
+
+pub(crate) fn get_token(key: &str) -> Result<String, String> {
+    let entry = Entry::new(SERVICE_NAME, key)
+        .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
+    
+    entry
+        .get_password()
+        .map_err(|e| format!("Failed to retrieve token: {}", e))
+}
+
 /// Check if user is authenticated (has stored tokens)
 #[tauri::command]
 pub async fn is_authenticated() -> Result<bool, String> {
@@ -0,0 +1,478 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::database::Database;
+use crate::encryption;
+use crate::file_storage::FileStorageManager;
+use crate::models::{BackupInfo, BackupKind, BackupSettings};
+use crate::AppState;
+
+// Each backup is a directory named after the time it was taken:
+//
+//   backups/2024-01-02_030405/
+//     nobraindev.db     online backup of the database
+//     files/...         copy of the stored files directory
+//     manifest.json     written last, with a checksum of everything else
+//
+// A backup is written to `<name>.partial` and renamed when complete, so a
+// directory without the suffix always holds a whole backup.
+
+/// Identifies a NoBrainDev backup manifest
+const BACKUP_FORMAT: &str = "nobraindev-backup";
+
+const MANIFEST_PATH: &str = "manifest.json";
+const DATABASE_PATH: &str = "nobraindev.db";
+const FILES_DIR: &str = "files";
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// How often the scheduler checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    kind: BackupKind,
+    created_at: String,
+    app_version: String,
+    /// SHA-256 (hex) of every other file, keyed by its path in the backup
+    checksums: BTreeMap<String, String>,
+    #[serde(default)]
+    missing_files: Vec<String>,
+    /// The database is encrypted with the library key
+    #[serde(default)]
+    encrypted: bool,
+}
+
+/// Where backups are kept
+pub fn backups_dir(settings: &BackupSettings) -> Result<PathBuf> {
+    let dir = match &settings.directory {
+        Some(directory) => PathBuf::from(directory),
+        None => dirs::data_local_dir()
+            .context("Failed to get app data directory")?
+            .join("nobraindev")
+            .join("backups"),
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    Ok(dir)
+}
+
+fn lock(db: &Mutex<Database>) -> Result<std::sync::MutexGuard<'_, Database>> {
+    db.lock().map_err(|e| anyhow!(e.to_string()))
+}
+
+/// The directory of backup `id`, refusing anything that isn't a plain name
+fn backup_path(root: &Path, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) || id.ends_with(PARTIAL_SUFFIX) {
+        anyhow::bail!("Invalid backup '{}'", id);
+    }
+    let path = root.join(id);
+    if !path.join(MANIFEST_PATH).is_file() {
+        anyhow::bail!("Backup '{}' not found", id);
+    }
+    Ok(path)
+}
+
+/// Back up the database and stored files. The database is only locked while
+/// SQLite copies it.
+pub fn create_backup(db: &Mutex<Database>, kind: BackupKind) -> Result<BackupInfo> {
+    let settings = {
+        let db = lock(db)?;
+        if db.is_locked() {
+            anyhow::bail!("The library is locked");
+        }
+        db.get_backup_settings()?
+    };
+    let root = backups_dir(&settings)?;
+
+    let created_at = chrono::Utc::now();
+    let base = created_at.format("%Y-%m-%d_%H%M%S").to_string();
+    let mut id = base.clone();
+    let mut attempt = 2;
+    while root.join(&id).exists() {
+        id = format!("{}-{}", base, attempt);
+        attempt += 1;
+    }
+
+    let partial = root.join(format!("{}{}", id, PARTIAL_SUFFIX));
+    let _ = fs::remove_dir_all(&partial);
+
+    let result = write_backup(db, &partial, kind, &created_at.to_rfc3339()).and_then(|_| {
+        fs::rename(&partial, root.join(&id)).context("Failed to move the backup into place")?;
+        read_info(&root.join(&id))
+    });
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&partial);
+    }
+
+    let info = result?;
+    println!(
+        "💾 Backed up the library to {} ({} files, {} bytes)",
+        root.join(&info.id).display(), info.files, info.size_bytes
+    );
+    Ok(info)
+}
+
+fn write_backup(db: &Mutex<Database>, dir: &Path, kind: BackupKind, created_at: &str) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let database = dir.join(DATABASE_PATH);
+    let encrypted = {
+        let db = lock(db)?;
+        db.backup_to(&database)?;
+        db.is_encrypted()
+    };
+
+    // Copied after the database: files are stored before the rows that point
+    // at them, so at worst the copy has a file the database doesn't know yet
+    let storage = FileStorageManager::new()?;
+    copy_dir(storage.get_storage_dir(), &dir.join(FILES_DIR))?;
+
+    // Make sure the copy is usable before recording it
+    let missing_files = check_database(&database, encrypted)?
+        .into_iter()
+        .filter(|storage_path| {
+            Path::new(storage_path)
+                .strip_prefix(storage.get_storage_dir())
+                .map_or(true, |relative| !dir.join(FILES_DIR).join(relative).is_file())
+        })
+        .collect();
+
+    let mut checksums = BTreeMap::new();
+    for path in walk(dir)? {
+        checksums.insert(relative_name(dir, &path), sha256_file(&path)?);
+    }
+
+    let manifest = Manifest {
+        format: BACKUP_FORMAT.to_string(),
+        kind,
+        created_at: created_at.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        checksums,
+        missing_files,
+        encrypted,
+    };
+    fs::write(dir.join(MANIFEST_PATH), serde_json::to_vec_pretty(&manifest)?)
+        .context("Failed to write the backup manifest")?;
+    Ok(())
+}
+
+/// Run SQLite's integrity check on a backed-up database and make sure it is
+/// a NoBrainDev library. Returns the storage paths of its files.
+fn check_database(path: &Path, encrypted: bool) -> Result<Vec<String>> {
+    let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    if encrypted {
+        encryption::apply_key(&conn)?;
+    }
+
+    let problems: Vec<String> = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()
+        .context("The backed-up database is unreadable")?;
+    if problems.first().map(String::as_str) != Some("ok") {
+        anyhow::bail!("The backed-up database is damaged: {}", problems.join("; "));
+    }
+
+    let tables: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('snippets', 'folders', 'files')",
+        [],
+        |row| row.get(0),
+    )?;
+    if tables != 3 {
+        anyhow::bail!("Not a NoBrainDev database");
+    }
+
+    let storage_paths = conn
+        .prepare("SELECT storage_path FROM files")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(storage_paths)
+}
+
+/// Check every file of a backup against its manifest, then the database itself
+fn verify_dir(dir: &Path) -> Result<BackupInfo> {
+    let info = read_info(dir)?;
+    let manifest = read_manifest(dir)?;
+
+    if !manifest.checksums.contains_key(DATABASE_PATH) {
+        anyhow::bail!("The backup has no database");
+    }
+    for (name, expected) in &manifest.checksums {
+        let path = name.split('/').fold(dir.to_path_buf(), |path, part| path.join(part));
+        if !path.is_file() {
+            anyhow::bail!("{} is missing from the backup", name);
+        }
+        if !sha256_file(&path)?.eq_ignore_ascii_case(expected) {
+            anyhow::bail!("{} is corrupted (checksum mismatch)", name);
+        }
+    }
+
+    check_database(&dir.join(DATABASE_PATH), manifest.encrypted)?;
+    Ok(info)
+}
+
+pub fn verify_backup(db: &Mutex<Database>, id: &str) -> Result<BackupInfo> {
+    let root = backups_dir(&lock(db)?.get_backup_settings()?)?;
+    let info = verify_dir(&backup_path(&root, id)?)?;
+    println!("💾 Backup {} verified", id);
+    Ok(info)
+}
+
+/// Replace the library with a backup, after verifying it. The current library
+/// is backed up first.
+pub fn restore_backup(db: &Mutex<Database>, id: &str) -> Result<BackupInfo> {
+    let root = backups_dir(&lock(db)?.get_backup_settings()?)?;
+    let dir = backup_path(&root, id)?;
+    let info = verify_dir(&dir)?;
+
+    // SQLCipher can't copy pages between a plaintext and an encrypted database
+    match (read_manifest(&dir)?.encrypted, lock(db)?.is_encrypted()) {
+        (false, true) => anyhow::bail!("This backup was taken before the library was encrypted and can't be restored into it"),
+        (true, false) => anyhow::bail!("This backup is encrypted; enable encryption to restore it"),
+        _ => {}
+    }
+
+    let safety = create_backup(db, BackupKind::PreRestore).context("Failed to back up the current library first")?;
+
+    // Files first, so that a failure leaves the database as it was. Files the
+    // current library has are kept; the backup's may be referred to.
+    let storage = FileStorageManager::new()?;
+    copy_dir(&dir.join(FILES_DIR), storage.get_storage_dir())?;
+    lock(db)?.restore_from(&dir.join(DATABASE_PATH))?;
+
+    println!("💾 Restored backup {} (the previous library was saved as {})", id, safety.id);
+    Ok(info)
+}
+
+pub fn delete_backup(db: &Mutex<Database>, id: &str) -> Result<()> {
+    let root = backups_dir(&lock(db)?.get_backup_settings()?)?;
+    let dir = backup_path(&root, id)?;
+    fs::remove_dir_all(&dir).with_context(|| format!("Failed to delete {}", dir.display()))
+}
+
+/// Encrypt the backups taken before the library was, so they don't keep a
+/// plaintext copy of it. Each is encrypted in place with its manifest written
+/// last, so running it again finishes an interrupted one. Returns how many
+/// were encrypted.
+pub fn encrypt_backups(root: &Path) -> Result<usize> {
+    let plaintext: Vec<BackupInfo> = list_dir(root)?.into_iter().filter(|b| !b.encrypted).collect();
+    for backup in &plaintext {
+        encrypt_backup(&root.join(&backup.id)).with_context(|| format!("Failed to encrypt backup {}", backup.id))?;
+    }
+    if !plaintext.is_empty() {
+        println!("🔐 Encrypted {} backups", plaintext.len());
+    }
+    Ok(plaintext.len())
+}
+
+fn encrypt_backup(dir: &Path) -> Result<()> {
+    let database = dir.join(DATABASE_PATH);
+    if encryption::is_plaintext_database(&database) {
+        let temp = dir.join(format!("{}.encrypting", DATABASE_PATH));
+        {
+            let conn = rusqlite::Connection::open(&database)
+                .with_context(|| format!("Failed to open {}", database.display()))?;
+            encryption::export_encrypted(&conn, &temp)?;
+        }
+        fs::rename(&temp, &database).context("Failed to replace the backed-up database")?;
+    }
+    if dir.join(FILES_DIR).is_dir() {
+        encryption::encrypt_stored_files(&dir.join(FILES_DIR))?;
+    }
+    check_database(&database, true)?;
+
+    let mut manifest = read_manifest(dir)?;
+    manifest.checksums.clear();
+    for path in walk(dir)? {
+        let name = relative_name(dir, &path);
+        if name != MANIFEST_PATH {
+            manifest.checksums.insert(name, sha256_file(&path)?);
+        }
+    }
+    manifest.encrypted = true;
+
+    let temp = dir.join(format!("{}.tmp", MANIFEST_PATH));
+    fs::write(&temp, serde_json::to_vec_pretty(&manifest)?)?;
+    fs::rename(&temp, dir.join(MANIFEST_PATH)).context("Failed to write the backup manifest")
+}
+
+/// How many backups are still plaintext
+pub fn plaintext_backups(root: &Path) -> Result<usize> {
+    Ok(list_dir(root)?.into_iter().filter(|b| !b.encrypted).count())
+}
+
+/// Complete backups, newest first
+pub fn list_backups(db: &Mutex<Database>) -> Result<Vec<BackupInfo>> {
+    let root = backups_dir(&lock(db)?.get_backup_settings()?)?;
+    list_dir(&root)
+}
+
+fn list_dir(root: &Path) -> Result<Vec<BackupInfo>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(root).with_context(|| format!("Failed to read {}", root.display()))? {
+        let path = entry?.path();
+        let is_partial = path.to_string_lossy().ends_with(PARTIAL_SUFFIX);
+        if !path.is_dir() || is_partial || !path.join(MANIFEST_PATH).is_file() {
+            continue;
+        }
+        match read_info(&path) {
+            Ok(info) => backups.push(info),
+            Err(e) => eprintln!("Skipping backup {}: {:#}", path.display(), e),
+        }
+    }
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` scheduled backups, and any left
+/// half-written. Manual and pre-restore backups stay until deleted.
+pub fn rotate(db: &Mutex<Database>) -> Result<usize> {
+    let settings = lock(db)?.get_backup_settings()?;
+    let root = backups_dir(&settings)?;
+
+    for entry in fs::read_dir(&root)? {
+        let path = entry?.path();
+        if path.is_dir() && path.to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+
+    let mut deleted = 0;
+    let scheduled = list_dir(&root)?.into_iter().filter(|b| b.kind == BackupKind::Scheduled);
+    for backup in scheduled.skip(settings.keep.max(1) as usize) {
+        match fs::remove_dir_all(root.join(&backup.id)) {
+            Ok(()) => deleted += 1,
+            Err(e) => eprintln!("Failed to delete old backup {}: {}", backup.id, e),
+        }
+    }
+    if deleted > 0 {
+        println!("💾 Deleted {} old backups", deleted);
+    }
+    Ok(deleted)
+}
+
+/// Take a backup if one is due, then rotate
+pub fn run_scheduled(app_handle: &AppHandle) -> Result<Option<BackupInfo>> {
+    let state = app_handle.state::<AppState>();
+    let settings = {
+        let db = lock(&state.db)?;
+        if db.is_locked() {
+            return Ok(None);
+        }
+        db.get_backup_settings()?
+    };
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let latest = list_dir(&backups_dir(&settings)?)?
+        .first()
+        .and_then(|b| chrono::DateTime::parse_from_rfc3339(&b.created_at).ok());
+    let interval = chrono::Duration::hours(settings.interval_hours.max(1) as i64);
+    if latest.is_some_and(|latest| chrono::Utc::now() - latest.with_timezone(&chrono::Utc) < interval) {
+        return Ok(None);
+    }
+
+    let info = create_backup(&state.db, BackupKind::Scheduled)?;
+    rotate(&state.db)?;
+    let _ = app_handle.emit("backup-created", &info);
+    Ok(Some(info))
+}
+
+/// Spawn the background thread that takes scheduled backups
+pub fn spawn_backup_scheduler(app_handle: AppHandle) {
+    thread::spawn(move || loop {
+        if let Err(e) = run_scheduled(&app_handle) {
+            eprintln!("Scheduled backup failed: {:#}", e);
+        }
+        thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let manifest: Manifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_PATH))?)
+        .context("The backup manifest is invalid")?;
+    if manifest.format != BACKUP_FORMAT {
+        anyhow::bail!("Not a NoBrainDev backup");
+    }
+    Ok(manifest)
+}
+
+fn read_info(dir: &Path) -> Result<BackupInfo> {
+    let manifest = read_manifest(dir)?;
+    let mut size_bytes = 0;
+    for path in walk(dir)? {
+        size_bytes += fs::metadata(&path)?.len();
+    }
+
+    Ok(BackupInfo {
+        id: dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        kind: manifest.kind,
+        created_at: manifest.created_at,
+        app_version: manifest.app_version,
+        size_bytes,
+        files: manifest.checksums.keys().filter(|name| name.starts_with(&format!("{}/", FILES_DIR))).count(),
+        missing_files: manifest.missing_files,
+        encrypted: manifest.encrypted,
+    })
+}
+
+/// Every file below `dir`, in a stable order. Symlinks are skipped.
+fn walk(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current).with_context(|| format!("Failed to read {}", current.display()))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("Failed to create {}", to.display()))?;
+    if !from.is_dir() {
+        return Ok(());
+    }
+    for path in walk(from)? {
+        let target = to.join(path.strip_prefix(from)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&path, &target).with_context(|| format!("Failed to copy {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// `path` relative to `dir`, with `/` separators
+fn relative_name(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
@@ -0,0 +1,583 @@
+use regex::{Regex, RegexBuilder};
+use std::sync::LazyLock;
+
+// Single-value clipboard categories; anything else falls through to code/general
+static UUID_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap()
+});
+
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:(?:https?|ftp|wss?|file)://\S+|www\.[\w-]+(?:\.[\w-]+)+\S*)$").unwrap()
+});
+
+static EMAIL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:mailto:)?[\w.%+-]+@[\w-]+(?:\.[\w-]+)*\.[a-z]{2,}$").unwrap()
+});
+
+static COLOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^(?:
+            \#(?:[0-9a-f]{3}|[0-9a-f]{4}|[0-9a-f]{6}|[0-9a-f]{8})
+          | rgba?\(\s*\d{1,3}%?\s*,\s*\d{1,3}%?\s*,\s*\d{1,3}%?\s*(?:,\s*(?:0|1|0?\.\d+|\d{1,3}%)\s*)?\)
+          | rgba?\(\s*\d{1,3}%?\s+\d{1,3}%?\s+\d{1,3}%?\s*(?:/\s*(?:0|1|0?\.\d+|\d{1,3}%)\s*)?\)
+          | hsla?\(\s*\d{1,3}(?:deg)?\s*,?\s*\d{1,3}%\s*,?\s*\d{1,3}%\s*(?:[,/]\s*(?:0|1|0?\.\d+|\d{1,3}%)\s*)?\)
+        )$",
+    )
+    .unwrap()
+});
+
+static PATH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:~|\.{1,2})?/(?:[^\s/]+/?)*$|^~$|^[A-Za-z]:\\(?:[^\\/:*?<>|\r\n]+\\?)*$|^\\\\[^\\\s]+\\[^\r\n]*$").unwrap()
+});
+
+static SQL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?isx)^\s*(?:--[^\n]*\n\s*)*(?:
+            SELECT\s.+\sFROM\s
+          | INSERT\s+INTO\s
+          | UPDATE\s+[\w.`"\[\]]+\s+SET\s
+          | DELETE\s+FROM\s
+          | CREATE\s+(?:OR\s+REPLACE\s+)?(?:UNIQUE\s+)?(?:TEMP(?:ORARY)?\s+)?(?:TABLE|INDEX|VIEW|TRIGGER|SCHEMA|DATABASE|FUNCTION|PROCEDURE)\s
+          | ALTER\s+TABLE\s
+          | DROP\s+(?:TABLE|INDEX|VIEW|SCHEMA|DATABASE)\s
+          | TRUNCATE\s+(?:TABLE\s+)?\w
+          | WITH\s+(?:RECURSIVE\s+)?\w+\s+AS\s*\(
+          | MERGE\s+INTO\s
+        )"#,
+    )
+    .unwrap()
+});
+
+/// Commands that almost always start a shell line when they are the first word
+const SHELL_COMMANDS: &[&str] = &[
+    "apt", "apt-get", "aws", "awk", "brew", "bun", "cargo", "cat", "cd", "chmod", "chown", "cp",
+    "curl", "deno", "docker", "docker-compose", "echo", "export", "find", "gcloud", "gh", "git",
+    "go", "grep", "gzip", "helm", "kill", "kubectl", "ln", "ls", "make", "mkdir", "mv", "npm",
+    "npx", "pip", "pip3", "pnpm", "ps", "psql", "rm", "rsync", "scp", "sed", "source", "ssh",
+    "sudo", "systemctl", "tail", "tar", "terraform", "touch", "unzip", "wget", "xargs", "yarn",
+];
+
+static SHELL_PROMPT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:\$|#|%|>)\s+\S").unwrap());
+
+static SHELL_SYNTAX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\s\|\s\w|\s&&\s|\s\|\|\s|\s-{1,2}[\w-]+|\$\{?\w+\}?|\s>{1,2}\s?\S|\\$)").unwrap()
+});
+
+static STACK_FRAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?x)^\s*(?:
+            at\s+[\w$.<>\[\]/]+\s*\(.*:\d+(?::\d+)?\)      # Java/JS: at foo.bar(File.java:12)
+          | at\s+\S+:\d+:\d+                                # JS: at file.js:1:2
+          | at\s+[\w$.<>]+\((?:Native\ Method|Unknown\ Source)\)
+          | File\s+"[^"]+",\s+line\s+\d+                    # Python
+          | \d+:\s+0x[0-9a-f]+\s+-\s+                       # Rust backtrace
+          | \d+:\s+[\w:<>]+$                                # Rust backtrace symbol
+          | [\w./-]+\.go:\d+(?:\s+\+0x[0-9a-f]+)?$          # Go
+          | \#\d+\s+(?:0x[0-9a-f]+\s+in\s+)?\S+             # gdb / Ruby
+          | from\s+\S+:\d+:in\s                             # Ruby
+          | \.\.\.\s+\d+\s+more$                            # Java: ... 12 more
+        )"#,
+    )
+    .unwrap()
+});
+
+static STACK_HEADER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^(?:Traceback \(most recent call last\):|Exception in thread |Caused by: |thread '.+' panicked at |panic: |goroutine \d+ \[|Uncaught \w*Error|\w+(?:\.\w+)*(?:Error|Exception)(?:: |$))",
+    )
+    .unwrap()
+});
+
+static YAML_KEY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:-\s+)?[\w.\x22'/-]+:(?:\s+\S.*|\s*[|>][-+]?)?$").unwrap());
+
+static YAML_ITEM_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*-\s+\S").unwrap());
+
+/// Per-language signatures: (pattern, weight). Strong, unambiguous markers weigh more.
+/// Order matters for ties, so a language whose patterns are a superset of another's
+/// (TypeScript over JavaScript) comes after it.
+const LANGUAGE_SIGNATURES: &[(&str, &[(&str, u32)])] = &[
+    ("rust", &[
+        (r"\bfn\s+\w+\s*[<(]", 2),
+        (r"\blet\s+mut\b", 3),
+        (r"\bimpl(?:<[^>]*>)?\s+\w+", 2),
+        (r"\bpub(?:\(crate\))?\s+(?:fn|struct|enum|trait|mod|use)\b", 3),
+        (r"\b(?:println|vec|format|eprintln|macro_rules)!", 3),
+        (r"\buse\s+(?:std|crate|super|self)::", 3),
+        (r"->\s*(?:Result|Option|Self|&|Vec|String|impl)\b", 2),
+        (r"#\[derive\(", 3),
+        (r"&(?:mut\s+)?self\b", 2),
+        (r"\bmatch\s+\w+.*\{", 1),
+    ]),
+    ("python", &[
+        (r"(?m)^\s*def\s+\w+\s*\(.*\)\s*(?:->\s*[\w\[\], .]+)?:\s*$", 3),
+        (r"(?m)^\s*from\s+[\w.]+\s+import\s+", 3),
+        (r"(?m)^\s*import\s+[\w.]+(?:\s+as\s+\w+)?\s*$", 1),
+        (r"(?m)^\s*class\s+\w+(?:\(.*\))?:\s*$", 3),
+        (r"(?m)^\s*elif\s.*:\s*$", 3),
+        (r"\bself\.\w+", 1),
+        (r#"(?m)^if __name__ == ['"]__main__['"]:"#, 3),
+        (r"\bprint\(", 1),
+        (r"(?m)^\s*(?:for|while|if|with|try|except.*)\b.*:\s*$", 1),
+        (r"\b(?:None|True|False)\b", 1),
+    ]),
+    ("go", &[
+        (r"(?m)^package\s+\w+\s*$", 3),
+        (r"\bfunc\s+(?:\(\w+\s+\*?\w+\)\s*)?\w+\s*\(", 3),
+        (r"\w+\s*:=\s*", 2),
+        (r"\bfmt\.\w+\(", 3),
+        (r"\bgo\s+func\b", 3),
+        (r"\berr\s*!=\s*nil\b", 3),
+        (r"\bchan\s+\w+", 2),
+    ]),
+    ("java", &[
+        (r"\bpublic\s+(?:static\s+)?(?:final\s+)?(?:class|interface|enum)\s+\w+", 3),
+        (r"\bpublic\s+static\s+void\s+main\s*\(\s*String", 3),
+        (r"\bSystem\.(?:out|err)\.print", 3),
+        (r"(?m)^\s*import\s+(?:java|javax|org|com)\.[\w.]+(?:\.\*)?;\s*$", 3),
+        (r"@Override\b", 2),
+        (r"\b(?:private|protected|public)\s+(?:static\s+)?(?:final\s+)?[\w<>\[\]]+\s+\w+\s*[;=(]", 1),
+        (r"\bnew\s+\w+(?:<.*>)?\(", 1),
+    ]),
+    ("kotlin", &[
+        (r"\bfun\s+(?:<[^>]+>\s*)?[\w.]+\s*\(", 3),
+        (r"\bval\s+\w+(?:\s*:\s*[\w<>?]+)?\s*=", 2),
+        (r"\bvar\s+\w+\s*:\s*[\w<>?]+", 1),
+        (r"\bdata\s+class\b", 3),
+        (r"\bcompanion\s+object\b", 3),
+        (r"(?m)^\s*import\s+kotlinx?\.", 3),
+    ]),
+    ("swift", &[
+        (r"(?m)^\s*import\s+(?:UIKit|SwiftUI|Foundation|Combine)\s*$", 3),
+        (r"\bguard\s+let\b", 3),
+        (r"\bif\s+let\s+\w+\s*=", 2),
+        (r"\bfunc\s+\w+\s*\(.*\)\s*(?:->\s*[\w<>?\[\]]+\s*)?\{", 2),
+        (r"\bvar\s+\w+\s*:\s*[\w<>?\[\]]+\s*(?:=|\{)", 1),
+        (r"\blet\s+\w+\s*:\s*[\w<>?\[\]]+\s*=", 1),
+        (r"@(?:State|Published|IBOutlet|objc)\b", 3),
+    ]),
+    ("cpp", &[
+        (r"(?m)^\s*#include\s*[<\x22]", 3),
+        (r"\bstd::\w+", 3),
+        (r"\b(?:cout|cerr)\s*<<", 3),
+        (r"\btemplate\s*<", 3),
+        (r"\bint\s+main\s*\(", 2),
+        (r"\b(?:nullptr|constexpr|namespace\s+\w+\s*\{)", 2),
+        (r"(?m)^\s*#(?:define|ifndef|ifdef|pragma)\b", 2),
+    ]),
+    ("csharp", &[
+        (r"(?m)^\s*using\s+System(?:\.[\w.]+)?;\s*$", 3),
+        (r"(?m)^\s*namespace\s+[\w.]+\s*[;{]?\s*$", 2),
+        (r"\bConsole\.Write(?:Line)?\(", 3),
+        (r"\{\s*get;\s*(?:private\s+)?set;\s*\}", 3),
+        (r"\bvar\s+\w+\s*=\s*new\s+\w+", 2),
+        (r"\basync\s+Task(?:<.*>)?\s+\w+\(", 3),
+        (r"\bpublic\s+(?:static\s+)?(?:async\s+)?\w+(?:<.*>)?\s+\w+\s*\(", 1),
+    ]),
+    ("php", &[
+        (r"<\?php", 5),
+        (r"\$\w+\s*=", 2),
+        (r"\$this->\w+", 3),
+        (r"\bfunction\s+\w+\s*\(\s*(?:\??\w+\s+)?\$", 3),
+        (r"\becho\s+[\x22'$]", 1),
+        (r"(?m)^\s*namespace\s+[\w\\]+;\s*$", 2),
+    ]),
+    ("ruby", &[
+        (r"(?m)^\s*def\s+[\w?!]+(?:\(.*\))?\s*$", 2),
+        (r"(?m)^\s*end\s*$", 2),
+        (r"\bputs\s", 2),
+        (r"\battr_(?:accessor|reader|writer)\b", 3),
+        (r"(?m)^\s*require(?:_relative)?\s+['\x22]", 2),
+        (r"\.each(?:_with_index)?\s+do\s*\|", 3),
+        (r"(?m)^\s*module\s+[A-Z]\w*\s*$", 2),
+        (r"\bdo\s*\|\w+(?:,\s*\w+)*\|", 2),
+    ]),
+    ("html", &[
+        (r"(?i)<!DOCTYPE\s+html", 5),
+        (r"(?i)<(?:html|head|body|div|span|section|header|footer|nav|ul|ol|li|table|tr|td|form|input|button|script|style|link|meta|p|a|img|h[1-6])\b[^>]*>", 2),
+        (r"(?i)</(?:html|head|body|div|span|section|ul|ol|li|table|form|p|a|h[1-6])>", 2),
+    ]),
+    ("css", &[
+        (r"(?m)^\s*(?:[.#]?[\w-]+|\*)(?:[\s,>+~]+[.#:]?[\w-]+|::?[\w-]+(?:\(.*?\))?)*\s*\{\s*$", 2),
+        (r"(?m)^\s*[a-z-]+\s*:\s*[^;{}]+;\s*$", 1),
+        (r"(?m)^\s*@(?:media|import|keyframes|font-face|tailwind|apply)\b", 3),
+        (r"(?m)^\s*--[\w-]+\s*:", 2),
+    ]),
+    ("javascript", &[
+        (r"\b(?:const|let|var)\s+\w+\s*=", 2),
+        (r"=>\s*[{(\w]", 2),
+        (r"\bfunction\s*\w*\s*\(", 2),
+        (r"\bconsole\.(?:log|error|warn)\(", 3),
+        (r"\brequire\(['\x22]", 3),
+        (r"(?m)^\s*export\s+(?:default|const|function|class|async)\b", 3),
+        (r#"(?m)^\s*import\s+.+\s+from\s+['"]"#, 3),
+        (r"\b(?:document|window)\.\w+", 2),
+        (r"\bawait\s+\w+", 1),
+    ]),
+    ("typescript", &[
+        (r"\b(?:const|let|var)\s+\w+\s*=", 2),
+        (r"=>\s*[{(\w]", 2),
+        (r"\bfunction\s*\w*\s*\(", 2),
+        (r"\bconsole\.(?:log|error|warn)\(", 3),
+        (r"\brequire\(['\x22]", 3),
+        (r"(?m)^\s*export\s+(?:default|const|function|class|async)\b", 3),
+        (r#"(?m)^\s*import\s+.+\s+from\s+['"]"#, 3),
+        (r"\b(?:document|window)\.\w+", 2),
+        (r"\bawait\s+\w+", 1),
+        (r":\s*(?:string|number|boolean|any|void|unknown|never)(?:\[\])?\b", 3),
+        (r"\b(?:interface|type)\s+\w+(?:<.*>)?\s*(?:=|\{|extends)", 3),
+        (r"\b(?:private|public|readonly)\s+\w+\s*:", 2),
+        (r"\bas\s+(?:const|string|number|any|unknown)\b", 2),
+    ]),
+    ("markdown", &[
+        (r"(?m)^#{1,6}\s+\S", 2),
+        (r"(?m)^\s*[-*+]\s+\S", 1),
+        (r"\[[^\]]+\]\([^)]+\)", 2),
+        (r"(?m)^```", 2),
+        (r"\*\*[^*]+\*\*", 1),
+        (r"(?m)^>\s+\S", 1),
+    ]),
+];
+
+/// Minimum score a language needs before we are willing to call text "code"
+const MIN_LANGUAGE_SCORE: u32 = 3;
+
+type CompiledSignature = (&'static str, Vec<(Regex, u32)>);
+
+static LANGUAGE_PATTERNS: LazyLock<Vec<CompiledSignature>> = LazyLock::new(|| {
+    LANGUAGE_SIGNATURES
+        .iter()
+        .map(|(language, patterns)| {
+            let compiled = patterns
+                .iter()
+                .map(|(pattern, weight)| {
+                    let regex = RegexBuilder::new(pattern)
+                        .build()
+                        .expect("invalid language signature");
+                    (regex, *weight)
+                })
+                .collect();
+            (*language, compiled)
+        })
+        .collect()
+});
+
+/// Classify clipboard content into a category.
+///
+/// Returns one of `url`, `email`, `color`, `uuid`, `path`, `json`, `yaml`, `sql`,
+/// `shell`, `stacktrace`, a programming language name (e.g. `rust`, `python`) for
+/// other code, or `general` for plain text.
+pub fn classify_content(content: &str) -> String {
+    let text = content.trim();
+
+    if text.is_empty() {
+        return "general".to_string();
+    }
+
+    let single_line = !text.contains('\n');
+
+    if single_line {
+        if UUID_RE.is_match(text) {
+            return "uuid".to_string();
+        }
+        if URL_RE.is_match(text) {
+            return "url".to_string();
+        }
+        if EMAIL_RE.is_match(text) {
+            return "email".to_string();
+        }
+        if COLOR_RE.is_match(text) {
+            return "color".to_string();
+        }
+    }
+
+    if is_json(text) {
+        return "json".to_string();
+    }
+
+    if is_stack_trace(text) {
+        return "stacktrace".to_string();
+    }
+
+    if single_line && PATH_RE.is_match(text) {
+        return "path".to_string();
+    }
+
+    if is_sql(text) {
+        return "sql".to_string();
+    }
+
+    if is_shell(text) {
+        return "shell".to_string();
+    }
+
+    if is_yaml(text) {
+        return "yaml".to_string();
+    }
+
+    match score_languages(text) {
+        Some(language) if language != "markdown" => language.to_string(),
+        _ => "general".to_string(),
+    }
+}
+
+/// Detect the programming language of a code snippet, using the same language
+/// names as the editor (`javascript`, `bash`, `plaintext`, ...).
+pub fn detect_language(content: &str) -> String {
+    let text = content.trim();
+
+    if text.is_empty() {
+        return "plaintext".to_string();
+    }
+
+    if is_json(text) {
+        return "json".to_string();
+    }
+    if is_sql(text) {
+        return "sql".to_string();
+    }
+    if is_shell(text) {
+        return "bash".to_string();
+    }
+    if is_yaml(text) {
+        return "yaml".to_string();
+    }
+
+    score_languages(text)
+        .map(|language| language.to_string())
+        .unwrap_or_else(|| "plaintext".to_string())
+}
+
+fn is_json(text: &str) -> bool {
+    let starts_like_json = (text.starts_with('{') && text.ends_with('}'))
+        || (text.starts_with('[') && text.ends_with(']'));
+
+    starts_like_json && serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+fn is_sql(text: &str) -> bool {
+    SQL_RE.is_match(text)
+}
+
+fn is_stack_trace(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+
+    let frames = lines.iter().filter(|l| STACK_FRAME_RE.is_match(l)).count();
+    let has_header = STACK_HEADER_RE.is_match(text);
+
+    frames >= 3 || (has_header && frames >= 1)
+}
+
+fn is_shell(text: &str) -> bool {
+    if text.starts_with("#!") {
+        let first_line = text.lines().next().unwrap_or_default();
+        return ["sh", "bash", "zsh", "fish"]
+            .iter()
+            .any(|shell| first_line.ends_with(&format!("/{}", shell)) || first_line.ends_with(&format!(" {}", shell)));
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect();
+
+    if lines.is_empty() || lines.len() > 30 {
+        return false;
+    }
+
+    let command_lines = lines
+        .iter()
+        .filter(|line| {
+            if SHELL_PROMPT_RE.is_match(line) && !line.starts_with("# ") {
+                return true;
+            }
+            let first_word = line.split_whitespace().next().unwrap_or_default();
+            SHELL_COMMANDS.contains(&first_word)
+                && (line.split_whitespace().count() > 1 || SHELL_SYNTAX_RE.is_match(line))
+        })
+        .count();
+
+    // Allow continuation lines and the occasional variable assignment in longer scripts
+    command_lines * 2 > lines.len()
+}
+
+fn is_yaml(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|l| {
+            let trimmed = l.trim();
+            !trimmed.is_empty() && !trimmed.starts_with('#') && trimmed != "---"
+        })
+        .collect();
+
+    if lines.len() < 2 {
+        return false;
+    }
+
+    // Code with braces or semicolons at line ends is not YAML
+    if lines.iter().any(|l| {
+        let trimmed = l.trim_end();
+        trimmed.ends_with(';') || trimmed.ends_with('{') || trimmed.ends_with('}')
+    }) {
+        return false;
+    }
+
+    let key_lines = lines.iter().filter(|l| YAML_KEY_RE.is_match(l)).count();
+    let structured = lines
+        .iter()
+        .filter(|l| YAML_KEY_RE.is_match(l) || YAML_ITEM_RE.is_match(l) || l.starts_with("  "))
+        .count();
+
+    key_lines >= 2 && structured == lines.len()
+}
+
+fn score_languages(text: &str) -> Option<&'static str> {
+    let mut best: Option<(&'static str, u32)> = None;
+
+    for (language, patterns) in LANGUAGE_PATTERNS.iter() {
+        let score: u32 = patterns
+            .iter()
+            .filter(|(regex, _)| regex.is_match(text))
+            .map(|(_, weight)| weight)
+            .sum();
+
+        if score >= MIN_LANGUAGE_SCORE && best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((language, score));
+        }
+    }
+
+    best.map(|(language, _)| language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_category(expected: &str, samples: &[&str]) {
+        for sample in samples {
+            assert_eq!(classify_content(sample), expected, "{:?}", sample);
+        }
+    }
+
+    #[test]
+    fn classifies_urls() {
+        assert_category("url", &[
+            "https://example.com/path?query=1#frag",
+            "http://localhost:3000",
+            "www.example.co.uk/page",
+            "wss://socket.example.com/live",
+        ]);
+    }
+
+    #[test]
+    fn classifies_emails() {
+        assert_category("email", &["jane.doe+work@example.com", "mailto:support@mail.example.org"]);
+    }
+
+    #[test]
+    fn classifies_colors() {
+        assert_category("color", &["#fff", "#1e90ffcc", "rgb(30, 144, 255)", "rgba(0 0 0 / 50%)", "hsl(210deg, 100%, 56%)"]);
+    }
+
+    #[test]
+    fn classifies_uuids() {
+        assert_category("uuid", &["123e4567-e89b-12d3-a456-426614174000", "123E4567-E89B-12D3-A456-426614174000"]);
+    }
+
+    #[test]
+    fn classifies_paths() {
+        assert_category("path", &["/usr/local/bin", "~/Documents/notes.txt", "./src/main.rs", r"C:\Users\jane\Desktop"]);
+    }
+
+    #[test]
+    fn classifies_json() {
+        assert_category("json", &[r#"{"name": "nobraindev", "tags": ["a", "b"]}"#, "[1, 2, 3]"]);
+    }
+
+    #[test]
+    fn classifies_yaml() {
+        assert_category("yaml", &["name: build\non:\n  push:\n    branches: [main]\njobs:\n  test:\n    runs-on: ubuntu-latest"]);
+    }
+
+    #[test]
+    fn classifies_sql() {
+        assert_category("sql", &[
+            "SELECT id, title FROM snippets WHERE deleted_at IS NULL",
+            "-- cleanup\nDELETE FROM clipboard_history WHERE pinned = 0",
+            "create table notes (id integer primary key)",
+        ]);
+    }
+
+    #[test]
+    fn classifies_shell() {
+        assert_category("shell", &[
+            "git commit -am \"fix\" && git push",
+            "$ npm install --save-dev vite",
+            "#!/bin/bash\necho hello",
+        ]);
+    }
+
+    #[test]
+    fn classifies_stack_traces() {
+        assert_category("stacktrace", &[
+            "Traceback (most recent call last):\n  File \"app.py\", line 3, in <module>\n    main()\nValueError: bad",
+            "java.lang.NullPointerException\n\tat com.example.App.run(App.java:12)\n\tat com.example.App.main(App.java:5)",
+        ]);
+    }
+
+    #[test]
+    fn classifies_code_by_language() {
+        assert_eq!(classify_content("fn main() {\n    let mut total = 0;\n    println!(\"{}\", total);\n}"), "rust");
+        assert_eq!(classify_content("def greet(name):\n    return f'hi {name}'\n\nclass User:\n    pass"), "python");
+        assert_eq!(classify_content("package main\n\nimport \"fmt\"\n\nfunc main() {\n\tfmt.Println(\"hi\")\n}"), "go");
+    }
+
+    #[test]
+    fn classifies_everything_else_as_general() {
+        assert_category("general", &["", "   ", "Remember to buy milk", "Meeting moved to 3pm, see you there."]);
+    }
+
+    #[test]
+    fn does_not_mistake_prose_for_urls_or_emails() {
+        assert_category("general", &[
+            "Visit https://example.com for details",
+            "example.com",
+            "Email me at jane@example.com tomorrow",
+            "jane@localhost",
+        ]);
+    }
+
+    #[test]
+    fn does_not_mistake_near_json_for_json() {
+        assert_ne!(classify_content("{name: 'not json'}"), "json");
+        assert_ne!(classify_content("[this is a note]"), "json");
+        assert_ne!(classify_content("{\"unterminated\": true"), "json");
+    }
+
+    #[test]
+    fn does_not_mistake_prose_for_code() {
+        assert_category("general", &[
+            "Let me know if the match tomorrow is still on.",
+            "I will import the photos from my camera and select the best ones.",
+            "The function of this committee is to review proposals.",
+        ]);
+    }
+
+    #[test]
+    fn does_not_mistake_multiline_text_for_single_value_categories() {
+        assert_ne!(classify_content("https://example.com\nhttps://example.org"), "url");
+        assert_ne!(classify_content("jane@example.com\njohn@example.com"), "email");
+    }
+
+    #[test]
+    fn detects_editor_languages() {
+        assert_eq!(detect_language("ls -la | grep rs"), "bash");
+        assert_eq!(detect_language(r#"{"a": 1}"#), "json");
+        assert_eq!(detect_language("just some words"), "plaintext");
+    }
+}
@@ -1,19 +1,112 @@
 use tauri::State;
 
-use crate::models::{Folder, ModelInfo, SearchResult, Snippet, File};
+use crate::models::{BackupInfo, BackupKind, BackupSettings, ClipboardRetentionSettings, DiffLineKind, EncryptionStatus, ExpansionSettings, ExportReport, Folder, FolderKind, HotkeyAction, HotkeyBinding, ImportConflict, ImportMode, ImportReport, LibraryItemKind, ModelInfo, PasteQueueStatus, PruneReport, RevisionRetention, SearchResult, SecretVaultStatus, SmartFolderQuery, Snippet, SnippetDiff, SnippetRender, SnippetRevision, SyncEncryptionStatus, SyncKeySetup, SyncReport, SyncServer, SyncStatus, Tag, TrashContents, TrashItemKind, TrashSettings, VaultSettings, VaultSyncReport, File};
 use crate::search::{get_models_dir, SearchEngine};
+use crate::secrets::{self, SECRET_MASK};
 use crate::search::download_model as download_model_internal;
 use crate::file_storage::FileStorageManager;
+use crate::backup;
+use crate::classifier;
+use crate::database::Database;
+use crate::diff;
+use crate::encryption;
+use crate::expansion;
+use crate::importers::{self, ImportTarget, ImporterInfo};
+use crate::jetbrains_templates;
+use crate::launcher;
+use crate::markdown_vault;
+use crate::library_archive;
+use crate::smart_folders;
+use crate::sync;
+use crate::sync_crypto;
+use crate::templates;
+use crate::trash;
+use crate::vscode_snippets;
+use crate::transforms::{self, TransformInfo, TransformOutput, TransformPipeline, TransformResult};
 use crate::AppState;
+use std::collections::HashMap;
 use std::sync::Mutex;
 
 // Global state to store the previous application
 static PREVIOUS_APP: Mutex<Option<String>> = Mutex::new(None);
 
+/// Fill in the snippet language from its content when the caller left it to us
+fn resolve_snippet_language(snippet: &mut Snippet) {
+    let language = snippet.language.trim();
+    if language.is_empty() || language.eq_ignore_ascii_case("auto") {
+        snippet.language = classifier::detect_language(&snippet.content);
+    }
+}
+
+/// Trim the snippet's trigger and make sure no other snippet (than `id`) uses it
+fn validate_trigger(db: &Database, snippet: &mut Snippet, id: Option<i64>) -> Result<(), String> {
+    snippet.trigger = snippet.trigger.take().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let Some(trigger) = snippet.trigger.as_deref() else {
+        return Ok(());
+    };
+
+    if trigger.chars().any(char::is_whitespace) {
+        return Err("Triggers cannot contain spaces".to_string());
+    }
+    let length = trigger.chars().count();
+    if !(2..=expansion::MAX_TRIGGER_LENGTH).contains(&length) {
+        return Err(format!("Triggers must be 2 to {} characters long", expansion::MAX_TRIGGER_LENGTH));
+    }
+
+    match db.find_snippet_by_trigger(trigger).map_err(|e| e.to_string())? {
+        Some(other) if Some(other) != id => Err(format!("Trigger '{}' is already used by another snippet", trigger)),
+        _ => Ok(()),
+    }
+}
+
+/// Turn the content of a snippet being saved into what's stored. Secret
+/// snippets are encrypted, which needs the vault unlocked. Content left
+/// masked keeps the stored secret as it is; a secret only becomes a normal
+/// snippet when saved with its revealed content.
+fn seal_secret(state: &AppState, db: &Database, snippet: &mut Snippet, id: Option<i64>) -> Result<(), String> {
+    let stored = match id {
+        Some(id) => db.get_secret_content(id).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    if let Some(stored) = stored.filter(|_| snippet.content == SECRET_MASK) {
+        snippet.content = stored;
+        snippet.secret = true;
+    } else if snippet.secret {
+        let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+        snippet.content = session.encrypt(&snippet.content).map_err(|e| format!("{:#}", e))?;
+    }
+
+    if snippet.secret && snippet.trigger.is_some() {
+        return Err("Secret snippets can't have a trigger".to_string());
+    }
+    Ok(())
+}
+
+/// Make the text expansion listener pick up changed triggers
+pub(crate) fn invalidate_triggers(state: &AppState) {
+    if let Ok(mut expansion) = state.expansion.lock() {
+        expansion.invalidate();
+    }
+}
+
 #[tauri::command]
 pub fn create_snippet(state: State<AppState>, snippet: Snippet) -> Result<i64, String> {
+    create_snippet_internal(&state, snippet)
+}
+
+/// Store a snippet and its embedding (when the model is loaded)
+pub(crate) fn create_snippet_internal(state: &AppState, mut snippet: Snippet) -> Result<i64, String> {
+    resolve_snippet_language(&mut snippet);
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    validate_trigger(&db, &mut snippet, None)?;
+    seal_secret(state, &db, &mut snippet, None)?;
     let snippet_id = db.create_snippet(&snippet).map_err(|e| e.to_string())?;
+    invalidate_triggers(state);
+    if snippet.secret {
+        return Ok(snippet_id);
+    }
 
     // Generate and store embedding if model is loaded
     let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
@@ -49,8 +142,21 @@ pub fn get_snippet(state: State<AppState>, id: i64) -> Result<Option<Snippet>, S
 
 #[tauri::command]
 pub fn update_snippet(state: State<AppState>, id: i64, snippet: Snippet) -> Result<(), String> {
+    update_snippet_internal(&state, id, snippet)
+}
+
+/// Update a snippet (archiving the replaced version) and refresh its embedding
+fn update_snippet_internal(state: &AppState, id: i64, mut snippet: Snippet) -> Result<(), String> {
+    resolve_snippet_language(&mut snippet);
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    validate_trigger(&db, &mut snippet, Some(id))?;
+    seal_secret(state, &db, &mut snippet, Some(id))?;
     db.update_snippet(id, &snippet).map_err(|e| e.to_string())?;
+    invalidate_triggers(state);
+    if snippet.secret {
+        return Ok(());
+    }
 
     // Regenerate embedding if model is loaded
     let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
@@ -72,39 +178,164 @@ pub fn update_snippet(state: State<AppState>, id: i64, snippet: Snippet) -> Resu
     Ok(())
 }
 
+/// Move a snippet to the trash
 #[tauri::command]
 pub fn delete_snippet(state: State<AppState>, id: i64) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_snippet(id).map_err(|e| e.to_string())
+    db.trash_snippet(id).map_err(|e| e.to_string())?;
+    invalidate_triggers(&state);
+    Ok(())
+}
+
+/// Previous versions of a snippet, newest first
+#[tauri::command]
+pub fn get_snippet_revisions(state: State<AppState>, snippet_id: i64) -> Result<Vec<SnippetRevision>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_snippet_revisions(snippet_id).map_err(|e| e.to_string())
+}
+
+/// Line-level diff between two versions of a snippet. Leave a revision ID out
+/// to compare against the current version.
+#[tauri::command]
+pub fn diff_snippet_revisions(
+    state: State<AppState>,
+    snippet_id: i64,
+    from_revision: Option<i64>,
+    to_revision: Option<i64>,
+) -> Result<SnippetDiff, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    // (title, content) of a revision, or of the current snippet
+    let load = |revision_id: Option<i64>| -> Result<(String, String), String> {
+        match revision_id {
+            Some(revision_id) => {
+                let revision = db.get_snippet_revision(revision_id)
+                    .map_err(|e| e.to_string())?
+                    .filter(|r| r.snippet_id == snippet_id)
+                    .ok_or_else(|| format!("Revision {} not found for snippet {}", revision_id, snippet_id))?;
+                Ok((revision.title, revision.content))
+            }
+            None => {
+                let snippet = db.get_snippet(snippet_id)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("Snippet {} not found", snippet_id))?;
+                Ok((snippet.title, snippet.content))
+            }
+        }
+    };
+
+    let (from_title, from_content) = load(from_revision)?;
+    let (to_title, to_content) = load(to_revision)?;
+
+    let lines = diff::diff_lines(&from_content, &to_content);
+    let added = lines.iter().filter(|l| l.kind == DiffLineKind::Added).count();
+    let removed = lines.iter().filter(|l| l.kind == DiffLineKind::Removed).count();
+
+    Ok(SnippetDiff {
+        snippet_id,
+        from_revision,
+        to_revision,
+        title_changed: from_title != to_title,
+        lines,
+        added,
+        removed,
+    })
+}
+
+/// Bring back an old version. The version being replaced goes into history,
+/// so a restore can itself be undone.
+#[tauri::command]
+pub fn restore_snippet_revision(state: State<AppState>, revision_id: i64) -> Result<Snippet, String> {
+    let (revision, current) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let revision = db.get_snippet_revision(revision_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Revision {} not found", revision_id))?;
+        let current = db.get_snippet(revision.snippet_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Snippet {} not found", revision.snippet_id))?;
+        (revision, current)
+    };
+
+    let restored = Snippet {
+        title: revision.title,
+        content: revision.content,
+        language: revision.language,
+        description: revision.description,
+        tags: revision.tags,
+        ..current
+    };
+    update_snippet_internal(&state, revision.snippet_id, restored)?;
+
+    println!("⏪ Restored snippet {} to revision {}", revision.snippet_id, revision_id);
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_snippet(revision.snippet_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Snippet {} not found", revision.snippet_id))
+}
+
+#[tauri::command]
+pub fn get_revision_retention(state: State<AppState>) -> Result<RevisionRetention, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_revision_retention().map_err(|e| e.to_string())
 }
 
+/// Save how much history to keep and apply it to every snippet right away.
+/// Returns the number of revisions removed.
 #[tauri::command]
-pub fn search_snippets(state: State<AppState>, query: String) -> Result<Vec<Snippet>, String> {
+pub fn set_revision_retention(state: State<AppState>, retention: RevisionRetention) -> Result<usize, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.search_snippets(&query).map_err(|e| e.to_string())
+    db.set_revision_retention(&retention).map_err(|e| e.to_string())?;
+    db.prune_revisions(None, &retention).map_err(|e| e.to_string())
 }
 
+/// Keyword search. Snippets must have every tag in `tags` (exact match),
+/// which can also be given as `tag:name` words in the query.
+#[tauri::command]
+pub fn search_snippets(state: State<AppState>, query: String, tags: Option<Vec<String>>) -> Result<Vec<Snippet>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.search_snippets(&query, &tags.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Semantic search with the same exact tag filtering as `search_snippets`
 #[tauri::command]
 pub fn semantic_search(
     state: State<AppState>,
     query: String,
+    tags: Option<Vec<String>>,
 ) -> Result<Vec<SearchResult>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
 
-    let snippets = db.get_all_snippets().map_err(|e| e.to_string())?;
+    let (text, mut tag_filter) = crate::tags::split_tag_filters(&query);
+    tag_filter.extend(tags.unwrap_or_default());
+
+    let mut snippets = db.get_all_snippets().map_err(|e| e.to_string())?;
+    if !tag_filter.is_empty() {
+        let tagged = db.get_snippet_ids_with_tags(&tag_filter).map_err(|e| e.to_string())?;
+        snippets.retain(|s| s.id.is_some_and(|id| tagged.contains(&id)));
+
+        // Only tags were given, so every tagged snippet matches
+        if text.is_empty() {
+            return Ok(snippets
+                .into_iter()
+                .map(|snippet| SearchResult { snippet, score: 1.0, highlight: None })
+                .collect());
+        }
+    }
 
     if let Some(engine) = search_engine.as_ref() {
         // Get all stored embeddings
         let embeddings = db.get_all_embeddings().map_err(|e| e.to_string())?;
         
         engine
-            .semantic_search(&query, &snippets, &embeddings)
+            .semantic_search(&text, &snippets, &embeddings)
             .map_err(|e| e.to_string())
     } else {
         // Fallback to regular search if no model is loaded
         let results = db
-            .search_snippets(&query)
+            .search_snippets(&text, &tag_filter)
             .map_err(|e| e.to_string())?
             .into_iter()
             .map(|snippet| SearchResult {
@@ -182,7 +413,8 @@ pub async fn load_model(state: State<'_, AppState>) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let snippets = db.get_all_snippets().map_err(|e| e.to_string())?;
 
-    for snippet in snippets {
+    // Secret snippets are never embedded
+    for snippet in snippets.into_iter().filter(|s| !s.secret) {
         if let Some(id) = snippet.id {
             // Check if embedding already exists
             if db.get_embedding(id).map_err(|e| e.to_string())?.is_none() {
@@ -214,7 +446,7 @@ pub async fn regenerate_embeddings(state: State<'_, AppState>) -> Result<String,
     let snippets = db.get_all_snippets().map_err(|e| e.to_string())?;
 
     let mut count = 0;
-    for snippet in snippets {
+    for snippet in snippets.into_iter().filter(|s| !s.secret) {
         if let Some(id) = snippet.id {
             let text = SearchEngine::generate_snippet_text(&snippet);
             if let Ok(embedding) = engine.generate_embedding(&text) {
@@ -229,22 +461,75 @@ pub async fn regenerate_embeddings(state: State<'_, AppState>) -> Result<String,
 
 // Folder management commands
 
+/// Create a folder at the top level, or inside `parent_id`
+#[tauri::command]
+pub fn create_folder(
+    state: State<AppState>,
+    name: String,
+    icon: Option<String>,
+    parent_id: Option<i64>,
+) -> Result<i64, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_folder(&name, icon.as_deref(), parent_id).map_err(|e| e.to_string())
+}
+
+/// Create a folder whose contents are the snippets matching `query`
 #[tauri::command]
-pub fn create_folder(state: State<AppState>, name: String, icon: Option<String>) -> Result<i64, String> {
+pub fn create_smart_folder(
+    state: State<AppState>,
+    name: String,
+    icon: Option<String>,
+    parent_id: Option<i64>,
+    query: SmartFolderQuery,
+) -> Result<i64, String> {
+    let query = smart_folders::normalize(query)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.create_smart_folder(&name, icon.as_deref(), parent_id, &query).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_smart_folder(state: State<AppState>, id: i64, query: SmartFolderQuery) -> Result<(), String> {
+    let query = smart_folders::normalize(query)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.update_smart_folder(id, &query).map_err(|e| e.to_string())
+}
+
+/// Evaluate a smart folder query without saving it
+#[tauri::command]
+pub fn preview_smart_folder(state: State<AppState>, query: SmartFolderQuery) -> Result<Vec<SearchResult>, String> {
+    let query = smart_folders::normalize(query)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
+    smart_folders::evaluate(&db, search_engine.as_ref(), &query).map_err(|e| e.to_string())
+}
+
+/// All folders as a flat list (build the tree from `parent_id`). With
+/// `include_descendants`, `snippet_count` rolls up subfolders. Smart folders
+/// (`kind: "smart"`) have no count here, see `get_smart_folder_counts`.
+#[tauri::command]
+pub fn get_all_folders(state: State<AppState>, include_descendants: Option<bool>) -> Result<Vec<Folder>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.create_folder(&name, icon.as_deref()).map_err(|e| e.to_string())
+    db.get_all_folders(include_descendants.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_all_folders(state: State<AppState>) -> Result<Vec<Folder>, String> {
+pub fn get_folder(state: State<AppState>, id: i64, include_descendants: Option<bool>) -> Result<Option<Folder>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_all_folders().map_err(|e| e.to_string())
+    db.get_folder(id, include_descendants.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
+/// Live snippet counts of smart folders by folder ID, fetched after the folder
+/// list so that listing never waits on their searches. Smart folders that
+/// search by meaning are left out.
 #[tauri::command]
-pub fn get_folder(state: State<AppState>, id: i64) -> Result<Option<Folder>, String> {
+pub fn get_smart_folder_counts(state: State<AppState>) -> Result<HashMap<i64, i64>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_folder(id).map_err(|e| e.to_string())
+    let folders = db.get_all_folders(false).map_err(|e| e.to_string())?;
+
+    Ok(folders
+        .iter()
+        .filter_map(|folder| Some((folder.id?, smart_folders::count(&db, folder.query.as_ref()?)?)))
+        .collect())
 }
 
 #[tauri::command]
@@ -253,19 +538,60 @@ pub fn update_folder(state: State<AppState>, id: i64, name: String, icon: Option
     db.update_folder(id, &name, icon.as_deref()).map_err(|e| e.to_string())
 }
 
+/// Move a folder to the trash. Its snippets and files become uncategorized
+/// until the folder is restored.
 #[tauri::command]
 pub fn delete_folder(state: State<AppState>, id: i64) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_folder(id).map_err(|e| e.to_string())
+    db.trash_folder(id).map_err(|e| e.to_string())
 }
 
+/// Move a folder with its subfolders under `parent_id`, or to the top level
+#[tauri::command]
+pub fn move_folder(state: State<AppState>, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.move_folder(id, parent_id).map_err(|e| e.to_string())
+}
+
+/// Set the display order of the folders directly under `parent_id`
+#[tauri::command]
+pub fn reorder_folders(state: State<AppState>, parent_id: Option<i64>, folder_ids: Vec<i64>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.reorder_folders(parent_id, &folder_ids).map_err(|e| e.to_string())
+}
+
+/// Snippets in a folder, or matching its saved search for smart folders
 #[tauri::command]
 pub fn get_snippets_by_folder(
     state: State<AppState>,
     folder_id: Option<i64>,
+    include_descendants: Option<bool>,
 ) -> Result<Vec<Snippet>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_snippets_by_folder(folder_id).map_err(|e| e.to_string())
+    folder_snippets(&state, &db, folder_id, include_descendants.unwrap_or(false))
+}
+
+fn folder_snippets(
+    state: &AppState,
+    db: &Database,
+    folder_id: Option<i64>,
+    include_descendants: bool,
+) -> Result<Vec<Snippet>, String> {
+    if let Some(fid) = folder_id {
+        let folder = db.get_folder(fid, false).map_err(|e| e.to_string())?;
+        if let Some(error) = folder.as_ref().and_then(|f| f.query_error.clone()) {
+            return Err(error);
+        }
+        if let Some(query) = folder.and_then(|f| f.query) {
+            let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
+            let results = smart_folders::evaluate(db, search_engine.as_ref(), &query)
+                .map_err(|e| e.to_string())?;
+            return Ok(results.into_iter().map(|r| r.snippet).collect());
+        }
+    }
+
+    db.get_snippets_by_folder(folder_id, include_descendants)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -298,24 +624,36 @@ pub fn read_system_clipboard() -> Result<String, String> {
         .map_err(|e| format!("Failed to read clipboard: {}", e))
 }
 
+/// Save a clipboard entry. When no category is given it is classified from the content.
 #[tauri::command]
 pub fn save_clipboard_entry(
     state: State<AppState>,
     content: String,
     source: String,
-    category: String,
+    category: Option<String>,
     created_at: Option<String>,
 ) -> Result<i64, String> {
+    if state.secrets.lock().map_err(|e| e.to_string())?.was_pasted(&content) {
+        return Err("Secret snippets aren't saved to the clipboard history".to_string());
+    }
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let timestamp = created_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let category = category.unwrap_or_else(|| classifier::classify_content(&content));
     db.save_clipboard_entry(&content, &source, &category, &timestamp)
         .map_err(|e| e.to_string())
 }
 
+/// With `favorites_only`, just the entries marked as favorites
 #[tauri::command]
-pub fn get_clipboard_history(state: State<AppState>, limit: i64) -> Result<Vec<crate::models::ClipboardEntry>, String> {
+pub fn get_clipboard_history(
+    state: State<AppState>,
+    limit: i64,
+    favorites_only: Option<bool>,
+) -> Result<Vec<crate::models::ClipboardEntry>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_clipboard_history(limit).map_err(|e| e.to_string())
+    db.get_clipboard_history(limit, favorites_only.unwrap_or(false))
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -330,10 +668,108 @@ pub fn delete_clipboard_entry(state: State<AppState>, id: i64) -> Result<(), Str
     db.delete_clipboard_entry(id).map_err(|e| e.to_string())
 }
 
+/// Clear clipboard history. Pinned and favorite entries are kept unless `force` is true.
+#[tauri::command]
+pub fn clear_clipboard_history(state: State<AppState>, force: Option<bool>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.clear_clipboard_history(force.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn pin_clipboard_entry(state: State<AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.pin_clipboard_entry(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unpin_clipboard_entry(state: State<AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.unpin_clipboard_entry(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_clipboard_entry_favorite(state: State<AppState>, id: i64, favorite: bool) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_clipboard_entry_favorite(id, favorite).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reorder_pinned_clipboard_entries(state: State<AppState>, ids: Vec<i64>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.reorder_pinned_clipboard_entries(&ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn classify_content(content: String) -> String {
+    classifier::classify_content(&content)
+}
+
+#[tauri::command]
+pub fn detect_language(content: String) -> String {
+    classifier::detect_language(&content)
+}
+
+#[tauri::command]
+pub fn list_transforms() -> Vec<TransformInfo> {
+    transforms::list_transforms()
+}
+
+/// Run a clipboard entry through a transform pipeline, then preview, paste or save the result
+#[tauri::command]
+pub fn transform_clipboard_entry(
+    state: State<AppState>,
+    id: i64,
+    transform: TransformPipeline,
+    output: Option<TransformOutput>,
+) -> Result<TransformResult, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let entry = db.get_clipboard_entry(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Clipboard entry not found".to_string())?;
+
+    let content = transforms::apply_pipeline(&entry.content, &transform)
+        .map_err(|e| format!("{:#}", e))?;
+
+    match output.unwrap_or_default() {
+        TransformOutput::Preview => Ok(TransformResult { content, entry_id: None }),
+        TransformOutput::Paste | TransformOutput::PastePlain => {
+            // Release the database before the (slow) paste simulation
+            drop(db);
+            paste_text_transient(&content, output == Some(TransformOutput::PastePlain))?;
+            Ok(TransformResult { content, entry_id: None })
+        }
+        TransformOutput::Save => {
+            let category = classifier::classify_content(&content);
+            let now = chrono::Utc::now().to_rfc3339();
+            let entry_id = db.save_clipboard_entry(&content, "transform", &category, &now)
+                .map_err(|e| e.to_string())?;
+            Ok(TransformResult { content, entry_id: Some(entry_id) })
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_clipboard_retention(state: State<AppState>) -> Result<ClipboardRetentionSettings, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_clipboard_retention().map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-pub fn clear_clipboard_history(state: State<AppState>) -> Result<(), String> {
+pub fn set_clipboard_retention(
+    state: State<AppState>,
+    settings: ClipboardRetentionSettings,
+) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.clear_clipboard_history().map_err(|e| e.to_string())
+    db.set_clipboard_retention(&settings).map_err(|e| e.to_string())
+}
+
+/// Apply the retention policy immediately instead of waiting for the background task.
+/// Pinned entries are only pruned when `force` is true.
+#[tauri::command]
+pub fn prune_clipboard_history(app_handle: tauri::AppHandle, force: Option<bool>) -> Result<PruneReport, String> {
+    crate::retention::run_clipboard_pruning(&app_handle, force.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -350,13 +786,9 @@ pub fn update_clipboard_entry(
         .map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-pub fn show_clipboard_popup(app_handle: tauri::AppHandle) -> Result<(), String> {
-    use tauri::{Emitter, Manager};
-
-    println!("📋 Showing clipboard popup window");
-
-    // Capture the previous application BEFORE showing the popup
+/// Remember the frontmost app (window on Linux) so a later paste can return to it.
+/// Must run BEFORE a popup takes focus.
+fn capture_frontmost_app() {
     #[cfg(target_os = "macos")]
     {
         use std::process::Command;
@@ -401,113 +833,140 @@ pub fn show_clipboard_popup(app_handle: tauri::AppHandle) -> Result<(), String>
         }
     }
 
-    // Try to get both the main window and clipboard popup window
-    let main_window = app_handle.get_webview_window("main");
-    let clipboard_popup = app_handle.get_webview_window("clipboard-popup");
+    #[cfg(target_os = "linux")]
+    {
+        if let Err(e) = crate::linux_paste::capture_active_window() {
+            println!("⚠️  Failed to capture previous window: {}", e);
+        }
+    }
+}
 
-    if let (Some(main_window), Some(clipboard_popup)) = (main_window, clipboard_popup) {
-        // Get current mouse cursor position from the main window
-        // This works better across multiple monitors
-        let (cursor_x, cursor_y) = if let Ok(pos) = main_window.cursor_position() {
-            let cursor_x = pos.x as i32;
-            let cursor_y = pos.y as i32;
-            println!("🖱️ Cursor position from main window: x={}, y={}", cursor_x, cursor_y);
-            (cursor_x, cursor_y)
-        } else {
-            // Fallback: try to get from primary monitor
-            println!("⚠️ Could not get cursor position from main window, using fallback");
-            (100, 100) // Default fallback position
-        };
+/// Move `popup` next to the mouse cursor, keeping it on the cursor's monitor
+fn position_near_cursor(
+    main_window: &tauri::WebviewWindow,
+    popup: &tauri::WebviewWindow,
+    popup_width: i32,
+    popup_height: i32,
+) -> Result<(), String> {
+    // Get current mouse cursor position from the main window
+    // This works better across multiple monitors
+    let (cursor_x, cursor_y) = if let Ok(pos) = main_window.cursor_position() {
+        let cursor_x = pos.x as i32;
+        let cursor_y = pos.y as i32;
+        println!("🖱️ Cursor position from main window: x={}, y={}", cursor_x, cursor_y);
+        (cursor_x, cursor_y)
+    } else {
+        // Fallback: try to get from primary monitor
+        println!("⚠️ Could not get cursor position from main window, using fallback");
+        (100, 100) // Default fallback position
+    };
 
-        // Popup dimensions (must match tauri.conf.json)
-        let popup_width = 570;
-        let popup_height = 400;
-        let offset = 15;
+    let offset = 15;
 
-        // Find which monitor the cursor is on
-        let target_monitor = if let Ok(monitors) = clipboard_popup.available_monitors() {
-            monitors
-                .into_iter()
-                .find(|monitor| {
-                    let monitor_pos = monitor.position();
-                    let monitor_size = monitor.size();
-                    cursor_x >= monitor_pos.x as i32
-                        && cursor_x < (monitor_pos.x as i32 + monitor_size.width as i32)
-                        && cursor_y >= monitor_pos.y as i32
-                        && cursor_y < (monitor_pos.y as i32 + monitor_size.height as i32)
-                })
-                .or_else(|| {
-                    // Fallback to primary monitor if cursor is not found on any monitor
-                    clipboard_popup.primary_monitor().ok().flatten()
-                })
-        } else {
-            clipboard_popup.primary_monitor().ok().flatten()
-        };
+    // Find which monitor the cursor is on
+    let target_monitor = if let Ok(monitors) = popup.available_monitors() {
+        monitors
+            .into_iter()
+            .find(|monitor| {
+                let monitor_pos = monitor.position();
+                let monitor_size = monitor.size();
+                cursor_x >= monitor_pos.x as i32
+                    && cursor_x < (monitor_pos.x as i32 + monitor_size.width as i32)
+                    && cursor_y >= monitor_pos.y as i32
+                    && cursor_y < (monitor_pos.y as i32 + monitor_size.height as i32)
+            })
+            .or_else(|| {
+                // Fallback to primary monitor if cursor is not found on any monitor
+                popup.primary_monitor().ok().flatten()
+            })
+    } else {
+        popup.primary_monitor().ok().flatten()
+    };
 
-        let monitor = match target_monitor {
-            Some(m) => m,
-            None => return Err("Failed to find monitor for cursor position".to_string()),
-        };
+    let monitor = match target_monitor {
+        Some(m) => m,
+        None => return Err("Failed to find monitor for cursor position".to_string()),
+    };
 
-        let screen_pos = monitor.position();
-        let screen_size = monitor.size();
-
-        println!("📺 Monitor position: x={}, y={}, size={}x{}",
-            screen_pos.x, screen_pos.y, screen_size.width, screen_size.height);
-
-        // Calculate monitor boundaries
-        let monitor_left = screen_pos.x as i32;
-        let monitor_right = screen_pos.x as i32 + screen_size.width as i32;
-        let monitor_top = screen_pos.y as i32;
-        let monitor_bottom = screen_pos.y as i32 + screen_size.height as i32;
-
-        // Calculate popup position (below and right of cursor by default)
-        let mut x = cursor_x + offset;
-        let mut y = cursor_y + offset;
-
-        // Adjust horizontal position if popup would go off right edge
-        if x + popup_width > monitor_right {
-            // Try to position to the left of the cursor
-            x = cursor_x - popup_width - offset;
-            // If that would go off the left edge, clamp to left edge
-            if x < monitor_left {
-                x = monitor_left;
-            }
-        }
+    let screen_pos = monitor.position();
+    let screen_size = monitor.size();
+
+    println!("📺 Monitor position: x={}, y={}, size={}x{}",
+        screen_pos.x, screen_pos.y, screen_size.width, screen_size.height);
+
+    // Calculate monitor boundaries
+    let monitor_left = screen_pos.x as i32;
+    let monitor_right = screen_pos.x as i32 + screen_size.width as i32;
+    let monitor_top = screen_pos.y as i32;
+    let monitor_bottom = screen_pos.y as i32 + screen_size.height as i32;
+
+    // Calculate popup position (below and right of cursor by default)
+    let mut x = cursor_x + offset;
+    let mut y = cursor_y + offset;
 
-        // Adjust horizontal position if popup would go off left edge
+    // Adjust horizontal position if popup would go off right edge
+    if x + popup_width > monitor_right {
+        // Try to position to the left of the cursor
+        x = cursor_x - popup_width - offset;
+        // If that would go off the left edge, clamp to left edge
         if x < monitor_left {
             x = monitor_left;
         }
+    }
 
-        // Final horizontal check to ensure it doesn't overflow right edge
-        if x + popup_width > monitor_right {
-            x = monitor_right - popup_width;
-        }
+    // Adjust horizontal position if popup would go off left edge
+    if x < monitor_left {
+        x = monitor_left;
+    }
 
-        // Adjust vertical position if popup would go off bottom edge
-        if y + popup_height > monitor_bottom {
-            y = cursor_y - popup_height - offset;
-            // If that would go off the top edge, clamp to top edge
-            if y < monitor_top {
-                y = monitor_top;
-            }
-        }
+    // Final horizontal check to ensure it doesn't overflow right edge
+    if x + popup_width > monitor_right {
+        x = monitor_right - popup_width;
+    }
 
-        // Adjust vertical position if popup would go off top edge
+    // Adjust vertical position if popup would go off bottom edge
+    if y + popup_height > monitor_bottom {
+        y = cursor_y - popup_height - offset;
+        // If that would go off the top edge, clamp to top edge
         if y < monitor_top {
             y = monitor_top;
         }
+    }
 
-        // Final vertical check to ensure it doesn't overflow bottom edge
-        if y + popup_height > monitor_bottom {
-            y = monitor_bottom - popup_height;
-        }
+    // Adjust vertical position if popup would go off top edge
+    if y < monitor_top {
+        y = monitor_top;
+    }
+
+    // Final vertical check to ensure it doesn't overflow bottom edge
+    if y + popup_height > monitor_bottom {
+        y = monitor_bottom - popup_height;
+    }
+
+    println!("📍 Positioning popup at: x={}, y={}", x, y);
+
+    popup.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
+        .map_err(|e| format!("Failed to set popup position: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn show_clipboard_popup(app_handle: tauri::AppHandle) -> Result<(), String> {
+    use tauri::{Emitter, Manager};
 
-        println!("📍 Positioning popup at: x={}, y={}", x, y);
+    println!("📋 Showing clipboard popup window");
+
+    // Capture the previous application BEFORE showing the popup
+    capture_frontmost_app();
+
+    // Try to get both the main window and clipboard popup window
+    let main_window = app_handle.get_webview_window("main");
+    let clipboard_popup = app_handle.get_webview_window("clipboard-popup");
 
-        clipboard_popup.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
-            .map_err(|e| format!("Failed to set popup position: {}", e))?;
+    if let (Some(main_window), Some(clipboard_popup)) = (main_window, clipboard_popup) {
+        // Popup dimensions (must match tauri.conf.json)
+        position_near_cursor(&main_window, &clipboard_popup, 570, 400)?;
 
         // Show and focus the popup window
         clipboard_popup.show().map_err(|e| format!("Failed to show popup: {}", e))?;
@@ -540,73 +999,196 @@ pub fn hide_clipboard_popup(app_handle: tauri::AppHandle) -> Result<(), String>
     }
 }
 
+/// Show the quick-insert snippet launcher next to the cursor
 #[tauri::command]
-pub fn register_clipboard_hotkey(app_handle: tauri::AppHandle) -> Result<(), String> {
-    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+pub fn show_snippet_launcher(app_handle: tauri::AppHandle) -> Result<(), String> {
+    use tauri::{Emitter, Manager};
 
-    // List of fallback shortcuts to try, in order of preference
-    let shortcuts_to_try = vec![
-        "Cmd+Shift+C",  // Most unique and macOS-specific
-        "Cmd+Option+C",
-        "Cmd+Ctrl+C",
-        "F6",
-        "F7",
-    ];
+    println!("⚡ Showing snippet launcher window");
 
-    let shortcuts = app_handle.global_shortcut();
-    let mut registered_shortcut: Option<String> = None;
+    // Capture the previous application BEFORE showing the launcher
+    capture_frontmost_app();
 
-    for shortcut_str in shortcuts_to_try {
-        println!("🔑 Attempting to register global shortcut: {}", shortcut_str);
+    let main_window = app_handle.get_webview_window("main");
+    let launcher = app_handle.get_webview_window("snippet-launcher");
 
-        // Check if already registered to avoid duplicates
-        if shortcuts.is_registered(shortcut_str) {
-            println!("ℹ️  Shortcut '{}' is already registered, trying next...", shortcut_str);
-            continue;
-        }
+    if let (Some(main_window), Some(launcher)) = (main_window, launcher) {
+        // Launcher dimensions (must match tauri.conf.json)
+        position_near_cursor(&main_window, &launcher, 620, 420)?;
 
-        // Try to register the global shortcut with event handler in one call
-        let app_handle_clone = app_handle.clone();
-        let shortcut_str_clone = shortcut_str.to_string();
+        launcher.show().map_err(|e| format!("Failed to show launcher: {}", e))?;
+        launcher.set_focus().map_err(|e| format!("Failed to focus launcher: {}", e))?;
 
-        match shortcuts.on_shortcut(shortcut_str, move |_app, _shortcut, event| {
-            println!("⚡ Shortcut event received! State: {:?}", event.state);
-            if event.state == ShortcutState::Pressed {
-                println!("🎯 Global shortcut '{}' pressed!", shortcut_str_clone);
+        // Reset the search and reload snippets in the launcher
+        let _ = launcher.emit("snippet-launcher-triggered", ());
 
-                // Show the clipboard popup window directly
-                match show_clipboard_popup(app_handle_clone.clone()) {
-                    Ok(_) => println!("✅ Clipboard popup window opened"),
-                    Err(e) => println!("❌ Failed to show clipboard popup: {}", e),
-                }
-            }
-        }) {
-            Ok(_) => {
-                println!("✅ Successfully registered global shortcut with listener: {}", shortcut_str);
-                registered_shortcut = Some(shortcut_str.to_string());
-                break;
-            }
-            Err(e) => {
-                println!("⚠️  Failed to register shortcut '{}': {}", shortcut_str, e);
-                // Try the next shortcut in the list
-            }
-        }
+        println!("✅ Snippet launcher window shown");
+        Ok(())
+    } else {
+        Err("Could not find main window or snippet launcher window".to_string())
     }
+}
 
-    let _shortcut_str = registered_shortcut.ok_or_else(|| {
-        "Failed to register any global shortcut. Tried: Cmd+Shift+C, Cmd+Option+C, Cmd+Ctrl+C, F6, F7. \
-         You may need to grant accessibility permissions or close conflicting applications.".to_string()
-    })?;
-
-    println!("✅ Global clipboard hotkey registration complete");
+#[tauri::command]
+pub fn hide_snippet_launcher(app_handle: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
 
-    Ok(())
+    if let Some(launcher) = app_handle.get_webview_window("snippet-launcher") {
+        launcher.hide().map_err(|e| format!("Failed to hide launcher: {}", e))?;
+        Ok(())
+    } else {
+        Err("Snippet launcher window not found".to_string())
+    }
 }
 
-/// Capture the name of the current frontmost application (called before popup opens)
+/// Fuzzy keyword search blended with semantic search (when the model is loaded)
 #[tauri::command]
-pub fn capture_previous_app() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
+pub fn search_snippet_launcher(
+    state: State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let snippets = db.get_all_snippets().map_err(|e| e.to_string())?;
+
+    let semantic = if query.trim().is_empty() {
+        Vec::new()
+    } else {
+        let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
+        match search_engine.as_ref() {
+            Some(engine) => {
+                let embeddings = db.get_all_embeddings().map_err(|e| e.to_string())?;
+                engine
+                    .semantic_search(&query, &snippets, &embeddings)
+                    .unwrap_or_else(|e| {
+                        eprintln!("Semantic search failed: {}", e);
+                        Vec::new()
+                    })
+            }
+            None => Vec::new(),
+        }
+    };
+
+    Ok(launcher::rank(&query, &snippets, &semantic, limit.unwrap_or(20)))
+}
+
+/// Expand a snippet's template. Without `values`, snippets that have variables
+/// return them for prompting; with `values`, only variables that have no value
+/// and no default are returned.
+#[tauri::command]
+pub fn render_snippet(
+    state: State<AppState>,
+    id: i64,
+    values: Option<HashMap<String, String>>,
+) -> Result<SnippetRender, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let snippet = db.get_snippet(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Snippet {} not found", id))?;
+
+    templates::render(&snippet.content, values.as_ref()).map_err(|e| e.to_string())
+}
+
+/// Render a snippet's template variables (see `render_snippet`) and paste the
+/// result into the app that was focused before the launcher opened
+#[tauri::command]
+pub fn paste_snippet(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    id: i64,
+    values: Option<HashMap<String, String>>,
+    as_plain_text: Option<bool>,
+) -> Result<SnippetRender, String> {
+    use tauri::Manager;
+
+    let snippet = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_snippet(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Snippet {} not found", id))?
+    };
+
+    // Secrets are pasted as they are, without template rendering
+    if snippet.secret {
+        let content = reveal_secret(&state, id)?;
+        if let Some(launcher) = app_handle.get_webview_window("snippet-launcher") {
+            let _ = launcher.hide();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        paste_secret_transient(&state, &content)?;
+        println!("✅ Pasted secret snippet {} to cursor", id);
+        return Ok(SnippetRender::Rendered { content: SECRET_MASK.to_string() });
+    }
+
+    let rendered = templates::render(&snippet.content, values.as_ref()).map_err(|e| e.to_string())?;
+
+    if let SnippetRender::Rendered { content } = &rendered {
+        if let Some(launcher) = app_handle.get_webview_window("snippet-launcher") {
+            let _ = launcher.hide();
+        }
+        // Let focus leave the launcher before sending the paste
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        paste_text_transient(content, as_plain_text.unwrap_or(false))?;
+        println!("✅ Pasted snippet {} to cursor", id);
+    }
+
+    Ok(rendered)
+}
+
+/// Register every configured global hotkey and report which ones are active
+#[tauri::command]
+pub fn register_clipboard_hotkey(app_handle: tauri::AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    let bindings = crate::hotkeys::register_all(&app_handle)?;
+    println!("✅ Global hotkey registration complete");
+    Ok(bindings)
+}
+
+/// Configured shortcut and registration state of every global action
+#[tauri::command]
+pub fn get_hotkey_bindings(app_handle: tauri::AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    crate::hotkeys::bindings(&app_handle)
+}
+
+/// Rebind a global action at runtime. Pass `None` to unbind it.
+#[tauri::command]
+pub fn set_hotkey_binding(
+    app_handle: tauri::AppHandle,
+    action: HotkeyAction,
+    shortcut: Option<String>,
+) -> Result<HotkeyBinding, String> {
+    crate::hotkeys::bind(&app_handle, action, shortcut)
+}
+
+#[tauri::command]
+pub fn reset_hotkey_bindings(app_handle: tauri::AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    crate::hotkeys::reset(&app_handle)
+}
+
+#[tauri::command]
+pub fn get_expansion_settings(app_handle: tauri::AppHandle) -> Result<ExpansionSettings, String> {
+    expansion::settings(&app_handle)
+}
+
+/// Save the text expansion pause flag and per-app allow/deny lists
+#[tauri::command]
+pub fn set_expansion_settings(
+    app_handle: tauri::AppHandle,
+    settings: ExpansionSettings,
+) -> Result<ExpansionSettings, String> {
+    expansion::set_settings(&app_handle, settings)
+}
+
+#[tauri::command]
+pub fn set_expansion_paused(app_handle: tauri::AppHandle, paused: bool) -> Result<ExpansionSettings, String> {
+    expansion::set_paused(&app_handle, paused)
+}
+
+/// Capture the name of the current frontmost application (called before popup opens)
+#[tauri::command]
+pub fn capture_previous_app() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
     {
         use std::process::Command;
 
@@ -634,9 +1216,14 @@ pub fn capture_previous_app() -> Result<(), String> {
         }
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux_paste::capture_active_window()?;
+    }
+
+    #[cfg(target_os = "windows")]
     {
-        // For Windows/Linux, we'll use a simpler approach
+        // For Windows, we'll use a simpler approach
         // Just store None and let the paste logic handle it
         println!("⚠️  capture_previous_app not fully implemented for this platform");
     }
@@ -656,7 +1243,77 @@ pub fn paste_as_plain_text() -> Result<(), String> {
     paste_internal(true)
 }
 
-fn paste_internal(as_plain_text: bool) -> Result<(), String> {
+/// Paste `text` into the previous app, then restore whatever was on the system
+/// clipboard so the pasted text never shows up as a new history entry
+pub(crate) fn paste_text_transient(text: &str, as_plain_text: bool) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+
+    let previous = clipboard.get_text().ok();
+    clipboard.set_text(text)
+        .map_err(|e| format!("Failed to set clipboard: {}", e))?;
+
+    paste_internal(as_plain_text)?;
+
+    if let Some(previous) = previous {
+        // Give the target app time to read the clipboard before restoring it
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_text(previous);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Paste a secret like `paste_text_transient`. Clipboard managers are asked
+/// to skip it, it's cleared from the clipboard afterwards, and the clipboard
+/// history refuses it (see `save_clipboard_entry`).
+fn paste_secret_transient(state: &AppState, text: &str) -> Result<(), String> {
+    state.secrets.lock().map_err(|e| e.to_string())?.remember_paste(text);
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    let previous = clipboard.get_text().ok();
+
+    let set = clipboard.set();
+    #[cfg(target_os = "macos")]
+    let set = arboard::SetExtApple::exclude_from_history(set);
+    #[cfg(target_os = "windows")]
+    let set = arboard::SetExtWindows::exclude_from_cloud(arboard::SetExtWindows::exclude_from_history(set));
+    #[cfg(target_os = "linux")]
+    let set = arboard::SetExtLinux::exclude_from_history(set);
+    set.text(text).map_err(|e| format!("Failed to set clipboard: {}", e))?;
+
+    // Already plain text; converting it would put it back without the hint
+    paste_internal(false)?;
+
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        if let Ok(mut clipboard) = arboard::Clipboard::new() {
+            let _ = match previous {
+                Some(previous) => clipboard.set_text(previous),
+                None => clipboard.clear(),
+            };
+        }
+    });
+
+    Ok(())
+}
+
+/// Forget the app captured when a popup opened, so the next paste goes to the focused app
+pub(crate) fn forget_previous_app() {
+    if let Ok(mut prev_app) = PREVIOUS_APP.lock() {
+        *prev_app = None;
+    }
+
+    #[cfg(target_os = "linux")]
+    crate::linux_paste::forget_active_window();
+}
+
+pub(crate) fn paste_internal(as_plain_text: bool) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         use std::process::Command;
@@ -746,7 +1403,7 @@ fn paste_internal(as_plain_text: bool) -> Result<(), String> {
         *prev_app = None;
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "windows")]
     {
         use std::process::Command;
         use std::thread;
@@ -755,31 +1412,109 @@ fn paste_internal(as_plain_text: bool) -> Result<(), String> {
         // Wait for the popup to close
         thread::sleep(Duration::from_millis(200));
 
-        // For Windows/Linux, use platform-specific paste simulation
-        #[cfg(target_os = "windows")]
-        {
-            // Windows: Use PowerShell to send Ctrl+V
-            Command::new("powershell")
-                .arg("-c")
-                .arg("(New-Object -ComObject WScript.Shell).SendKeys('^{V}')")
-                .output()
-                .map_err(|e| format!("Failed to paste: {}", e))?;
-        }
+        // Windows: Use PowerShell to send Ctrl+V
+        Command::new("powershell")
+            .arg("-c")
+            .arg("(New-Object -ComObject WScript.Shell).SendKeys('^{V}')")
+            .output()
+            .map_err(|e| format!("Failed to paste: {}", e))?;
+    }
 
-        #[cfg(target_os = "linux")]
-        {
-            // Linux: Use xdotool to send Ctrl+V
-            Command::new("xdotool")
-                .arg("key")
-                .arg("ctrl+v")
-                .output()
-                .map_err(|e| format!("Failed to paste: {}", e))?;
+    #[cfg(target_os = "linux")]
+    {
+        if as_plain_text {
+            let mut clipboard = arboard::Clipboard::new()
+                .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+
+            if let Ok(text) = clipboard.get_text() {
+                // Write back as plain text (this strips formatting)
+                clipboard.set_text(&text)
+                    .map_err(|e| format!("Failed to set plain text: {}", e))?;
+            }
         }
+
+        // Refocus the captured window and send Ctrl+V (Ctrl+Shift+V in terminals)
+        crate::linux_paste::paste()?;
+    }
+
+    Ok(())
+}
+
+/// Copy the current selection of the focused app to the system clipboard
+pub(crate) fn copy_selection_internal() -> Result<(), String> {
+    use std::thread;
+    use std::time::Duration;
+
+    // Let the user release the shortcut's modifier keys first
+    thread::sleep(Duration::from_millis(200));
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+
+        let copy_script = r#"
+        tell application "System Events"
+            keystroke "c" using command down
+        end tell
+        "#;
+
+        Command::new("osascript")
+            .arg("-e")
+            .arg(copy_script)
+            .output()
+            .map_err(|e| format!("Failed to copy: {}", e))?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::process::Command;
+
+        Command::new("powershell")
+            .arg("-c")
+            .arg("(New-Object -ComObject WScript.Shell).SendKeys('^{C}')")
+            .output()
+            .map_err(|e| format!("Failed to copy: {}", e))?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux_paste::copy_selection()?;
     }
 
+    // Give the app time to publish the selection
+    thread::sleep(Duration::from_millis(150));
+
     Ok(())
 }
 
+/// Queue clipboard entries for sequential pasting. Each press of the queue
+/// shortcut (or call to `paste_next_in_queue`) pastes the next one.
+#[tauri::command]
+pub fn start_paste_queue(
+    app_handle: tauri::AppHandle,
+    entry_ids: Vec<i64>,
+    timeout_secs: Option<u64>,
+) -> Result<PasteQueueStatus, String> {
+    let timeout = timeout_secs.map(std::time::Duration::from_secs);
+    crate::paste_queue::start(&app_handle, &entry_ids, timeout)
+}
+
+#[tauri::command]
+pub fn paste_next_in_queue(app_handle: tauri::AppHandle) -> Result<PasteQueueStatus, String> {
+    crate::paste_queue::paste_next(&app_handle)
+}
+
+#[tauri::command]
+pub fn get_paste_queue(state: State<AppState>) -> Result<PasteQueueStatus, String> {
+    let queue = state.paste_queue.lock().map_err(|e| e.to_string())?;
+    Ok(queue.status())
+}
+
+#[tauri::command]
+pub fn reset_paste_queue(app_handle: tauri::AppHandle) -> Result<PasteQueueStatus, String> {
+    crate::paste_queue::reset(&app_handle)
+}
+
 #[tauri::command]
 pub fn get_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
@@ -836,6 +1571,7 @@ pub fn upload_file(
         tags,
         created_at: now.clone(),
         updated_at: now,
+        deleted_at: None,
     };
 
     let file_id = db.create_file(&file)
@@ -897,27 +1633,66 @@ pub fn update_file(
     Ok(())
 }
 
+/// Move a file to the trash. Its blob stays on disk until the file is purged.
 #[tauri::command]
 pub fn delete_file(state: State<AppState>, id: i64) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.trash_file(id).map_err(|e| e.to_string())
+}
 
-    // Get file record to retrieve storage path
-    let file = db.get_file(id)
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "File not found".to_string())?;
+#[tauri::command]
+pub fn get_trash(state: State<AppState>) -> Result<TrashContents, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_trash().map_err(|e| e.to_string())
+}
 
-    // Delete from local storage
-    let storage_manager = FileStorageManager::new()
-        .map_err(|e| format!("Failed to initialize storage: {}", e))?;
+/// Take an item out of the trash. A restored folder gets its snippets and files back.
+#[tauri::command]
+pub fn restore_from_trash(state: State<AppState>, kind: TrashItemKind, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    match kind {
+        TrashItemKind::Snippet => db.restore_snippet(id),
+        TrashItemKind::Folder => db.restore_folder(id),
+        TrashItemKind::File => db.restore_file(id),
+    }
+    .map_err(|e| e.to_string())?;
 
-    storage_manager.delete_file(&file.storage_path)
-        .map_err(|e| format!("Failed to delete file from storage: {}", e))?;
+    if kind == TrashItemKind::Snippet {
+        invalidate_triggers(&state);
+    }
+    Ok(())
+}
 
-    // Delete from database
-    db.delete_file(id)
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn delete_permanently(state: State<AppState>, kind: TrashItemKind, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    trash::purge_item(&db, kind, id)
+}
 
-    Ok(())
+/// Permanently delete everything in the trash. Returns how many items were deleted.
+#[tauri::command]
+pub fn empty_trash(state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    trash::purge(&db, None)
+}
+
+#[tauri::command]
+pub fn get_trash_settings(state: State<AppState>) -> Result<TrashSettings, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_trash_settings().map_err(|e| e.to_string())
+}
+
+/// Save the trash retention period and purge anything that is now past it
+#[tauri::command]
+pub fn set_trash_settings(app_handle: tauri::AppHandle, settings: TrashSettings) -> Result<usize, String> {
+    use tauri::Manager;
+
+    {
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_trash_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    trash::purge_expired(&app_handle)
 }
 
 #[tauri::command]
@@ -935,8 +1710,984 @@ pub fn get_files_by_folder(
     db.get_files_by_folder(folder_id).map_err(|e| e.to_string())
 }
 
+/// Keyword search over files, with exact tag filtering like `search_snippets`
+#[tauri::command]
+pub fn search_files(state: State<AppState>, query: String, tags: Option<Vec<String>>) -> Result<Vec<File>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.search_files(&query, &tags.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+// Tags
+
+#[tauri::command]
+pub fn get_all_tags(state: State<AppState>) -> Result<Vec<Tag>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_all_tags().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn rename_tag(state: State<AppState>, id: i64, name: String) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.rename_tag(id, &name).map_err(|e| e.to_string())
+}
+
+/// Replace the `source_ids` tags with `target_id` everywhere they are used
+#[tauri::command]
+pub fn merge_tags(state: State<AppState>, source_ids: Vec<i64>, target_id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.merge_tags(&source_ids, target_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_tag(state: State<AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_tag(id).map_err(|e| e.to_string())
+}
+
+/// Set a tag's `#rrggbb` color, or clear it with `None`
+#[tauri::command]
+pub fn set_tag_color(state: State<AppState>, id: i64, color: Option<String>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_tag_color(id, color.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_clipboard_entry_tags(state: State<AppState>, id: i64, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_clipboard_entry_tags(id, &tags).map_err(|e| e.to_string())
+}
+
+// Library export/import
+
+/// Embed newly imported snippets when the model is loaded
+pub(crate) fn embed_snippets(state: &AppState, db: &Database, snippet_ids: &[i64]) -> Result<(), String> {
+    let search_engine = state.search_engine.lock().map_err(|e| e.to_string())?;
+    let Some(engine) = search_engine.as_ref() else {
+        return Ok(());
+    };
+
+    for &id in snippet_ids {
+        let Some(snippet) = db.get_snippet(id).map_err(|e| e.to_string())? else { continue };
+        let text = SearchEngine::generate_snippet_text(&snippet);
+        match engine.generate_embedding(&text) {
+            Ok(embedding) => {
+                let _ = db.store_embedding(id, &embedding);
+            }
+            Err(e) => {
+                eprintln!("Failed to generate embedding: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write snippets, folders, files (with their data), clipboard history and
+/// tags to a zip archive at `path`
+#[tauri::command]
+pub fn export_library(state: State<AppState>, path: String) -> Result<ExportReport, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    library_archive::export_library(&db, std::path::Path::new(&path)).map_err(|e| e.to_string())
+}
+
+/// Import an archive made by `export_library`, merging it into the library
+/// (the default) or replacing the library with it
+#[tauri::command]
+pub fn import_library(state: State<AppState>, path: String, mode: Option<ImportMode>) -> Result<ImportReport, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let report = library_archive::import_library(&db, std::path::Path::new(&path), mode.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    invalidate_triggers(&state);
+    embed_snippets(&state, &db, &report.snippet_ids)?;
+    Ok(report)
+}
+
+// VS Code snippets
+
+/// Files to import: the given files, plus the matching files directly inside
+/// any given directory
+fn expand_import_paths(paths: &[String], matches: impl Fn(&std::path::Path) -> bool) -> Result<Vec<std::path::PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths.iter().map(std::path::PathBuf::from) {
+        if path.is_dir() {
+            let mut entries: Vec<_> = std::fs::read_dir(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && matches(p))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Save converted snippets into `folder_id`, skipping ones the library already
+/// has. A trigger that is invalid or taken is dropped and reported.
+fn import_snippets(db: &Database, report: &mut ImportReport, snippets: Vec<Snippet>, folder_id: Option<i64>) -> Result<(), String> {
+    for mut snippet in snippets {
+        snippet.folder_id = folder_id;
+        resolve_snippet_language(&mut snippet);
+
+        if db.snippet_exists(&snippet.title, &snippet.content, &snippet.language).map_err(|e| e.to_string())? {
+            report.duplicates_skipped += 1;
+            continue;
+        }
+
+        if let Err(reason) = validate_trigger(db, &mut snippet, None) {
+            report.conflicts.push(ImportConflict {
+                kind: LibraryItemKind::Snippet,
+                name: snippet.title.clone(),
+                resolution: format!("{}; imported without a trigger", reason),
+            });
+            snippet.trigger = None;
+        }
+
+        let id = db.create_snippet(&snippet).map_err(|e| e.to_string())?;
+        report.snippet_ids.push(id);
+        report.snippets += 1;
+    }
+    Ok(())
+}
+
+/// Import VS Code snippet files (`<language>.json` or `.code-snippets`) into
+/// `folder_id`. Directories are searched for snippet files, one level deep.
+/// Snippets the library already has are skipped.
+#[tauri::command]
+pub fn import_vscode_snippets(
+    state: State<AppState>,
+    paths: Vec<String>,
+    folder_id: Option<i64>,
+) -> Result<ImportReport, String> {
+    let files = expand_import_paths(&paths, vscode_snippets::is_snippets_file)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::default();
+    // An import that fails halfway leaves nothing behind
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+
+    for path in files {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| vscode_snippets::parse(&text, &file_name));
+
+        let (snippets, skipped) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::Snippet,
+                    name: file_name,
+                    resolution: format!("Skipped the file: {:#}", e),
+                });
+                continue;
+            }
+        };
+
+        for (name, reason) in skipped {
+            report.conflicts.push(ImportConflict {
+                kind: LibraryItemKind::Snippet,
+                name: format!("{} ({})", name, file_name),
+                resolution: format!("Skipped: {}", reason),
+            });
+        }
+
+        import_snippets(&db, &mut report, snippets, folder_id)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    println!(
+        "📥 Imported {} VS Code snippets ({} duplicates skipped, {} skipped entries)",
+        report.snippets, report.duplicates_skipped, report.conflicts.len()
+    );
+
+    invalidate_triggers(&state);
+    embed_snippets(&state, &db, &report.snippet_ids)?;
+    Ok(report)
+}
+
+/// Export a folder's snippets (including subfolders; everything when
+/// `folder_id` is `None`) to `directory` as VS Code snippet files, one per
+/// language. Existing files there are merged into. Returns the written paths.
+#[tauri::command]
+pub fn export_vscode_snippets(
+    state: State<AppState>,
+    folder_id: Option<i64>,
+    directory: String,
+) -> Result<Vec<String>, String> {
+    let mut snippets = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        match folder_id {
+            Some(_) => folder_snippets(&state, &db, folder_id, true)?,
+            None => db.get_all_snippets().map_err(|e| e.to_string())?,
+        }
+    };
+    snippets.retain(|s| !s.secret);
+    if snippets.is_empty() {
+        return Err("There are no snippets to export".to_string());
+    }
+
+    let directory = std::path::Path::new(&directory);
+    let mut existing = std::collections::BTreeMap::new();
+    for entry in std::fs::read_dir(directory).map_err(|e| format!("Failed to read {}: {}", directory.display(), e))? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        if let (Some(name), Ok(text)) = (path.file_name(), std::fs::read_to_string(&path)) {
+            existing.insert(name.to_string_lossy().to_string(), text);
+        }
+    }
+
+    let files = vscode_snippets::export(&snippets, &existing).map_err(|e| e.to_string())?;
+    let mut written = Vec::new();
+    for (name, contents) in files {
+        let path = directory.join(name);
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    println!("📤 Exported {} snippets to {} VS Code snippet files", snippets.len(), written.len());
+    Ok(written)
+}
+
+// JetBrains live templates
+
+/// Group name for snippets outside any folder
+const UNCATEGORIZED_GROUP: &str = "Uncategorized";
+
+/// Import JetBrains live template files. Each template group goes into the
+/// folder at its path (`Work/Go`, as written by the export), creating the
+/// missing folders. Directories are searched for `.xml` files, one level deep.
+#[tauri::command]
+pub fn import_jetbrains_templates(state: State<AppState>, paths: Vec<String>) -> Result<ImportReport, String> {
+    let files = expand_import_paths(&paths, |p| p.extension().and_then(|e| e.to_str()) == Some("xml"))?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut report = ImportReport::default();
+    // An import that fails halfway leaves no snippets or folders behind
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+
+    for path in files {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| jetbrains_templates::parse(&text, &file_name));
+
+        let set = match parsed {
+            Ok(set) => set,
+            Err(e) => {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::Snippet,
+                    name: file_name,
+                    resolution: format!("Skipped the file: {:#}", e),
+                });
+                continue;
+            }
+        };
+
+        for (name, note) in set.notes {
+            report.conflicts.push(ImportConflict {
+                kind: LibraryItemKind::Snippet,
+                name: format!("{} ({})", name, set.group),
+                resolution: note,
+            });
+        }
+
+        let folder_id = ensure_folder_path(&db, &set.group, &mut report.folders)?;
+        import_snippets(&db, &mut report, set.snippets, folder_id)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    println!(
+        "📥 Imported {} live templates into {} new folders ({} duplicates skipped)",
+        report.snippets, report.folders, report.duplicates_skipped
+    );
+
+    invalidate_triggers(&state);
+    embed_snippets(&state, &db, &report.snippet_ids)?;
+    Ok(report)
+}
+
+/// Manual folder at `path` (folder names joined by `/`), creating the missing
+/// ones and counting them in `created`
+fn ensure_folder_path(db: &Database, path: &str, created: &mut usize) -> Result<Option<i64>, String> {
+    let folders = db.get_all_folders(false).map_err(|e| e.to_string())?;
+    let mut parent = None;
+
+    for name in path.split('/').map(str::trim).filter(|n| !n.is_empty()) {
+        let existing = folders
+            .iter()
+            .find(|f| f.parent_id == parent && f.kind == FolderKind::Manual && f.name == name)
+            .and_then(|f| f.id);
+        parent = Some(match existing {
+            Some(id) => id,
+            None => {
+                *created += 1;
+                db.create_folder(name, None, parent).map_err(|e| e.to_string())?
+            }
+        });
+    }
+    Ok(parent)
+}
+
+/// Names from the top-level folder down to `id`, joined by `/`
+fn folder_path(folders: &HashMap<i64, Folder>, id: i64) -> String {
+    let mut names = Vec::new();
+    let mut current = folders.get(&id);
+    while let Some(folder) = current {
+        // Guards against a corrupt parent cycle
+        if names.len() > folders.len() {
+            break;
+        }
+        names.push(folder.name.as_str());
+        current = folder.parent_id.and_then(|parent| folders.get(&parent));
+    }
+    names.reverse();
+    names.join("/")
+}
+
+/// Export snippets to `directory` as JetBrains live template files, one
+/// template group (and file) per folder, named by the folder's full path.
+/// Covers a folder and its subfolders, or everything when `folder_id` is
+/// `None`; a smart folder is one group. Groups whose file names would clash
+/// get numbered ones; existing files of the same name are overwritten.
+/// Returns the written paths.
+#[tauri::command]
+pub fn export_jetbrains_templates(
+    state: State<AppState>,
+    folder_id: Option<i64>,
+    directory: String,
+) -> Result<Vec<String>, String> {
+    // By folder, so a folder called "Uncategorized" stays apart from snippets without one
+    let mut groups: std::collections::BTreeMap<Option<i64>, (String, Vec<Snippet>)> = std::collections::BTreeMap::new();
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let folders: HashMap<i64, Folder> = db
+            .get_all_folders(false)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter_map(|f| Some((f.id?, f)))
+            .collect();
+
+        let smart_folder = folder_id
+            .and_then(|id| folders.get(&id))
+            .filter(|f| f.kind == FolderKind::Smart);
+        let snippets = match folder_id {
+            Some(_) => folder_snippets(&state, &db, folder_id, true)?,
+            None => db.get_all_snippets().map_err(|e| e.to_string())?,
+        };
+
+        for snippet in snippets.into_iter().filter(|s| !s.secret) {
+            let folder = smart_folder.and_then(|f| f.id).or(snippet.folder_id).filter(|id| folders.contains_key(id));
+            let (_, snippets) = groups.entry(folder).or_insert_with(|| {
+                let group = folder.map_or_else(|| UNCATEGORIZED_GROUP.to_string(), |id| folder_path(&folders, id));
+                (group, Vec::new())
+            });
+            snippets.push(snippet);
+        }
+    }
+    if groups.is_empty() {
+        return Err("There are no snippets to export".to_string());
+    }
+
+    let mut groups: Vec<(String, Vec<Snippet>)> = groups.into_values().collect();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    let file_names = jetbrains_templates::file_names(groups.iter().map(|(group, _)| group.as_str()));
+
+    let directory = std::path::Path::new(&directory);
+    let mut written = Vec::new();
+    for ((group, snippets), file_name) in groups.iter().zip(file_names) {
+        let path = directory.join(format!("{}.xml", file_name));
+        std::fs::write(&path, jetbrains_templates::export(group, snippets))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    println!("📤 Exported {} template groups to {}", written.len(), directory.display());
+    Ok(written)
+}
+
+// Markdown vault
+
+#[tauri::command]
+pub fn get_vault_settings(state: State<AppState>) -> Result<VaultSettings, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_vault_settings().map_err(|e| e.to_string())
+}
+
+/// Mirror snippets to `settings.directory` (or stop with `None`), syncing
+/// right away. Moving the vault starts over as if it had never been synced.
+#[tauri::command]
+pub fn set_vault_settings(state: State<AppState>, settings: VaultSettings) -> Result<VaultSyncReport, String> {
+    let directory = settings.directory.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if let Some(directory) = &directory {
+            let path = std::path::Path::new(directory);
+            if !path.is_absolute() {
+                return Err("Choose the vault directory by its full path".to_string());
+            }
+            std::fs::create_dir_all(path).map_err(|e| format!("Failed to create {}: {}", directory, e))?;
+        }
+
+        if db.get_vault_settings().map_err(|e| e.to_string())?.directory != directory {
+            db.clear_vault_records().map_err(|e| e.to_string())?;
+        }
+        db.set_vault_settings(&VaultSettings { directory }).map_err(|e| e.to_string())?;
+    }
+
+    Ok(markdown_vault::sync_now(&state)?.unwrap_or_default())
+}
+
+/// Sync the vault now rather than waiting for the watcher
+#[tauri::command]
+pub fn sync_vault(state: State<AppState>) -> Result<VaultSyncReport, String> {
+    Ok(markdown_vault::sync_now(&state)?.unwrap_or_default())
+}
+
+// Importers for other apps
+
+#[tauri::command]
+pub fn list_importers() -> Vec<ImporterInfo> {
+    importers::list_importers()
+}
+
+/// Import another app's export with one of the `list_importers` importers.
+/// Snippets go into `folder_id`, keeping the source's folders below it, and
+/// clipboard entries into the history; anything already here is skipped.
+/// With `dry_run` nothing is saved and the report counts what would be added.
+#[tauri::command]
+pub fn run_importer(
+    state: State<AppState>,
+    importer: String,
+    paths: Vec<String>,
+    folder_id: Option<i64>,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    let importer = importers::find_importer(&importer).map_err(|e| e.to_string())?;
+    let files = expand_import_paths(&paths, |p| importer.reads(p))?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    if let Some(id) = folder_id {
+        let folder = db.get_folder(id, false).map_err(|e| e.to_string())?.ok_or("Folder not found")?;
+        if folder.kind == FolderKind::Smart {
+            return Err("Snippets can't be imported into a smart folder".to_string());
+        }
+    }
+
+    let item_kind = match importer.target {
+        ImportTarget::Snippets => LibraryItemKind::Snippet,
+        ImportTarget::Clipboard => LibraryItemKind::ClipboardEntry,
+    };
+    let mut report = ImportReport { dry_run, ..Default::default() };
+    let mut folder_ids = HashMap::new();
+    // An import that fails halfway leaves nothing behind
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    // Within one import, as a dry run can't ask the database about these
+    let mut seen_snippets = std::collections::HashSet::new();
+    let mut seen_triggers = std::collections::HashSet::new();
+    let mut seen_clipboard = std::collections::HashSet::new();
+
+    for path in files {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let batch = match importer.read(&path) {
+            Ok(batch) => batch,
+            Err(e) => {
+                report.conflicts.push(ImportConflict {
+                    kind: item_kind,
+                    name: file_name,
+                    resolution: format!("Skipped the file: {:#}", e),
+                });
+                continue;
+            }
+        };
+        report.conflicts.extend(batch.notes);
+
+        for imported in batch.snippets {
+            let mut snippet = imported.snippet;
+            resolve_snippet_language(&mut snippet);
+
+            let key = (snippet.title.clone(), snippet.content.clone(), snippet.language.clone());
+            if !seen_snippets.insert(key)
+                || db.snippet_exists(&snippet.title, &snippet.content, &snippet.language).map_err(|e| e.to_string())?
+            {
+                report.duplicates_skipped += 1;
+                continue;
+            }
+
+            let trigger_check = validate_trigger(&db, &mut snippet, None).and_then(|_| match &snippet.trigger {
+                Some(trigger) if !seen_triggers.insert(trigger.clone()) => {
+                    Err(format!("Trigger '{}' is already used by another snippet", trigger))
+                }
+                _ => Ok(()),
+            });
+            if let Err(e) = trigger_check {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::Snippet,
+                    name: snippet.title.clone(),
+                    resolution: format!("{}; imported without a trigger", e),
+                });
+                snippet.trigger = None;
+            }
+
+            snippet.folder_id = import_folder(&db, &mut folder_ids, folder_id, &imported.folder, dry_run, &mut report)?;
+            report.snippets += 1;
+            if !dry_run {
+                let id = db.create_snippet(&snippet).map_err(|e| e.to_string())?;
+                db.set_snippet_timestamps(id, &snippet.created_at, &snippet.updated_at)
+                    .map_err(|e| e.to_string())?;
+                report.snippet_ids.push(id);
+            }
+        }
+
+        for entry in batch.clipboard {
+            if !seen_clipboard.insert(entry.content.clone())
+                || db.clipboard_content_exists(&entry.content).map_err(|e| e.to_string())?
+            {
+                report.duplicates_skipped += 1;
+                continue;
+            }
+
+            report.clipboard_entries += 1;
+            if dry_run {
+                continue;
+            }
+            let category = if entry.category.is_empty() {
+                classifier::classify_content(&entry.content)
+            } else {
+                entry.category
+            };
+            let id = db
+                .save_clipboard_entry(&entry.content, &entry.source, &category, &entry.created_at)
+                .map_err(|e| e.to_string())?;
+            if entry.pinned {
+                db.pin_clipboard_entry(id).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    println!(
+        "📥 {} {}: {} snippets, {} folders, {} clipboard entries ({} duplicates skipped)",
+        if dry_run { "Previewed import from" } else { "Imported from" },
+        importer.name, report.snippets, report.folders, report.clipboard_entries, report.duplicates_skipped
+    );
+
+    if !dry_run {
+        tx.commit().map_err(|e| e.to_string())?;
+        invalidate_triggers(&state);
+        embed_snippets(&state, &db, &report.snippet_ids)?;
+    }
+    Ok(report)
+}
+
+/// The manual folder at `path` below `root`, created if it doesn't exist.
+/// `folder_ids` remembers paths already resolved; on a dry run, folders that
+/// would be created are counted but have no ID.
+fn import_folder(
+    db: &Database,
+    folder_ids: &mut HashMap<Vec<String>, Option<i64>>,
+    root: Option<i64>,
+    path: &[String],
+    dry_run: bool,
+    report: &mut ImportReport,
+) -> Result<Option<i64>, String> {
+    let path: Vec<String> = path.iter().map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+    let mut parent = root;
+
+    for depth in 1..=path.len() {
+        let key = path[..depth].to_vec();
+        if let Some(id) = folder_ids.get(&key) {
+            parent = *id;
+            continue;
+        }
+
+        let name = &path[depth - 1];
+        // Nothing exists below a folder that is yet to be created
+        let existing = if depth > 1 && parent.is_none() {
+            None
+        } else {
+            db.get_all_folders(false)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|f| f.parent_id == parent && f.kind == FolderKind::Manual && f.name == *name)
+                .and_then(|f| f.id)
+        };
+
+        let id = match existing {
+            Some(id) => Some(id),
+            None => {
+                report.folders += 1;
+                if dry_run {
+                    None
+                } else {
+                    Some(db.create_folder(name, None, parent).map_err(|e| e.to_string())?)
+                }
+            }
+        };
+        folder_ids.insert(key, id);
+        parent = id;
+    }
+
+    Ok(parent)
+}
+
+// Backups
+
+#[tauri::command]
+pub fn get_backup_settings(state: State<AppState>) -> Result<BackupSettings, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_backup_settings().map_err(|e| e.to_string())
+}
+
+/// Save the backup settings. Backups beyond the new `keep` are deleted.
+#[tauri::command]
+pub fn set_backup_settings(state: State<AppState>, settings: BackupSettings) -> Result<(), String> {
+    if settings.interval_hours == 0 || settings.keep == 0 {
+        return Err("Back up at least every hour and keep at least one backup".to_string());
+    }
+    let directory = settings.directory.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if directory.as_deref().is_some_and(|d| !std::path::Path::new(d).is_absolute()) {
+        return Err("Choose the backup directory by its full path".to_string());
+    }
+
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_backup_settings(&BackupSettings { directory, ..settings }).map_err(|e| e.to_string())?;
+    }
+    backup::rotate(&state.db).map_err(|e| format!("{:#}", e))?;
+    Ok(())
+}
+
+/// Complete backups, newest first
+#[tauri::command]
+pub fn list_backups(state: State<AppState>) -> Result<Vec<BackupInfo>, String> {
+    backup::list_backups(&state.db).map_err(|e| format!("{:#}", e))
+}
+
+/// Back up now. The backup is kept until deleted; only scheduled ones rotate.
+#[tauri::command]
+pub fn create_backup(state: State<AppState>) -> Result<BackupInfo, String> {
+    let info = backup::create_backup(&state.db, BackupKind::Manual).map_err(|e| format!("{:#}", e))?;
+    backup::rotate(&state.db).map_err(|e| format!("{:#}", e))?;
+    Ok(info)
+}
+
+/// Check a backup's files against their checksums and its database's integrity
+#[tauri::command]
+pub fn verify_backup(state: State<AppState>, id: String) -> Result<BackupInfo, String> {
+    backup::verify_backup(&state.db, &id).map_err(|e| format!("{:#}", e))
+}
+
+/// Replace the library with a verified backup. The current library is backed
+/// up first, so a restore can be undone.
+#[tauri::command]
+pub fn restore_backup(app_handle: tauri::AppHandle, state: State<AppState>, id: String) -> Result<BackupInfo, String> {
+    use tauri::Emitter;
+
+    let info = backup::restore_backup(&state.db, &id).map_err(|e| format!("{:#}", e))?;
+    invalidate_triggers(&state);
+    let _ = app_handle.emit("backup-restored", &info);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn delete_backup(state: State<AppState>, id: String) -> Result<(), String> {
+    backup::delete_backup(&state.db, &id).map_err(|e| format!("{:#}", e))
+}
+
+// Encryption at rest
+
+fn encryption_status(db: &Database) -> Result<EncryptionStatus, String> {
+    let storage = FileStorageManager::new().map_err(|e| e.to_string())?;
+    let backups = db
+        .get_backup_settings()
+        .and_then(|settings| backup::backups_dir(&settings))
+        .and_then(|root| backup::plaintext_backups(&root))
+        .map_err(|e| format!("{:#}", e))?;
+    encryption::status(db.is_encrypted(), storage.get_storage_dir(), backups).map_err(|e| format!("{:#}", e))
+}
+
+#[tauri::command]
+pub fn get_encryption_status(state: State<AppState>) -> Result<EncryptionStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    encryption_status(&db)
+}
+
+/// Encrypt the library: create its key, then encrypt the stored files, the
+/// database and the existing backups in place. Running it again finishes an
+/// interrupted migration.
+#[tauri::command]
+pub fn enable_encryption(state: State<AppState>, use_keyring: bool, passphrase: Option<String>) -> Result<EncryptionStatus, String> {
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    if passphrase.as_ref().is_some_and(|p| p.chars().count() < encryption::MIN_PASSPHRASE_LENGTH) {
+        return Err(format!("Use a passphrase of at least {} characters", encryption::MIN_PASSPHRASE_LENGTH));
+    }
+
+    // Held throughout, so nothing is written to the database while it's exported
+    let mut db = state.db.lock().map_err(|e| e.to_string())?;
+    if db.is_locked() {
+        return Err("The library is locked".to_string());
+    }
+    encryption::enable(use_keyring, passphrase.as_deref()).map_err(|e| format!("{:#}", e))?;
+
+    let storage = FileStorageManager::new().map_err(|e| e.to_string())?;
+    encryption::encrypt_stored_files(storage.get_storage_dir()).map_err(|e| format!("{:#}", e))?;
+    db.encrypt().map_err(|e| format!("{:#}", e))?;
+    let backups = backup::backups_dir(&db.get_backup_settings().map_err(|e| e.to_string())?).map_err(|e| format!("{:#}", e))?;
+    backup::encrypt_backups(&backups).map_err(|e| format!("{:#}", e))?;
+
+    encryption_status(&db)
+}
+
+/// Add a passphrase to a library whose key is only in the keyring
+#[tauri::command]
+pub fn add_encryption_passphrase(state: State<AppState>, passphrase: String) -> Result<EncryptionStatus, String> {
+    if passphrase.chars().count() < encryption::MIN_PASSPHRASE_LENGTH {
+        return Err(format!("Use a passphrase of at least {} characters", encryption::MIN_PASSPHRASE_LENGTH));
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    encryption::add_passphrase(&passphrase).map_err(|e| format!("{:#}", e))?;
+    encryption_status(&db)
+}
+
+/// Load the key from the keyring again and open the library
+#[tauri::command]
+pub fn retry_encryption_keyring(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+    use tauri::Emitter;
+
+    {
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+        if !db.is_locked() {
+            return Ok(());
+        }
+        encryption::retry_keyring().map_err(|e| format!("{:#}", e))?;
+        *db = Database::new().map_err(|e| format!("{:#}", e))?;
+    }
+    invalidate_triggers(&state);
+    let _ = app_handle.emit("library-unlocked", ());
+    Ok(())
+}
+
+/// Start an empty library in place of a locked one whose key is lost. The
+/// encrypted library is moved aside, not deleted; returns where to.
+#[tauri::command]
+pub fn reset_locked_library(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<String, String> {
+    use tauri::Emitter;
+
+    let aside = {
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+        if !db.is_locked() {
+            return Err("The library isn't locked".to_string());
+        }
+        let database = Database::get_db_path().map_err(|e| format!("{:#}", e))?;
+        let storage = FileStorageManager::new().map_err(|e| e.to_string())?;
+        let aside = encryption::set_aside_library(&database, storage.get_storage_dir()).map_err(|e| format!("{:#}", e))?;
+        *db = Database::new().map_err(|e| format!("{:#}", e))?;
+        aside
+    };
+    invalidate_triggers(&state);
+    let _ = app_handle.emit("library-unlocked", ());
+    Ok(aside.to_string_lossy().to_string())
+}
+
+/// Unlock an encrypted library with its passphrase and open it
+#[tauri::command]
+pub fn unlock_library(app_handle: tauri::AppHandle, state: State<AppState>, passphrase: String) -> Result<(), String> {
+    use tauri::Emitter;
+
+    {
+        let mut db = state.db.lock().map_err(|e| e.to_string())?;
+        if !db.is_locked() {
+            return Ok(());
+        }
+        encryption::unlock(&passphrase).map_err(|e| format!("{:#}", e))?;
+        *db = Database::new().map_err(|e| format!("{:#}", e))?;
+    }
+    invalidate_triggers(&state);
+    let _ = app_handle.emit("library-unlocked", ());
+    Ok(())
+}
+
+// Secret snippets
+
+#[tauri::command]
+pub fn get_secret_vault_status(state: State<AppState>) -> Result<SecretVaultStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let config = db.get_secret_vault().map_err(|e| e.to_string())?;
+    let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+    Ok(secrets::status(config.as_ref(), &mut session))
+}
+
+/// Set the secret vault's master password, or change it (which needs the
+/// current one). Secrets stay encrypted with the same key. Leaves the vault
+/// unlocked.
+#[tauri::command]
+pub fn set_secret_password(
+    state: State<AppState>,
+    current_password: Option<String>,
+    password: String,
+) -> Result<SecretVaultStatus, String> {
+    if password.chars().count() < encryption::MIN_PASSPHRASE_LENGTH {
+        return Err(format!("Use a password of at least {} characters", encryption::MIN_PASSPHRASE_LENGTH));
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (config, key) = match db.get_secret_vault().map_err(|e| e.to_string())? {
+        Some(config) => {
+            let key = secrets::unwrap_key(&config, current_password.as_deref().unwrap_or_default())
+                .map_err(|e| format!("{:#}", e))?;
+            let config = secrets::wrap_key(&key, &password, config.lock_after_minutes).map_err(|e| format!("{:#}", e))?;
+            (config, key)
+        }
+        None => secrets::create_vault(&password).map_err(|e| format!("{:#}", e))?,
+    };
+    db.set_secret_vault(&config).map_err(|e| e.to_string())?;
+
+    let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+    session.unlock(&key, config.lock_after_minutes);
+    println!("🔑 Secret vault password set");
+    Ok(secrets::status(Some(&config), &mut session))
+}
+
+/// Unlock secret snippets until they've been left alone for the lock timeout
+#[tauri::command]
+pub fn unlock_secrets(state: State<AppState>, password: String) -> Result<SecretVaultStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let config = db
+        .get_secret_vault()
+        .map_err(|e| e.to_string())?
+        .ok_or("Set a password for secret snippets first")?;
+    let key = secrets::unwrap_key(&config, &password).map_err(|e| format!("{:#}", e))?;
+
+    let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+    session.unlock(&key, config.lock_after_minutes);
+    Ok(secrets::status(Some(&config), &mut session))
+}
+
+#[tauri::command]
+pub fn lock_secrets(state: State<AppState>) -> Result<(), String> {
+    state.secrets.lock().map_err(|e| e.to_string())?.lock();
+    Ok(())
+}
+
+/// How many idle minutes until secret snippets lock again
+#[tauri::command]
+pub fn set_secret_lock_timeout(state: State<AppState>, minutes: u32) -> Result<SecretVaultStatus, String> {
+    if !(1..=24 * 60).contains(&minutes) {
+        return Err("Lock after between 1 minute and 24 hours".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut config = db
+        .get_secret_vault()
+        .map_err(|e| e.to_string())?
+        .ok_or("Set a password for secret snippets first")?;
+    config.lock_after_minutes = minutes;
+    db.set_secret_vault(&config).map_err(|e| e.to_string())?;
+
+    let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+    session.set_lock_after(minutes);
+    Ok(secrets::status(Some(&config), &mut session))
+}
+
+/// The decrypted content of a secret snippet. Needs the vault unlocked.
+#[tauri::command]
+pub fn reveal_secret_snippet(state: State<AppState>, id: i64) -> Result<String, String> {
+    reveal_secret(&state, id)
+}
+
+fn reveal_secret(state: &AppState, id: i64) -> Result<String, String> {
+    let stored = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_secret_content(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Snippet {} isn't a secret", id))?
+    };
+    let mut session = state.secrets.lock().map_err(|e| e.to_string())?;
+    session.decrypt(&stored).map_err(|e| format!("{:#}", e))
+}
+
+// End-to-end encrypted sync
+
+fn check_sync_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < encryption::MIN_PASSPHRASE_LENGTH {
+        return Err(format!("Use a passphrase of at least {} characters", encryption::MIN_PASSPHRASE_LENGTH));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_sync_encryption_status() -> Result<SyncEncryptionStatus, String> {
+    Ok(sync_crypto::status())
+}
+
+/// Create the sync keys. Upload the returned bundle and show the recovery key.
+#[tauri::command]
+pub fn setup_sync_encryption(passphrase: String) -> Result<SyncKeySetup, String> {
+    check_sync_passphrase(&passphrase)?;
+    sync_crypto::setup(&passphrase).map_err(|e| format!("{:#}", e))
+}
+
+/// Unlock the key bundle downloaded from the server
+#[tauri::command]
+pub fn unlock_sync_encryption(bundle: String, passphrase: String) -> Result<SyncEncryptionStatus, String> {
+    sync_crypto::unlock(&bundle, &passphrase).map_err(|e| format!("{:#}", e))?;
+    Ok(sync_crypto::status())
+}
+
+#[tauri::command]
+pub fn recover_sync_encryption(bundle: String, recovery_key: String, new_passphrase: String) -> Result<SyncKeySetup, String> {
+    check_sync_passphrase(&new_passphrase)?;
+    sync_crypto::recover(&bundle, &recovery_key, &new_passphrase).map_err(|e| format!("{:#}", e))
+}
+
+#[tauri::command]
+pub fn change_sync_passphrase(current_passphrase: String, new_passphrase: String) -> Result<SyncKeySetup, String> {
+    check_sync_passphrase(&new_passphrase)?;
+    sync_crypto::change_passphrase(&current_passphrase, &new_passphrase).map_err(|e| format!("{:#}", e))
+}
+
+/// Start encrypting with a new sync key. Call `resync_library` and sync,
+/// then `retire_old_sync_keys`.
+#[tauri::command]
+pub fn rotate_sync_key(passphrase: String) -> Result<SyncKeySetup, String> {
+    sync_crypto::rotate(&passphrase).map_err(|e| format!("{:#}", e))
+}
+
+/// Drop the sync keys before the current one, once the library was pushed
+/// again in full with it
+#[tauri::command]
+pub fn retire_old_sync_keys(state: State<AppState>) -> Result<SyncKeySetup, String> {
+    let resynced = state.db.lock().map_err(|e| e.to_string())?.get_sync_state().map_err(|e| e.to_string())?.resynced_key_version;
+    sync_crypto::retire_old_keys(resynced).map_err(|e| format!("{:#}", e))
+}
+
+// Sync
+
+/// Pull changes from `server`, then push local ones. Runs off the main
+/// thread, since it waits on the network.
+#[tauri::command]
+pub async fn sync_library(app_handle: tauri::AppHandle, server: SyncServer) -> Result<SyncReport, String> {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<AppState>();
+        sync::sync_with(&state.db, &server).map_err(|e| format!("{:#}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_sync_status(state: State<AppState>) -> Result<SyncStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    sync::status(&db).map_err(|e| format!("{:#}", e))
+}
+
+/// Push the whole library again on the next sync, re-encrypting it with the
+/// current sync key
 #[tauri::command]
-pub fn search_files(state: State<AppState>, query: String) -> Result<Vec<File>, String> {
+pub fn resync_library(state: State<AppState>) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.search_files(&query).map_err(|e| e.to_string())
+    sync::resync(&db).map_err(|e| format!("{:#}", e))
 }
@@ -1,12 +1,98 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
+use rusqlite::backup::Backup;
+use rusqlite::types::ValueRef;
 use rusqlite::{params, Connection, OptionalExtension};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::models::{Snippet, Folder, File};
+use crate::encryption;
+use crate::models::{BackupSettings, SecretVaultConfig, ClipboardEntry, ClipboardRetentionSettings, ExpansionSettings, File, Folder, FolderKind, HotkeyAction, HotkeySettings, ImportConflict, ImportMode, ImportReport, LibraryData, LibraryItemKind, PruneReport, RetentionPolicy, RevisionRetention, SmartFolderQuery, Snippet, SnippetRevision, SyncBlob, SyncRecord, SyncRecordKind, SyncState, Tag, TrashContents, TrashItemKind, TrashSettings, VaultSettings};
+use crate::secrets::SECRET_MASK;
+use crate::tags;
 
 const MODEL_VERSION: &str = "all-MiniLM-L6-v2";
 
+/// Columns read by `row_to_snippet`, in order
+const SNIPPET_COLUMNS: &str =
+    "id, title, content, language, description, tags, folder_id, created_at, updated_at, trigger_keyword, deleted_at, secret";
+
+/// Columns read by `row_to_file`, in order
+const FILE_COLUMNS: &str =
+    "id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at, deleted_at";
+
+/// Columns read by `row_to_folder`, in order (followed by a snippet count)
+const FOLDER_COLUMNS: &str =
+    "f.id, f.name, f.icon, f.parent_id, f.sort_order, f.created_at, f.updated_at, f.deleted_at, f.smart_query";
+
+/// Columns read by `row_to_clipboard_entry`, in order
+const CLIPBOARD_COLUMNS: &str = "id, content, source, category, created_at, pinned, pin_order, favorite";
+
+/// Folders below `?1` (inclusive) whose `deleted_at` is `?2`: NULL for live
+/// folders, or the timestamp a subtree was moved to the trash with
+const FOLDER_SUBTREE: &str =
+    "WITH RECURSIVE subtree(id) AS (
+         SELECT id FROM folders WHERE id = ?1
+         UNION
+         SELECT f.id FROM folders f JOIN subtree ON f.parent_id = subtree.id WHERE f.deleted_at IS ?2
+     )";
+
+const CLIPBOARD_RETENTION_KEY: &str = "clipboard_retention";
+const HOTKEY_SETTINGS_KEY: &str = "hotkeys";
+const EXPANSION_SETTINGS_KEY: &str = "text_expansion";
+const REVISION_RETENTION_KEY: &str = "revision_retention";
+const TRASH_SETTINGS_KEY: &str = "trash";
+const VAULT_SETTINGS_KEY: &str = "markdown_vault";
+const VAULT_FOLDERS_KEY: &str = "markdown_vault_folders";
+const BACKUP_SETTINGS_KEY: &str = "backups";
+const SECRET_VAULT_KEY: &str = "secret_vault";
+const SYNC_STATE_KEY: &str = "sync";
+
+/// Columns read by `row_to_sync_record`, in order
+const SYNC_RECORD_COLUMNS: &str = "uuid, kind, local_id, hlc, versions, deleted, dirty, blob";
+
+/// The table of each kind of synced record and the columns that sync.
+/// `storage_path` is local, but is read and written along with the rest.
+fn sync_columns(kind: SyncRecordKind) -> (&'static str, &'static [&'static str]) {
+    match kind {
+        SyncRecordKind::Snippet => ("snippets", &[
+            "title", "content", "language", "description", "tags", "folder_id", "trigger_keyword",
+            "created_at", "updated_at", "deleted_at", "trashed_folder_id",
+        ]),
+        SyncRecordKind::Folder => ("folders", &[
+            "name", "icon", "parent_id", "sort_order", "smart_query", "created_at", "updated_at", "deleted_at",
+        ]),
+        SyncRecordKind::ClipboardEntry => ("clipboard_history", &[
+            "content", "source", "category", "created_at", "pinned", "pin_order", "favorite",
+        ]),
+        SyncRecordKind::File => ("files", &[
+            "filename", "file_type", "file_size", "folder_id", "storage_path", "mime_type", "description", "tags",
+            "created_at", "updated_at", "deleted_at", "trashed_folder_id",
+        ]),
+    }
+}
+
+fn sql_to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null | ValueRef::Blob(_) => Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+    }
+}
+
+fn json_to_sql(value: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as Sql;
+    match value {
+        Value::Null => Sql::Null,
+        Value::Bool(b) => Sql::Integer(*b as i64),
+        Value::Number(n) => n.as_i64().map(Sql::Integer).unwrap_or_else(|| Sql::Real(n.as_f64().unwrap_or_default())),
+        Value::String(text) => Sql::Text(text.clone()),
+        other => Sql::Text(other.to_string()),
+    }
+}
+
 // List of random folder emojis
 const FOLDER_EMOJIS: &[&str] = &[
     "📁", "📂", "🗂️", "📚", "📖", "📝", "✏️", "🎨", "🎯", "💡",
@@ -16,8 +102,81 @@ const FOLDER_EMOJIS: &[&str] = &[
     "🏠", "🏢", "🏗️", "🏭", "🌟", "⭐", "🌙", "☀️", "🌈", "🍀",
 ];
 
+/// Kinds of items that can be tagged, each with its own join table
+#[derive(Debug, Clone, Copy)]
+enum TaggedKind {
+    Snippet,
+    File,
+    Clipboard,
+}
+
+impl TaggedKind {
+    const ALL: [TaggedKind; 3] = [TaggedKind::Snippet, TaggedKind::File, TaggedKind::Clipboard];
+
+    fn join_table(self) -> &'static str {
+        match self {
+            TaggedKind::Snippet => "snippet_tags",
+            TaggedKind::File => "file_tags",
+            TaggedKind::Clipboard => "clipboard_tags",
+        }
+    }
+
+    fn item_table(self) -> &'static str {
+        match self {
+            TaggedKind::Snippet => "snippets",
+            TaggedKind::File => "files",
+            TaggedKind::Clipboard => "clipboard_history",
+        }
+    }
+
+    /// Snippets and files also keep their tags as a string column
+    fn has_tag_string(self) -> bool {
+        !matches!(self, TaggedKind::Clipboard)
+    }
+}
+
+/// A write transaction that nests: inside an open transaction it is a
+/// savepoint, so a `Database` method can run within `Database::transaction`.
+/// It rolls back when dropped without `commit`.
+pub struct Transaction<'a> {
+    conn: &'a Connection,
+    nested: bool,
+    done: bool,
+}
+
+impl Transaction<'_> {
+    pub fn commit(mut self) -> Result<()> {
+        self.done = true;
+        self.conn.execute_batch(if self.nested { "RELEASE nested" } else { "COMMIT" })?;
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for Transaction<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            let rollback = if self.nested { "ROLLBACK TO nested; RELEASE nested" } else { "ROLLBACK" };
+            if let Err(e) = self.conn.execute_batch(rollback) {
+                println!("⚠️ Failed to roll back: {}", e);
+            }
+        }
+    }
+}
+
 pub struct Database {
     conn: Connection,
+    /// The database is encrypted with SQLCipher
+    encrypted: bool,
+    /// A stand-in until the library is unlocked, see `Database::locked`
+    locked: bool,
 }
 
 impl Database {
@@ -29,6 +188,13 @@ impl Database {
             .as_nanos() as usize;
         FOLDER_EMOJIS[timestamp % FOLDER_EMOJIS.len()]
     }
+    /// Starts a transaction, or a savepoint within the one already open
+    pub fn transaction(&self) -> Result<Transaction<'_>> {
+        let nested = !self.conn.is_autocommit();
+        self.conn.execute_batch(if nested { "SAVEPOINT nested" } else { "BEGIN" })?;
+        Ok(Transaction { conn: &self.conn, nested, done: false })
+    }
+
     pub fn new() -> Result<Self> {
         let db_path = Self::get_db_path()?;
         
@@ -37,15 +203,72 @@ impl Database {
             std::fs::create_dir_all(parent)?;
         }
 
+        // A plaintext database is left as it is until it's migrated
+        let encrypted = encryption::is_enabled() && !encryption::is_plaintext_database(&db_path);
+        if encrypted && !encryption::is_unlocked() {
+            return Self::locked();
+        }
+
         let conn = Connection::open(&db_path)
             .context("Failed to open database connection")?;
+        if encrypted {
+            encryption::apply_key(&conn)?;
+        }
+
+        let db = Database { conn, encrypted, locked: false };
+        db.initialize()?;
+        Ok(db)
+    }
+
+    /// A fresh database that only lives in memory
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self> {
+        let db = Database { conn: Connection::open_in_memory()?, encrypted: false, locked: false };
+        db.initialize()?;
+        Ok(db)
+    }
 
-        let db = Database { conn };
+    /// An empty, read-only database standing in for an encrypted library
+    /// until its passphrase is entered
+    fn locked() -> Result<Self> {
+        let db = Database { conn: Connection::open_in_memory()?, encrypted: true, locked: true };
         db.initialize()?;
+        db.conn.pragma_update(None, "query_only", true)?;
+        println!("🔒 The library is locked until its passphrase is entered");
         Ok(db)
     }
 
-    fn get_db_path() -> Result<PathBuf> {
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /// Encrypt a plaintext database with SQLCipher: export it to an encrypted
+    /// copy, which then replaces it
+    pub fn encrypt(&mut self) -> Result<()> {
+        if self.encrypted {
+            return Ok(());
+        }
+        let db_path = Self::get_db_path()?;
+        let temp = db_path.with_extension("db.encrypting");
+        encryption::export_encrypted(&self.conn, &temp)?;
+
+        // Close the plaintext database before replacing it
+        drop(std::mem::replace(&mut self.conn, Connection::open_in_memory()?));
+        std::fs::rename(&temp, &db_path).context("Failed to replace the database with the encrypted one")?;
+
+        let conn = Connection::open(&db_path).context("Failed to open database connection")?;
+        encryption::apply_key(&conn)?;
+        self.conn = conn;
+        self.encrypted = true;
+        println!("🔐 Encrypted the database");
+        self.initialize()
+    }
+
+    pub(crate) fn get_db_path() -> Result<PathBuf> {
         let app_data = dirs::data_local_dir()
             .context("Failed to get app data directory")?
             .join("nobraindev");
@@ -54,13 +277,93 @@ impl Database {
         Ok(app_data.join("nobraindev.db"))
     }
 
+    fn row_to_folder(row: &rusqlite::Row) -> rusqlite::Result<Folder> {
+        // A damaged search must not turn into one that matches everything
+        let (query, query_error) = match row.get::<_, Option<String>>(8)? {
+            Some(json) => match serde_json::from_str::<SmartFolderQuery>(&json) {
+                Ok(query) => (Some(query), None),
+                Err(e) => (None, Some(format!("The saved search can't be read ({}). Edit the folder to replace it", e))),
+            },
+            None => (None, None),
+        };
+
+        Ok(Folder {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            icon: row.get(2)?,
+            parent_id: row.get(3)?,
+            sort_order: row.get(4)?,
+            kind: if query.is_some() || query_error.is_some() { FolderKind::Smart } else { FolderKind::Manual },
+            query,
+            query_error,
+            created_at: row.get(5)?,
+            updated_at: row.get(6)?,
+            deleted_at: row.get(7)?,
+            snippet_count: row.get(9)?,
+        })
+    }
+
+    /// Tags are filled in separately by `load_clipboard_tags`
+    fn row_to_clipboard_entry(row: &rusqlite::Row) -> rusqlite::Result<ClipboardEntry> {
+        Ok(ClipboardEntry {
+            id: Some(row.get(0)?),
+            content: row.get(1)?,
+            source: row.get(2)?,
+            category: row.get(3)?,
+            created_at: row.get(4)?,
+            pinned: row.get(5)?,
+            pin_order: row.get(6)?,
+            favorite: row.get(7)?,
+            tags: Vec::new(),
+        })
+    }
+
+    fn row_to_file(row: &rusqlite::Row) -> rusqlite::Result<File> {
+        Ok(File {
+            id: Some(row.get(0)?),
+            filename: row.get(1)?,
+            file_type: row.get(2)?,
+            file_size: row.get(3)?,
+            folder_id: row.get(4)?,
+            storage_path: row.get(5)?,
+            cloud_storage_path: row.get(6)?,
+            mime_type: row.get(7)?,
+            description: row.get(8)?,
+            tags: row.get(9)?,
+            created_at: row.get(10)?,
+            updated_at: row.get(11)?,
+            deleted_at: row.get(12)?,
+        })
+    }
+
+    /// A secret snippet's content is masked; see `get_secret_content`
+    fn row_to_snippet(row: &rusqlite::Row) -> rusqlite::Result<Snippet> {
+        let secret: bool = row.get(11)?;
+        Ok(Snippet {
+            id: Some(row.get(0)?),
+            title: row.get(1)?,
+            content: if secret { SECRET_MASK.to_string() } else { row.get(2)? },
+            language: row.get(3)?,
+            description: row.get(4)?,
+            tags: row.get(5)?,
+            folder_id: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+            trigger: row.get(9)?,
+            deleted_at: row.get(10)?,
+            secret,
+        })
+    }
+
     fn initialize(&self) -> Result<()> {
         // Create folders table first (before snippets, since snippets references it)
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS folders (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
                 icon TEXT,
+                parent_id INTEGER REFERENCES folders(id),
+                sort_order INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )",
@@ -129,6 +432,17 @@ impl Database {
             [],
         )?;
 
+        // Migrate: Add text expansion trigger to snippets
+        let has_trigger: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('snippets') WHERE name = 'trigger_keyword'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if !has_trigger {
+            self.conn.execute("ALTER TABLE snippets ADD COLUMN trigger_keyword TEXT", [])?;
+        }
+
         // Only create folder index if folder_id exists
         let folder_id_exists: std::result::Result<bool, _> = self.conn.query_row(
             "SELECT COUNT(*) > 0 FROM pragma_table_info('snippets') WHERE name = 'folder_id'",
@@ -154,6 +468,27 @@ impl Database {
             [],
         )?;
 
+        // Create snippet history table (previous versions, written on every update)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS snippet_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                snippet_id INTEGER NOT NULL REFERENCES snippets(id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                language TEXT NOT NULL,
+                description TEXT,
+                tags TEXT,
+                saved_at TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_snippet_revisions_snippet ON snippet_revisions(snippet_id, id DESC)",
+            [],
+        )?;
+
         // Create clipboard history table
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS clipboard_history (
@@ -172,6 +507,58 @@ impl Database {
             [],
         )?;
 
+        // Migrate: Add pinned to clipboard history (pinned entries are never pruned)
+        let has_pinned: std::result::Result<bool, rusqlite::Error> = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('clipboard_history') WHERE name = 'pinned'",
+            [],
+            |row| row.get(0)
+        );
+
+        if has_pinned.is_err() || !has_pinned.unwrap_or(false) {
+            let _ = self.conn.execute(
+                "ALTER TABLE clipboard_history ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Migrate: Add pin_order so pinned entries keep a user-defined order
+        let has_pin_order: std::result::Result<bool, rusqlite::Error> = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('clipboard_history') WHERE name = 'pin_order'",
+            [],
+            |row| row.get(0)
+        );
+
+        if has_pin_order.is_err() || !has_pin_order.unwrap_or(false) {
+            let _ = self.conn.execute(
+                "ALTER TABLE clipboard_history ADD COLUMN pin_order INTEGER",
+                [],
+            );
+        }
+
+        // Migrate: Add favorite to clipboard history (favorites are never pruned)
+        let has_favorite: std::result::Result<bool, rusqlite::Error> = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('clipboard_history') WHERE name = 'favorite'",
+            [],
+            |row| row.get(0)
+        );
+
+        if has_favorite.is_err() || !has_favorite.unwrap_or(false) {
+            let _ = self.conn.execute(
+                "ALTER TABLE clipboard_history ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0",
+                [],
+            );
+        }
+
+        // Create settings table (JSON values keyed by setting name)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         // Create files table
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS files (
@@ -196,196 +583,641 @@ impl Database {
             [],
         )?;
 
+        // Migrate: Trash. `deleted_at` marks an item as in the trash; snippets and
+        // files of a trashed folder remember it in `trashed_folder_id`
+        for table in ["snippets", "folders", "files"] {
+            self.add_column_if_missing(table, "deleted_at", "TEXT")?;
+        }
+        for table in ["snippets", "files"] {
+            self.add_column_if_missing(table, "trashed_folder_id", "INTEGER")?;
+        }
+
+        // Only live snippets claim their trigger, so one in the trash can't
+        // block it. Replaces the older index that covered trashed snippets too.
+        self.conn.execute_batch(
+            "DROP INDEX IF EXISTS idx_snippets_trigger;
+             CREATE UNIQUE INDEX IF NOT EXISTS idx_snippets_live_trigger
+                 ON snippets(trigger_keyword) WHERE trigger_keyword IS NOT NULL AND deleted_at IS NULL;",
+        )?;
+
+        self.migrate_nested_folders()?;
+        self.migrate_tags()?;
+
+        // Migrate: Smart folders keep their saved search as JSON
+        self.add_column_if_missing("folders", "smart_query", "TEXT")?;
+
+        // Migrate: Secret snippets, whose content is encrypted
+        self.add_column_if_missing("snippets", "secret", "INTEGER NOT NULL DEFAULT 0")?;
+
+        // Markdown vault: the file each snippet was last synced to, and a hash
+        // of that file's contents at the time
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS vault_files (
+                snippet_id INTEGER PRIMARY KEY,
+                path TEXT NOT NULL,
+                hash TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        self.migrate_sync_journal()?;
+
+        Ok(())
+    }
+
+    /// Migrate: The sync change journal. Triggers note every record that's
+    /// written, unless the write comes from sync itself (`sync_applying` has a
+    /// row then). Everything already in the library is noted once, the first
+    /// time the tables are created.
+    fn migrate_sync_journal(&self) -> Result<()> {
+        let has_journal: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'sync_records'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sync_records (
+                uuid TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                local_id INTEGER NOT NULL,
+                hlc TEXT NOT NULL,
+                versions TEXT NOT NULL,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                dirty INTEGER NOT NULL DEFAULT 0,
+                blob TEXT,
+                UNIQUE (kind, local_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_sync_records_dirty ON sync_records(dirty) WHERE dirty = 1;
+            CREATE TABLE IF NOT EXISTS sync_journal (
+                kind TEXT NOT NULL,
+                local_id INTEGER NOT NULL,
+                PRIMARY KEY (kind, local_id)
+            );
+            CREATE TABLE IF NOT EXISTS sync_applying (active INTEGER NOT NULL);
+            DELETE FROM sync_applying;",
+        )?;
+
+        let journaled = [
+            ("snippets", SyncRecordKind::Snippet, "id"),
+            ("folders", SyncRecordKind::Folder, "id"),
+            ("clipboard_history", SyncRecordKind::ClipboardEntry, "id"),
+            ("files", SyncRecordKind::File, "id"),
+            ("clipboard_tags", SyncRecordKind::ClipboardEntry, "item_id"),
+        ];
+        for (table, kind, id_column) in journaled {
+            for (event, row) in [("insert", "NEW"), ("update", "NEW"), ("delete", "OLD")] {
+                self.conn.execute_batch(&format!(
+                    "CREATE TRIGGER IF NOT EXISTS sync_journal_{table}_{event}
+                     AFTER {event} ON {table}
+                     WHEN NOT EXISTS (SELECT 1 FROM sync_applying)
+                     BEGIN
+                         INSERT OR IGNORE INTO sync_journal (kind, local_id) VALUES ('{kind}', {row}.{id_column});
+                     END;",
+                    kind = kind.as_str(),
+                ))?;
+            }
+        }
+
+        if !has_journal {
+            self.journal_library_for_sync()?;
+        }
+
+        Ok(())
+    }
+
+    /// Migrate: Normalized tags shared by snippets, files and clipboard entries.
+    /// The `tags` strings of snippets and files are parsed once when the tables
+    /// are first created, and kept in sync with the join tables afterwards.
+    fn migrate_tags(&self) -> Result<()> {
+        let has_tags: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'tags'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let tx = self.transaction()?;
+
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                color TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        for kind in TaggedKind::ALL {
+            tx.execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {table} (
+                    item_id INTEGER NOT NULL,
+                    tag_id INTEGER NOT NULL REFERENCES tags(id),
+                    position INTEGER NOT NULL DEFAULT 0,
+                    PRIMARY KEY (item_id, tag_id)
+                );
+                CREATE INDEX IF NOT EXISTS idx_{table}_tag ON {table}(tag_id);",
+                table = kind.join_table()
+            ))?;
+        }
+
+        if !has_tags {
+            let mut migrated = 0;
+            for kind in [TaggedKind::Snippet, TaggedKind::File] {
+                let table = kind.item_table();
+                let mut stmt = tx.prepare(&format!("SELECT id, tags FROM {} WHERE tags IS NOT NULL", table))?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                drop(stmt);
+
+                for (id, raw) in rows {
+                    let names = self.canonical_tag_names(tags::parse_tags(&raw))?;
+                    self.set_item_tags(kind, id, &names)?;
+                    tx.execute(
+                        &format!("UPDATE {} SET tags = ?1 WHERE id = ?2", table),
+                        params![tags::format_tags(&names), id],
+                    )?;
+                    migrated += 1;
+                }
+            }
+            if migrated > 0 {
+                println!("✅ Migrated tags of {} snippets and files", migrated);
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Migrate: Nested folders. Names used to be globally UNIQUE, which SQLite
+    /// can't drop in place, so older tables are rebuilt once. Names only have
+    /// to be unique among the live folders sharing a parent.
+    fn migrate_nested_folders(&self) -> Result<()> {
+        let has_parent_id: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('folders') WHERE name = 'parent_id'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if !has_parent_id {
+            let tx = self.transaction()?;
+            tx.execute_batch(
+                "CREATE TABLE folders_nested (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    icon TEXT,
+                    parent_id INTEGER REFERENCES folders(id),
+                    sort_order INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    deleted_at TEXT
+                );
+                INSERT INTO folders_nested (id, name, icon, sort_order, created_at, updated_at, deleted_at)
+                    SELECT id, name, icon, ROW_NUMBER() OVER (ORDER BY name COLLATE NOCASE), created_at, updated_at, deleted_at
+                    FROM folders;
+                DROP TABLE folders;
+                ALTER TABLE folders_nested RENAME TO folders;
+                CREATE INDEX IF NOT EXISTS idx_folders_name ON folders(name);",
+            )?;
+            tx.commit()?;
+            println!("✅ Migrated folders to a nested hierarchy");
+        }
+
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_folders_parent ON folders(parent_id);
+             CREATE UNIQUE INDEX IF NOT EXISTS idx_folders_sibling_name
+                 ON folders(COALESCE(parent_id, 0), name) WHERE deleted_at IS NULL;",
+        )?;
+
+        Ok(())
+    }
+
+    /// SQLite doesn't support IF NOT EXISTS for ALTER TABLE, so check the schema first
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: bool = self.conn.query_row(
+            &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1", table),
+            params![column],
+            |row| row.get(0),
+        )?;
+
+        if !exists {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                [],
+            )?;
+        }
+
         Ok(())
     }
 
     pub fn create_snippet(&self, snippet: &Snippet) -> Result<i64> {
         let now = Utc::now().to_rfc3339();
+        self.ensure_assignable_folder(snippet.folder_id)?;
+        let tag_names = self.canonical_tag_names(tags::parse_tags(snippet.tags.as_deref().unwrap_or_default()))?;
+        let tx = self.transaction()?;
 
-        self.conn.execute(
-            "INSERT INTO snippets (title, content, language, description, tags, folder_id, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        tx.execute(
+            "INSERT INTO snippets (title, content, language, description, tags, folder_id, created_at, updated_at, trigger_keyword, secret)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 snippet.title,
                 snippet.content,
                 snippet.language,
                 snippet.description,
-                snippet.tags,
+                tags::format_tags(&tag_names),
                 snippet.folder_id,
                 now,
-                now
+                now,
+                snippet.trigger,
+                snippet.secret
             ],
         )?;
 
-        Ok(self.conn.last_insert_rowid())
+        let id = tx.last_insert_rowid();
+        self.set_item_tags(TaggedKind::Snippet, id, &tag_names)?;
+
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Keep an imported snippet's dates from the app it came from
+    pub fn set_snippet_timestamps(&self, id: i64, created_at: &str, updated_at: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE snippets SET created_at = ?1, updated_at = ?2 WHERE id = ?3",
+            params![created_at, updated_at, id],
+        )?;
+        Ok(())
+    }
+
+    /// Whether a live snippet has exactly this title, content and language
+    pub fn snippet_exists(&self, title: &str, content: &str, language: &str) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM snippets
+             WHERE title = ?1 AND content = ?2 AND language = ?3 AND deleted_at IS NULL",
+            params![title, content, language],
+            |row| row.get(0),
+        )?)
     }
 
     pub fn get_snippet(&self, id: i64) -> Result<Option<Snippet>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, language, description, tags, folder_id, created_at, updated_at
-             FROM snippets WHERE id = ?1"
-        )?;
-
-        let snippet = stmt.query_row(params![id], |row| {
-            Ok(Snippet {
-                id: Some(row.get(0)?),
-                title: row.get(1)?,
-                content: row.get(2)?,
-                language: row.get(3)?,
-                description: row.get(4)?,
-                tags: row.get(5)?,
-                folder_id: row.get(6)?,
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        }).optional()?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM snippets WHERE id = ?1",
+            SNIPPET_COLUMNS
+        ))?;
+
+        let snippet = stmt.query_row(params![id], Self::row_to_snippet).optional()?;
 
         Ok(snippet)
     }
 
     pub fn get_all_snippets(&self) -> Result<Vec<Snippet>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, language, description, tags, folder_id, created_at, updated_at
-             FROM snippets ORDER BY updated_at DESC"
-        )?;
-
-        let snippets = stmt.query_map([], |row| {
-            Ok(Snippet {
-                id: Some(row.get(0)?),
-                title: row.get(1)?,
-                content: row.get(2)?,
-                language: row.get(3)?,
-                description: row.get(4)?,
-                tags: row.get(5)?,
-                folder_id: row.get(6)?,
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM snippets WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+            SNIPPET_COLUMNS
+        ))?;
+
+        let snippets = stmt
+            .query_map([], Self::row_to_snippet)?
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(snippets)
     }
 
+    /// Update a snippet, first moving the version it replaces into history.
+    /// Updates that leave title, content, language, description and tags as
+    /// they were (e.g. moving folders) don't create a revision. Secret
+    /// snippets keep no history or embedding.
     pub fn update_snippet(&self, id: i64, snippet: &Snippet) -> Result<()> {
         let now = Utc::now().to_rfc3339();
+        self.ensure_assignable_folder(snippet.folder_id)?;
+        let tag_names = self.canonical_tag_names(tags::parse_tags(snippet.tags.as_deref().unwrap_or_default()))?;
+        let tag_string = tags::format_tags(&tag_names);
+        let tx = self.transaction()?;
+
+        let archived = tx.execute(
+            "INSERT INTO snippet_revisions (snippet_id, title, content, language, description, tags, saved_at, created_at)
+             SELECT id, title, content, language, description, tags, updated_at, ?2
+             FROM snippets
+             WHERE id = ?1 AND secret = 0 AND NOT ?8
+               AND NOT (title IS ?3 AND content IS ?4 AND language IS ?5 AND description IS ?6 AND tags IS ?7)",
+            params![
+                id,
+                now,
+                snippet.title,
+                snippet.content,
+                snippet.language,
+                snippet.description,
+                tag_string,
+                snippet.secret
+            ],
+        )?;
 
-        self.conn.execute(
+        if snippet.secret {
+            // Earlier versions would give the secret away
+            tx.execute("DELETE FROM snippet_revisions WHERE snippet_id = ?1", params![id])?;
+            tx.execute("DELETE FROM embeddings WHERE snippet_id = ?1", params![id])?;
+        }
+
+        tx.execute(
             "UPDATE snippets
-             SET title = ?1, content = ?2, language = ?3, description = ?4, tags = ?5, folder_id = ?6, updated_at = ?7
-             WHERE id = ?8",
+             SET title = ?1, content = ?2, language = ?3, description = ?4, tags = ?5, folder_id = ?6, updated_at = ?7,
+                 trigger_keyword = ?8, secret = ?10
+             WHERE id = ?9",
             params![
                 snippet.title,
                 snippet.content,
                 snippet.language,
                 snippet.description,
-                snippet.tags,
+                tag_string,
                 snippet.folder_id,
                 now,
-                id
+                snippet.trigger,
+                id,
+                snippet.secret
             ],
         )?;
+        self.set_item_tags(TaggedKind::Snippet, id, &tag_names)?;
+
+        if archived > 0 {
+            let retention = self.get_revision_retention()?;
+            self.prune_revisions(Some(id), &retention)?;
+        }
 
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn delete_snippet(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM snippets WHERE id = ?1", params![id])?;
-        Ok(())
+    /// The encrypted content of a secret snippet; `None` if it isn't one
+    pub fn get_secret_content(&self, id: i64) -> Result<Option<String>> {
+        Ok(self.conn
+            .query_row(
+                "SELECT content FROM snippets WHERE id = ?1 AND secret = 1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?)
     }
 
-    pub fn search_snippets(&self, query: &str) -> Result<Vec<Snippet>> {
-        let search_pattern = format!("%{}%", query);
+    // Snippet revision history
+
+    fn row_to_revision(row: &rusqlite::Row) -> rusqlite::Result<SnippetRevision> {
+        Ok(SnippetRevision {
+            id: row.get(0)?,
+            snippet_id: row.get(1)?,
+            title: row.get(2)?,
+            content: row.get(3)?,
+            language: row.get(4)?,
+            description: row.get(5)?,
+            tags: row.get(6)?,
+            saved_at: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    }
 
+    /// Previous versions of a snippet, newest first
+    pub fn get_snippet_revisions(&self, snippet_id: i64) -> Result<Vec<SnippetRevision>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, language, description, tags, folder_id, created_at, updated_at
-             FROM snippets
-             WHERE title LIKE ?1
-                OR content LIKE ?1
-                OR description LIKE ?1
-                OR tags LIKE ?1
-             ORDER BY updated_at DESC"
-        )?;
-
-        let snippets = stmt.query_map(params![search_pattern], |row| {
-            Ok(Snippet {
-                id: Some(row.get(0)?),
-                title: row.get(1)?,
-                content: row.get(2)?,
-                language: row.get(3)?,
-                description: row.get(4)?,
-                tags: row.get(5)?,
-                folder_id: row.get(6)?,
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+            "SELECT id, snippet_id, title, content, language, description, tags, saved_at, created_at
+             FROM snippet_revisions
+             WHERE snippet_id = ?1
+             ORDER BY id DESC"
+        )?;
 
-        Ok(snippets)
+        let revisions = stmt
+            .query_map(params![snippet_id], Self::row_to_revision)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(revisions)
     }
 
-    // Folder CRUD methods
-    pub fn create_folder(&self, name: &str, icon: Option<&str>) -> Result<i64> {
-        let now = Utc::now().to_rfc3339();
-        let folder_icon = icon.unwrap_or_else(|| Self::get_random_emoji());
+    pub fn get_snippet_revision(&self, id: i64) -> Result<Option<SnippetRevision>> {
+        let revision = self.conn
+            .query_row(
+                "SELECT id, snippet_id, title, content, language, description, tags, saved_at, created_at
+                 FROM snippet_revisions
+                 WHERE id = ?1",
+                params![id],
+                Self::row_to_revision,
+            )
+            .optional()?;
 
-        self.conn.execute(
-            "INSERT INTO folders (name, icon, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![name, folder_icon, now, now],
-        )?;
+        Ok(revision)
+    }
 
-        Ok(self.conn.last_insert_rowid())
+    pub fn get_revision_retention(&self) -> Result<RevisionRetention> {
+        Ok(self.get_setting(REVISION_RETENTION_KEY)?.unwrap_or_default())
     }
 
-    pub fn get_all_folders(&self) -> Result<Vec<Folder>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, icon, created_at, updated_at
-             FROM folders ORDER BY name"
-        )?;
+    pub fn set_revision_retention(&self, retention: &RevisionRetention) -> Result<()> {
+        self.set_setting(REVISION_RETENTION_KEY, retention)
+    }
 
-        let folders = stmt.query_map([], |row| {
-            Ok(Folder {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                icon: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
-                snippet_count: None,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    /// Drop revisions outside `retention`, for one snippet or (with `None`) all of them.
+    /// Returns how many were removed.
+    pub fn prune_revisions(&self, snippet_id: Option<i64>, retention: &RevisionRetention) -> Result<usize> {
+        let mut removed = 0;
+
+        if let Some(max_revisions) = retention.max_revisions {
+            removed += self.conn.execute(
+                "DELETE FROM snippet_revisions WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, ROW_NUMBER() OVER (PARTITION BY snippet_id ORDER BY id DESC) AS position
+                        FROM snippet_revisions
+                        WHERE ?1 IS NULL OR snippet_id = ?1
+                    )
+                    WHERE position > ?2
+                )",
+                params![snippet_id, max_revisions.max(0)],
+            )?;
+        }
 
-        Ok(folders)
+        if let Some(max_age_days) = retention.max_age_days {
+            let cutoff = (Utc::now() - chrono::Duration::days(max_age_days.max(0))).to_rfc3339();
+            removed += self.conn.execute(
+                "DELETE FROM snippet_revisions WHERE (?1 IS NULL OR snippet_id = ?1) AND created_at < ?2",
+                params![snippet_id, cutoff],
+            )?;
+        }
+
+        Ok(removed)
     }
 
-    pub fn get_folder(&self, id: i64) -> Result<Option<Folder>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT f.id, f.name, f.icon, f.created_at, f.updated_at, COUNT(s.id) as snippet_count
-             FROM folders f
-             LEFT JOIN snippets s ON s.folder_id = f.id
-             WHERE f.id = ?1
-             GROUP BY f.id"
+    /// Keyword search. `tag:name` words in the query and `tag_filter` both
+    /// require an exact tag; a snippet must have all of them.
+    pub fn search_snippets(&self, query: &str, tag_filter: &[String]) -> Result<Vec<Snippet>> {
+        let (text, query_tags) = tags::split_tag_filters(query);
+        let tag_names = tags::normalize_tags(query_tags.iter().chain(tag_filter).map(String::as_str));
+        let search_pattern = format!("%{}%", text);
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM snippets
+             WHERE deleted_at IS NULL
+               AND (title LIKE ?1
+                OR (secret = 0 AND content LIKE ?1)
+                OR description LIKE ?1
+                OR tags LIKE ?1
+                OR trigger_keyword LIKE ?1)
+               AND {}
+             ORDER BY updated_at DESC",
+            SNIPPET_COLUMNS,
+            Self::tag_filter_sql(TaggedKind::Snippet, tag_names.len(), 2)
+        ))?;
+
+        let snippets = stmt
+            .query_map(
+                rusqlite::params_from_iter(std::iter::once(search_pattern).chain(tag_names)),
+                Self::row_to_snippet,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(snippets)
+    }
+
+    // Folder CRUD methods
+    pub fn create_folder(&self, name: &str, icon: Option<&str>, parent_id: Option<i64>) -> Result<i64> {
+        self.insert_folder(name, icon, parent_id, None)
+    }
+
+    /// Create a smart folder showing the snippets that match `query`
+    pub fn create_smart_folder(
+        &self,
+        name: &str,
+        icon: Option<&str>,
+        parent_id: Option<i64>,
+        query: &SmartFolderQuery,
+    ) -> Result<i64> {
+        self.insert_folder(name, icon, parent_id, Some(query))
+    }
+
+    fn insert_folder(
+        &self,
+        name: &str,
+        icon: Option<&str>,
+        parent_id: Option<i64>,
+        query: Option<&SmartFolderQuery>,
+    ) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        let folder_icon = icon.unwrap_or_else(|| Self::get_random_emoji());
+        let smart_query = query.map(serde_json::to_string).transpose()?;
+
+        if let Some(parent_id) = parent_id {
+            self.ensure_parent_folder(parent_id)?;
+        }
+        self.ensure_folder_name_available(name, parent_id, None)?;
+
+        self.conn.execute(
+            "INSERT INTO folders (name, icon, parent_id, sort_order, smart_query, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![name, folder_icon, parent_id, self.next_folder_sort_order(parent_id)?, smart_query, now, now],
         )?;
 
-        let folder = stmt.query_row(params![id], |row| {
-            Ok(Folder {
-                id: Some(row.get(0)?),
-                name: row.get(1)?,
-                icon: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
-                snippet_count: Some(row.get(5)?),
-            })
-        }).optional()?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Replace the saved search of a smart folder
+    pub fn update_smart_folder(&self, id: i64, query: &SmartFolderQuery) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE folders SET smart_query = ?1, updated_at = ?2 WHERE id = ?3 AND smart_query IS NOT NULL",
+            params![serde_json::to_string(query)?, Utc::now().to_rfc3339(), id],
+        )?;
+        if updated == 0 {
+            anyhow::bail!("Not a smart folder");
+        }
+        Ok(())
+    }
+
+    /// All live folders as a flat list, ordered by position among their siblings.
+    /// `snippet_count` includes subfolders when `include_descendants` is set.
+    /// Smart folders are left without a count; their saved search decides it.
+    pub fn get_all_folders(&self, include_descendants: bool) -> Result<Vec<Folder>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, (SELECT COUNT(*) FROM snippets s WHERE s.folder_id = f.id AND s.deleted_at IS NULL)
+             FROM folders f WHERE f.deleted_at IS NULL ORDER BY f.sort_order, f.name COLLATE NOCASE",
+            FOLDER_COLUMNS
+        ))?;
+
+        let mut folders = stmt
+            .query_map([], Self::row_to_folder)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if include_descendants {
+            let parents: HashMap<i64, Option<i64>> = folders
+                .iter()
+                .filter_map(|f| Some((f.id?, f.parent_id)))
+                .collect();
+            let mut totals: HashMap<i64, i64> = HashMap::new();
+
+            for folder in &folders {
+                let (Some(id), Some(count)) = (folder.id, folder.snippet_count) else { continue };
+                // Add each folder's own count to itself and every ancestor
+                let mut current = Some(id);
+                let mut depth = 0;
+                while let Some(folder_id) = current {
+                    *totals.entry(folder_id).or_default() += count;
+                    current = parents.get(&folder_id).copied().flatten();
+                    depth += 1;
+                    if depth > parents.len() {
+                        break; // Guard against a corrupted parent cycle
+                    }
+                }
+            }
+
+            for folder in &mut folders {
+                folder.snippet_count = folder.id.and_then(|id| totals.get(&id).copied());
+            }
+        }
+
+        for folder in folders.iter_mut().filter(|f| f.kind == FolderKind::Smart) {
+            folder.snippet_count = None;
+        }
+
+        Ok(folders)
+    }
 
-        Ok(folder)
+    pub fn get_folder(&self, id: i64, include_descendants: bool) -> Result<Option<Folder>> {
+        let folder = if include_descendants {
+            self.conn.query_row(
+                &format!(
+                    "{} SELECT {}, (SELECT COUNT(*) FROM snippets s WHERE s.folder_id IN subtree AND s.deleted_at IS NULL)
+                     FROM folders f WHERE f.id = ?1",
+                    FOLDER_SUBTREE, FOLDER_COLUMNS
+                ),
+                params![id, None::<String>],
+                Self::row_to_folder,
+            )
+        } else {
+            self.conn.query_row(
+                &format!(
+                    "SELECT {}, (SELECT COUNT(*) FROM snippets s WHERE s.folder_id = f.id AND s.deleted_at IS NULL)
+                     FROM folders f WHERE f.id = ?1",
+                    FOLDER_COLUMNS
+                ),
+                params![id],
+                Self::row_to_folder,
+            )
+        }
+        .optional()?;
+
+        // Smart folders have no stored snippets; their count comes from their query
+        Ok(folder.map(|mut folder| {
+            if folder.kind == FolderKind::Smart {
+                folder.snippet_count = None;
+            }
+            folder
+        }))
     }
 
     pub fn update_folder(&self, id: i64, name: &str, icon: Option<&str>) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
+        let parent_id: Option<i64> = self.conn.query_row(
+            "SELECT parent_id FROM folders WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ).optional()?.context("Folder not found")?;
+        self.ensure_folder_name_available(name, parent_id, Some(id))?;
+
         if let Some(icon_str) = icon {
             self.conn.execute(
                 "UPDATE folders SET name = ?1, icon = ?2, updated_at = ?3 WHERE id = ?4",
@@ -401,56 +1233,176 @@ impl Database {
         Ok(())
     }
 
-    pub fn delete_folder(&self, id: i64) -> Result<()> {
-        // Set all snippets in this folder to uncategorized (folder_id = NULL)
+    /// Move a folder (with its subfolders) under `parent_id`, or to the top
+    /// level with `None`. It is placed after its new siblings.
+    pub fn move_folder(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+        let folder = self.get_folder(id, false)?.context("Folder not found")?;
+        if folder.deleted_at.is_some() {
+            anyhow::bail!("Folder is in the trash");
+        }
+        if folder.parent_id == parent_id {
+            return Ok(());
+        }
+
+        if let Some(parent_id) = parent_id {
+            self.ensure_parent_folder(parent_id)?;
+            if self.folder_subtree(id, None)?.contains(&parent_id) {
+                anyhow::bail!("A folder can't be moved into itself or one of its subfolders");
+            }
+        }
+        self.ensure_folder_name_available(&folder.name, parent_id, Some(id))?;
+
         self.conn.execute(
-            "UPDATE snippets SET folder_id = NULL WHERE folder_id = ?1",
-            params![id],
+            "UPDATE folders SET parent_id = ?1, sort_order = ?2, updated_at = ?3 WHERE id = ?4",
+            params![parent_id, self.next_folder_sort_order(parent_id)?, Utc::now().to_rfc3339(), id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Set the order of the folders directly under `parent_id`. `folder_ids`
+    /// must all be children of that parent; siblings left out keep their place after them.
+    pub fn reorder_folders(&self, parent_id: Option<i64>, folder_ids: &[i64]) -> Result<()> {
+        let tx = self.transaction()?;
+
+        for (position, id) in folder_ids.iter().enumerate() {
+            let updated = tx.execute(
+                "UPDATE folders SET sort_order = ?1 WHERE id = ?2 AND parent_id IS ?3 AND deleted_at IS NULL",
+                params![position as i64, id, parent_id],
+            )?;
+            if updated == 0 {
+                anyhow::bail!("Folder {} is not in the folder being reordered", id);
+            }
+        }
+
+        let mut stmt = tx.prepare(
+            "SELECT id FROM folders WHERE parent_id IS ?1 AND deleted_at IS NULL ORDER BY sort_order, name COLLATE NOCASE"
         )?;
+        let others: Vec<i64> = stmt
+            .query_map(params![parent_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|id| !folder_ids.contains(id))
+            .collect();
+        drop(stmt);
 
-        // Then delete the folder
-        self.conn.execute("DELETE FROM folders WHERE id = ?1", params![id])?;
+        for (offset, id) in others.iter().enumerate() {
+            tx.execute(
+                "UPDATE folders SET sort_order = ?1 WHERE id = ?2",
+                params![(folder_ids.len() + offset) as i64, id],
+            )?;
+        }
 
+        tx.commit()?;
         Ok(())
     }
 
-    // Snippet-folder relationship methods
-    pub fn get_snippets_by_folder(&self, folder_id: Option<i64>) -> Result<Vec<Snippet>> {
-        let sql = if folder_id.is_some() {
-            "SELECT id, title, content, language, description, tags, folder_id, created_at, updated_at
-             FROM snippets WHERE folder_id = ?1 ORDER BY updated_at DESC"
-        } else {
-            "SELECT id, title, content, language, description, tags, folder_id, created_at, updated_at
-             FROM snippets WHERE folder_id IS NULL ORDER BY updated_at DESC"
+    /// IDs of `id` and the folders below it whose `deleted_at` matches (see `FOLDER_SUBTREE`)
+    fn folder_subtree(&self, id: i64, deleted_at: Option<&str>) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare(&format!("{} SELECT id FROM subtree", FOLDER_SUBTREE))?;
+        let ids = stmt
+            .query_map(params![id, deleted_at], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Folders can only be put inside live manual folders
+    fn ensure_parent_folder(&self, id: i64) -> Result<()> {
+        let (deleted_at, smart_query): (Option<String>, Option<String>) = self.conn
+            .query_row(
+                "SELECT deleted_at, smart_query FROM folders WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .context("Parent folder not found")?;
+        if deleted_at.is_some() {
+            anyhow::bail!("Parent folder is in the trash");
+        }
+        if smart_query.is_some() {
+            anyhow::bail!("Smart folders can't contain other folders");
+        }
+        Ok(())
+    }
+
+    /// Snippets can only be assigned to manual folders
+    fn ensure_assignable_folder(&self, folder_id: Option<i64>) -> Result<()> {
+        let Some(folder_id) = folder_id else {
+            return Ok(());
         };
+        let smart: Option<bool> = self.conn
+            .query_row(
+                "SELECT smart_query IS NOT NULL FROM folders WHERE id = ?1",
+                params![folder_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if smart == Some(true) {
+            anyhow::bail!("Snippets can't be moved into a smart folder");
+        }
+        Ok(())
+    }
 
-        let mut stmt = self.conn.prepare(sql)?;
-
-        let snippet_mapper = |row: &rusqlite::Row| -> std::result::Result<Snippet, rusqlite::Error> {
-            Ok(Snippet {
-                id: Some(row.get(0)?),
-                title: row.get(1)?,
-                content: row.get(2)?,
-                language: row.get(3)?,
-                description: row.get(4)?,
-                tags: row.get(5)?,
-                folder_id: row.get(6)?,
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
+    fn ensure_folder_name_available(&self, name: &str, parent_id: Option<i64>, except_id: Option<i64>) -> Result<()> {
+        let taken: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM folders
+             WHERE name = ?1 AND parent_id IS ?2 AND deleted_at IS NULL AND id IS NOT ?3",
+            params![name, parent_id, except_id],
+            |row| row.get(0),
+        )?;
+        if taken {
+            anyhow::bail!("A folder named '{}' already exists here", name);
+        }
+        Ok(())
+    }
+
+    fn next_folder_sort_order(&self, parent_id: Option<i64>) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM folders WHERE parent_id IS ?1 AND deleted_at IS NULL",
+            params![parent_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    // Snippet-folder relationship methods
+
+    /// Snippets in a folder (optionally including its subfolders), or the
+    /// uncategorized ones with `None`
+    pub fn get_snippets_by_folder(&self, folder_id: Option<i64>, include_descendants: bool) -> Result<Vec<Snippet>> {
+        let Some(fid) = folder_id else {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT {} FROM snippets WHERE folder_id IS NULL AND deleted_at IS NULL ORDER BY updated_at DESC",
+                SNIPPET_COLUMNS
+            ))?;
+            let snippets = stmt
+                .query_map([], Self::row_to_snippet)?
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(snippets);
         };
 
-        let snippets = if let Some(fid) = folder_id {
-            stmt.query_map(params![fid], snippet_mapper)?
+        let snippets = if include_descendants {
+            let mut stmt = self.conn.prepare(&format!(
+                "{} SELECT {} FROM snippets WHERE folder_id IN subtree AND deleted_at IS NULL ORDER BY updated_at DESC",
+                FOLDER_SUBTREE, SNIPPET_COLUMNS
+            ))?;
+            let snippets = stmt.query_map(params![fid, None::<String>], Self::row_to_snippet)?
+                .collect::<Result<Vec<_>, _>>()?;
+            snippets
         } else {
-            stmt.query_map(params![], snippet_mapper)?
-        }
-        .collect::<Result<Vec<_>, _>>()?;
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT {} FROM snippets WHERE folder_id = ?1 AND deleted_at IS NULL ORDER BY updated_at DESC",
+                SNIPPET_COLUMNS
+            ))?;
+            let snippets = stmt.query_map(params![fid], Self::row_to_snippet)?
+                .collect::<Result<Vec<_>, _>>()?;
+            snippets
+        };
 
         Ok(snippets)
     }
 
     pub fn update_snippet_folder(&self, snippet_id: i64, folder_id: Option<i64>) -> Result<()> {
+        self.ensure_assignable_folder(folder_id)?;
         self.conn.execute(
             "UPDATE snippets SET folder_id = ?1 WHERE id = ?2",
             params![folder_id, snippet_id],
@@ -459,9 +1411,10 @@ impl Database {
     }
 
     pub fn organize_snippets(&self, mappings: &[(i64, Option<i64>)]) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
+        let tx = self.transaction()?;
 
         for (snippet_id, folder_id) in mappings {
+            self.ensure_assignable_folder(*folder_id)?;
             tx.execute(
                 "UPDATE snippets SET folder_id = ?1 WHERE id = ?2",
                 params![folder_id, snippet_id],
@@ -537,65 +1490,234 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 
-    pub fn get_clipboard_history(&self, limit: i64) -> Result<Vec<crate::models::ClipboardEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, source, category, created_at
-             FROM clipboard_history
-             ORDER BY created_at DESC
-             LIMIT ?1"
-        )?;
+    pub fn clipboard_content_exists(&self, content: &str) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM clipboard_history WHERE content = ?1",
+            params![content],
+            |row| row.get(0),
+        )?)
+    }
 
-        let entries = stmt.query_map(params![limit], |row| {
-            Ok(crate::models::ClipboardEntry {
-                id: Some(row.get(0)?),
-                content: row.get(1)?,
-                source: row.get(2)?,
-                category: row.get(3)?,
-                created_at: row.get(4)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    /// Pinned entries come first (in pin order), followed by the most recent ones
+    pub fn get_clipboard_history(&self, limit: i64, favorites_only: bool) -> Result<Vec<ClipboardEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM clipboard_history
+             WHERE ?2 = 0 OR favorite = 1
+             ORDER BY pinned DESC, pin_order ASC, created_at DESC
+             LIMIT ?1",
+            CLIPBOARD_COLUMNS
+        ))?;
+
+        let mut entries = stmt
+            .query_map(params![limit, favorites_only], Self::row_to_clipboard_entry)?
+            .collect::<Result<Vec<_>, _>>()?;
+        self.load_clipboard_tags(&mut entries)?;
 
         Ok(entries)
     }
 
-    pub fn get_clipboard_entry(&self, id: i64) -> Result<Option<crate::models::ClipboardEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, content, source, category, created_at
-             FROM clipboard_history WHERE id = ?1"
-        )?;
+    pub fn get_clipboard_entry(&self, id: i64) -> Result<Option<ClipboardEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM clipboard_history WHERE id = ?1",
+            CLIPBOARD_COLUMNS
+        ))?;
 
-        let entry = stmt.query_row(params![id], |row| {
-            Ok(crate::models::ClipboardEntry {
-                id: Some(row.get(0)?),
-                content: row.get(1)?,
-                source: row.get(2)?,
-                category: row.get(3)?,
-                created_at: row.get(4)?,
-            })
-        }).optional()?;
+        let mut entry = stmt.query_row(params![id], Self::row_to_clipboard_entry).optional()?;
+        self.load_clipboard_tags(entry.as_mut_slice())?;
+
+        Ok(entry)
+    }
+
+    /// Most recently captured entry, ignoring pin order
+    pub fn get_latest_clipboard_entry(&self) -> Result<Option<ClipboardEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM clipboard_history ORDER BY created_at DESC, id DESC LIMIT 1",
+            CLIPBOARD_COLUMNS
+        ))?;
+
+        let mut entry = stmt.query_row([], Self::row_to_clipboard_entry).optional()?;
+        self.load_clipboard_tags(entry.as_mut_slice())?;
 
         Ok(entry)
     }
 
+    fn load_clipboard_tags(&self, entries: &mut [ClipboardEntry]) -> Result<()> {
+        for entry in entries {
+            if let Some(id) = entry.id {
+                entry.tags = self.get_item_tags(TaggedKind::Clipboard, id)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn delete_clipboard_entry(&self, id: i64) -> Result<()> {
         self.conn.execute(
             "DELETE FROM clipboard_history WHERE id = ?1",
             params![id],
         )?;
+        self.delete_orphaned_clipboard_tags()?;
 
         Ok(())
     }
 
-    pub fn clear_clipboard_history(&self) -> Result<()> {
+    /// Delete clipboard history, keeping pinned and favorite entries unless
+    /// `include_pinned` is set
+    pub fn clear_clipboard_history(&self, include_pinned: bool) -> Result<()> {
         self.conn.execute(
-            "DELETE FROM clipboard_history",
+            "DELETE FROM clipboard_history WHERE ?1 OR (pinned = 0 AND favorite = 0)",
+            params![include_pinned],
+        )?;
+        self.delete_orphaned_clipboard_tags()?;
+
+        Ok(())
+    }
+
+    /// Foreign keys aren't enforced on this connection, so tag links of
+    /// deleted clipboard entries are removed by hand
+    fn delete_orphaned_clipboard_tags(&self) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM clipboard_tags WHERE item_id NOT IN (SELECT id FROM clipboard_history)",
             [],
         )?;
+        Ok(())
+    }
+
+    pub fn pin_clipboard_entry(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE clipboard_history
+             SET pinned = 1,
+                 pin_order = (SELECT COALESCE(MAX(pin_order), 0) + 1 FROM clipboard_history WHERE pinned = 1)
+             WHERE id = ?1 AND pinned = 0",
+            params![id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn set_clipboard_entry_favorite(&self, id: i64, favorite: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE clipboard_history SET favorite = ?2 WHERE id = ?1",
+            params![id, favorite],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn unpin_clipboard_entry(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE clipboard_history SET pinned = 0, pin_order = NULL WHERE id = ?1",
+            params![id],
+        )?;
+
+        // Close the gap so quick slots stay contiguous
+        self.reorder_pinned_clipboard_entries(&[])
+    }
+
+    /// Set the order of pinned entries. Pinned entries missing from `ids` keep
+    /// their relative order after the listed ones.
+    pub fn reorder_pinned_clipboard_entries(&self, ids: &[i64]) -> Result<()> {
+        let mut remaining: Vec<i64> = self
+            .conn
+            .prepare("SELECT id FROM clipboard_history WHERE pinned = 1 ORDER BY pin_order ASC")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        remaining.retain(|id| !ids.contains(id));
+
+        let tx = self.transaction()?;
 
+        for (index, id) in ids.iter().chain(remaining.iter()).enumerate() {
+            tx.execute(
+                "UPDATE clipboard_history SET pin_order = ?1 WHERE id = ?2 AND pinned = 1",
+                params![index as i64 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
         Ok(())
     }
 
+    // Retention settings and pruning
+
+    pub fn get_clipboard_retention(&self) -> Result<ClipboardRetentionSettings> {
+        Ok(self.get_setting(CLIPBOARD_RETENTION_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_clipboard_retention(&self, settings: &ClipboardRetentionSettings) -> Result<()> {
+        self.set_setting(CLIPBOARD_RETENTION_KEY, settings)
+    }
+
+    /// Remove clipboard entries that fall outside the retention policy of their
+    /// category. Categories without a dedicated policy share the default one, so
+    /// their entry and byte limits apply to them as a group. Pinned and favorite
+    /// entries are exempt unless `include_pinned` is set.
+    pub fn prune_clipboard_history(&self, settings: &ClipboardRetentionSettings, include_pinned: bool) -> Result<PruneReport> {
+        let now = Utc::now();
+        let mut to_delete: Vec<(i64, String, i64)> = Vec::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, category, LENGTH(CAST(content AS BLOB)), created_at
+             FROM clipboard_history
+             WHERE ?1 OR (pinned = 0 AND favorite = 0)
+             ORDER BY created_at DESC, id DESC"
+        )?;
+
+        let rows = stmt.query_map(params![include_pinned], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        // Running (entry count, byte total) per policy group, newest entries first
+        let mut usage: HashMap<Option<&str>, (i64, i64)> = HashMap::new();
+
+        for (id, category, bytes, created_at) in &rows {
+            let (group, policy): (Option<&str>, &RetentionPolicy) = match settings.category_policies.get(category) {
+                Some(policy) => (Some(category.as_str()), policy),
+                None => (None, &settings.default_policy),
+            };
+
+            let (count, total_bytes) = usage.entry(group).or_insert((0, 0));
+            *count += 1;
+            *total_bytes += bytes;
+
+            let too_many = policy.max_entries.is_some_and(|max| *count > max);
+            let too_large = policy.max_total_bytes.is_some_and(|max| *total_bytes > max);
+            let too_old = policy.max_age_days.is_some_and(|days| {
+                chrono::DateTime::parse_from_rfc3339(created_at)
+                    .map(|ts| now.signed_duration_since(ts) > chrono::Duration::days(days))
+                    .unwrap_or(false)
+            });
+
+            if too_many || too_large || too_old {
+                to_delete.push((*id, category.clone(), *bytes));
+                // Removed entries don't count against the group's limits
+                *count -= 1;
+                *total_bytes -= bytes;
+            }
+        }
+
+        let mut report = PruneReport {
+            pruned_at: now.to_rfc3339(),
+            ..Default::default()
+        };
+
+        let tx = self.transaction()?;
+        for (id, category, bytes) in to_delete {
+            tx.execute("DELETE FROM clipboard_history WHERE id = ?1", params![id])?;
+            report.removed += 1;
+            report.removed_bytes += bytes;
+            *report.removed_by_category.entry(category).or_insert(0) += 1;
+        }
+        self.delete_orphaned_clipboard_tags()?;
+        tx.commit()?;
+
+        Ok(report)
+    }
+
     pub fn update_clipboard_entry(&self, id: i64, content: &str, source: &str, category: &str, updated_at: &str) -> Result<()> {
         self.conn.execute(
             "UPDATE clipboard_history
@@ -610,7 +1732,10 @@ impl Database {
     // File CRUD methods
 
     pub fn create_file(&self, file: &File) -> Result<i64> {
-        self.conn.execute(
+        let tag_names = self.canonical_tag_names(tags::parse_tags(file.tags.as_deref().unwrap_or_default()))?;
+        let tx = self.transaction()?;
+
+        tx.execute(
             "INSERT INTO files (filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
             params![
@@ -622,100 +1747,62 @@ impl Database {
                 file.cloud_storage_path,
                 file.mime_type,
                 file.description,
-                file.tags,
+                tags::format_tags(&tag_names),
                 file.created_at,
                 file.updated_at,
             ],
         )?;
 
-        Ok(self.conn.last_insert_rowid())
+        let id = tx.last_insert_rowid();
+        self.set_item_tags(TaggedKind::File, id, &tag_names)?;
+
+        tx.commit()?;
+        Ok(id)
     }
 
     pub fn get_file(&self, id: i64) -> Result<Option<File>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at
-             FROM files WHERE id = ?1"
-        )?;
-
-        let file = stmt.query_row(params![id], |row| {
-            Ok(File {
-                id: Some(row.get(0)?),
-                filename: row.get(1)?,
-                file_type: row.get(2)?,
-                file_size: row.get(3)?,
-                folder_id: row.get(4)?,
-                storage_path: row.get(5)?,
-                cloud_storage_path: row.get(6)?,
-                mime_type: row.get(7)?,
-                description: row.get(8)?,
-                tags: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
-            })
-        }).optional()?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM files WHERE id = ?1",
+            FILE_COLUMNS
+        ))?;
+
+        let file = stmt.query_row(params![id], Self::row_to_file).optional()?;
 
         Ok(file)
     }
 
     pub fn get_all_files(&self) -> Result<Vec<File>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at
-             FROM files ORDER BY updated_at DESC"
-        )?;
-
-        let files = stmt.query_map([], |row| {
-            Ok(File {
-                id: Some(row.get(0)?),
-                filename: row.get(1)?,
-                file_type: row.get(2)?,
-                file_size: row.get(3)?,
-                folder_id: row.get(4)?,
-                storage_path: row.get(5)?,
-                cloud_storage_path: row.get(6)?,
-                mime_type: row.get(7)?,
-                description: row.get(8)?,
-                tags: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM files WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+            FILE_COLUMNS
+        ))?;
+
+        let files = stmt
+            .query_map([], Self::row_to_file)?
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(files)
     }
 
     pub fn get_files_by_folder(&self, folder_id: Option<i64>) -> Result<Vec<File>> {
         let sql = if folder_id.is_some() {
-            "SELECT id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at
-             FROM files WHERE folder_id = ?1 ORDER BY updated_at DESC"
+            format!(
+                "SELECT {} FROM files WHERE folder_id = ?1 AND deleted_at IS NULL ORDER BY updated_at DESC",
+                FILE_COLUMNS
+            )
         } else {
-            "SELECT id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at
-             FROM files WHERE folder_id IS NULL ORDER BY updated_at DESC"
+            format!(
+                "SELECT {} FROM files WHERE folder_id IS NULL AND deleted_at IS NULL ORDER BY updated_at DESC",
+                FILE_COLUMNS
+            )
         };
 
-        let mut stmt = self.conn.prepare(sql)?;
-
-        let file_mapper = |row: &rusqlite::Row| -> std::result::Result<File, rusqlite::Error> {
-            Ok(File {
-                id: Some(row.get(0)?),
-                filename: row.get(1)?,
-                file_type: row.get(2)?,
-                file_size: row.get(3)?,
-                folder_id: row.get(4)?,
-                storage_path: row.get(5)?,
-                cloud_storage_path: row.get(6)?,
-                mime_type: row.get(7)?,
-                description: row.get(8)?,
-                tags: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
-            })
-        };
+        let mut stmt = self.conn.prepare(&sql)?;
 
         let files = if let Some(fid) = folder_id {
-            stmt.query_map(params![fid], file_mapper)?
+            stmt.query_map(params![fid], Self::row_to_file)?
         } else {
-            stmt.query_map(params![], file_mapper)?
+            stmt.query_map(params![], Self::row_to_file)?
         }
         .collect::<Result<Vec<_>, _>>()?;
 
@@ -724,8 +1811,10 @@ impl Database {
 
     pub fn update_file(&self, id: i64, file: &File) -> Result<()> {
         let now = Utc::now().to_rfc3339();
+        let tag_names = self.canonical_tag_names(tags::parse_tags(file.tags.as_deref().unwrap_or_default()))?;
+        let tx = self.transaction()?;
 
-        self.conn.execute(
+        tx.execute(
             "UPDATE files
              SET filename = ?1, file_type = ?2, file_size = ?3, folder_id = ?4, storage_path = ?5, cloud_storage_path = ?6, mime_type = ?7, description = ?8, tags = ?9, updated_at = ?10
              WHERE id = ?11",
@@ -738,60 +1827,1450 @@ impl Database {
                 file.cloud_storage_path,
                 file.mime_type,
                 file.description,
-                file.tags,
+                tags::format_tags(&tag_names),
                 now,
                 id
             ],
         )?;
+        self.set_item_tags(TaggedKind::File, id, &tag_names)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Keyword search with the same exact tag filtering as `search_snippets`
+    pub fn search_files(&self, query: &str, tag_filter: &[String]) -> Result<Vec<File>> {
+        let (text, query_tags) = tags::split_tag_filters(query);
+        let tag_names = tags::normalize_tags(query_tags.iter().chain(tag_filter).map(String::as_str));
+        let search_pattern = format!("%{}%", text);
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM files
+             WHERE deleted_at IS NULL
+               AND (filename LIKE ?1
+                OR description LIKE ?1
+                OR tags LIKE ?1
+                OR file_type LIKE ?1)
+               AND {}
+             ORDER BY updated_at DESC",
+            FILE_COLUMNS,
+            Self::tag_filter_sql(TaggedKind::File, tag_names.len(), 2)
+        ))?;
+
+        let files = stmt
+            .query_map(
+                rusqlite::params_from_iter(std::iter::once(search_pattern).chain(tag_names)),
+                Self::row_to_file,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(files)
+    }
+
+    // Tags
+
+    /// Replace an item's tags, creating tags that don't exist yet.
+    /// Does not touch the item's own `tags` string.
+    fn set_item_tags(&self, kind: TaggedKind, item_id: i64, names: &[String]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let table = kind.join_table();
+
+        self.conn.execute(&format!("DELETE FROM {} WHERE item_id = ?1", table), params![item_id])?;
+
+        for (position, name) in names.iter().enumerate() {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO tags (name, created_at) VALUES (?1, ?2)",
+                params![name, now],
+            )?;
+            self.conn.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {} (item_id, tag_id, position)
+                     SELECT ?1, id, ?2 FROM tags WHERE name = ?3",
+                    table
+                ),
+                params![item_id, position as i64, name],
+            )?;
+        }
 
         Ok(())
     }
 
-    pub fn delete_file(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM files WHERE id = ?1", params![id])?;
+    /// Spell names the way existing tags are spelled (tags ignore case)
+    fn canonical_tag_names(&self, names: Vec<String>) -> Result<Vec<String>> {
+        names
+            .into_iter()
+            .map(|name| {
+                let existing: Option<String> = self.conn
+                    .query_row("SELECT name FROM tags WHERE name = ?1", params![name], |row| row.get(0))
+                    .optional()?;
+                Ok(existing.unwrap_or(name))
+            })
+            .collect()
+    }
+
+    fn get_item_tags(&self, kind: TaggedKind, item_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT t.name FROM {} j JOIN tags t ON t.id = j.tag_id
+             WHERE j.item_id = ?1 ORDER BY j.position, t.name",
+            kind.join_table()
+        ))?;
+        let names = stmt
+            .query_map(params![item_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names)
+    }
+
+    /// IDs of the items linked to any of `tag_ids`
+    fn items_with_any_tag(&self, kind: TaggedKind, tag_ids: &[i64]) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT item_id FROM {} WHERE tag_id IN ({})",
+            kind.join_table(),
+            vec!["?"; tag_ids.len()].join(", ")
+        ))?;
+        let ids = stmt
+            .query_map(rusqlite::params_from_iter(tag_ids), |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Rewrite the `tags` strings of items after their tags were renamed,
+    /// merged or deleted. The items count as updated so sync picks them up.
+    fn refresh_tag_strings(&self, kind: TaggedKind, item_ids: &[i64]) -> Result<()> {
+        if !kind.has_tag_string() {
+            return Ok(());
+        }
+
+        let now = Utc::now().to_rfc3339();
+        for &id in item_ids {
+            let names = self.get_item_tags(kind, id)?;
+            self.conn.execute(
+                &format!("UPDATE {} SET tags = ?1, updated_at = ?2 WHERE id = ?3", kind.item_table()),
+                params![tags::format_tags(&names), now, id],
+            )?;
+        }
         Ok(())
     }
 
-    pub fn search_files(&self, query: &str) -> Result<Vec<File>> {
-        let search_pattern = format!("%{}%", query);
+    /// SQL condition (on the item's `id`) requiring every one of `count` tag
+    /// names, bound from parameter `?first_param` onwards
+    fn tag_filter_sql(kind: TaggedKind, count: usize, first_param: usize) -> String {
+        if count == 0 {
+            return "1".to_string();
+        }
+
+        let placeholders: Vec<String> = (first_param..first_param + count).map(|i| format!("?{}", i)).collect();
+        format!(
+            "id IN (SELECT j.item_id FROM {} j JOIN tags t ON t.id = j.tag_id
+                    WHERE t.name IN ({}) GROUP BY j.item_id HAVING COUNT(*) = {})",
+            kind.join_table(),
+            placeholders.join(", "),
+            count
+        )
+    }
+
+    /// IDs of live snippets that have all of `tag_names`
+    pub fn get_snippet_ids_with_tags(&self, tag_names: &[String]) -> Result<Vec<i64>> {
+        let tag_names = tags::normalize_tags(tag_names.iter().map(String::as_str));
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id FROM snippets WHERE deleted_at IS NULL AND {}",
+            Self::tag_filter_sql(TaggedKind::Snippet, tag_names.len(), 1)
+        ))?;
+        let ids = stmt
+            .query_map(rusqlite::params_from_iter(tag_names), |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
 
+    /// Every tag with how many snippets, files and clipboard entries use it
+    pub fn get_all_tags(&self) -> Result<Vec<Tag>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at
-             FROM files
-             WHERE filename LIKE ?1
-                OR description LIKE ?1
-                OR tags LIKE ?1
-                OR file_type LIKE ?1
-             ORDER BY updated_at DESC"
-        )?;
-
-        let files = stmt.query_map(params![search_pattern], |row| {
-            Ok(File {
-                id: Some(row.get(0)?),
-                filename: row.get(1)?,
-                file_type: row.get(2)?,
-                file_size: row.get(3)?,
-                folder_id: row.get(4)?,
-                storage_path: row.get(5)?,
-                cloud_storage_path: row.get(6)?,
-                mime_type: row.get(7)?,
-                description: row.get(8)?,
-                tags: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
+            "SELECT t.id, t.name, t.color, t.created_at,
+                    (SELECT COUNT(*) FROM snippet_tags j JOIN snippets s ON s.id = j.item_id
+                     WHERE j.tag_id = t.id AND s.deleted_at IS NULL),
+                    (SELECT COUNT(*) FROM file_tags j JOIN files f ON f.id = j.item_id
+                     WHERE j.tag_id = t.id AND f.deleted_at IS NULL),
+                    (SELECT COUNT(*) FROM clipboard_tags j WHERE j.tag_id = t.id)
+             FROM tags t
+             ORDER BY t.name"
+        )?;
+
+        let tags = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                created_at: row.get(3)?,
+                snippet_count: row.get(4)?,
+                file_count: row.get(5)?,
+                clipboard_count: row.get(6)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(files)
+        Ok(tags)
     }
 
-    pub fn update_file_cloud_path(&self, id: i64, cloud_path: &str) -> Result<()> {
+    fn ensure_tag_exists(&self, id: i64) -> Result<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM tags WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            anyhow::bail!("Tag not found");
+        }
+        Ok(())
+    }
+
+    /// Rename a tag everywhere it is used. Changing only the case is allowed;
+    /// renaming onto another existing tag is not (merge them instead).
+    pub fn rename_tag(&self, id: i64, name: &str) -> Result<()> {
+        let name = tags::normalize_tag(name).context("Tag names can't be empty or contain commas")?;
+        self.ensure_tag_exists(id)?;
+
+        let clash: Option<String> = self.conn
+            .query_row("SELECT name FROM tags WHERE name = ?1 AND id != ?2", params![name, id], |row| row.get(0))
+            .optional()?;
+        if let Some(existing) = clash {
+            anyhow::bail!("A tag named '{}' already exists. Merge the tags instead", existing);
+        }
+
+        let tx = self.transaction()?;
+        tx.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+        for kind in TaggedKind::ALL {
+            let items = self.items_with_any_tag(kind, &[id])?;
+            self.refresh_tag_strings(kind, &items)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Move every use of `source_ids` over to `target_id` and delete the source tags
+    pub fn merge_tags(&self, source_ids: &[i64], target_id: i64) -> Result<()> {
+        self.ensure_tag_exists(target_id)?;
+        let source_ids: Vec<i64> = source_ids.iter().copied().filter(|id| *id != target_id).collect();
+        if source_ids.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.transaction()?;
+        for kind in TaggedKind::ALL {
+            let items = self.items_with_any_tag(kind, &source_ids)?;
+            let table = kind.join_table();
+
+            for source_id in &source_ids {
+                // Items that already have the target keep its position
+                tx.execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO {table} (item_id, tag_id, position)
+                         SELECT item_id, ?2, position FROM {table} WHERE tag_id = ?1",
+                        table = table
+                    ),
+                    params![source_id, target_id],
+                )?;
+                tx.execute(&format!("DELETE FROM {} WHERE tag_id = ?1", table), params![source_id])?;
+            }
+
+            self.refresh_tag_strings(kind, &items)?;
+        }
+        for source_id in &source_ids {
+            tx.execute("DELETE FROM tags WHERE id = ?1", params![source_id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a tag from everything that uses it
+    pub fn delete_tag(&self, id: i64) -> Result<()> {
+        let tx = self.transaction()?;
+        for kind in TaggedKind::ALL {
+            let items = self.items_with_any_tag(kind, &[id])?;
+            tx.execute(&format!("DELETE FROM {} WHERE tag_id = ?1", kind.join_table()), params![id])?;
+            self.refresh_tag_strings(kind, &items)?;
+        }
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Set a tag's color (`#rrggbb`), or clear it with `None`
+    pub fn set_tag_color(&self, id: i64, color: Option<&str>) -> Result<()> {
+        if let Some(color) = color {
+            if !tags::is_valid_color(color) {
+                anyhow::bail!("Invalid color '{}', expected #rrggbb", color);
+            }
+        }
+        self.ensure_tag_exists(id)?;
+        self.conn.execute("UPDATE tags SET color = ?1 WHERE id = ?2", params![color, id])?;
+        Ok(())
+    }
+
+    /// Replace a clipboard entry's tags. Returns the cleaned-up tag names.
+    pub fn set_clipboard_entry_tags(&self, id: i64, names: &[String]) -> Result<Vec<String>> {
+        if self.get_clipboard_entry(id)?.is_none() {
+            anyhow::bail!("Clipboard entry not found");
+        }
+
+        let names = self.canonical_tag_names(tags::normalize_tags(names.iter().map(String::as_str)))?;
+        let tx = self.transaction()?;
+        self.set_item_tags(TaggedKind::Clipboard, id, &names)?;
+        tx.commit()?;
+        Ok(names)
+    }
+
+    // Markdown vault
+
+    pub fn get_vault_settings(&self) -> Result<VaultSettings> {
+        Ok(self.get_setting(VAULT_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_vault_settings(&self, settings: &VaultSettings) -> Result<()> {
+        self.set_setting(VAULT_SETTINGS_KEY, settings)
+    }
+
+    /// Path (relative to the vault) and hash of every synced snippet's file
+    pub fn get_vault_records(&self) -> Result<HashMap<i64, (String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT snippet_id, path, hash FROM vault_files")?;
+        let records = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(records)
+    }
+
+    pub fn set_vault_record(&self, snippet_id: i64, path: &str, hash: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO vault_files (snippet_id, path, hash) VALUES (?1, ?2, ?3)
+             ON CONFLICT(snippet_id) DO UPDATE SET path = excluded.path, hash = excluded.hash",
+            params![snippet_id, path, hash],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_vault_record(&self, snippet_id: i64) -> Result<()> {
+        self.conn.execute("DELETE FROM vault_files WHERE snippet_id = ?1", params![snippet_id])?;
+        Ok(())
+    }
+
+    /// Folder directories (relative to the vault) as of the last sync
+    pub fn get_vault_folders(&self) -> Result<Vec<String>> {
+        Ok(self.get_setting(VAULT_FOLDERS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_vault_folders(&self, paths: &[String]) -> Result<()> {
+        self.set_setting(VAULT_FOLDERS_KEY, &paths)
+    }
+
+    /// Forget what was synced, e.g. when the vault moves to another directory
+    pub fn clear_vault_records(&self) -> Result<()> {
+        self.conn.execute("DELETE FROM vault_files", [])?;
+        self.conn.execute("DELETE FROM settings WHERE key = ?1", params![VAULT_FOLDERS_KEY])?;
+        Ok(())
+    }
+
+    // Secret vault
+
+    pub fn get_secret_vault(&self) -> Result<Option<SecretVaultConfig>> {
+        self.get_setting(SECRET_VAULT_KEY)
+    }
+
+    pub fn set_secret_vault(&self, config: &SecretVaultConfig) -> Result<()> {
+        self.set_setting(SECRET_VAULT_KEY, config)
+    }
+
+    // Backups
+
+    pub fn get_backup_settings(&self) -> Result<BackupSettings> {
+        Ok(self.get_setting(BACKUP_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_backup_settings(&self, settings: &BackupSettings) -> Result<()> {
+        self.set_setting(BACKUP_SETTINGS_KEY, settings)
+    }
+
+    /// Copy the database to `path` with SQLite's online backup API
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        let mut destination = Connection::open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        // SQLCipher only copies pages between databases with the same key
+        if self.encrypted {
+            encryption::apply_key(&destination)?;
+        }
+        Backup::new(&self.conn, &mut destination)?
+            .run_to_completion(256, std::time::Duration::ZERO, None)
+            .context("Failed to back up the database")?;
+        Ok(())
+    }
+
+    /// Replace the whole database with the one at `path`, then bring it up
+    /// to the current schema
+    pub fn restore_from(&mut self, path: &Path) -> Result<()> {
+        let source = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if self.encrypted {
+            encryption::apply_key(&source)?;
+        }
+        Backup::new(&source, &mut self.conn)?
+            .run_to_completion(256, std::time::Duration::ZERO, None)
+            .context("Failed to restore the database")?;
+        self.initialize()
+    }
+
+    // Library export/import
+
+    /// The live library, as written to an export archive
+    pub fn get_library_data(&self) -> Result<LibraryData> {
+        let mut folders = self.get_all_folders(false)?;
+        // A smart folder without a readable search would come back as a manual one
+        folders.retain(|folder| folder.query_error.is_none());
+        for folder in &mut folders {
+            folder.snippet_count = None;
+        }
+
+        Ok(LibraryData {
+            folders,
+            // Secrets stay in this library, encrypted
+            snippets: self.get_all_snippets()?.into_iter().filter(|s| !s.secret).collect(),
+            files: self.get_all_files()?,
+            clipboard: self.get_clipboard_history(i64::MAX, false)?,
+            tags: self.get_all_tags()?,
+        })
+    }
+
+    /// Import a library in one transaction, giving every item a new ID.
+    /// `blob_paths` maps the archive's file IDs to where their data was stored;
+    /// files without one are skipped. Returns the report and the storage paths
+    /// that are no longer used (replaced files and skipped duplicates), which
+    /// the caller deletes once the import has succeeded.
+    pub fn import_library(
+        &self,
+        data: &LibraryData,
+        mode: ImportMode,
+        blob_paths: &HashMap<i64, String>,
+    ) -> Result<(ImportReport, Vec<String>)> {
+        let mut report = ImportReport { mode, ..Default::default() };
+        let mut unused_blobs = Vec::new();
+
+        let tx = self.transaction()?;
+
+        if mode == ImportMode::Replace {
+            let mut stmt = tx.prepare("SELECT storage_path FROM files")?;
+            let paths = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            drop(stmt);
+            unused_blobs.extend(paths);
+
+            // Archives never include secret snippets, so those are kept, with
+            // their revisions, tags and the folders they are in
+            tx.execute_batch(
+                "DELETE FROM snippet_revisions WHERE snippet_id IN (SELECT id FROM snippets WHERE secret = 0);
+                 DELETE FROM embeddings WHERE snippet_id IN (SELECT id FROM snippets WHERE secret = 0);
+                 DELETE FROM snippet_tags WHERE item_id NOT IN (SELECT id FROM snippets WHERE secret = 1);
+                 DELETE FROM file_tags;
+                 DELETE FROM clipboard_tags;
+                 DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM snippet_tags);
+                 DELETE FROM snippets WHERE secret = 0;
+                 DELETE FROM files;
+                 WITH RECURSIVE kept(id) AS (
+                     SELECT folder_id FROM snippets WHERE folder_id IS NOT NULL
+                     UNION
+                     SELECT f.parent_id FROM folders f JOIN kept ON f.id = kept.id WHERE f.parent_id IS NOT NULL
+                 )
+                 DELETE FROM folders WHERE id NOT IN (SELECT id FROM kept);
+                 DELETE FROM clipboard_history;",
+            )?;
+
+            let secrets: i64 = tx.query_row("SELECT COUNT(*) FROM snippets", [], |row| row.get(0))?;
+            if secrets > 0 {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::Snippet,
+                    name: format!("{} secret snippet{}", secrets, if secrets == 1 { "" } else { "s" }),
+                    resolution: "Kept, as archives don't include secret snippets".to_string(),
+                });
+            }
+        }
+
+        // Tags first, so their colors come along even where the library already
+        // has the tag without one
+        for tag in &data.tags {
+            let Some(name) = tags::normalize_tag(&tag.name) else { continue };
+            let color = tag.color.as_deref().filter(|c| tags::is_valid_color(c));
+            tx.execute(
+                "INSERT OR IGNORE INTO tags (name, color, created_at) VALUES (?1, ?2, ?3)",
+                params![name, color, tag.created_at],
+            )?;
+            if color.is_some() {
+                tx.execute(
+                    "UPDATE tags SET color = ?2 WHERE name = ?1 AND color IS NULL",
+                    params![name, color],
+                )?;
+            }
+        }
+
+        // Folders, parents before their children
+        let mut folder_ids: HashMap<i64, i64> = HashMap::new();
+        let mut smart_folder_ids: Vec<i64> = Vec::new();
+        let mut pending: Vec<&Folder> = data.folders.iter().filter(|f| f.id.is_some()).collect();
+        while !pending.is_empty() {
+            let ready: Vec<&Folder> = pending
+                .iter()
+                .copied()
+                .filter(|f| f.parent_id.is_none_or(|p| folder_ids.contains_key(&p) || !pending.iter().any(|o| o.id == Some(p))))
+                .collect();
+            // Only a cycle is left; break it by importing those at the top level
+            let (batch, detach) = if ready.is_empty() { (pending.clone(), true) } else { (ready, false) };
+            pending.retain(|f| !batch.iter().any(|b| b.id == f.id));
+
+            for folder in batch {
+                let old_id = folder.id.unwrap_or_default();
+                let parent_id = if detach {
+                    None
+                } else {
+                    folder.parent_id.and_then(|p| folder_ids.get(&p).copied())
+                        .filter(|p| !smart_folder_ids.contains(p))
+                };
+                let is_smart = folder.query.is_some();
+
+                let existing: Option<(i64, Option<String>)> = tx
+                    .query_row(
+                        "SELECT id, smart_query FROM folders
+                         WHERE name = ?1 AND parent_id IS ?2 AND deleted_at IS NULL",
+                        params![folder.name, parent_id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?;
+
+                // A folder of the same kind and name is the same folder
+                let mut name = folder.name.clone();
+                if let Some((existing_id, existing_query)) = existing {
+                    if is_smart == existing_query.is_some() {
+                        folder_ids.insert(old_id, existing_id);
+                        if is_smart {
+                            smart_folder_ids.push(existing_id);
+                            let existing_query = existing_query.and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+                            let imported_query = folder.query.as_ref().map(serde_json::to_value).transpose()?;
+                            if existing_query != imported_query {
+                                report.conflicts.push(ImportConflict {
+                                    kind: LibraryItemKind::Folder,
+                                    name: folder.name.clone(),
+                                    resolution: "A smart folder with this name exists; kept its search".to_string(),
+                                });
+                            }
+                        }
+                        report.duplicates_skipped += 1;
+                        continue;
+                    }
+                    name = self.imported_folder_name(&folder.name, parent_id)?;
+                    report.conflicts.push(ImportConflict {
+                        kind: LibraryItemKind::Folder,
+                        name: folder.name.clone(),
+                        resolution: format!("Imported as '{}'", name),
+                    });
+                }
+
+                tx.execute(
+                    "INSERT INTO folders (name, icon, parent_id, sort_order, smart_query, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        name,
+                        folder.icon,
+                        parent_id,
+                        self.next_folder_sort_order(parent_id)?,
+                        folder.query.as_ref().map(serde_json::to_string).transpose()?,
+                        folder.created_at,
+                        folder.updated_at,
+                    ],
+                )?;
+                let new_id = tx.last_insert_rowid();
+                folder_ids.insert(old_id, new_id);
+                if is_smart {
+                    smart_folder_ids.push(new_id);
+                }
+                report.folders += 1;
+            }
+        }
+        let map_folder = |folder_id: Option<i64>| {
+            folder_id
+                .and_then(|id| folder_ids.get(&id).copied())
+                .filter(|id| !smart_folder_ids.contains(id))
+        };
+
+        for snippet in &data.snippets {
+            if self.snippet_exists(&snippet.title, &snippet.content, &snippet.language)? {
+                report.duplicates_skipped += 1;
+                continue;
+            }
+
+            let title_taken: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM snippets WHERE title = ?1 AND deleted_at IS NULL",
+                params![snippet.title],
+                |row| row.get(0),
+            )?;
+            if title_taken {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::Snippet,
+                    name: snippet.title.clone(),
+                    resolution: "A different snippet has this title; kept both".to_string(),
+                });
+            }
+
+            let mut trigger = snippet.trigger.clone();
+            if let Some(keyword) = &trigger {
+                if self.find_snippet_by_trigger(keyword)?.is_some() {
+                    report.conflicts.push(ImportConflict {
+                        kind: LibraryItemKind::Snippet,
+                        name: snippet.title.clone(),
+                        resolution: format!("Trigger '{}' is already used; imported without it", keyword),
+                    });
+                    trigger = None;
+                }
+            }
+
+            let tag_names = self.canonical_tag_names(tags::parse_tags(snippet.tags.as_deref().unwrap_or_default()))?;
+            tx.execute(
+                "INSERT INTO snippets (title, content, language, description, tags, folder_id, created_at, updated_at, trigger_keyword)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    snippet.title,
+                    snippet.content,
+                    snippet.language,
+                    snippet.description,
+                    tags::format_tags(&tag_names),
+                    map_folder(snippet.folder_id),
+                    snippet.created_at,
+                    snippet.updated_at,
+                    trigger,
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            self.set_item_tags(TaggedKind::Snippet, id, &tag_names)?;
+            report.snippet_ids.push(id);
+            report.snippets += 1;
+        }
+
+        for file in &data.files {
+            let Some(storage_path) = file.id.and_then(|id| blob_paths.get(&id)) else {
+                report.conflicts.push(ImportConflict {
+                    kind: LibraryItemKind::File,
+                    name: file.filename.clone(),
+                    resolution: "File data is missing from the archive; skipped".to_string(),
+                });
+                continue;
+            };
+
+            let duplicate: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM files
+                 WHERE filename = ?1 AND file_size = ?2 AND created_at = ?3 AND deleted_at IS NULL",
+                params![file.filename, file.file_size, file.created_at],
+                |row| row.get(0),
+            )?;
+            if duplicate {
+                unused_blobs.push(storage_path.clone());
+                report.duplicates_skipped += 1;
+                continue;
+            }
+
+            let tag_names = self.canonical_tag_names(tags::parse_tags(file.tags.as_deref().unwrap_or_default()))?;
+            tx.execute(
+                "INSERT INTO files (filename, file_type, file_size, folder_id, storage_path, cloud_storage_path, mime_type, description, tags, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    file.filename,
+                    file.file_type,
+                    file.file_size,
+                    map_folder(file.folder_id),
+                    storage_path,
+                    file.cloud_storage_path,
+                    file.mime_type,
+                    file.description,
+                    tags::format_tags(&tag_names),
+                    file.created_at,
+                    file.updated_at,
+                ],
+            )?;
+            self.set_item_tags(TaggedKind::File, tx.last_insert_rowid(), &tag_names)?;
+            report.files += 1;
+        }
+
+        // Imported pins go after the existing ones, keeping their order
+        let mut next_pin: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(pin_order), 0) + 1 FROM clipboard_history WHERE pinned = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        let mut clipboard: Vec<&ClipboardEntry> = data.clipboard.iter().collect();
+        clipboard.sort_by_key(|e| (!e.pinned, e.pin_order));
+
+        for entry in clipboard {
+            let duplicate: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM clipboard_history WHERE content = ?1 AND created_at = ?2",
+                params![entry.content, entry.created_at],
+                |row| row.get(0),
+            )?;
+            if duplicate {
+                report.duplicates_skipped += 1;
+                continue;
+            }
+
+            let pin_order = entry.pinned.then(|| {
+                next_pin += 1;
+                next_pin - 1
+            });
+            tx.execute(
+                "INSERT INTO clipboard_history (content, source, category, created_at, pinned, pin_order, favorite)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![entry.content, entry.source, entry.category, entry.created_at, entry.pinned, pin_order, entry.favorite],
+            )?;
+            let names = self.canonical_tag_names(tags::normalize_tags(entry.tags.iter().map(String::as_str)))?;
+            self.set_item_tags(TaggedKind::Clipboard, tx.last_insert_rowid(), &names)?;
+            report.clipboard_entries += 1;
+        }
+
+        tx.commit()?;
+        Ok((report, unused_blobs))
+    }
+
+    /// "Name (imported)", or "Name (imported 2)" and so on if that is taken too
+    fn imported_folder_name(&self, name: &str, parent_id: Option<i64>) -> Result<String> {
+        let mut attempt = 1;
+        loop {
+            let candidate = if attempt == 1 {
+                format!("{} (imported)", name)
+            } else {
+                format!("{} (imported {})", name, attempt)
+            };
+            if self.ensure_folder_name_available(&candidate, parent_id, None).is_ok() {
+                return Ok(candidate);
+            }
+            attempt += 1;
+        }
+    }
+
+    // Trash
+
+    pub fn trash_snippet(&self, id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE snippets SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
+    pub fn trash_file(&self, id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE files SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Move a folder and its subfolders to the trash. Their snippets and files
+    /// become uncategorized but remember their folder, so restoring puts them back.
+    pub fn trash_folder(&self, id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let subtree = self.folder_subtree(id, None)?;
+        let tx = self.transaction()?;
+
+        for folder_id in subtree {
+            tx.execute(
+                "UPDATE folders SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+                params![folder_id, now],
+            )?;
+            for table in ["snippets", "files"] {
+                tx.execute(
+                    &format!("UPDATE {} SET trashed_folder_id = folder_id, folder_id = NULL WHERE folder_id = ?1", table),
+                    params![folder_id],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Take a snippet out of the trash. If another snippet took its trigger
+    /// meanwhile, the restored one loses it.
+    pub fn restore_snippet(&self, id: i64) -> Result<()> {
+        let tx = self.transaction()?;
+        let cleared = tx.execute(
+            "UPDATE snippets SET trigger_keyword = NULL
+             WHERE id = ?1 AND trigger_keyword IN
+                 (SELECT trigger_keyword FROM snippets WHERE deleted_at IS NULL AND id != ?1)",
+            params![id],
+        )?;
+        tx.execute("UPDATE snippets SET deleted_at = NULL WHERE id = ?1", params![id])?;
+        tx.commit()?;
+
+        if cleared > 0 {
+            println!("⚠️ Trigger of restored snippet {} is used by another snippet; removed it", id);
+        }
+        Ok(())
+    }
+
+    pub fn restore_file(&self, id: i64) -> Result<()> {
+        self.conn.execute("UPDATE files SET deleted_at = NULL WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Take a folder out of the trash together with the subfolders trashed with
+    /// it, and move their former snippets and files back, unless they were put
+    /// in another folder meanwhile. If its parent is still in the trash the
+    /// folder is restored to the top level.
+    pub fn restore_folder(&self, id: i64) -> Result<()> {
+        let folder = self.get_folder(id, false)?.context("Folder not found")?;
+        let Some(deleted_at) = folder.deleted_at else {
+            return Ok(());
+        };
+
+        let parent_is_live = match folder.parent_id {
+            Some(parent_id) => self.ensure_parent_folder(parent_id).is_ok(),
+            None => true,
+        };
+        let parent_id = if parent_is_live { folder.parent_id } else { None };
+        self.ensure_folder_name_available(&folder.name, parent_id, Some(id))?;
+
+        let subtree = self.folder_subtree(id, Some(&deleted_at))?;
+        let tx = self.transaction()?;
+
+        if !parent_is_live {
+            tx.execute(
+                "UPDATE folders SET parent_id = NULL, sort_order = ?2 WHERE id = ?1",
+                params![id, self.next_folder_sort_order(None)?],
+            )?;
+        }
+
+        for folder_id in subtree {
+            tx.execute("UPDATE folders SET deleted_at = NULL WHERE id = ?1", params![folder_id])?;
+            for table in ["snippets", "files"] {
+                tx.execute(
+                    &format!(
+                        "UPDATE {} SET folder_id = trashed_folder_id WHERE trashed_folder_id = ?1 AND folder_id IS NULL",
+                        table
+                    ),
+                    params![folder_id],
+                )?;
+                tx.execute(
+                    &format!("UPDATE {} SET trashed_folder_id = NULL WHERE trashed_folder_id = ?1", table),
+                    params![folder_id],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Permanently delete a snippet with its history and embedding
+    pub fn purge_snippet(&self, id: i64) -> Result<()> {
+        // Foreign keys aren't enforced on this connection, so cascade by hand
+        let tx = self.transaction()?;
+        tx.execute("DELETE FROM snippet_revisions WHERE snippet_id = ?1", params![id])?;
+        tx.execute("DELETE FROM embeddings WHERE snippet_id = ?1", params![id])?;
+        tx.execute("DELETE FROM snippet_tags WHERE item_id = ?1", params![id])?;
+        tx.execute("DELETE FROM snippets WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Permanently delete a file row. The caller removes the blob afterwards.
+    pub fn purge_file(&self, id: i64) -> Result<()> {
+        let tx = self.transaction()?;
+        tx.execute("DELETE FROM file_tags WHERE item_id = ?1", params![id])?;
+        tx.execute("DELETE FROM files WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Permanently delete a folder and the subfolders trashed with it.
+    /// Their former contents stay uncategorized.
+    pub fn purge_folder(&self, id: i64) -> Result<()> {
+        let Some(folder) = self.get_folder(id, false)? else {
+            return Ok(());
+        };
+        let subtree = self.folder_subtree(id, folder.deleted_at.as_deref())?;
+
+        let tx = self.transaction()?;
+        for folder_id in subtree {
+            for table in ["snippets", "files"] {
+                tx.execute(
+                    &format!("UPDATE {} SET trashed_folder_id = NULL WHERE trashed_folder_id = ?1", table),
+                    params![folder_id],
+                )?;
+                tx.execute(
+                    &format!("UPDATE {} SET folder_id = NULL WHERE folder_id = ?1", table),
+                    params![folder_id],
+                )?;
+            }
+            // Subfolders trashed separately earlier end up at the top level
+            tx.execute("UPDATE folders SET parent_id = NULL WHERE parent_id = ?1", params![folder_id])?;
+            tx.execute("DELETE FROM folders WHERE id = ?1", params![folder_id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_trash(&self) -> Result<TrashContents> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM snippets WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            SNIPPET_COLUMNS
+        ))?;
+        let snippets = stmt
+            .query_map([], Self::row_to_snippet)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Subfolders trashed along with their parent are restored with it, so only
+        // the top of each trashed subtree is listed
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, (SELECT COUNT(*) FROM snippets s WHERE s.trashed_folder_id = f.id AND s.deleted_at IS NULL)
+             FROM folders f
+             LEFT JOIN folders p ON p.id = f.parent_id
+             WHERE f.deleted_at IS NOT NULL AND (p.deleted_at IS NULL OR p.deleted_at != f.deleted_at)
+             ORDER BY f.deleted_at DESC",
+            FOLDER_COLUMNS
+        ))?;
+        let folders = stmt
+            .query_map([], Self::row_to_folder)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM files WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            FILE_COLUMNS
+        ))?;
+        let files = stmt
+            .query_map([], Self::row_to_file)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TrashContents { snippets, folders, files })
+    }
+
+    /// Trashed items deleted before `cutoff` (RFC 3339), or all of them with `None`
+    pub fn get_trashed_items(&self, cutoff: Option<&str>) -> Result<Vec<(TrashItemKind, i64)>> {
+        let mut items = Vec::new();
+
+        for (kind, table) in [
+            (TrashItemKind::Snippet, "snippets"),
+            (TrashItemKind::Folder, "folders"),
+            (TrashItemKind::File, "files"),
+        ] {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT id FROM {} WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at < ?1)",
+                table
+            ))?;
+            let ids = stmt
+                .query_map(params![cutoff], |row| row.get::<_, i64>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            items.extend(ids.into_iter().map(|id| (kind, id)));
+        }
+
+        Ok(items)
+    }
+
+    pub fn get_trash_settings(&self) -> Result<TrashSettings> {
+        Ok(self.get_setting(TRASH_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_trash_settings(&self, settings: &TrashSettings) -> Result<()> {
+        self.set_setting(TRASH_SETTINGS_KEY, settings)
+    }
+
+    // Global hotkeys
+
+    /// Stored bindings, with defaults filled in for actions that were never configured
+    pub fn get_hotkey_settings(&self) -> Result<HotkeySettings> {
+        let mut settings: HotkeySettings = self.get_setting(HOTKEY_SETTINGS_KEY)?.unwrap_or_default();
+        for action in HotkeyAction::ALL {
+            settings
+                .bindings
+                .entry(action)
+                .or_insert_with(|| Some(action.default_shortcut().to_string()));
+        }
+        Ok(settings)
+    }
+
+    pub fn set_hotkey_settings(&self, settings: &HotkeySettings) -> Result<()> {
+        self.set_setting(HOTKEY_SETTINGS_KEY, settings)
+    }
+
+    // Text expansion
+
+    pub fn get_expansion_settings(&self) -> Result<ExpansionSettings> {
+        Ok(self.get_setting(EXPANSION_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_expansion_settings(&self, settings: &ExpansionSettings) -> Result<()> {
+        self.set_setting(EXPANSION_SETTINGS_KEY, settings)
+    }
+
+    /// Every snippet trigger keyword, mapped to its snippet ID
+    pub fn get_snippet_triggers(&self) -> Result<HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT trigger_keyword, id FROM snippets WHERE trigger_keyword IS NOT NULL AND deleted_at IS NULL"
+        )?;
+
+        let triggers = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(triggers)
+    }
+
+    /// ID of the live snippet using `trigger`, if any
+    pub fn find_snippet_by_trigger(&self, trigger: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row(
+                "SELECT id FROM snippets WHERE trigger_keyword = ?1 AND deleted_at IS NULL",
+                params![trigger],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to look up snippet trigger")
+    }
+
+    // Settings methods
+
+    pub fn get_setting<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match value {
+            Some(json) => Ok(Some(
+                serde_json::from_str(&json)
+                    .with_context(|| format!("Invalid value stored for setting '{}'", key))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_setting<T: serde::Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let json = serde_json::to_string(value)?;
+
+        self.conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, json, now],
+        )?;
+
+        Ok(())
+    }
+
+    // Sync journal
+
+    pub fn get_sync_state(&self) -> Result<SyncState> {
+        Ok(self.get_setting(SYNC_STATE_KEY)?.unwrap_or_default())
+    }
+
+    pub fn set_sync_state(&self, state: &SyncState) -> Result<()> {
+        self.set_setting(SYNC_STATE_KEY, state)
+    }
+
+    /// Take the records written since the journal was last drained
+    pub fn drain_sync_journal(&self) -> Result<Vec<(SyncRecordKind, i64)>> {
+        let tx = self.transaction()?;
+        let mut stmt = tx.prepare("SELECT kind, local_id FROM sync_journal ORDER BY rowid")?;
+        let entries = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        tx.execute("DELETE FROM sync_journal", [])?;
+        tx.commit()?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(kind, id)| Some((SyncRecordKind::parse(&kind)?, id)))
+            .collect())
+    }
+
+    pub fn journal_sync_change(&self, kind: SyncRecordKind, local_id: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO sync_journal (kind, local_id) VALUES (?1, ?2)",
+            params![kind.as_str(), local_id],
+        )?;
+        Ok(())
+    }
+
+    /// Note every record in the library, so all of it is pushed again
+    pub fn journal_library_for_sync(&self) -> Result<()> {
+        for kind in SyncRecordKind::ALL {
+            let (table, _) = sync_columns(kind);
+            self.conn.execute(
+                &format!("INSERT OR IGNORE INTO sync_journal (kind, local_id) SELECT ?1, id FROM {}", table),
+                params![kind.as_str()],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Upload every file blob again on its next push
+    pub fn forget_sync_blobs(&self) -> Result<()> {
+        self.conn.execute("UPDATE sync_records SET blob = NULL", [])?;
+        Ok(())
+    }
+
+    /// Records with local changes that haven't been pushed yet. Secret
+    /// snippets aren't synced, so they don't count.
+    pub fn count_pending_sync_changes(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM (
+                 SELECT kind, local_id FROM sync_journal
+                 UNION
+                 SELECT kind, local_id FROM sync_records WHERE dirty = 1
+             )
+             WHERE NOT (kind = 'snippet' AND local_id IN (SELECT id FROM snippets WHERE secret = 1))",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// While set, writes aren't noted in the journal, so changes applied
+    /// from the server aren't pushed back
+    pub fn set_sync_applying(&self, applying: bool) -> Result<()> {
+        self.conn.execute("DELETE FROM sync_applying", [])?;
+        if applying {
+            self.conn.execute("INSERT INTO sync_applying (active) VALUES (1)", [])?;
+        }
+        Ok(())
+    }
+
+    fn row_to_sync_record(row: &rusqlite::Row) -> rusqlite::Result<SyncRecord> {
+        let kind: String = row.get(1)?;
+        let versions: String = row.get(4)?;
+        let blob: Option<String> = row.get(7)?;
+        Ok(SyncRecord {
+            uuid: row.get(0)?,
+            kind: SyncRecordKind::parse(&kind)
+                .ok_or_else(|| rusqlite::Error::InvalidColumnType(1, kind, rusqlite::types::Type::Text))?,
+            local_id: row.get(2)?,
+            hlc: row.get(3)?,
+            versions: serde_json::from_str(&versions).unwrap_or_default(),
+            deleted: row.get(5)?,
+            dirty: row.get(6)?,
+            blob: blob.and_then(|blob| serde_json::from_str(&blob).ok()),
+        })
+    }
+
+    pub fn get_sync_record(&self, uuid: &str) -> Result<Option<SyncRecord>> {
+        Ok(self.conn
+            .query_row(
+                &format!("SELECT {} FROM sync_records WHERE uuid = ?1", SYNC_RECORD_COLUMNS),
+                params![uuid],
+                Self::row_to_sync_record,
+            )
+            .optional()?)
+    }
+
+    pub fn get_sync_record_for(&self, kind: SyncRecordKind, local_id: i64) -> Result<Option<SyncRecord>> {
+        Ok(self.conn
+            .query_row(
+                &format!("SELECT {} FROM sync_records WHERE kind = ?1 AND local_id = ?2", SYNC_RECORD_COLUMNS),
+                params![kind.as_str(), local_id],
+                Self::row_to_sync_record,
+            )
+            .optional()?)
+    }
+
+    /// Records to push, folders first so the records inside them can refer to them
+    pub fn get_dirty_sync_records(&self) -> Result<Vec<SyncRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sync_records WHERE dirty = 1
+             ORDER BY kind != 'folder', hlc",
+            SYNC_RECORD_COLUMNS
+        ))?;
+        let records = stmt
+            .query_map([], Self::row_to_sync_record)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    pub fn save_sync_record(&self, record: &SyncRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO sync_records (uuid, kind, local_id, hlc, versions, deleted, dirty, blob)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(uuid) DO UPDATE SET
+                 local_id = excluded.local_id, hlc = excluded.hlc, versions = excluded.versions,
+                 deleted = excluded.deleted, dirty = excluded.dirty, blob = excluded.blob",
+            params![
+                record.uuid,
+                record.kind.as_str(),
+                record.local_id,
+                record.hlc,
+                serde_json::to_string(&record.versions)?,
+                record.deleted,
+                record.dirty,
+                record.blob.as_ref().map(serde_json::to_string).transpose()?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The version `hlc` of a record was pushed. A newer local version stays dirty.
+    pub fn mark_sync_record_pushed(&self, uuid: &str, hlc: &str, blob: Option<&SyncBlob>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE sync_records SET blob = COALESCE(?2, blob) WHERE uuid = ?1",
+            params![uuid, blob.map(serde_json::to_string).transpose()?],
+        )?;
         self.conn.execute(
-            "UPDATE files SET cloud_storage_path = ?1 WHERE id = ?2",
-            params![cloud_path, id],
+            "UPDATE sync_records SET dirty = 0 WHERE uuid = ?1 AND hlc = ?2",
+            params![uuid, hlc],
         )?;
         Ok(())
     }
+
+    /// The synced columns of a record as JSON, with local folder IDs. Secret
+    /// snippets never leave this device, so they read as missing.
+    pub fn read_sync_row(&self, kind: SyncRecordKind, id: i64) -> Result<Option<Map<String, Value>>> {
+        let (table, columns) = sync_columns(kind);
+        let secret_filter = if kind == SyncRecordKind::Snippet { " AND secret = 0" } else { "" };
+        let mut row = self.conn
+            .query_row(
+                &format!("SELECT {} FROM {} WHERE id = ?1{}", columns.join(", "), table, secret_filter),
+                params![id],
+                |row| {
+                    let mut values = Map::new();
+                    for (i, column) in columns.iter().enumerate() {
+                        values.insert(column.to_string(), sql_to_json(row.get_ref(i)?));
+                    }
+                    Ok(values)
+                },
+            )
+            .optional()?;
+
+        if let (Some(row), SyncRecordKind::ClipboardEntry) = (row.as_mut(), kind) {
+            row.insert("tags".to_string(), self.get_item_tags(TaggedKind::Clipboard, id)?.into());
+        }
+        Ok(row)
+    }
+
+    /// Write a record pulled from the server, creating it when `id` is `None`.
+    /// A trigger keyword or folder name that's taken here is changed on this
+    /// device only, so two devices don't keep renaming each other's records.
+    pub fn write_sync_row(&self, kind: SyncRecordKind, id: Option<i64>, row: &Map<String, Value>) -> Result<i64> {
+        let (table, columns) = sync_columns(kind);
+        let mut values: Vec<(&str, rusqlite::types::Value)> = columns
+            .iter()
+            .filter_map(|column| Some((*column, json_to_sql(row.get(*column)?))))
+            .collect();
+
+        for (column, value) in values.iter_mut() {
+            match (kind, *column, &*value) {
+                (SyncRecordKind::Snippet, "trigger_keyword", rusqlite::types::Value::Text(trigger))
+                    if self.find_snippet_by_trigger(trigger)?.is_some_and(|other| Some(other) != id) =>
+                {
+                    println!("⚠️ Trigger '{}' of a synced snippet is already used here; leaving it out", trigger);
+                    *value = rusqlite::types::Value::Null;
+                }
+                (SyncRecordKind::Folder, "name", rusqlite::types::Value::Text(name)) if row.get("deleted_at").is_none_or(Value::is_null) => {
+                    let parent_id = row.get("parent_id").and_then(Value::as_i64);
+                    let mut candidate = name.clone();
+                    let mut n = 2;
+                    while self.ensure_folder_name_available(&candidate, parent_id, id).is_err() {
+                        candidate = format!("{} ({})", name, n);
+                        n += 1;
+                    }
+                    *value = rusqlite::types::Value::Text(candidate);
+                }
+                _ => {}
+            }
+        }
+
+        let tx = self.transaction()?;
+
+        let id = match id {
+            Some(id) => {
+                if kind == SyncRecordKind::Snippet {
+                    // The version being replaced goes to history, as with local edits
+                    tx.execute(
+                        "INSERT INTO snippet_revisions (snippet_id, title, content, language, description, tags, saved_at, created_at)
+                         SELECT id, title, content, language, description, tags, updated_at, ?2
+                         FROM snippets
+                         WHERE id = ?1 AND secret = 0
+                           AND NOT (title IS ?3 AND content IS ?4 AND language IS ?5 AND description IS ?6 AND tags IS ?7)",
+                        params![
+                            id,
+                            Utc::now().to_rfc3339(),
+                            row.get("title").and_then(Value::as_str),
+                            row.get("content").and_then(Value::as_str),
+                            row.get("language").and_then(Value::as_str),
+                            row.get("description").and_then(Value::as_str),
+                            row.get("tags").and_then(Value::as_str),
+                        ],
+                    )?;
+                }
+                let assignments: Vec<String> = values.iter().map(|(column, _)| format!("{} = ?", column)).collect();
+                let mut params: Vec<rusqlite::types::Value> = values.into_iter().map(|(_, value)| value).collect();
+                params.push(id.into());
+                tx.execute(
+                    &format!("UPDATE {} SET {} WHERE id = ?", table, assignments.join(", ")),
+                    rusqlite::params_from_iter(params),
+                )?;
+                id
+            }
+            None => {
+                let names: Vec<&str> = values.iter().map(|(column, _)| *column).collect();
+                let placeholders = vec!["?"; names.len()].join(", ");
+                tx.execute(
+                    &format!("INSERT INTO {} ({}) VALUES ({})", table, names.join(", "), placeholders),
+                    rusqlite::params_from_iter(values.into_iter().map(|(_, value)| value)),
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+
+        let tagged = match kind {
+            SyncRecordKind::Snippet => Some((TaggedKind::Snippet, tags::parse_tags(row.get("tags").and_then(Value::as_str).unwrap_or_default()))),
+            SyncRecordKind::File => Some((TaggedKind::File, tags::parse_tags(row.get("tags").and_then(Value::as_str).unwrap_or_default()))),
+            SyncRecordKind::ClipboardEntry => Some((
+                TaggedKind::Clipboard,
+                tags::normalize_tags(
+                    row.get("tags").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str),
+                ),
+            )),
+            SyncRecordKind::Folder => None,
+        };
+        if let Some((tagged_kind, names)) = tagged {
+            let names = self.canonical_tag_names(names)?;
+            self.set_item_tags(tagged_kind, id, &names)?;
+        }
+
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Permanently delete a record that was deleted on another device.
+    /// Returns the storage path of a deleted file, for the caller to remove.
+    pub fn delete_sync_row(&self, kind: SyncRecordKind, id: i64) -> Result<Option<String>> {
+        match kind {
+            SyncRecordKind::Snippet => self.purge_snippet(id)?,
+            SyncRecordKind::ClipboardEntry => self.delete_clipboard_entry(id)?,
+            SyncRecordKind::File => {
+                let path = self.get_file(id)?.map(|file| file.storage_path);
+                self.purge_file(id)?;
+                return Ok(path);
+            }
+            SyncRecordKind::Folder => {
+                // Whatever is still inside ends up uncategorized or at the top level
+                let tx = self.transaction()?;
+                for table in ["snippets", "files"] {
+                    tx.execute(&format!("UPDATE {} SET folder_id = NULL WHERE folder_id = ?1", table), params![id])?;
+                    tx.execute(
+                        &format!("UPDATE {} SET trashed_folder_id = NULL WHERE trashed_folder_id = ?1", table),
+                        params![id],
+                    )?;
+                }
+                tx.execute("UPDATE folders SET parent_id = NULL WHERE parent_id = ?1", params![id])?;
+                tx.execute("DELETE FROM folders WHERE id = ?1", params![id])?;
+                tx.commit()?;
+            }
+        }
+        Ok(None)
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(title: &str) -> Snippet {
+        let now = Utc::now().to_rfc3339();
+        Snippet {
+            id: None,
+            title: title.to_string(),
+            content: title.to_string(),
+            language: "text".to_string(),
+            description: None,
+            tags: None,
+            folder_id: None,
+            created_at: now.clone(),
+            updated_at: now,
+            trigger: None,
+            deleted_at: None,
+            secret: false,
+        }
+    }
+
+    #[test]
+    fn transaction_rolls_back_nested_writes_when_dropped() {
+        let db = Database::open_in_memory().unwrap();
+        {
+            let _tx = db.transaction().unwrap();
+            db.create_folder("Imported", None, None).unwrap();
+            db.create_snippet(&snippet("one")).unwrap();
+        }
+        assert!(db.get_all_snippets().unwrap().is_empty());
+        assert!(db.get_all_folders(false).unwrap().is_empty());
+
+        let tx = db.transaction().unwrap();
+        db.create_snippet(&snippet("two")).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(db.get_all_snippets().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn nested_transaction_rolls_back_only_its_own_writes() {
+        let db = Database::open_in_memory().unwrap();
+        let outer = db.transaction().unwrap();
+        db.create_snippet(&snippet("kept")).unwrap();
+        {
+            let _inner = db.transaction().unwrap();
+            db.create_snippet(&snippet("dropped")).unwrap();
+        }
+        outer.commit().unwrap();
+
+        let titles: Vec<String> = db.get_all_snippets().unwrap().into_iter().map(|s| s.title).collect();
+        assert_eq!(titles, ["kept"]);
+    }
+
+    #[test]
+    fn replace_import_keeps_secret_snippets() {
+        let db = Database::open_in_memory().unwrap();
+        let work = db.create_folder("Work", None, None).unwrap();
+        let keys = db.create_folder("Keys", None, Some(work)).unwrap();
+        db.create_folder("Empty", None, None).unwrap();
+
+        let mut secret = snippet("API token");
+        secret.secret = true;
+        secret.folder_id = Some(keys);
+        secret.tags = Some("ops".to_string());
+        let secret_id = db.create_snippet(&secret).unwrap();
+        // From before it was made secret
+        db.conn
+            .execute(
+                "INSERT INTO snippet_revisions (snippet_id, title, content, language, saved_at, created_at)
+                 VALUES (?1, 'API token', 'old', 'text', '2026-01-01', '2026-01-01')",
+                [secret_id],
+            )
+            .unwrap();
+        let plain = Snippet { folder_id: Some(work), tags: Some("stale".to_string()), ..snippet("plain") };
+        db.create_snippet(&plain).unwrap();
+
+        let data = LibraryData { snippets: vec![snippet("imported")], ..Default::default() };
+        let (report, _) = db.import_library(&data, ImportMode::Replace, &HashMap::new()).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+
+        let snippets = db.get_all_snippets().unwrap();
+        let titles: Vec<&str> = snippets.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles.len(), 2);
+        assert!(titles.contains(&"API token") && titles.contains(&"imported"));
+
+        let kept = db.get_snippet(secret_id).unwrap().unwrap();
+        assert!(kept.secret);
+        assert_eq!(kept.folder_id, Some(keys));
+        assert_eq!(kept.tags.as_deref(), Some("ops"));
+        assert_eq!(db.get_snippet_revisions(secret_id).unwrap().len(), 1);
+
+        let mut folders: Vec<String> = db.get_all_folders(false).unwrap().into_iter().map(|f| f.name).collect();
+        folders.sort();
+        assert_eq!(folders, ["Keys", "Work"]);
+        let tags: Vec<String> = db.get_all_tags().unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(tags, ["ops"]);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::models::{DiffLine, DiffLineKind};
+
+/// Above this many cells the LCS table is skipped and the changed middle is
+/// reported as removed-then-added (keeps huge pastes from allocating gigabytes)
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Line-level diff of `old` against `new` (longest common subsequence)
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Common prefix and suffix never need the LCS table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut oi, mut ni) = (0, 0);
+
+    let mut push = |kind: DiffLineKind, text: &str, oi: &mut usize, ni: &mut usize| {
+        let old_line = (kind != DiffLineKind::Added).then(|| {
+            *oi += 1;
+            *oi
+        });
+        let new_line = (kind != DiffLineKind::Removed).then(|| {
+            *ni += 1;
+            *ni
+        });
+        lines.push(DiffLine { kind, text: text.to_string(), old_line, new_line });
+    };
+
+    for line in &old[..prefix] {
+        push(DiffLineKind::Unchanged, line, &mut oi, &mut ni);
+    }
+
+    for (kind, line) in diff_middle(old_mid, new_mid) {
+        push(kind, line, &mut oi, &mut ni);
+    }
+
+    for line in &old[old.len() - suffix..] {
+        push(DiffLineKind::Unchanged, line, &mut oi, &mut ni);
+    }
+
+    lines
+}
+
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffLineKind, &'a str)> {
+    if (old.len() + 1).saturating_mul(new.len() + 1) > MAX_LCS_CELLS {
+        return old
+            .iter()
+            .map(|l| (DiffLineKind::Removed, *l))
+            .chain(new.iter().map(|l| (DiffLineKind::Added, *l)))
+            .collect();
+    }
+
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push((DiffLineKind::Unchanged, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            result.push((DiffLineKind::Removed, old[i]));
+            i += 1;
+        } else {
+            result.push((DiffLineKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|l| (DiffLineKind::Removed, *l)));
+    result.extend(new[j..].iter().map(|l| (DiffLineKind::Added, *l)));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each line as `" text"`, `"+text"` or `"-text"`
+    fn render(old: &str, new: &str) -> Vec<String> {
+        diff_lines(old, new)
+            .into_iter()
+            .map(|line| {
+                let sign = match line.kind {
+                    DiffLineKind::Unchanged => ' ',
+                    DiffLineKind::Added => '+',
+                    DiffLineKind::Removed => '-',
+                };
+                format!("{}{}", sign, line.text)
+            })
+            .collect()
+    }
+
+    fn numbers(old: &str, new: &str) -> Vec<(Option<usize>, Option<usize>)> {
+        diff_lines(old, new).into_iter().map(|l| (l.old_line, l.new_line)).collect()
+    }
+
+    #[test]
+    fn empty() {
+        assert!(diff_lines("", "").is_empty());
+        assert_eq!(render("", "a\nb"), ["+a", "+b"]);
+        assert_eq!(render("a\nb", ""), ["-a", "-b"]);
+        assert_eq!(numbers("", "a"), [(None, Some(1))]);
+        assert_eq!(numbers("a", ""), [(Some(1), None)]);
+    }
+
+    #[test]
+    fn identical() {
+        assert_eq!(render("a\nb\nc", "a\nb\nc"), [" a", " b", " c"]);
+        assert_eq!(numbers("a\nb", "a\nb"), [(Some(1), Some(1)), (Some(2), Some(2))]);
+        // A trailing newline doesn't add a line
+        assert_eq!(render("a\n", "a"), [" a"]);
+    }
+
+    #[test]
+    fn insert_only() {
+        assert_eq!(render("a\nc", "a\nb\nc"), [" a", "+b", " c"]);
+        assert_eq!(render("b", "a\nb\nc"), ["+a", " b", "+c"]);
+        assert_eq!(
+            numbers("a\nc", "a\nb\nc"),
+            [(Some(1), Some(1)), (None, Some(2)), (Some(2), Some(3))]
+        );
+    }
+
+    #[test]
+    fn delete_only() {
+        assert_eq!(render("a\nb\nc", "a\nc"), [" a", "-b", " c"]);
+        assert_eq!(render("a\nb\nc", "b"), ["-a", " b", "-c"]);
+        assert_eq!(
+            numbers("a\nb\nc", "a\nc"),
+            [(Some(1), Some(1)), (Some(2), None), (Some(3), Some(2))]
+        );
+    }
+
+    #[test]
+    fn replacement_keeps_common_lines() {
+        assert_eq!(
+            render("x\na\ny\nb\nz", "x\nA\ny\nB\nz"),
+            [" x", "-a", "+A", " y", "-b", "+B", " z"]
+        );
+        // Repeated lines must not be matched twice
+        assert_eq!(render("a\na", "a"), [" a", "-a"]);
+        assert_eq!(render("a", "a\na"), [" a", "+a"]);
+    }
+
+    #[test]
+    fn huge_changes_skip_the_lcs_table() {
+        let old: Vec<String> = (0..2_100).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..2_100).map(|i| format!("new {}", i)).collect();
+        let lines = diff_lines(&format!("same\n{}", old.join("\n")), &format!("same\n{}", new.join("\n")));
+
+        assert_eq!(lines.len(), 1 + 2 * 2_100);
+        assert_eq!(lines[0].kind, DiffLineKind::Unchanged);
+        assert!(lines[1..=2_100].iter().all(|l| l.kind == DiffLineKind::Removed));
+        assert!(lines[2_101..].iter().all(|l| l.kind == DiffLineKind::Added));
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{anyhow, Context, Result};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use keyring::Entry;
+use hkdf::Hkdf;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::auth::SERVICE_NAME;
+use crate::models::EncryptionStatus;
+
+// The library key is 32 random bytes. The database (SQLCipher) and the
+// stored files (XChaCha20-Poly1305) each use a key derived from it with
+// HKDF-SHA256, told apart by the info string.
+//
+// The key is kept in the OS keyring, and/or wrapped with a key derived from a
+// passphrase (Argon2id). How it's kept is recorded in `encryption.json` next
+// to the database; encryption is enabled when that file exists.
+
+const CONFIG_PATH: &str = "encryption.json";
+const KEYRING_KEY: &str = "library_encryption_key";
+
+/// Start of every encrypted file, followed by the nonce
+const FILE_MAGIC: &[u8] = b"NBDENC1\0";
+const NONCE_LENGTH: usize = 24;
+pub(crate) const SALT_LENGTH: usize = 16;
+const ENCRYPTING_SUFFIX: &str = ".encrypting";
+
+/// Start of every plaintext SQLite database
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Config {
+    /// The key is in the OS keyring
+    keyring: bool,
+    /// Argon2id salt for the passphrase (base64)
+    #[serde(default)]
+    salt: Option<String>,
+    /// The key encrypted with the passphrase's key (base64 of nonce and ciphertext)
+    #[serde(default)]
+    wrapped_key: Option<String>,
+}
+
+struct Keys {
+    /// The library key, kept to wrap it with a passphrase added later
+    library: [u8; 32],
+    database: [u8; 32],
+    files: [u8; 32],
+}
+
+/// The keys once loaded from the keyring or unlocked with the passphrase
+static KEYS: RwLock<Option<Keys>> = RwLock::new(None);
+
+fn config_path() -> Result<PathBuf> {
+    let app_data = dirs::data_local_dir()
+        .context("Failed to get app data directory")?
+        .join("nobraindev");
+    fs::create_dir_all(&app_data)?;
+    Ok(app_data.join(CONFIG_PATH))
+}
+
+fn read_config() -> Result<Option<Config>> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config = serde_json::from_slice(&fs::read(&path)?)
+        .with_context(|| format!("{} is invalid", path.display()))?;
+    Ok(Some(config))
+}
+
+fn write_config(config: &Config) -> Result<()> {
+    let path = config_path()?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, serde_json::to_vec_pretty(config)?)?;
+    fs::rename(&temp, &path).context("Failed to save the encryption settings")
+}
+
+/// Whether the library is (being) encrypted
+pub fn is_enabled() -> bool {
+    config_path().is_ok_and(|path| path.exists())
+}
+
+pub fn is_unlocked() -> bool {
+    KEYS.read().is_ok_and(|keys| keys.is_some())
+}
+
+fn derive(key: &[u8; 32], info: &[u8]) -> [u8; 32] {
+    let mut derived = [0u8; 32];
+    Hkdf::<Sha256>::new(None, key)
+        .expand(info, &mut derived)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    derived
+}
+
+fn set_key(key: &[u8; 32]) -> Result<()> {
+    let keys = Keys {
+        library: *key,
+        database: derive(key, b"nobraindev library database key"),
+        files: derive(key, b"nobraindev library files key"),
+    };
+    *KEYS.write().map_err(|e| anyhow!(e.to_string()))? = Some(keys);
+    Ok(())
+}
+
+fn decode_key(text: &str) -> Result<[u8; 32]> {
+    BASE64
+        .decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("The stored encryption key is invalid")
+}
+
+fn keyring_entry() -> Result<Entry> {
+    Entry::new(SERVICE_NAME, KEYRING_KEY).map_err(|e| anyhow!("Failed to create keyring entry: {}", e))
+}
+
+/// The passphrase's key, which wraps the library key
+pub(crate) fn passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive a key from the passphrase: {}", e))?;
+    Ok(key)
+}
+
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+pub(crate) fn seal(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>> {
+    let nonce: [u8; NONCE_LENGTH] = random_bytes();
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(XNonce::from_slice(&nonce), data)
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+/// `None` if the data was tampered with or the key is wrong
+pub(crate) fn open(key: &[u8; 32], sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    XChaCha20Poly1305::new(key.into()).decrypt(XNonce::from_slice(nonce), ciphertext).ok()
+}
+
+/// Load the key from the keyring at startup. Returns false when encryption
+/// is off, or when the library has to be unlocked with its passphrase.
+pub fn load_key() -> Result<bool> {
+    let Some(config) = read_config()? else { return Ok(false) };
+    if !config.keyring {
+        return Ok(false);
+    }
+
+    match keyring_entry()?.get_password() {
+        Ok(key) => {
+            set_key(&decode_key(&key)?)?;
+            Ok(true)
+        }
+        Err(e) if config.wrapped_key.is_some() => {
+            eprintln!("Encryption key unavailable from the keyring, the passphrase is needed: {}", e);
+            Ok(false)
+        }
+        Err(e) => Err(anyhow!("The encryption key is missing from the keyring: {}", e)),
+    }
+}
+
+/// Wrap the library key with the passphrase's key
+fn wrap_key(config: &mut Config, key: &[u8; 32], passphrase: &str) -> Result<()> {
+    let salt: [u8; SALT_LENGTH] = random_bytes();
+    config.salt = Some(BASE64.encode(salt));
+    config.wrapped_key = Some(BASE64.encode(seal(&passphrase_key(passphrase, &salt)?, key)?));
+    Ok(())
+}
+
+/// Unlock the library with its passphrase
+pub fn unlock(passphrase: &str) -> Result<()> {
+    let config = read_config()?.context("The library isn't encrypted")?;
+    let (Some(salt), Some(wrapped_key)) = (&config.salt, &config.wrapped_key) else {
+        anyhow::bail!("The library has no passphrase");
+    };
+    let salt = BASE64.decode(salt).context("The passphrase salt is invalid")?;
+    let wrapped_key = BASE64.decode(wrapped_key).context("The wrapped key is invalid")?;
+
+    let key: [u8; 32] = open(&passphrase_key(passphrase, &salt)?, &wrapped_key)
+        .and_then(|key| key.try_into().ok())
+        .context("Wrong passphrase")?;
+    set_key(&key)?;
+    println!("🔐 Library unlocked");
+    Ok(())
+}
+
+/// Create the library key, keeping it in the keyring and/or wrapped with a
+/// passphrase. Once it exists (e.g. after an interrupted migration) it's
+/// kept as it is.
+pub fn enable(use_keyring: bool, passphrase: Option<&str>) -> Result<()> {
+    if is_enabled() {
+        if !is_unlocked() {
+            anyhow::bail!("The library is locked");
+        }
+        return Ok(());
+    }
+    if !use_keyring && passphrase.is_none() {
+        anyhow::bail!("Keep the key in the keyring, or choose a passphrase");
+    }
+
+    let key: [u8; 32] = random_bytes();
+    let mut config = Config { keyring: use_keyring, ..Config::default() };
+
+    if let Some(passphrase) = passphrase {
+        wrap_key(&mut config, &key, passphrase)?;
+    }
+    if use_keyring {
+        keyring_entry()?
+            .set_password(&BASE64.encode(key))
+            .map_err(|e| anyhow!("Failed to store the key in the keyring (use a passphrase instead): {}", e))?;
+    }
+
+    write_config(&config)?;
+    set_key(&key)?;
+    println!("🔐 Created the library encryption key");
+    Ok(())
+}
+
+/// Add a passphrase to a library whose key is only in the keyring, so it can
+/// still be unlocked if the keyring loses the key
+pub fn add_passphrase(passphrase: &str) -> Result<()> {
+    let mut config = read_config()?.context("The library isn't encrypted")?;
+    if config.wrapped_key.is_some() {
+        anyhow::bail!("The library already has a passphrase");
+    }
+    let key = {
+        let keys = KEYS.read().map_err(|e| anyhow!(e.to_string()))?;
+        keys.as_ref().context("The library is locked")?.library
+    };
+    wrap_key(&mut config, &key, passphrase)?;
+    write_config(&config)?;
+    println!("🔐 Added a passphrase to the library key");
+    Ok(())
+}
+
+/// Try the keyring again, e.g. once the OS keychain is unlocked
+pub fn retry_keyring() -> Result<()> {
+    if !load_key()? {
+        anyhow::bail!("The encryption key isn't in the keyring");
+    }
+    Ok(())
+}
+
+/// Give up on a library whose key is lost: move the encrypted database, the
+/// stored files and the encryption settings into a new directory next to
+/// them, so that an empty library can be started. Nothing is deleted, in case
+/// the key turns up. Returns the directory they were moved to.
+pub fn set_aside_library(database: &Path, storage_dir: &Path) -> Result<PathBuf> {
+    let config = config_path()?;
+    let parent = config.parent().context("The encryption settings have no directory")?;
+    let aside = parent.join(format!("locked-library-{}", chrono::Utc::now().format("%Y-%m-%d_%H%M%S")));
+    fs::create_dir_all(&aside).with_context(|| format!("Failed to create {}", aside.display()))?;
+
+    let mut moves = vec![database.to_path_buf(), storage_dir.to_path_buf()];
+    for suffix in ["-wal", "-shm"] {
+        moves.push(PathBuf::from(format!("{}{}", database.display(), suffix)));
+    }
+    // Last: until it's gone the library still counts as encrypted
+    moves.push(config);
+    for path in moves.iter().filter(|path| path.exists()) {
+        let target = aside.join(path.file_name().context("Invalid library path")?);
+        fs::rename(path, &target).with_context(|| format!("Failed to move {}", path.display()))?;
+    }
+
+    *KEYS.write().map_err(|e| anyhow!(e.to_string()))? = None;
+    println!("🔐 Set the locked library aside in {}", aside.display());
+    Ok(aside)
+}
+
+/// The SQLCipher key of the database, as a raw key literal
+pub fn database_key() -> Result<String> {
+    let keys = KEYS.read().map_err(|e| anyhow!(e.to_string()))?;
+    let keys = keys.as_ref().context("The library is locked")?;
+    Ok(format!("x'{}'", keys.database.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+/// Key a connection to an encrypted database, and make sure the key fits
+pub fn apply_key(conn: &Connection) -> Result<()> {
+    conn.pragma_update(None, "key", database_key()?)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .context("The database can't be read with the library key")?;
+    Ok(())
+}
+
+/// Export the database `conn` has open to an encrypted copy at `destination`
+pub fn export_encrypted(conn: &Connection, destination: &Path) -> Result<()> {
+    let _ = fs::remove_file(destination);
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        params![destination.to_string_lossy(), database_key()?],
+    )?;
+    let exported = conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()));
+    conn.execute("DETACH DATABASE encrypted", [])?;
+    if let Err(e) = exported {
+        let _ = fs::remove_file(destination);
+        return Err(e).context("Failed to encrypt the database");
+    }
+    Ok(())
+}
+
+/// Whether `path` is an unencrypted SQLite database. A missing or empty
+/// file isn't: it's created encrypted when encryption is on.
+pub fn is_plaintext_database(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    fs::File::open(path)
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut header))
+        .is_ok_and(|_| header == SQLITE_HEADER)
+}
+
+pub fn is_encrypted_file_data(data: &[u8]) -> bool {
+    data.starts_with(FILE_MAGIC)
+}
+
+/// Encrypt a stored file's contents. Left as they are while encryption is off.
+pub fn encrypt_file_data(data: &[u8]) -> Result<Vec<u8>> {
+    let keys = KEYS.read().map_err(|e| anyhow!(e.to_string()))?;
+    match keys.as_ref() {
+        Some(keys) => Ok([FILE_MAGIC, &seal(&keys.files, data)?].concat()),
+        None if is_enabled() => Err(anyhow!("The library is locked")),
+        None => Ok(data.to_vec()),
+    }
+}
+
+/// Decrypt a stored file's contents; plaintext files are returned as they are
+pub fn decrypt_file_data(data: Vec<u8>) -> Result<Vec<u8>> {
+    let Some(sealed) = data.strip_prefix(FILE_MAGIC) else { return Ok(data) };
+    let keys = KEYS.read().map_err(|e| anyhow!(e.to_string()))?;
+    let keys = keys.as_ref().context("The library is locked")?;
+    open(&keys.files, sealed).context("The file is corrupted or was encrypted with another key")
+}
+
+fn plaintext_files(storage_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(storage_dir).with_context(|| format!("Failed to read {}", storage_dir.display()))? {
+        let entry = entry?;
+        // Left behind by an interrupted `encrypt_stored_files`
+        if !entry.file_type()?.is_file() || entry.file_name().to_string_lossy().ends_with(ENCRYPTING_SUFFIX) {
+            continue;
+        }
+        let mut magic = [0u8; FILE_MAGIC.len()];
+        let is_encrypted = fs::File::open(entry.path())
+            .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut magic))
+            .is_ok_and(|_| is_encrypted_file_data(&magic));
+        if !is_encrypted {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+/// Encrypt the stored files that are still plaintext, each replaced in one
+/// rename. Returns how many were encrypted.
+pub fn encrypt_stored_files(storage_dir: &Path) -> Result<usize> {
+    let files = plaintext_files(storage_dir)?;
+    for path in &files {
+        let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let temp = path.with_file_name(format!(
+            ".{}{}",
+            path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(),
+            ENCRYPTING_SUFFIX
+        ));
+        fs::write(&temp, encrypt_file_data(&data)?).with_context(|| format!("Failed to write {}", temp.display()))?;
+        fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    }
+    if !files.is_empty() {
+        println!("🔐 Encrypted {} stored files", files.len());
+    }
+    Ok(files.len())
+}
+
+pub fn status(database_encrypted: bool, storage_dir: &Path, plaintext_backups: usize) -> Result<EncryptionStatus> {
+    let config = read_config()?;
+    Ok(EncryptionStatus {
+        enabled: config.is_some(),
+        keyring: config.as_ref().is_some_and(|c| c.keyring),
+        passphrase: config.as_ref().is_some_and(|c| c.wrapped_key.is_some()),
+        locked: config.is_some() && !is_unlocked(),
+        database_encrypted,
+        plaintext_files: if config.is_some() { plaintext_files(storage_dir)?.len() } else { 0 },
+        plaintext_backups: if config.is_some() { plaintext_backups } else { 0 },
+    })
+}
@@ -0,0 +1,383 @@
+// Text expansion: type a snippet's trigger (e.g. `;k8slogs`) in any app and it
+// is replaced with the rendered snippet.
+//
+// A background thread watches keystrokes through `rdev` and keeps a short buffer
+// of recently typed characters. When the buffer ends with a trigger, the trigger
+// is erased with synthetic backspaces and the snippet is pasted through the same
+// transient-clipboard path the launcher uses. On Linux the listener needs an X11
+// session (or XWayland windows); on macOS the app needs Accessibility access.
+//
+// The keyboard hook is only installed once expansion is active (not paused, and
+// at least one snippet has a trigger). rdev can't remove a hook again, so when
+// expansion stops being active the listener goes idle and ignores keystrokes.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use rdev::{EventType, Key};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::{ExpansionSettings, SnippetRender};
+use crate::templates;
+use crate::AppState;
+
+/// Longest trigger accepted; also how many typed characters are remembered
+pub const MAX_TRIGGER_LENGTH: usize = 64;
+
+/// Set while we send synthetic keys so the listener does not see its own output
+static EXPANDING: AtomicBool = AtomicBool::new(false);
+
+/// Expansion isn't paused and some snippet has a trigger; otherwise keystrokes
+/// are dropped before they are looked at
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Characters typed since the last cursor movement, click or shortcut
+#[derive(Default)]
+struct TypedBuffer {
+    chars: Vec<char>,
+}
+
+impl TypedBuffer {
+    fn push_str(&mut self, text: &str) {
+        self.chars.extend(text.chars());
+        if self.chars.len() > MAX_TRIGGER_LENGTH {
+            let excess = self.chars.len() - MAX_TRIGGER_LENGTH;
+            self.chars.drain(..excess);
+        }
+    }
+
+    fn backspace(&mut self) {
+        self.chars.pop();
+    }
+
+    fn clear(&mut self) {
+        self.chars.clear();
+    }
+
+    fn ends_with(&self, trigger: &str) -> bool {
+        let trigger: Vec<char> = trigger.chars().collect();
+        self.chars.ends_with(&trigger)
+    }
+}
+
+/// Listener state shared with the commands that change snippets or settings
+#[derive(Default)]
+pub struct ExpansionState {
+    settings: ExpansionSettings,
+    triggers: HashMap<String, i64>,
+    /// Triggers must be reloaded from the database before the next match
+    stale: bool,
+    buffer: TypedBuffer,
+    /// Ctrl or Cmd is held, so keystrokes are shortcuts rather than text
+    shortcut_held: bool,
+    /// The keyboard hook thread has been started
+    hooked: bool,
+    /// Set by `init`, so that `invalidate` can start or idle the listener
+    app_handle: Option<AppHandle>,
+}
+
+impl ExpansionState {
+    /// Reload triggers before the next keystroke is matched, and start or idle
+    /// the listener once the caller has released the database
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+        if let Some(app_handle) = self.app_handle.clone() {
+            thread::spawn(move || {
+                if let Err(e) = refresh(&app_handle) {
+                    println!("⚠️  Failed to refresh text expansion: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Longest trigger the typed text ends with
+    fn matching_trigger(&self) -> Option<(String, i64)> {
+        self.triggers
+            .iter()
+            .filter(|(trigger, _)| self.buffer.ends_with(trigger))
+            .max_by_key(|(trigger, _)| trigger.chars().count())
+            .map(|(trigger, id)| (trigger.clone(), *id))
+    }
+}
+
+/// Load settings and triggers, and start listening if expansion is in use
+pub fn init(app_handle: AppHandle) {
+    let state = app_handle.state::<AppState>();
+    if let Ok(mut expansion) = state.expansion.lock() {
+        expansion.app_handle = Some(app_handle.clone());
+    }
+
+    if let Err(e) = refresh(&app_handle) {
+        println!("⚠️  Failed to start text expansion: {}", e);
+    }
+}
+
+/// Reload settings and triggers, then start the listener if expansion just
+/// became active, or let it idle if it no longer is
+pub fn refresh(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let (settings, triggers) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (
+            db.get_expansion_settings().map_err(|e| e.to_string())?,
+            db.get_snippet_triggers().map_err(|e| e.to_string())?,
+        )
+    };
+    let active = !settings.paused && !triggers.is_empty();
+
+    let start = {
+        let mut expansion = state.expansion.lock().map_err(|e| e.to_string())?;
+        expansion.settings = settings;
+        expansion.triggers = triggers;
+        expansion.stale = false;
+        expansion.buffer.clear();
+
+        let start = active && !expansion.hooked;
+        expansion.hooked |= active;
+        start
+    };
+
+    if ACTIVE.swap(active, Ordering::SeqCst) != active {
+        println!("⌨️  Text expansion {}", if active { "listening" } else { "idle" });
+    }
+
+    if start {
+        let handle = app_handle.clone();
+        thread::spawn(move || {
+            println!("⌨️  Text expansion listener started");
+            let callback_handle = handle.clone();
+            if let Err(e) = rdev::listen(move |event| handle_event(&callback_handle, event.event_type, event.name)) {
+                println!("❌ Text expansion listener stopped: {:?}", e);
+            }
+            // Allow a later refresh to try again
+            if let Ok(mut expansion) = handle.state::<AppState>().expansion.lock() {
+                expansion.hooked = false;
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_event(app_handle: &AppHandle, event_type: EventType, name: Option<String>) {
+    if !ACTIVE.load(Ordering::SeqCst) || EXPANDING.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let state = app_handle.state::<AppState>();
+    let stale = {
+        let Ok(mut expansion) = state.expansion.lock() else { return };
+
+        match event_type {
+            EventType::KeyPress(Key::ControlLeft | Key::ControlRight | Key::MetaLeft | Key::MetaRight) => {
+                expansion.shortcut_held = true;
+                return;
+            }
+            EventType::KeyRelease(Key::ControlLeft | Key::ControlRight | Key::MetaLeft | Key::MetaRight) => {
+                expansion.shortcut_held = false;
+                return;
+            }
+            // Modifiers that only change which character is typed
+            EventType::KeyPress(Key::ShiftLeft | Key::ShiftRight | Key::Alt | Key::AltGr | Key::CapsLock) => return,
+            EventType::KeyPress(Key::Backspace) => {
+                expansion.buffer.backspace();
+                return;
+            }
+            EventType::KeyPress(_) => {
+                let text = name.filter(|t| !t.is_empty() && !t.chars().any(char::is_control));
+                match text {
+                    Some(text) if !expansion.shortcut_held => expansion.buffer.push_str(&text),
+                    // Enter, Tab, arrows, shortcuts... the caret may have moved
+                    _ => {
+                        expansion.buffer.clear();
+                        return;
+                    }
+                }
+            }
+            // A click can move the caret anywhere
+            EventType::ButtonPress(_) => {
+                expansion.buffer.clear();
+                return;
+            }
+            _ => return,
+        }
+
+        if expansion.settings.paused {
+            return;
+        }
+        expansion.stale
+    };
+
+    // Commands lock the database before the expansion state, so never hold
+    // the expansion state while waiting for the database
+    let triggers = if stale {
+        let loaded = state.db.lock().map_err(|e| e.to_string())
+            .and_then(|db| db.get_snippet_triggers().map_err(|e| e.to_string()));
+        match loaded {
+            Ok(triggers) => Some(triggers),
+            Err(e) => {
+                println!("⚠️  Failed to load snippet triggers: {}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    let matched = {
+        let Ok(mut expansion) = state.expansion.lock() else { return };
+        if let Some(triggers) = triggers {
+            expansion.triggers = triggers;
+            expansion.stale = false;
+        }
+
+        let matched = expansion.matching_trigger();
+        if matched.is_some() {
+            expansion.buffer.clear();
+        }
+        matched
+    };
+
+    if let Some((trigger, snippet_id)) = matched {
+        // Never block the listener thread: the OS may drop a slow event tap
+        let handle = app_handle.clone();
+        thread::spawn(move || {
+            if let Err(e) = expand(&handle, &trigger, snippet_id) {
+                println!("❌ Failed to expand '{}': {}", trigger, e);
+            }
+        });
+    }
+}
+
+/// Replace the just-typed `trigger` with the snippet
+fn expand(app_handle: &AppHandle, trigger: &str, snippet_id: i64) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+
+    let app = frontmost_app_id();
+    if app.as_deref().is_some_and(|app| app.to_lowercase().contains("nobraindev")) {
+        // Typing a trigger into our own editor must not expand it
+        return Ok(());
+    }
+
+    let settings = state.expansion.lock().map_err(|e| e.to_string())?.settings.clone();
+    if !settings.allows_app(app.as_deref()) {
+        println!("⏭️  Skipping expansion of '{}' in {}", trigger, app.as_deref().unwrap_or("unknown app"));
+        return Ok(());
+    }
+
+    let snippet = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_snippet(snippet_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Snippet {} not found", snippet_id))?
+    };
+
+    // Variables with defaults are filled in; anything else needs the launcher
+    let rendered = templates::render(&snippet.content, Some(&HashMap::new())).map_err(|e| e.to_string())?;
+
+    EXPANDING.store(true, Ordering::SeqCst);
+    let result = erase(trigger.chars().count()).and_then(|_| match rendered {
+        SnippetRender::Rendered { content } => {
+            // Paste into whatever has focus now, not a window captured earlier
+            crate::commands::forget_previous_app();
+            crate::commands::paste_text_transient(&content, false)
+        }
+        SnippetRender::NeedsInput { .. } => {
+            crate::commands::show_snippet_launcher(app_handle.clone())?;
+            app_handle
+                .emit_to("snippet-launcher", "snippet-launcher-prompt", snippet_id)
+                .map_err(|e| e.to_string())
+        }
+    });
+    // Let the synthetic keys drain before listening again
+    thread::sleep(Duration::from_millis(100));
+    EXPANDING.store(false, Ordering::SeqCst);
+
+    if result.is_ok() {
+        println!("✅ Expanded '{}' to snippet {}", trigger, snippet_id);
+    }
+    result
+}
+
+/// Delete `count` characters before the caret
+fn erase(count: usize) -> Result<(), String> {
+    for _ in 0..count {
+        for event in [EventType::KeyPress(Key::Backspace), EventType::KeyRelease(Key::Backspace)] {
+            rdev::simulate(&event).map_err(|_| "Failed to simulate backspace".to_string())?;
+            // Some apps drop events that arrive faster than they repaint
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    Ok(())
+}
+
+/// Identifier of the focused app as used by the allow/deny lists: bundle ID on
+/// macOS, window class on Linux, process name on Windows
+#[cfg(target_os = "macos")]
+fn frontmost_app_id() -> Option<String> {
+    let script = r#"
+    tell application "System Events"
+        return bundle identifier of first application process whose frontmost is true
+    end tell
+    "#;
+
+    let output = std::process::Command::new("osascript").arg("-e").arg(script).output().ok()?;
+    let bundle_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!bundle_id.is_empty() && bundle_id != "missing value").then_some(bundle_id)
+}
+
+#[cfg(target_os = "windows")]
+fn frontmost_app_id() -> Option<String> {
+    let script = r#"
+    Add-Type -Name W -Namespace U -MemberDefinition '[DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow(); [DllImport("user32.dll")] public static extern int GetWindowThreadProcessId(IntPtr h, out int p);'
+    $p = 0
+    [U.W]::GetWindowThreadProcessId([U.W]::GetForegroundWindow(), [ref]$p) | Out-Null
+    (Get-Process -Id $p).ProcessName
+    "#;
+
+    let output = std::process::Command::new("powershell").arg("-c").arg(script).output().ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(target_os = "linux")]
+fn frontmost_app_id() -> Option<String> {
+    crate::linux_paste::active_window_class()
+}
+
+fn emit_settings(app_handle: &AppHandle, settings: &ExpansionSettings) {
+    let _ = app_handle.emit("text-expansion-changed", settings);
+}
+
+pub fn settings(app_handle: &AppHandle) -> Result<ExpansionSettings, String> {
+    let state = app_handle.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_expansion_settings().map_err(|e| e.to_string())
+}
+
+/// Persist `settings` and apply them to the running listener
+pub fn set_settings(app_handle: &AppHandle, settings: ExpansionSettings) -> Result<ExpansionSettings, String> {
+    let state = app_handle.state::<AppState>();
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_expansion_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    refresh(app_handle)?;
+
+    println!("⌨️  Text expansion {}", if settings.paused { "paused" } else { "active" });
+    emit_settings(app_handle, &settings);
+    Ok(settings)
+}
+
+pub fn set_paused(app_handle: &AppHandle, paused: bool) -> Result<ExpansionSettings, String> {
+    let mut current = settings(app_handle)?;
+    current.paused = paused;
+    set_settings(app_handle, current)
+}
+
+/// Flip the global pause (bound to a hotkey)
+pub fn toggle(app_handle: &AppHandle) -> Result<(), String> {
+    let paused = settings(app_handle)?.paused;
+    set_paused(app_handle, !paused).map(|_| ())
+}
@@ -3,6 +3,8 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Write;
 
+use crate::encryption;
+
 const MAX_FILE_SIZE: i64 = 50 * 1024 * 1024; // 50MB
 
 pub struct FileStorageManager {
@@ -24,6 +26,13 @@ impl FileStorageManager {
         Ok(FileStorageManager { storage_dir })
     }
 
+    /// Storage in `storage_dir` instead of the app's data directory
+    #[cfg(test)]
+    pub(crate) fn in_dir(storage_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&storage_dir)?;
+        Ok(FileStorageManager { storage_dir })
+    }
+
     /// Save uploaded file to local storage
     /// Returns the storage path where the file was saved
     pub fn save_file(&self, filename: &str, file_data: &[u8]) -> Result<PathBuf> {
@@ -41,10 +50,13 @@ impl FileStorageManager {
         // Validate file size
         Self::validate_size(file_data.len() as i64)?;
 
+        // Encrypted when encryption at rest is on
+        let file_data = encryption::encrypt_file_data(file_data)?;
+
         // Write file to disk
         let mut file = fs::File::create(&file_path)
             .context("Failed to create file")?;
-        file.write_all(file_data)
+        file.write_all(&file_data)
             .context("Failed to write file data")?;
 
         Ok(file_path)
@@ -57,8 +69,9 @@ impl FileStorageManager {
             return Err(anyhow::anyhow!("File not found: {}", storage_path));
         }
 
-        fs::read(file_path)
-            .context("Failed to read file")
+        let file_data = fs::read(file_path)
+            .context("Failed to read file")?;
+        encryption::decrypt_file_data(file_data)
     }
 
     /// Delete file from local storage
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::models::{HotkeyAction, HotkeyBinding, HotkeySettings, Snippet};
+use crate::AppState;
+
+/// Shortcuts currently registered with the OS, and why the others failed
+#[derive(Default)]
+pub struct HotkeyRegistry {
+    active: HashMap<HotkeyAction, Shortcut>,
+    errors: HashMap<HotkeyAction, String>,
+}
+
+/// Longest title generated for snippets captured from a selection
+const CAPTURED_TITLE_LENGTH: usize = 60;
+
+/// Actions whose shortcut is only registered while `arm`ed, so it isn't taken
+/// from other apps the rest of the time
+fn on_demand(action: HotkeyAction) -> bool {
+    matches!(action, HotkeyAction::PasteQueueNext)
+}
+
+fn parse_shortcut(shortcut: &str) -> Result<Shortcut, String> {
+    shortcut
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))
+}
+
+fn load_settings(app_handle: &AppHandle) -> Result<HotkeySettings, String> {
+    let state = app_handle.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_hotkey_settings().map_err(|e| e.to_string())
+}
+
+/// Current binding and registration state of every action
+pub fn bindings(app_handle: &AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    let settings = load_settings(app_handle)?;
+    let state = app_handle.state::<AppState>();
+    let registry = state.hotkeys.lock().map_err(|e| e.to_string())?;
+
+    Ok(HotkeyAction::ALL
+        .iter()
+        .map(|action| HotkeyBinding {
+            action: *action,
+            label: action.label().to_string(),
+            shortcut: settings.bindings.get(action).cloned().flatten(),
+            active: registry.active.contains_key(action),
+            error: registry.errors.get(action).cloned(),
+        })
+        .collect())
+}
+
+fn register(app_handle: &AppHandle, action: HotkeyAction, shortcut: Shortcut) -> Result<(), String> {
+    let handle = app_handle.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                println!("🎯 Global shortcut for '{}' pressed!", action.label());
+                if let Err(e) = trigger(&handle, action) {
+                    println!("❌ Failed to run '{}': {}", action.label(), e);
+                }
+            }
+        })
+        .map_err(|e| format!("Could not register {} (it may be used by another application): {}", shortcut, e))
+}
+
+/// (Re)register every configured binding, recording failures per action
+pub fn register_all(app_handle: &AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    let settings = load_settings(app_handle)?;
+    let state = app_handle.state::<AppState>();
+
+    {
+        let mut registry = state.hotkeys.lock().map_err(|e| e.to_string())?;
+        let armed: Vec<HotkeyAction> = registry.active.keys().copied().filter(|a| on_demand(*a)).collect();
+        for (_, shortcut) in registry.active.drain() {
+            let _ = app_handle.global_shortcut().unregister(shortcut);
+        }
+        registry.errors.clear();
+
+        for action in HotkeyAction::ALL {
+            if on_demand(action) && !armed.contains(&action) {
+                continue;
+            }
+            let Some(shortcut_str) = settings.bindings.get(&action).cloned().flatten() else {
+                continue;
+            };
+
+            let result = parse_shortcut(&shortcut_str).and_then(|shortcut| {
+                if registry.active.values().any(|s| *s == shortcut) {
+                    return Err(format!("'{}' is already bound to another action", shortcut_str));
+                }
+                register(app_handle, action, shortcut).map(|_| shortcut)
+            });
+
+            match result {
+                Ok(shortcut) => {
+                    println!("✅ Registered global shortcut for '{}': {}", action.label(), shortcut_str);
+                    registry.active.insert(action, shortcut);
+                }
+                Err(e) => {
+                    println!("⚠️  {}", e);
+                    registry.errors.insert(action, e);
+                }
+            }
+        }
+    }
+
+    bindings(app_handle)
+}
+
+/// Bind `action` to `shortcut` (or unbind it with `None`) without a restart.
+/// The new shortcut is registered before it is persisted, so a failed rebind
+/// leaves the previous binding in place.
+pub fn bind(app_handle: &AppHandle, action: HotkeyAction, shortcut: Option<String>) -> Result<HotkeyBinding, String> {
+    let shortcut_str = shortcut.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let new_shortcut = shortcut_str.as_deref().map(parse_shortcut).transpose()?;
+
+    let mut settings = load_settings(app_handle)?;
+
+    if let Some(new_shortcut) = new_shortcut {
+        for (other, other_str) in &settings.bindings {
+            let Some(other_str) = other_str else { continue };
+            if *other != action && parse_shortcut(other_str).is_ok_and(|s| s == new_shortcut) {
+                return Err(format!("'{}' is already bound to '{}'", other_str, other.label()));
+            }
+        }
+    }
+
+    let state = app_handle.state::<AppState>();
+    {
+        let mut registry = state.hotkeys.lock().map_err(|e| e.to_string())?;
+        let previous = registry.active.remove(&action);
+
+        match (previous, new_shortcut) {
+            // Already registered for this action; nothing to swap
+            (Some(previous), Some(new_shortcut)) if previous == new_shortcut => {
+                registry.active.insert(action, previous);
+            }
+            (previous, new_shortcut) => {
+                if let Some(previous) = previous {
+                    let _ = app_handle.global_shortcut().unregister(previous);
+                }
+
+                // An on-demand shortcut that isn't armed is registered by `arm`
+                let new_shortcut = new_shortcut.filter(|_| !on_demand(action) || previous.is_some());
+                if let Some(new_shortcut) = new_shortcut {
+                    if let Err(e) = register(app_handle, action, new_shortcut) {
+                        // Put the old binding back so the action keeps working
+                        if let Some(previous) = previous {
+                            if register(app_handle, action, previous).is_ok() {
+                                registry.active.insert(action, previous);
+                            }
+                        }
+                        return Err(e);
+                    }
+                    registry.active.insert(action, new_shortcut);
+                }
+            }
+        }
+
+        registry.errors.remove(&action);
+    }
+
+    settings.bindings.insert(action, shortcut_str.clone());
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_hotkey_settings(&settings).map_err(|e| e.to_string())?;
+    }
+
+    println!("🔑 '{}' bound to {}", action.label(), shortcut_str.as_deref().unwrap_or("nothing"));
+
+    let active = state.hotkeys.lock().map_err(|e| e.to_string())?.active.contains_key(&action);
+    Ok(HotkeyBinding {
+        action,
+        label: action.label().to_string(),
+        active,
+        shortcut: shortcut_str,
+        error: None,
+    })
+}
+
+/// Register the shortcut of an on-demand action. Returns the shortcut, or
+/// `None` when the action is unbound.
+pub fn arm(app_handle: &AppHandle, action: HotkeyAction) -> Result<Option<String>, String> {
+    let Some(shortcut_str) = load_settings(app_handle)?.bindings.get(&action).cloned().flatten() else {
+        return Ok(None);
+    };
+    let shortcut = parse_shortcut(&shortcut_str)?;
+
+    let state = app_handle.state::<AppState>();
+    let mut registry = state.hotkeys.lock().map_err(|e| e.to_string())?;
+    if !registry.active.contains_key(&action) {
+        if let Err(e) = register(app_handle, action, shortcut) {
+            registry.errors.insert(action, e.clone());
+            return Err(e);
+        }
+        registry.active.insert(action, shortcut);
+        registry.errors.remove(&action);
+    }
+
+    Ok(Some(shortcut_str))
+}
+
+/// Unregister the shortcut of an on-demand action
+pub fn disarm(app_handle: &AppHandle, action: HotkeyAction) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let mut registry = state.hotkeys.lock().map_err(|e| e.to_string())?;
+    if let Some(shortcut) = registry.active.remove(&action) {
+        let _ = app_handle.global_shortcut().unregister(shortcut);
+    }
+    Ok(())
+}
+
+/// Restore the platform default for every action
+pub fn reset(app_handle: &AppHandle) -> Result<Vec<HotkeyBinding>, String> {
+    {
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_hotkey_settings(&HotkeySettings::default()).map_err(|e| e.to_string())?;
+    }
+    register_all(app_handle)
+}
+
+/// Run the action bound to a shortcut
+pub fn trigger(app_handle: &AppHandle, action: HotkeyAction) -> Result<(), String> {
+    match action {
+        HotkeyAction::ClipboardPopup => crate::commands::show_clipboard_popup(app_handle.clone()),
+        HotkeyAction::SnippetSearch => crate::commands::show_snippet_launcher(app_handle.clone()),
+        HotkeyAction::PasteLastClip => paste_last_clip(app_handle),
+        HotkeyAction::CaptureSelection => capture_selection(app_handle),
+        HotkeyAction::ToggleExpansion => crate::expansion::toggle(app_handle),
+        HotkeyAction::PasteQueueNext => crate::paste_queue::paste_next(app_handle).map(|_| ()),
+    }
+}
+
+fn paste_last_clip(app_handle: &AppHandle) -> Result<(), String> {
+    let entry = {
+        let state = app_handle.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_latest_clipboard_entry().map_err(|e| e.to_string())?
+    }
+    .ok_or_else(|| "Clipboard history is empty".to_string())?;
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    clipboard.set_text(&entry.content)
+        .map_err(|e| format!("Failed to set clipboard: {}", e))?;
+
+    crate::commands::paste_internal(false)
+}
+
+/// Copy the selection in the focused app and save it as a new snippet
+fn capture_selection(app_handle: &AppHandle) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    let previous = clipboard.get_text().ok();
+    // Clear first so an empty selection is not mistaken for the old clipboard
+    let _ = clipboard.clear();
+
+    crate::commands::copy_selection_internal()?;
+
+    let content = clipboard.get_text().unwrap_or_default();
+
+    if let Some(previous) = previous {
+        let _ = clipboard.set_text(previous);
+    }
+
+    if content.trim().is_empty() {
+        return Err("Nothing is selected".to_string());
+    }
+
+    let title: String = content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(CAPTURED_TITLE_LENGTH)
+        .collect();
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let snippet = Snippet {
+        id: None,
+        title,
+        description: None,
+        content,
+        language: "auto".to_string(),
+        tags: None,
+        folder_id: None,
+        created_at: now.clone(),
+        updated_at: now,
+        trigger: None,
+        deleted_at: None,
+        secret: false,
+    };
+
+    let state = app_handle.state::<AppState>();
+    let snippet_id = crate::commands::create_snippet_internal(&state, snippet)?;
+
+    println!("✅ Captured selection as snippet {}", snippet_id);
+    let _ = app_handle.emit("snippet-created", snippet_id);
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::models::{ClipboardEntry, ImportConflict, LibraryItemKind, Snippet};
+
+// One-shot importers for other snippet managers and clipboard tools. Each
+// importer only reads its source into an `ImportBatch`; saving it, and the
+// dry-run preview, are shared (see `commands::run_importer`).
+
+mod cacher;
+mod copyq;
+mod gist;
+mod maccy;
+mod masscode;
+mod raycast;
+mod snippetslab;
+
+/// What an importer brings in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportTarget {
+    Snippets,
+    Clipboard,
+}
+
+/// A source another tool exported, and how to read it
+pub struct Importer {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Where to find the export, shown next to the file picker
+    pub hint: &'static str,
+    pub target: ImportTarget,
+    /// File extensions the importer reads; directories are searched for them
+    pub extensions: &'static [&'static str],
+    read: fn(&Path) -> Result<ImportBatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImporterInfo {
+    pub id: String,
+    pub name: String,
+    pub hint: String,
+    pub target: ImportTarget,
+    pub extensions: Vec<String>,
+}
+
+/// A snippet and the folder it goes in, as folder names below the folder
+/// being imported into (empty for that folder itself)
+#[derive(Debug)]
+pub struct ImportedSnippet {
+    pub folder: Vec<String>,
+    pub snippet: Snippet,
+}
+
+/// Everything read from one source, before anything is saved
+#[derive(Debug, Default)]
+pub struct ImportBatch {
+    pub snippets: Vec<ImportedSnippet>,
+    /// Entries with an empty category are classified when saved
+    pub clipboard: Vec<ClipboardEntry>,
+    /// Entries that were left out or changed, and why
+    pub notes: Vec<ImportConflict>,
+}
+
+const IMPORTERS: &[Importer] = &[
+    Importer {
+        id: "gist",
+        name: "GitHub Gist",
+        hint: "JSON from the GitHub gists API (one gist or a list). Each file of a multi-file gist becomes a snippet in a folder for the gist.",
+        target: ImportTarget::Snippets,
+        extensions: &["json"],
+        read: gist::read,
+    },
+    Importer {
+        id: "masscode",
+        name: "massCode",
+        hint: "massCode's db.json, in the storage folder set under Preferences › Storage.",
+        target: ImportTarget::Snippets,
+        extensions: &["json"],
+        read: masscode::read,
+    },
+    Importer {
+        id: "snippetslab",
+        name: "SnippetsLab",
+        hint: "A JSON export from SnippetsLab (File › Export › JSON).",
+        target: ImportTarget::Snippets,
+        extensions: &["json"],
+        read: snippetslab::read,
+    },
+    Importer {
+        id: "cacher",
+        name: "Cacher",
+        hint: "A JSON export from Cacher (Settings › Export). Labels become tags and team snippets go in a folder per team.",
+        target: ImportTarget::Snippets,
+        extensions: &["json"],
+        read: cacher::read,
+    },
+    Importer {
+        id: "raycast",
+        name: "Raycast",
+        hint: "A Raycast snippets export (Export Snippets command). Keywords become triggers.",
+        target: ImportTarget::Snippets,
+        extensions: &["json"],
+        read: raycast::read,
+    },
+    Importer {
+        id: "maccy",
+        name: "Maccy",
+        hint: "Maccy's Storage.sqlite, in ~/Library/Containers/org.p0deje.Maccy/Data/Library/Application Support/Maccy. Quit Maccy first.",
+        target: ImportTarget::Clipboard,
+        extensions: &["sqlite"],
+        read: maccy::read,
+    },
+    Importer {
+        id: "copyq",
+        name: "CopyQ",
+        hint: copyq::HINT,
+        target: ImportTarget::Clipboard,
+        extensions: &["json"],
+        read: copyq::read,
+    },
+];
+
+pub fn list_importers() -> Vec<ImporterInfo> {
+    IMPORTERS
+        .iter()
+        .map(|i| ImporterInfo {
+            id: i.id.to_string(),
+            name: i.name.to_string(),
+            hint: i.hint.to_string(),
+            target: i.target,
+            extensions: i.extensions.iter().map(|e| e.to_string()).collect(),
+        })
+        .collect()
+}
+
+pub fn find_importer(id: &str) -> Result<&'static Importer> {
+    IMPORTERS.iter().find(|i| i.id == id).ok_or_else(|| anyhow!("Unknown importer '{}'", id))
+}
+
+impl Importer {
+    pub fn read(&self, path: &Path) -> Result<ImportBatch> {
+        (self.read)(path)
+    }
+
+    pub fn reads(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+// Helpers shared by the importers
+
+/// Languages other tools name differently, as (theirs, ours)
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("plain_text", "auto"),
+    ("plaintext", "auto"),
+    ("plain text", "auto"),
+    ("text", "auto"),
+    ("txt", "auto"),
+    ("c_cpp", "cpp"),
+    ("c++", "cpp"),
+    ("c#", "csharp"),
+    ("golang", "go"),
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("py", "python"),
+    ("objective-c", "objectivec"),
+];
+
+/// File extensions for guessing a language from a file name
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("py", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("jsx", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("rs", "rust"),
+    ("go", "go"),
+    ("rb", "ruby"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("php", "php"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "bash"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("json", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("xml", "xml"),
+    ("md", "markdown"),
+    ("dockerfile", "dockerfile"),
+];
+
+/// A language name from another tool in our naming; unknown and plain-text
+/// languages become `auto`, to be detected from the content
+pub(crate) fn language(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return "auto".to_string();
+    }
+    LANGUAGE_ALIASES
+        .iter()
+        .find(|(theirs, _)| *theirs == name)
+        .map(|(_, ours)| ours.to_string())
+        .unwrap_or(name)
+}
+
+/// The language a file name suggests, or `auto`
+pub(crate) fn language_from_file_name(file_name: &str) -> String {
+    let path = Path::new(file_name);
+    let extension = path
+        .extension()
+        .or_else(|| path.file_name())
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    EXTENSION_LANGUAGES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map_or_else(|| "auto".to_string(), |(_, language)| language.to_string())
+}
+
+/// An RFC 3339 timestamp from a Unix time in seconds or milliseconds, or
+/// from a date string
+pub(crate) fn timestamp(value: &Value) -> Option<String> {
+    let parsed = match value {
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            // Anything past the year 5000 in seconds is really milliseconds
+            let millis = if n.abs() > 1e11 { n } else { n * 1000.0 };
+            chrono::DateTime::from_timestamp_millis(millis as i64)?
+        }
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .or_else(|_| {
+                chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc())
+            })
+            .ok()?,
+        _ => return None,
+    };
+    Some(parsed.to_rfc3339())
+}
+
+/// A new snippet; timestamps default to now
+pub(crate) fn snippet(title: &str, content: String, language: String) -> Snippet {
+    let now = chrono::Utc::now().to_rfc3339();
+    Snippet {
+        id: None,
+        title: title_or_untitled(title),
+        description: None,
+        content,
+        language,
+        tags: None,
+        folder_id: None,
+        created_at: now.clone(),
+        updated_at: now,
+        trigger: None,
+        deleted_at: None,
+        secret: false,
+    }
+}
+
+/// The first line of `title`, or "Untitled"
+pub(crate) fn title_or_untitled(title: &str) -> String {
+    match title.lines().map(str::trim).find(|l| !l.is_empty()) {
+        Some(line) => line.to_string(),
+        None => "Untitled".to_string(),
+    }
+}
+
+/// A clipboard entry to be classified when saved
+pub(crate) fn clipboard_entry(content: String, source: &str, created_at: String, pinned: bool) -> ClipboardEntry {
+    ClipboardEntry {
+        id: None,
+        content,
+        source: source.to_string(),
+        category: String::new(),
+        created_at,
+        pinned,
+        favorite: false,
+        pin_order: None,
+        tags: Vec::new(),
+    }
+}
+
+pub(crate) fn note(kind: LibraryItemKind, name: impl Into<String>, resolution: impl Into<String>) -> ImportConflict {
+    ImportConflict { kind, name: name.into(), resolution: resolution.into() }
+}
+
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<T> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(text.trim_start_matches('\u{feff}')).with_context(|| format!("Not {}", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read `contents` with `read`, from a file with the given extension
+    pub(super) fn read_fixture(read: fn(&Path) -> Result<ImportBatch>, extension: &str, contents: &str) -> ImportBatch {
+        let path = std::env::temp_dir().join(format!("nobraindev-import-{}.{}", uuid::Uuid::new_v4().simple(), extension));
+        std::fs::write(&path, contents).unwrap();
+        let batch = read(&path);
+        let _ = std::fs::remove_file(&path);
+        batch.unwrap()
+    }
+
+    #[test]
+    fn timestamp_tells_seconds_from_milliseconds() {
+        let expected = Some("2023-11-14T22:13:20+00:00".to_string());
+        assert_eq!(timestamp(&serde_json::json!(1_700_000_000)), expected);
+        assert_eq!(timestamp(&serde_json::json!(1_700_000_000_000_i64)), expected);
+        assert_eq!(timestamp(&serde_json::json!(1_700_000_000.5)), Some("2023-11-14T22:13:20.500+00:00".to_string()));
+        // Small values are seconds, not milliseconds after 1970
+        assert_eq!(timestamp(&serde_json::json!(86_400)), Some("1970-01-02T00:00:00+00:00".to_string()));
+
+        assert_eq!(timestamp(&serde_json::json!("2023-11-14T23:13:20+01:00")), expected);
+        assert_eq!(timestamp(&serde_json::json!("2023-11-14 22:13:20")), expected);
+        assert_eq!(timestamp(&serde_json::json!("yesterday")), None);
+        assert_eq!(timestamp(&Value::Null), None);
+    }
+
+    #[test]
+    fn languages_are_named_our_way() {
+        assert_eq!(language(" Golang "), "go");
+        assert_eq!(language("Plain Text"), "auto");
+        assert_eq!(language(""), "auto");
+        assert_eq!(language("Elixir"), "elixir");
+        assert_eq!(language_from_file_name("main.RS"), "rust");
+        assert_eq!(language_from_file_name("Dockerfile"), "dockerfile");
+        assert_eq!(language_from_file_name("notes"), "auto");
+    }
+}
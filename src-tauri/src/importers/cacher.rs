@@ -0,0 +1,171 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{language_from_file_name, read_json, snippet, timestamp, title_or_untitled, ImportBatch, ImportedSnippet};
+use crate::tags;
+
+/// A Cacher export: the personal library and those of the user's teams
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+    personal_library: Library,
+    #[serde(default)]
+    teams: Vec<Team>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Team {
+    name: String,
+    library: Library,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Library {
+    #[serde(default)]
+    snippets: Vec<CacherSnippet>,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    title: String,
+    #[serde(default)]
+    snippets: Vec<SnippetRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnippetRef {
+    guid: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacherSnippet {
+    guid: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    files: Vec<CacherFile>,
+    #[serde(default)]
+    created_at: Value,
+    #[serde(default)]
+    updated_at: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CacherFile {
+    #[serde(default)]
+    filename: String,
+    #[serde(default)]
+    content: String,
+}
+
+/// Labels become tags. A snippet with several files gets a folder with a
+/// snippet per file, like a multi-file gist; team snippets go in a folder
+/// named after the team.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let export: Export = read_json(path, "a Cacher export")?;
+
+    let mut batch = ImportBatch::default();
+    read_library(&mut batch, &export.personal_library, &[]);
+    for team in &export.teams {
+        read_library(&mut batch, &team.library, &[title_or_untitled(&team.name)]);
+    }
+    Ok(batch)
+}
+
+fn read_library(batch: &mut ImportBatch, library: &Library, folder: &[String]) {
+    let mut labels: HashMap<&str, Vec<&str>> = HashMap::new();
+    for label in &library.labels {
+        for snippet in &label.snippets {
+            labels.entry(snippet.guid.as_str()).or_default().push(label.title.as_str());
+        }
+    }
+
+    for entry in &library.snippets {
+        let tags = tags::format_tags(&tags::normalize_tags(
+            labels.get(entry.guid.as_str()).into_iter().flatten().copied(),
+        ));
+        let files: Vec<&CacherFile> = entry.files.iter().filter(|f| !f.content.trim().is_empty()).collect();
+        let multi_file = files.len() > 1;
+
+        let mut snippet_folder = folder.to_vec();
+        if multi_file {
+            snippet_folder.push(title_or_untitled(&entry.title));
+        }
+
+        for file in files {
+            let title = if multi_file || entry.title.trim().is_empty() { &file.filename } else { &entry.title };
+            let mut snippet = snippet(title, file.content.clone(), language_from_file_name(&file.filename));
+            snippet.description = entry.description.clone().filter(|d| !d.trim().is_empty());
+            snippet.tags = tags.clone();
+            if let Some(created) = timestamp(&entry.created_at) {
+                snippet.created_at = created;
+            }
+            if let Some(updated) = timestamp(&entry.updated_at) {
+                snippet.updated_at = updated;
+            }
+            batch.snippets.push(ImportedSnippet { folder: snippet_folder.clone(), snippet });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn reads_labels_multi_file_snippets_and_teams() {
+        let batch = read_fixture(
+            read,
+            "json",
+            r#"{
+                "personalLibrary": {
+                    "snippets": [
+                        {"guid": "s1", "title": "Curl", "description": "GET a URL",
+                         "files": [{"filename": "get.sh", "content": "curl $URL"}],
+                         "createdAt": "2024-01-02T03:04:05.000Z", "updatedAt": "2024-01-03T03:04:05.000Z"},
+                        {"guid": "s2", "title": "Service", "files": [
+                            {"filename": "main.go", "content": "package main"},
+                            {"filename": "Dockerfile", "content": "FROM golang"},
+                            {"filename": "empty.txt", "content": ""}]}
+                    ],
+                    "labels": [{"title": "Shell", "snippets": [{"guid": "s1"}]}, {"title": "http", "snippets": [{"guid": "s1"}]}]
+                },
+                "teams": [{"name": "Platform", "library": {"snippets": [
+                    {"guid": "s3", "title": "Query", "files": [{"filename": "q.sql", "content": "SELECT 1"}]}]}}]
+            }"#,
+        );
+
+        let found: Vec<(Vec<&str>, &str, &str)> = batch
+            .snippets
+            .iter()
+            .map(|s| {
+                (s.folder.iter().map(String::as_str).collect(), s.snippet.title.as_str(), s.snippet.language.as_str())
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (vec![], "Curl", "bash"),
+                (vec!["Service"], "main.go", "go"),
+                (vec!["Service"], "Dockerfile", "dockerfile"),
+                (vec!["Platform"], "Query", "sql"),
+            ]
+        );
+
+        let curl = &batch.snippets[0].snippet;
+        assert_eq!(curl.tags.as_deref(), Some("Shell, http"));
+        assert_eq!(curl.description.as_deref(), Some("GET a URL"));
+        assert_eq!(curl.created_at, "2024-01-02T03:04:05+00:00");
+        assert_eq!(curl.updated_at, "2024-01-03T03:04:05+00:00");
+        assert_eq!(batch.snippets[1].snippet.tags, None);
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::{clipboard_entry, read_json, timestamp, ImportBatch};
+
+// CopyQ's own export (.cpq) is a binary Qt data stream, so the history is
+// read from JSON written by CopyQ's scripting instead.
+
+pub const HINT: &str = "JSON written by CopyQ itself. Run: copyq eval \"var items = []; for (var i = 0; i < size(); ++i) items.push(str(read(i))); print(JSON.stringify(items))\" > copyq.json";
+
+/// An item as text, or as an object with its text and, optionally, its time
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Item {
+    Text(String),
+    Full {
+        text: String,
+        #[serde(default)]
+        time: Value,
+    },
+}
+
+/// Items are newest first, as CopyQ lists them. Without times they're dated
+/// a second apart, counting back from now, to keep that order.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let items: Vec<Item> = read_json(path, "CopyQ history (see the importer's instructions)")?;
+    let now = chrono::Utc::now();
+
+    let mut batch = ImportBatch::default();
+    for (row, item) in items.into_iter().enumerate() {
+        let (text, time) = match item {
+            Item::Text(text) => (text, None),
+            Item::Full { text, time } => (text, timestamp(&time)),
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let created_at = time.unwrap_or_else(|| (now - chrono::Duration::seconds(row as i64)).to_rfc3339());
+        batch.clipboard.push(clipboard_entry(text, "copyq", created_at, false));
+    }
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn dates_items_without_times_newest_first() {
+        let batch = read_fixture(read, "json", r#"["newest", {"text": "dated", "time": 1700000000}, "  ", "oldest"]"#);
+
+        let texts: Vec<&str> = batch.clipboard.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(texts, vec!["newest", "dated", "oldest"]);
+        assert!(batch.clipboard.iter().all(|e| e.source == "copyq" && !e.pinned));
+        assert_eq!(batch.clipboard[1].created_at, "2023-11-14T22:13:20+00:00");
+        let time = |i: usize| chrono::DateTime::parse_from_rfc3339(&batch.clipboard[i].created_at).unwrap();
+        assert!(time(0) > time(2));
+    }
+}
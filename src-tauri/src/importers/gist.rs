@@ -0,0 +1,141 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{language, language_from_file_name, note, read_json, snippet, timestamp, title_or_untitled, ImportBatch, ImportedSnippet};
+use crate::models::LibraryItemKind;
+
+/// A gist as the GitHub API returns it. List responses leave out file contents.
+#[derive(Debug, Deserialize)]
+struct Gist {
+    id: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    created_at: Value,
+    #[serde(default)]
+    updated_at: Value,
+    files: BTreeMap<String, GistFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GistFile {
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum GistDump {
+    List(Vec<Gist>),
+    One(Gist),
+}
+
+/// A single-file gist becomes one snippet titled by its description. A
+/// multi-file gist gets a folder, titled the same way, with a snippet per file.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let gists = match read_json(path, "a GitHub gist export")? {
+        GistDump::List(gists) => gists,
+        GistDump::One(gist) => vec![gist],
+    };
+
+    let mut batch = ImportBatch::default();
+    for gist in gists {
+        let description = gist.description.filter(|d| !d.trim().is_empty());
+        let multi_file = gist.files.len() > 1;
+        let folder = if multi_file {
+            vec![description.as_deref().map_or_else(|| format!("Gist {}", short_id(&gist.id)), title_or_untitled)]
+        } else {
+            Vec::new()
+        };
+
+        for (file_name, file) in gist.files {
+            let Some(content) = file.content.filter(|_| !file.truncated) else {
+                batch.notes.push(note(
+                    LibraryItemKind::Snippet,
+                    &file_name,
+                    "Skipped: the export doesn't include the whole file. Export each gist on its own (GET /gists/{id}).",
+                ));
+                continue;
+            };
+
+            let language = match file.language.as_deref().map(language) {
+                Some(language) if language != "auto" => language,
+                _ => language_from_file_name(&file_name),
+            };
+            let title = match (&description, multi_file) {
+                (Some(description), false) => description.clone(),
+                _ => file_name.clone(),
+            };
+
+            let mut snippet = snippet(&title, content, language);
+            // A description that is already the title isn't repeated
+            snippet.description = description.clone().filter(|d| d.trim() != snippet.title);
+            if let Some(created) = timestamp(&gist.created_at) {
+                snippet.created_at = created;
+            }
+            if let Some(updated) = timestamp(&gist.updated_at) {
+                snippet.updated_at = updated;
+            }
+            batch.snippets.push(ImportedSnippet { folder: folder.clone(), snippet });
+        }
+    }
+
+    Ok(batch)
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..7).unwrap_or(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn reads_single_and_multi_file_gists() {
+        let batch = read_fixture(
+            read,
+            "json",
+            r#"[
+                {"id": "aaaa1111", "description": "Retry helper", "created_at": "2024-01-02T03:04:05Z",
+                 "updated_at": "2024-02-03T04:05:06Z",
+                 "files": {"retry.py": {"language": "Python", "content": "def retry(): pass"}}},
+                {"id": "0123456789abc", "description": "", "created_at": "2024-01-02T03:04:05Z",
+                 "files": {"b.txt": {"language": "Text", "content": "notes"},
+                           "a.rs": {"language": null, "content": "fn a() {}"},
+                           "big.js": {"language": "JavaScript", "content": "...", "truncated": true}}}
+            ]"#,
+        );
+
+        let found: Vec<(&[String], &str, &str)> = batch
+            .snippets
+            .iter()
+            .map(|s| (s.folder.as_slice(), s.snippet.title.as_str(), s.snippet.language.as_str()))
+            .collect();
+        let gist_folder = ["Gist 0123456".to_string()];
+        assert_eq!(
+            found,
+            vec![
+                (&[][..], "Retry helper", "python"),
+                (&gist_folder[..], "a.rs", "rust"),
+                (&gist_folder[..], "b.txt", "auto"),
+            ]
+        );
+
+        let retry = &batch.snippets[0].snippet;
+        assert_eq!(retry.description, None);
+        assert_eq!(retry.created_at, "2024-01-02T03:04:05+00:00");
+        assert_eq!(retry.updated_at, "2024-02-03T04:05:06+00:00");
+
+        assert_eq!(batch.notes.len(), 1);
+        assert_eq!(batch.notes[0].name, "big.js");
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{clipboard_entry, ImportBatch};
+
+/// Core Data stores dates as seconds since 2001-01-01
+const CORE_DATA_EPOCH: i64 = 978_307_200;
+
+/// Pasteboard types holding plain text, most preferred first
+const TEXT_TYPES: &[&str] = &["public.utf8-plain-text", "NSStringPboardType", "public.plain-text"];
+
+struct Item {
+    copied_at: f64,
+    application: Option<String>,
+    pinned: bool,
+    text: String,
+    /// Position of the text's type in `TEXT_TYPES`
+    rank: usize,
+}
+
+/// Text entries of Maccy's history; images and files are left out. Pinned
+/// entries stay pinned.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.Z_PK, i.ZLASTCOPIEDAT, i.ZAPPLICATION, i.ZPIN IS NOT NULL, c.ZTYPE, c.ZVALUE
+             FROM ZHISTORYITEM i JOIN ZHISTORYITEMCONTENT c ON c.ZITEM = i.Z_PK",
+        )
+        .context("Not a Maccy database")?;
+
+    let mut items: HashMap<i64, Item> = HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<f64>>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, bool>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            // Usually a blob, but read text as well
+            match row.get_ref(5)? {
+                ValueRef::Blob(bytes) | ValueRef::Text(bytes) => Some(bytes.to_vec()),
+                _ => None,
+            },
+        ))
+    })?;
+
+    for row in rows {
+        let (id, copied_at, application, pinned, kind, value) = row?;
+        let Some(rank) = kind.and_then(|k| TEXT_TYPES.iter().position(|t| *t == k)) else { continue };
+        let Some(text) = value.and_then(|v| String::from_utf8(v).ok()).filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+        if items.get(&id).is_none_or(|item| rank < item.rank) {
+            items.insert(id, Item { copied_at: copied_at.unwrap_or_default(), application, pinned, text, rank });
+        }
+    }
+
+    let mut batch = ImportBatch::default();
+    let mut items: Vec<_> = items.into_values().collect();
+    items.sort_by(|a, b| a.copied_at.total_cmp(&b.copied_at));
+    for item in items {
+        let created_at = chrono::DateTime::from_timestamp(CORE_DATA_EPOCH + item.copied_at as i64, 0)
+            .unwrap_or_default()
+            .to_rfc3339();
+        let source = item.application.filter(|a| !a.is_empty()).unwrap_or_else(|| "maccy".to_string());
+        batch.clipboard.push(clipboard_entry(item.text, &source, created_at, item.pinned));
+    }
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_text_of_each_item_oldest_first() {
+        let path = std::env::temp_dir().join(format!("nobraindev-maccy-{}.sqlite", uuid::Uuid::new_v4().simple()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ZHISTORYITEM (Z_PK INTEGER PRIMARY KEY, ZLASTCOPIEDAT REAL, ZAPPLICATION TEXT, ZPIN TEXT);
+             CREATE TABLE ZHISTORYITEMCONTENT (ZITEM INTEGER, ZTYPE TEXT, ZVALUE BLOB);
+             INSERT INTO ZHISTORYITEM VALUES (1, 700000000, 'com.apple.Terminal', NULL), (2, 600000000, NULL, 'b'), (3, 650000000, 'Preview', NULL);
+             INSERT INTO ZHISTORYITEMCONTENT VALUES
+                 (1, 'public.plain-text', CAST('less preferred' AS BLOB)),
+                 (1, 'public.utf8-plain-text', CAST('ls -la' AS BLOB)),
+                 (1, 'public.png', X'89504E47'),
+                 (2, 'NSStringPboardType', 'hello'),
+                 (3, 'public.png', X'89504E47');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let batch = read(&path);
+        let _ = std::fs::remove_file(&path);
+        let entries = batch.unwrap().clipboard;
+
+        let found: Vec<(&str, &str, &str, bool)> = entries
+            .iter()
+            .map(|e| (e.content.as_str(), e.source.as_str(), e.created_at.as_str(), e.pinned))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("hello", "maccy", "2020-01-06T10:40:00+00:00", true),
+                ("ls -la", "com.apple.Terminal", "2023-03-08T20:26:40+00:00", false),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.category.is_empty()));
+    }
+}
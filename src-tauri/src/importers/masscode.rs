@@ -0,0 +1,166 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{language, read_json, snippet, timestamp, ImportBatch, ImportedSnippet};
+use crate::tags;
+
+/// massCode's `db.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Database {
+    #[serde(default)]
+    folders: Vec<Folder>,
+    snippets: Vec<MassCodeSnippet>,
+    #[serde(default)]
+    tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Folder {
+    id: String,
+    name: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MassCodeSnippet {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags_ids: Vec<String>,
+    /// Fragments
+    #[serde(default)]
+    content: Vec<Fragment>,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    created_at: Value,
+    #[serde(default)]
+    updated_at: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    value: String,
+}
+
+/// Folders keep their hierarchy. Each fragment of a snippet becomes a snippet
+/// of its own, titled "Snippet - Fragment" when there are several. Snippets
+/// in massCode's trash are left out.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let db: Database = read_json(path, "a massCode database")?;
+
+    let folders: HashMap<&str, &Folder> = db.folders.iter().map(|f| (f.id.as_str(), f)).collect();
+    let tag_names: HashMap<&str, &str> = db.tags.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+
+    let mut batch = ImportBatch::default();
+    for entry in &db.snippets {
+        if entry.is_deleted {
+            continue;
+        }
+
+        let folder = entry.folder_id.as_deref().map(|id| folder_path(&folders, id)).unwrap_or_default();
+        let tags = tags::format_tags(&tags::normalize_tags(
+            entry.tags_ids.iter().filter_map(|id| tag_names.get(id.as_str()).copied()),
+        ));
+
+        let fragments: Vec<&Fragment> = entry.content.iter().filter(|f| !f.value.trim().is_empty()).collect();
+        for fragment in &fragments {
+            let title = if fragments.len() > 1 && !fragment.label.trim().is_empty() {
+                format!("{} - {}", entry.name.trim(), fragment.label.trim())
+            } else {
+                entry.name.clone()
+            };
+
+            let mut snippet = snippet(&title, fragment.value.clone(), language(&fragment.language));
+            snippet.description = entry.description.clone().filter(|d| !d.trim().is_empty());
+            snippet.tags = tags.clone();
+            if let Some(created) = timestamp(&entry.created_at) {
+                snippet.created_at = created;
+            }
+            if let Some(updated) = timestamp(&entry.updated_at) {
+                snippet.updated_at = updated;
+            }
+            batch.snippets.push(ImportedSnippet { folder: folder.clone(), snippet });
+        }
+    }
+
+    Ok(batch)
+}
+
+/// Folder names from the top level down to `id`
+fn folder_path(folders: &HashMap<&str, &Folder>, id: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = folders.get(id);
+    // Bounded, in case of a parent cycle
+    while let Some(folder) = current.filter(|_| path.len() < folders.len()) {
+        path.push(folder.name.trim().to_string());
+        current = folder.parent_id.as_deref().and_then(|parent| folders.get(parent));
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn reads_folders_tags_and_fragments() {
+        let batch = read_fixture(
+            read,
+            "json",
+            r#"{
+                "folders": [{"id": "f1", "name": "Work"}, {"id": "f2", "name": "Go", "parentId": "f1"}],
+                "tags": [{"id": "t1", "name": "Loops"}, {"id": "t2", "name": "go"}],
+                "snippets": [
+                    {"name": "Range", "folderId": "f2", "tagsIds": ["t1", "t2", "gone"], "description": "Iterate",
+                     "content": [{"label": "Fragment 1", "language": "golang", "value": "for range x {}"}],
+                     "createdAt": 1700000000000, "updatedAt": 1700000060000},
+                    {"name": "Setup", "content": [
+                        {"label": "Install", "language": "sh", "value": "npm i"},
+                        {"label": "Run", "language": "plain_text", "value": "npm start"},
+                        {"label": "Empty", "language": "sh", "value": "  "}]},
+                    {"name": "Old", "isDeleted": true, "content": [{"label": "x", "language": "js", "value": "x"}]}
+                ]
+            }"#,
+        );
+
+        let titles: Vec<&str> = batch.snippets.iter().map(|s| s.snippet.title.as_str()).collect();
+        assert_eq!(titles, vec!["Range", "Setup - Install", "Setup - Run"]);
+
+        let range = &batch.snippets[0];
+        assert_eq!(range.folder, vec!["Work", "Go"]);
+        assert_eq!(range.snippet.language, "go");
+        assert_eq!(range.snippet.tags.as_deref(), Some("Loops, go"));
+        assert_eq!(range.snippet.description.as_deref(), Some("Iterate"));
+        assert_eq!(range.snippet.created_at, "2023-11-14T22:13:20+00:00");
+        assert_eq!(range.snippet.updated_at, "2023-11-14T22:14:20+00:00");
+
+        assert!(batch.snippets[1].folder.is_empty());
+        assert_eq!(batch.snippets[1].snippet.language, "bash");
+        assert_eq!(batch.snippets[2].snippet.language, "auto");
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::{note, read_json, snippet, ImportBatch, ImportedSnippet};
+use crate::models::LibraryItemKind;
+use crate::templates;
+
+/// One entry of a Raycast snippets export
+#[derive(Debug, Deserialize)]
+struct RaycastSnippet {
+    name: String,
+    text: String,
+    #[serde(default)]
+    keyword: Option<String>,
+}
+
+/// Raycast placeholders with a built-in equivalent, when used without options
+const BUILTINS: &[(&str, &str)] = &[
+    ("clipboard", "${CLIPBOARD}"),
+    ("date", "${DATE}"),
+    ("time", "${TIME}"),
+    ("datetime", "${DATETIME}"),
+    ("day", "${CURRENT_DAY_NAME}"),
+    ("uuid", "${UUID}"),
+];
+
+/// Every Raycast placeholder, including those with no equivalent here
+const PLACEHOLDERS: &[&str] = &[
+    "argument", "browser-tab", "clipboard", "cursor", "date", "datetime", "day", "selection", "snippet", "time", "uuid",
+];
+
+/// Keywords become triggers. Placeholders are converted to template syntax
+/// where there is an equivalent and kept as text otherwise.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let entries: Vec<RaycastSnippet> = read_json(path, "a Raycast snippets export")?;
+
+    let mut batch = ImportBatch::default();
+    for entry in entries {
+        let (content, unsupported) = convert(&entry.text);
+        if !unsupported.is_empty() {
+            batch.notes.push(note(
+                LibraryItemKind::Snippet,
+                &entry.name,
+                format!("Kept as text, with no equivalent here: {}", unsupported.join(", ")),
+            ));
+        }
+
+        let mut snippet = snippet(&entry.name, content, "auto".to_string());
+        snippet.trigger = entry.keyword.filter(|k| !k.trim().is_empty());
+        batch.snippets.push(ImportedSnippet { folder: Vec::new(), snippet });
+    }
+    Ok(batch)
+}
+
+/// Raycast text as a template, with the placeholders that were kept as text
+fn convert(text: &str) -> (String, Vec<String>) {
+    let mut content = String::with_capacity(text.len());
+    // Literal text is escaped as a whole, so that a `$` before a placeholder
+    // kept as text can't turn it into one
+    let mut literal = String::new();
+    let mut unsupported = Vec::new();
+    let mut unnamed_arguments = 0;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else { break };
+        let placeholder = &rest[start..=start + length];
+        literal.push_str(&rest[..start]);
+        rest = &rest[start + length + 1..];
+
+        // Braces in code are not placeholders
+        let kind = placeholder[1..].split(|c: char| c.is_whitespace() || c == '|' || c == '}').next().unwrap_or_default();
+        if !PLACEHOLDERS.contains(&kind) {
+            literal.push_str(placeholder);
+            continue;
+        }
+
+        match convert_placeholder(&placeholder[1..placeholder.len() - 1], &mut unnamed_arguments) {
+            Some(converted) => {
+                push_literal(&mut content, &std::mem::take(&mut literal));
+                content.push_str(&converted);
+            }
+            None => {
+                literal.push_str(placeholder);
+                unsupported.push(placeholder.to_string());
+            }
+        }
+    }
+
+    literal.push_str(rest);
+    push_literal(&mut content, &literal);
+    (content, unsupported)
+}
+
+/// Append escaped text that a placeholder may follow
+fn push_literal(content: &mut String, text: &str) {
+    content.push_str(&templates::escape(text, false));
+    // A trailing backslash would escape the placeholder's `$`
+    if text.ends_with('\\') {
+        content.push('\\');
+    }
+}
+
+fn convert_placeholder(inner: &str, unnamed_arguments: &mut usize) -> Option<String> {
+    // Modifiers (`{clipboard | uppercase}`) have no equivalent
+    if inner.contains('|') {
+        return None;
+    }
+    let inner = inner.trim();
+    let (kind, attributes) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+    let attributes = parse_attributes(attributes)?;
+
+    match kind {
+        "cursor" if attributes.is_empty() => Some("$0".to_string()),
+        "argument" => {
+            let mut name = None;
+            let mut default = None;
+            let mut choices = Vec::new();
+            for (key, value) in attributes {
+                match key.as_str() {
+                    "name" => name = Some(variable_name(&value)),
+                    "default" => default = Some(value),
+                    "options" => choices = value.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect(),
+                    _ => return None,
+                }
+            }
+            let name = name.unwrap_or_else(|| {
+                *unnamed_arguments += 1;
+                format!("argument{}", unnamed_arguments)
+            });
+            Some(templates::variable_source(&name, default.as_deref(), &choices))
+        }
+        _ if attributes.is_empty() => BUILTINS.iter().find(|(raycast, _)| *raycast == kind).map(|(_, ours)| ours.to_string()),
+        _ => None,
+    }
+}
+
+/// `name="Project" default="app"` as key/value pairs; `None` if malformed
+fn parse_attributes(text: &str) -> Option<Vec<(String, String)>> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let (key, after) = rest.split_once('=')?;
+        let after = after.trim_start();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+        attributes.push((key.trim().to_string(), value.to_string()));
+        rest = remaining.trim_start();
+    }
+    Some(attributes)
+}
+
+/// A template variable name: letters, digits and underscores, not starting
+/// with a digit (those are tab stops)
+fn variable_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn reads_keywords_and_converts_placeholders() {
+        let batch = read_fixture(
+            read,
+            "json",
+            r#"[
+                {"name": "Sign-off", "text": "Thanks,\n{argument name=\"Sender name\"} on {date}{cursor}", "keyword": "!thx"},
+                {"name": "Tab", "text": "{browser-tab} costs $5 in {clipboard | uppercase}", "keyword": " "},
+                {"name": "Code", "text": "fn main() {}\n{argument options=\"a,b\"}"}
+            ]"#,
+        );
+
+        let snippets: Vec<_> = batch.snippets.iter().map(|s| &s.snippet).collect();
+        assert_eq!(snippets[0].title, "Sign-off");
+        assert_eq!(snippets[0].trigger.as_deref(), Some("!thx"));
+        assert_eq!(snippets[0].content, "Thanks,\n${Sender_name} on ${DATE}$0");
+        assert_eq!(snippets[0].language, "auto");
+
+        assert_eq!(snippets[1].trigger, None);
+        assert_eq!(snippets[1].content, "{browser-tab} costs \\$5 in {clipboard | uppercase}");
+        assert_eq!(snippets[2].content, "fn main() {}\n${argument1|a,b|}");
+
+        assert_eq!(batch.notes.len(), 1);
+        assert_eq!(batch.notes[0].name, "Tab");
+        assert!(batch.notes[0].resolution.contains("{browser-tab}, {clipboard | uppercase}"));
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{language, read_json, snippet, timestamp, ImportBatch, ImportedSnippet};
+use crate::tags;
+
+/// A SnippetsLab JSON export. Older versions wrap the library in `contents`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Export {
+    Wrapped { contents: Library },
+    Bare(Library),
+}
+
+#[derive(Debug, Deserialize)]
+struct Library {
+    #[serde(default)]
+    folders: Vec<Folder>,
+    snippets: Vec<SnippetsLabSnippet>,
+    #[serde(default)]
+    tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Folder {
+    uuid: String,
+    title: String,
+    #[serde(default)]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    uuid: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnippetsLabSnippet {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    folder: Option<String>,
+    /// Tag UUIDs
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    fragments: Vec<Fragment>,
+    #[serde(default)]
+    date_created: Value,
+    #[serde(default)]
+    date_modified: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    content: String,
+    /// A Pygments lexer name, e.g. `PythonLexer`
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+/// Folders keep their hierarchy. Each fragment becomes a snippet, titled
+/// "Snippet - Fragment" when there are several; fragment notes become
+/// descriptions.
+pub fn read(path: &Path) -> Result<ImportBatch> {
+    let library = match read_json(path, "a SnippetsLab export")? {
+        Export::Wrapped { contents } => contents,
+        Export::Bare(library) => library,
+    };
+
+    let folders: HashMap<&str, &Folder> = library.folders.iter().map(|f| (f.uuid.as_str(), f)).collect();
+    let tag_names: HashMap<&str, &str> = library.tags.iter().map(|t| (t.uuid.as_str(), t.title.as_str())).collect();
+
+    let mut batch = ImportBatch::default();
+    for entry in &library.snippets {
+        let folder = entry.folder.as_deref().map(|id| folder_path(&folders, id)).unwrap_or_default();
+        let tags = tags::format_tags(&tags::normalize_tags(
+            entry.tags.iter().filter_map(|id| tag_names.get(id.as_str()).copied()),
+        ));
+
+        let fragments: Vec<&Fragment> = entry.fragments.iter().filter(|f| !f.content.trim().is_empty()).collect();
+        for fragment in &fragments {
+            let title = if fragments.len() > 1 && !fragment.title.trim().is_empty() {
+                format!("{} - {}", entry.title.trim(), fragment.title.trim())
+            } else {
+                entry.title.clone()
+            };
+
+            let mut snippet = snippet(&title, fragment.content.clone(), lexer_language(&fragment.language));
+            snippet.description = fragment.note.clone().filter(|n| !n.trim().is_empty());
+            snippet.tags = tags.clone();
+            if let Some(created) = timestamp(&entry.date_created) {
+                snippet.created_at = created;
+            }
+            if let Some(modified) = timestamp(&entry.date_modified) {
+                snippet.updated_at = modified;
+            }
+            batch.snippets.push(ImportedSnippet { folder: folder.clone(), snippet });
+        }
+    }
+
+    Ok(batch)
+}
+
+/// `PythonLexer` -> `python`, `CppLexer` -> `cpp`, `TextLexer` -> `auto`
+fn lexer_language(lexer: &str) -> String {
+    language(lexer.trim().strip_suffix("Lexer").unwrap_or(lexer))
+}
+
+/// Folder names from the top level down to `id`
+fn folder_path(folders: &HashMap<&str, &Folder>, id: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = folders.get(id);
+    // Bounded, in case of a parent cycle
+    while let Some(folder) = current.filter(|_| path.len() < folders.len()) {
+        path.push(folder.title.trim().to_string());
+        current = folder.parent.as_deref().and_then(|parent| folders.get(parent));
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::read_fixture;
+
+    #[test]
+    fn reads_wrapped_exports_with_lexer_languages() {
+        let batch = read_fixture(
+            read,
+            "json",
+            r#"{"contents": {
+                "folders": [{"uuid": "F1", "title": "Scripts"}, {"uuid": "F2", "title": "Deploy", "parent": "F1"}],
+                "tags": [{"uuid": "T1", "title": "ops"}],
+                "snippets": [
+                    {"title": "Release", "folder": "F2", "tags": ["T1", "missing"],
+                     "fragments": [{"title": "Fragment", "content": "make release", "language": "BashLexer", "note": "Needs VPN"}],
+                     "dateCreated": "2024-03-04 05:06:07", "dateModified": 1709528767},
+                    {"title": "Query", "fragments": [
+                        {"title": "Select", "content": "SELECT 1", "language": "SqlLexer"},
+                        {"title": "Notes", "content": "check the plan", "language": "TextLexer", "note": " "}]}
+                ]
+            }}"#,
+        );
+
+        let titles: Vec<&str> = batch.snippets.iter().map(|s| s.snippet.title.as_str()).collect();
+        assert_eq!(titles, vec!["Release", "Query - Select", "Query - Notes"]);
+
+        let release = &batch.snippets[0];
+        assert_eq!(release.folder, vec!["Scripts", "Deploy"]);
+        assert_eq!(release.snippet.language, "bash");
+        assert_eq!(release.snippet.description.as_deref(), Some("Needs VPN"));
+        assert_eq!(release.snippet.tags.as_deref(), Some("ops"));
+        assert_eq!(release.snippet.created_at, "2024-03-04T05:06:07+00:00");
+        assert_eq!(release.snippet.updated_at, "2024-03-04T05:06:07+00:00");
+
+        assert_eq!(batch.snippets[1].snippet.language, "sql");
+        assert_eq!(batch.snippets[2].snippet.language, "auto");
+        assert_eq!(batch.snippets[2].snippet.description, None);
+    }
+}
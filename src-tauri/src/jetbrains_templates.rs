@@ -0,0 +1,522 @@
+use anyhow::{Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashSet;
+
+use crate::models::Snippet;
+use crate::templates::{self, TemplatePart};
+use crate::vscode_snippets;
+
+/// Live template contexts and the languages they map to. Contexts match by
+/// prefix (`JAVA_STATEMENT` is Java); the first entry for a language is the
+/// one written on export.
+const CONTEXTS: &[(&str, &str)] = &[
+    ("JAVA_SCRIPT", "javascript"),
+    ("JS", "javascript"),
+    ("TypeScript", "typescript"),
+    ("TS", "typescript"),
+    ("JAVA_CODE", "java"),
+    ("JAVA", "java"),
+    ("KOTLIN", "kotlin"),
+    ("GO", "go"),
+    ("Python", "python"),
+    ("SHELL_SCRIPT", "bash"),
+    ("SQL", "sql"),
+    ("RUBY", "ruby"),
+    ("PHP", "php"),
+    ("HTML", "html"),
+    ("CSS", "css"),
+    ("XML", "xml"),
+    ("RUST", "rust"),
+];
+
+/// Context for languages JetBrains has no context for
+const OTHER_CONTEXT: &str = "OTHER";
+
+/// Tab stops have no JetBrains equivalent and are exported as `$TABSTOP_1$`
+const TAB_STOP_PREFIX: &str = "TABSTOP_";
+
+/// JetBrains functions for the built-ins that have one
+const BUILTIN_EXPRESSIONS: &[(&str, &str)] = &[
+    ("CLIPBOARD", "clipboard()"),
+    ("DATE", "date()"),
+    ("TIME", "time()"),
+];
+
+/// A parsed `<templateSet>`: one group of live templates
+#[derive(Debug)]
+pub struct TemplateSet {
+    pub group: String,
+    pub snippets: Vec<Snippet>,
+    /// Things that could not be carried over, as (template name, note)
+    pub notes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct Variable {
+    name: String,
+    expression: String,
+    default_value: String,
+}
+
+#[derive(Debug, Default)]
+struct Template {
+    name: String,
+    value: String,
+    description: Option<String>,
+    variables: Vec<Variable>,
+    contexts: Vec<String>,
+}
+
+/// Parse a live template file (`<templateSet group="...">`). A template's
+/// name is the abbreviation typed to expand it, so it becomes the trigger;
+/// the description is the title. `$VAR$` variables become template
+/// placeholders, with string defaults and `enum(...)` choices kept; `$END$`
+/// becomes the final cursor.
+pub fn parse(text: &str, file_name: &str) -> Result<TemplateSet> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+
+    let mut group = None;
+    let mut templates = Vec::new();
+    let mut current: Option<Template> = None;
+    let mut in_context = false;
+
+    loop {
+        let (element, empty) = match reader.read_event().context("Not a live template file")? {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(element) => {
+                match element.name().as_ref() {
+                    b"template" => templates.extend(current.take()),
+                    b"context" => in_context = false,
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"templateSet" => group = attribute(&element, "group")?,
+            b"template" => {
+                let template = Template {
+                    name: attribute(&element, "name")?.unwrap_or_default(),
+                    value: attribute(&element, "value")?.unwrap_or_default(),
+                    description: attribute(&element, "description")?.filter(|d| !d.trim().is_empty()),
+                    ..Default::default()
+                };
+                if empty {
+                    templates.push(template);
+                } else {
+                    current = Some(template);
+                }
+            }
+            b"variable" => {
+                if let Some(template) = current.as_mut() {
+                    template.variables.push(Variable {
+                        name: attribute(&element, "name")?.unwrap_or_default(),
+                        expression: attribute(&element, "expression")?.unwrap_or_default(),
+                        default_value: attribute(&element, "defaultValue")?.unwrap_or_default(),
+                    });
+                }
+            }
+            b"context" => in_context = !empty,
+            b"option" if in_context => {
+                if let (Some(template), Some(name)) = (current.as_mut(), attribute(&element, "name")?) {
+                    if attribute(&element, "value")?.as_deref() == Some("true") {
+                        template.contexts.push(name);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let Some(group) = group.filter(|g| !g.trim().is_empty()) else {
+        anyhow::bail!("{} is not a live template file (no templateSet group)", file_name);
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut snippets = Vec::new();
+    let mut notes = Vec::new();
+
+    for template in templates {
+        if template.name.trim().is_empty() {
+            notes.push((String::new(), "Skipped a template without a name".to_string()));
+            continue;
+        }
+
+        let mut template_notes = Vec::new();
+        let content = to_template(&template.value, &template.variables, &mut template_notes);
+        notes.extend(template_notes.into_iter().map(|note| (template.name.clone(), note)));
+
+        let abbreviation = template.name.trim().to_string();
+        snippets.push(Snippet {
+            id: None,
+            title: template.description.unwrap_or_else(|| abbreviation.clone()),
+            content,
+            language: context_language(&template.contexts),
+            description: None,
+            tags: None,
+            folder_id: None,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            trigger: Some(abbreviation),
+            deleted_at: None,
+            secret: false,
+        });
+    }
+
+    Ok(TemplateSet { group: group.trim().to_string(), snippets, notes })
+}
+
+/// Write snippets as a live template file for `group`. The trigger (or a
+/// slug of the title) is the abbreviation and the title the description.
+/// Placeholders become `$VAR$` variables; built-in arguments are kept as the
+/// variable's default.
+pub fn export(group: &str, snippets: &[Snippet]) -> String {
+    let mut xml = format!("<templateSet group=\"{}\">\n", escape_attribute(group));
+
+    for snippet in snippets {
+        let (value, variables) = from_template(&snippet.content);
+
+        xml.push_str(&format!(
+            "  <template name=\"{}\" value=\"{}\" description=\"{}\" toReformat=\"false\" toShortenFQNames=\"true\">\n",
+            escape_attribute(&snippet.trigger.clone().unwrap_or_else(|| vscode_snippets::slug(&snippet.title))),
+            escape_attribute(&value),
+            escape_attribute(&snippet.title),
+        ));
+        for variable in variables {
+            xml.push_str(&format!(
+                "    <variable name=\"{}\" expression=\"{}\" defaultValue=\"{}\" alwaysStopAt=\"true\" />\n",
+                escape_attribute(&variable.name),
+                escape_attribute(&variable.expression),
+                escape_attribute(&variable.default_value),
+            ));
+        }
+        xml.push_str("    <context>\n");
+        xml.push_str(&format!(
+            "      <option name=\"{}\" value=\"true\" />\n",
+            language_context(&snippet.language)
+        ));
+        xml.push_str("    </context>\n  </template>\n");
+    }
+
+    xml.push_str("</templateSet>\n");
+    xml
+}
+
+/// A file name (without `.xml`) for each group, in order. Characters that
+/// can't be in a file name become `_`, and a name already taken by an earlier
+/// group, ignoring case, gets a ` (2)`-style suffix.
+pub fn file_names<'a>(groups: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut taken = HashSet::new();
+    groups
+        .into_iter()
+        .map(|group| {
+            let sanitized: String = group
+                .chars()
+                .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') { c } else { '_' })
+                .collect();
+            let base = match sanitized.trim() {
+                "" => "templates".to_string(),
+                name => name.to_string(),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !taken.insert(name.to_lowercase()) {
+                name = format!("{} ({})", base, n);
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+/// Convert a JetBrains template value to template syntax
+fn to_template(value: &str, variables: &[Variable], notes: &mut Vec<String>) -> String {
+    let mut output = String::new();
+    let mut text = String::new();
+    let mut seen = HashSet::new();
+    let mut rest = value;
+
+    while let Some(i) = rest.find('$') {
+        text.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+
+        // `$$` is a literal dollar sign
+        if let Some(after) = after.strip_prefix('$') {
+            text.push('$');
+            rest = after;
+            continue;
+        }
+
+        match after.find('$').filter(|&end| is_variable_name(&after[..end])) {
+            Some(end) => {
+                output.push_str(&templates::escape(&std::mem::take(&mut text), false));
+                let name = &after[..end];
+                let first = seen.insert(name.to_string());
+                output.push_str(&placeholder(name, variables.iter().find(|v| v.name == name), first, notes));
+                rest = &after[end + 1..];
+            }
+            None => {
+                text.push('$');
+                rest = after;
+            }
+        }
+    }
+
+    text.push_str(rest);
+    output.push_str(&templates::escape(&text, false));
+    output
+}
+
+/// Template syntax for one `$NAME$`. Only the first occurrence of a
+/// variable carries its default or choices.
+fn placeholder(name: &str, variable: Option<&Variable>, first: bool, notes: &mut Vec<String>) -> String {
+    if name == "END" {
+        return "$0".to_string();
+    }
+
+    let default = variable.and_then(|v| string_literal(&v.default_value));
+    let expression = variable.map(|v| v.expression.trim()).unwrap_or_default();
+
+    if templates::is_builtin(name) {
+        return match default {
+            Some(arg) => format!("${{{}:{}}}", name, arg),
+            None => format!("${{{}}}", name),
+        };
+    }
+
+    let name = match name.strip_prefix(TAB_STOP_PREFIX) {
+        Some(number) if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) => number,
+        _ => name,
+    };
+    if !first {
+        return templates::variable_source(name, None, &[]);
+    }
+
+    let choices = expression
+        .strip_prefix("enum(")
+        .and_then(|e| e.strip_suffix(')'))
+        .and_then(string_literals)
+        .unwrap_or_default();
+    let default = default.or_else(|| string_literal(expression));
+
+    if let Some(variable) = variable {
+        let dropped = [&variable.default_value, &variable.expression]
+            .into_iter()
+            .map(|e| e.trim())
+            .filter(|e| !e.is_empty() && string_literal(e).is_none() && !e.starts_with("enum("))
+            .collect::<Vec<_>>();
+        for expression in dropped {
+            notes.push(format!("${}$ uses {}, which has no equivalent; fill it in by hand", name, expression));
+        }
+    }
+
+    templates::variable_source(name, default.as_deref(), &choices)
+}
+
+/// Convert template syntax to a JetBrains value and its variables
+fn from_template(content: &str) -> (String, Vec<Variable>) {
+    let mut value = String::new();
+    let mut variables: Vec<Variable> = Vec::new();
+
+    for part in templates::parts(content) {
+        let variable = match part {
+            TemplatePart::Text(text) => {
+                value.push_str(&text.replace('$', "$$"));
+                continue;
+            }
+            TemplatePart::Cursor => {
+                value.push_str("$END$");
+                continue;
+            }
+            TemplatePart::Variable { name, default, choices } => {
+                let name = if name.chars().all(|c| c.is_ascii_digit()) {
+                    format!("{}{}", TAB_STOP_PREFIX, name)
+                } else {
+                    name
+                };
+                let expression = if choices.is_empty() {
+                    String::new()
+                } else {
+                    format!("enum({})", choices.iter().map(|c| quote(c)).collect::<Vec<_>>().join(","))
+                };
+                Variable {
+                    name,
+                    expression,
+                    default_value: default.as_deref().map(quote).unwrap_or_default(),
+                }
+            }
+            TemplatePart::Builtin { name, arg } => Variable {
+                expression: BUILTIN_EXPRESSIONS
+                    .iter()
+                    .find(|(builtin, _)| *builtin == name)
+                    .map(|(_, expression)| expression.to_string())
+                    .unwrap_or_default(),
+                default_value: arg.as_deref().map(quote).unwrap_or_default(),
+                name,
+            },
+        };
+
+        value.push_str(&format!("${}$", variable.name));
+        if !variables.iter().any(|v| v.name == variable.name) {
+            variables.push(variable);
+        }
+    }
+
+    (value, variables)
+}
+
+fn context_language(contexts: &[String]) -> String {
+    contexts
+        .iter()
+        .find_map(|context| {
+            let context = context.to_uppercase();
+            CONTEXTS.iter().find_map(|(prefix, language)| {
+                let prefix = prefix.to_uppercase();
+                (context == prefix || context.starts_with(&format!("{}_", prefix))).then(|| language.to_string())
+            })
+        })
+        .unwrap_or_else(|| "auto".to_string())
+}
+
+fn language_context(language: &str) -> &'static str {
+    let language = language.trim().to_lowercase();
+    CONTEXTS
+        .iter()
+        .find(|(_, ours)| *ours == language)
+        .map(|(context, _)| *context)
+        .unwrap_or(OTHER_CONTEXT)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A JetBrains string literal (`"text"`)
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The text of `"text"`, or `None` when `expression` is anything else
+fn string_literal(expression: &str) -> Option<String> {
+    match string_literals(expression)?.as_slice() {
+        [text] => Some(text.clone()),
+        _ => None,
+    }
+}
+
+/// Comma-separated string literals, as in `enum("a", "b")`
+fn string_literals(list: &str) -> Option<Vec<String>> {
+    let mut literals = Vec::new();
+    let mut chars = list.trim().chars().peekable();
+
+    while chars.peek().is_some() {
+        if chars.next()? != '"' {
+            return None;
+        }
+        let mut literal = String::new();
+        loop {
+            match chars.next()? {
+                '\\' => literal.push(chars.next()?),
+                '"' => break,
+                c => literal.push(c),
+            }
+        }
+        literals.push(literal);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&',').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        } else if chars.peek().is_some() {
+            return None;
+        }
+    }
+
+    (!literals.is_empty()).then_some(literals)
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        if attribute.key.as_ref() == name.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Escape an attribute value. Line breaks are written as character
+/// references, as JetBrains does, so they survive attribute normalization.
+fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_is_the_trigger_and_description_the_title() {
+        let xml = r#"<templateSet group="Work/Go">
+  <template name="iferr" value="if err != nil { return $END$ }" description="Return on error" toReformat="false">
+    <context><option name="GO" value="true" /></context>
+  </template>
+  <template name="todo" value="// TODO" />
+</templateSet>"#;
+        let set = parse(xml, "go.xml").unwrap();
+
+        assert_eq!(set.group, "Work/Go");
+        assert_eq!(set.snippets[0].trigger.as_deref(), Some("iferr"));
+        assert_eq!(set.snippets[0].title, "Return on error");
+        assert_eq!(set.snippets[0].content, "if err != nil { return $0 }");
+        assert_eq!(set.snippets[0].language, "go");
+        assert_eq!(set.snippets[1].title, "todo");
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let set = parse(
+            r#"<templateSet group="Work/Go"><template name="pf" value="fmt.Printf(&quot;$FMT$&quot;)" description="Printf">
+    <variable name="FMT" expression="" defaultValue="&quot;%v&quot;" alwaysStopAt="true" />
+  </template></templateSet>"#,
+            "go.xml",
+        )
+        .unwrap();
+
+        let again = parse(&export(&set.group, &set.snippets), "go.xml").unwrap();
+
+        assert_eq!(again.group, "Work/Go");
+        assert_eq!(again.snippets[0].trigger.as_deref(), Some("pf"));
+        assert_eq!(again.snippets[0].title, "Printf");
+        assert_eq!(again.snippets[0].content, set.snippets[0].content);
+    }
+
+    #[test]
+    fn file_names_never_collide() {
+        let names = file_names(["Uncategorized", "Uncategorized", "Work/Go", "Work_Go", "go", "Go", "///"]);
+        assert_eq!(
+            names,
+            ["Uncategorized", "Uncategorized (2)", "Work_Go", "Work_Go (2)", "go", "Go (2)", "___"]
+        );
+        assert_eq!(file_names(["  "]), ["templates"]);
+    }
+}
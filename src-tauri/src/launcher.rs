@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::models::{SearchResult, Snippet};
+
+/// How much a semantic match counts relative to a perfect fuzzy title match
+const SEMANTIC_WEIGHT: f32 = 0.8;
+
+const TITLE_WEIGHT: f32 = 1.0;
+const TAGS_WEIGHT: f32 = 0.8;
+const DESCRIPTION_WEIGHT: f32 = 0.6;
+/// Content only counts on a plain substring match; fuzzy matching over code is noise
+const CONTENT_WEIGHT: f32 = 0.4;
+
+/// Score `query` as a subsequence of `text`, from 0 to 1. Consecutive characters
+/// and characters at word starts score higher. `None` if `text` lacks a query character.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<f32> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0.0;
+    let mut qi = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (ti, c) in text.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if !c.to_lowercase().eq(query[qi].to_lowercase()) {
+            continue;
+        }
+
+        let prev = ti.checked_sub(1).map(|p| text[p]);
+        let word_start = prev.is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()));
+
+        score += 1.0;
+        if last_match.is_some_and(|l| l + 1 == ti) {
+            score += 3.0;
+        }
+        if word_start {
+            score += 2.0;
+        }
+
+        last_match = Some(ti);
+        qi += 1;
+    }
+
+    if qi < query.len() {
+        return None;
+    }
+
+    Some(score / (query.len() as f32 * 6.0))
+}
+
+fn keyword_score(query: &str, snippet: &Snippet) -> Option<f32> {
+    let fields = [
+        (Some(snippet.title.as_str()), TITLE_WEIGHT),
+        (snippet.tags.as_deref(), TAGS_WEIGHT),
+        (snippet.description.as_deref(), DESCRIPTION_WEIGHT),
+    ];
+
+    let fuzzy = fields
+        .iter()
+        .filter_map(|(text, weight)| fuzzy_score(query, (*text)?).map(|s| s * weight))
+        .fold(None, |best: Option<f32>, s| Some(best.map_or(s, |b| b.max(s))));
+
+    let in_content = snippet.content.to_lowercase().contains(&query.to_lowercase());
+    let content = in_content.then_some(CONTENT_WEIGHT);
+
+    match (fuzzy, content) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Merge fuzzy keyword matches with semantic results into one ranked list.
+/// An empty query lists the most recently updated snippets.
+pub fn rank(query: &str, snippets: &[Snippet], semantic: &[SearchResult], limit: usize) -> Vec<SearchResult> {
+    let query = query.trim();
+
+    if query.is_empty() {
+        let mut recent: Vec<&Snippet> = snippets.iter().collect();
+        recent.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        return recent
+            .into_iter()
+            .take(limit)
+            .map(|snippet| SearchResult {
+                snippet: snippet.clone(),
+                score: 0.0,
+                highlight: None,
+            })
+            .collect();
+    }
+
+    let semantic_scores: HashMap<i64, f32> = semantic
+        .iter()
+        .filter_map(|r| Some((r.snippet.id?, r.score)))
+        .collect();
+
+    let mut results: Vec<SearchResult> = snippets
+        .iter()
+        .filter_map(|snippet| {
+            let keyword = keyword_score(query, snippet);
+            let semantic = snippet
+                .id
+                .and_then(|id| semantic_scores.get(&id))
+                .map(|s| s * SEMANTIC_WEIGHT);
+
+            let score = match (keyword, semantic) {
+                // Matching both ways is the strongest signal
+                (Some(k), Some(s)) => k.max(s) + k.min(s) * 0.5,
+                (k, s) => k.or(s)?,
+            };
+
+            Some(SearchResult {
+                snippet: snippet.clone(),
+                score,
+                highlight: None,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+    results
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::database::Database;
+use crate::file_storage::FileStorageManager;
+use crate::models::{ExportReport, ImportMode, ImportReport, LibraryData};
+
+/// Identifies a NoBrainDev library archive
+const ARCHIVE_FORMAT: &str = "nobraindev-library";
+
+/// Version of the archive layout and `library.json`. Archives written by a
+/// newer version are refused rather than half-imported.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const MANIFEST_PATH: &str = "manifest.json";
+const LIBRARY_PATH: &str = "library.json";
+
+/// `manifest.json`, written last
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    schema_version: u32,
+    app_version: String,
+    exported_at: String,
+    /// SHA-256 (hex) of every other entry, keyed by its path in the archive
+    checksums: BTreeMap<String, String>,
+}
+
+/// File blobs are stored by their ID in the exporting library
+fn blob_path(file_id: i64) -> String {
+    format!("files/{}", file_id)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write the whole library (with file blobs) to a zip archive at `path`
+pub fn export_library(db: &Database, path: &Path) -> Result<ExportReport> {
+    // Written next to the destination and moved into place at the end, so a
+    // failed export never leaves a truncated archive behind
+    let partial_path = path.with_extension("partial");
+
+    let result = write_archive(db, &partial_path)
+        .and_then(|report| {
+            fs::rename(&partial_path, path).context("Failed to move the archive into place")?;
+            Ok(report)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+
+    let report = result?;
+    println!(
+        "📦 Exported {} snippets, {} folders, {} files and {} clipboard entries to {}",
+        report.snippets, report.folders, report.files, report.clipboard_entries, path.display()
+    );
+    Ok(report)
+}
+
+fn write_archive(db: &Database, path: &Path) -> Result<ExportReport> {
+    let mut data = db.get_library_data()?;
+    let storage = FileStorageManager::new()?;
+    let mut report = ExportReport::default();
+    let mut checksums = BTreeMap::new();
+
+    let file = fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut exported_files = Vec::new();
+    for file in std::mem::take(&mut data.files) {
+        let Some(id) = file.id else { continue };
+        match storage.read_file(&file.storage_path) {
+            Ok(bytes) => {
+                let name = blob_path(id);
+                checksums.insert(name.clone(), sha256_hex(&bytes));
+                zip.start_file(name, options)?;
+                zip.write_all(&bytes)?;
+                exported_files.push(file);
+            }
+            Err(e) => {
+                eprintln!("Leaving {} out of the export: {}", file.filename, e);
+                report.missing_files.push(file.filename);
+            }
+        }
+    }
+    data.files = exported_files;
+
+    let library = serde_json::to_vec_pretty(&data)?;
+    checksums.insert(LIBRARY_PATH.to_string(), sha256_hex(&library));
+    zip.start_file(LIBRARY_PATH, options)?;
+    zip.write_all(&library)?;
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        schema_version: SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        checksums,
+    };
+    zip.start_file(MANIFEST_PATH, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    zip.finish()?;
+
+    report.folders = data.folders.len();
+    report.snippets = data.snippets.len();
+    report.files = data.files.len();
+    report.clipboard_entries = data.clipboard.len();
+    Ok(report)
+}
+
+/// Import an archive written by `export_library`. Every entry is checked
+/// against the manifest before the library is touched.
+pub fn import_library(db: &Database, path: &Path, mode: ImportMode) -> Result<ImportReport> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut zip = ZipArchive::new(file).context("Not a library archive")?;
+
+    let manifest: Manifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_PATH)?)
+        .context("The archive manifest is invalid")?;
+    if manifest.format != ARCHIVE_FORMAT {
+        anyhow::bail!("Not a library archive");
+    }
+    if manifest.schema_version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "This archive was made by a newer version of NoBrainDev ({}); update the app to import it",
+            manifest.app_version
+        );
+    }
+
+    let data: LibraryData = serde_json::from_slice(&read_verified(&mut zip, &manifest, LIBRARY_PATH)?)
+        .context("The archive's library data is invalid")?;
+
+    // Blobs are stored before the database import so that it can point at
+    // them, and removed again if anything goes wrong
+    let storage = FileStorageManager::new()?;
+    let mut blob_paths: HashMap<i64, String> = HashMap::new();
+
+    let result = store_blobs(&mut zip, &manifest, &data, &storage, &mut blob_paths)
+        .and_then(|_| db.import_library(&data, mode, &blob_paths));
+
+    match result {
+        Ok((report, unused_blobs)) => {
+            for storage_path in unused_blobs {
+                if let Err(e) = storage.delete_file(&storage_path) {
+                    eprintln!("Failed to delete file {} from storage: {}", storage_path, e);
+                }
+            }
+            println!(
+                "📦 Imported {} snippets, {} folders, {} files and {} clipboard entries ({} duplicates skipped, {} conflicts)",
+                report.snippets, report.folders, report.files, report.clipboard_entries,
+                report.duplicates_skipped, report.conflicts.len()
+            );
+            Ok(report)
+        }
+        Err(e) => {
+            for storage_path in blob_paths.values() {
+                let _ = storage.delete_file(storage_path);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn store_blobs(
+    zip: &mut ZipArchive<fs::File>,
+    manifest: &Manifest,
+    data: &LibraryData,
+    storage: &FileStorageManager,
+    blob_paths: &mut HashMap<i64, String>,
+) -> Result<()> {
+    for file in &data.files {
+        let Some(id) = file.id else { continue };
+        let name = blob_path(id);
+        if !manifest.checksums.contains_key(&name) {
+            // Reported as missing by the database import
+            continue;
+        }
+
+        let bytes = read_verified(zip, manifest, &name)?;
+        // A random prefix keeps files with the same name from overwriting each other
+        let stored_name = format!("{}_{}", uuid::Uuid::new_v4().simple(), file.filename);
+        let stored_path = storage.save_file(&stored_name, &bytes)?;
+        blob_paths.insert(id, stored_path.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+fn read_entry(zip: &mut ZipArchive<fs::File>, name: &str) -> Result<Vec<u8>> {
+    let mut entry = zip
+        .by_name(name)
+        .with_context(|| format!("The archive is missing {}", name))?;
+    FileStorageManager::validate_size(entry.size() as i64)?;
+
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read an entry and check it against the manifest's checksum
+fn read_verified(zip: &mut ZipArchive<fs::File>, manifest: &Manifest, name: &str) -> Result<Vec<u8>> {
+    let expected = manifest
+        .checksums
+        .get(name)
+        .with_context(|| format!("{} is not listed in the archive manifest", name))?;
+
+    let bytes = read_entry(zip, name)?;
+    if !sha256_hex(&bytes).eq_ignore_ascii_case(expected) {
+        anyhow::bail!("{} is corrupted (checksum mismatch)", name);
+    }
+    Ok(bytes)
+}
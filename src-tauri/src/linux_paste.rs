@@ -0,0 +1,281 @@
+// Paste-to-cursor support for Linux desktops.
+//
+// X11 sessions use `xdotool` to remember the window that was focused before the
+// popup opened, re-activate it and send the paste chord. Wayland has no global
+// focus API, so we rely on the compositor (sway / Hyprland IPC when available) to
+// refocus and inject keys through `wtype` (virtual-keyboard protocol) or `ydotool`.
+
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Window classes (lowercase) of terminals that paste with Ctrl+Shift+V
+const TERMINAL_CLASSES: &[&str] = &[
+    "alacritty", "foot", "footclient", "gnome-terminal", "gnome-terminal-server", "guake",
+    "hyper", "kitty", "konsole", "lxterminal", "mate-terminal", "org.gnome.console",
+    "org.gnome.ptyxis", "org.gnome.terminal", "org.wezfurlong.wezterm", "ptyxis", "qterminal",
+    "rio", "st", "st-256color", "terminator", "terminology", "tilix", "urxvt", "rxvt",
+    "wezterm", "xfce4-terminal", "xterm", "yakuake", "com.mitchellh.ghostty", "ghostty",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Session {
+    X11,
+    Wayland,
+}
+
+#[derive(Debug, Clone)]
+enum WindowRef {
+    X11(String),
+    Sway(i64),
+    Hyprland(String),
+}
+
+#[derive(Debug, Clone)]
+struct TargetWindow {
+    window: Option<WindowRef>,
+    class: Option<String>,
+}
+
+static PREVIOUS_WINDOW: Mutex<Option<TargetWindow>> = Mutex::new(None);
+
+fn detect_session() -> Session {
+    let session_type = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    if session_type.eq_ignore_ascii_case("wayland") || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Session::Wayland
+    } else {
+        Session::X11
+    }
+}
+
+/// Run a helper binary, turning a missing binary or non-zero exit into an error
+fn run(program: &str, args: &[&str]) -> Result<Output, String> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("'{}' is not installed", program)
+        } else {
+            format!("Failed to run {}: {}", program, e)
+        }
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(format!("{} {} failed: {}", program, args.join(" "), stderr));
+    }
+
+    Ok(output)
+}
+
+fn stdout_of(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn capture_x11() -> Result<TargetWindow, String> {
+    let window_id = stdout_of(&run("xdotool", &["getactivewindow"])?);
+    if window_id.is_empty() {
+        return Err("xdotool did not report an active window".to_string());
+    }
+
+    let class = run("xdotool", &["getwindowclassname", &window_id])
+        .map(|o| stdout_of(&o))
+        .ok()
+        .filter(|c| !c.is_empty());
+
+    Ok(TargetWindow {
+        window: Some(WindowRef::X11(window_id)),
+        class,
+    })
+}
+
+fn find_focused_sway_node(node: &serde_json::Value) -> Option<(i64, Option<String>)> {
+    if node["focused"].as_bool() == Some(true) {
+        let class = node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())
+            .map(|c| c.to_string());
+        return Some((node["id"].as_i64()?, class));
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(find_focused_sway_node)
+}
+
+fn capture_wayland() -> Result<TargetWindow, String> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        let output = run("hyprctl", &["activewindow", "-j"])?;
+        let window: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Unexpected hyprctl output: {}", e))?;
+        return Ok(TargetWindow {
+            window: window["address"].as_str().map(|a| WindowRef::Hyprland(a.to_string())),
+            class: window["class"].as_str().map(|c| c.to_string()),
+        });
+    }
+
+    if std::env::var_os("SWAYSOCK").is_some() {
+        let output = run("swaymsg", &["-t", "get_tree"])?;
+        let tree: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Unexpected swaymsg output: {}", e))?;
+        let (id, class) = find_focused_sway_node(&tree)
+            .ok_or_else(|| "sway did not report a focused window".to_string())?;
+        return Ok(TargetWindow {
+            window: Some(WindowRef::Sway(id)),
+            class,
+        });
+    }
+
+    // Other compositors: focus returns to the previous window when the popup hides
+    Ok(TargetWindow { window: None, class: None })
+}
+
+/// Remember the focused window so the paste can be sent back to it
+pub fn capture_active_window() -> Result<(), String> {
+    let target = match detect_session() {
+        Session::X11 => capture_x11(),
+        Session::Wayland => capture_wayland(),
+    };
+
+    let mut previous = PREVIOUS_WINDOW.lock().map_err(|e| e.to_string())?;
+    match target {
+        Ok(target) => {
+            println!("✅ Captured previous window: {:?}", target);
+            *previous = Some(target);
+            Ok(())
+        }
+        Err(e) => {
+            *previous = None;
+            Err(e)
+        }
+    }
+}
+
+fn activate(window: &WindowRef) -> Result<(), String> {
+    match window {
+        WindowRef::X11(id) => run("xdotool", &["windowactivate", "--sync", id]).map(|_| ()),
+        WindowRef::Sway(id) => run("swaymsg", &[&format!("[con_id={}]", id), "focus"]).map(|_| ()),
+        WindowRef::Hyprland(address) => {
+            run("hyprctl", &["dispatch", "focuswindow", &format!("address:{}", address)]).map(|_| ())
+        }
+    }
+}
+
+fn is_terminal(class: Option<&str>) -> bool {
+    class.is_some_and(|class| {
+        let class = class.to_lowercase();
+        TERMINAL_CLASSES.contains(&class.as_str())
+    })
+}
+
+/// Letter key of a Ctrl chord, with its Linux input keycode for ydotool
+#[derive(Debug, Clone, Copy)]
+struct ChordKey {
+    name: &'static str,
+    keycode: u16,
+}
+
+const PASTE_KEY: ChordKey = ChordKey { name: "v", keycode: 47 };
+const COPY_KEY: ChordKey = ChordKey { name: "c", keycode: 46 };
+
+fn send_chord_x11(key: ChordKey, terminal: bool) -> Result<(), String> {
+    let chord = if terminal {
+        format!("ctrl+shift+{}", key.name)
+    } else {
+        format!("ctrl+{}", key.name)
+    };
+    run("xdotool", &["key", "--clearmodifiers", &chord]).map(|_| ())
+}
+
+fn send_chord_wayland(key: ChordKey, terminal: bool) -> Result<(), String> {
+    let wtype_args: &[&str] = if terminal {
+        &["-M", "ctrl", "-M", "shift", "-k", key.name, "-m", "shift", "-m", "ctrl"]
+    } else {
+        &["-M", "ctrl", "-k", key.name, "-m", "ctrl"]
+    };
+
+    let wtype_error = match run("wtype", wtype_args) {
+        Ok(_) => return Ok(()),
+        Err(e) => e,
+    };
+
+    // ydotool works on any compositor through uinput (needs ydotoold running).
+    // Linux keycodes: 29 = LEFTCTRL, 42 = LEFTSHIFT
+    let modifiers: &[u16] = if terminal { &[29, 42] } else { &[29] };
+    let mut events: Vec<String> = modifiers.iter().map(|m| format!("{}:1", m)).collect();
+    events.push(format!("{}:1", key.keycode));
+    events.push(format!("{}:0", key.keycode));
+    events.extend(modifiers.iter().rev().map(|m| format!("{}:0", m)));
+
+    let mut ydotool_args = vec!["key"];
+    ydotool_args.extend(events.iter().map(String::as_str));
+
+    run("ydotool", &ydotool_args).map(|_| ()).map_err(|ydotool_error| {
+        format!(
+            "Could not simulate keystrokes on Wayland. Install wtype or ydotool. (wtype: {}; ydotool: {})",
+            wtype_error, ydotool_error
+        )
+    })
+}
+
+fn focused_window_class(session: Session) -> Option<String> {
+    match session {
+        Session::X11 => capture_x11().ok().and_then(|t| t.class),
+        Session::Wayland => capture_wayland().ok().and_then(|t| t.class),
+    }
+}
+
+/// Class of the currently focused window, if the session exposes it
+pub fn active_window_class() -> Option<String> {
+    focused_window_class(detect_session())
+}
+
+/// Drop the captured window so the next paste goes to the focused one
+pub fn forget_active_window() {
+    if let Ok(mut previous) = PREVIOUS_WINDOW.lock() {
+        *previous = None;
+    }
+}
+
+/// Send the copy chord to the focused window so its selection lands on the clipboard
+pub fn copy_selection() -> Result<(), String> {
+    let session = detect_session();
+    let terminal = is_terminal(focused_window_class(session).as_deref());
+
+    match session {
+        Session::X11 => send_chord_x11(COPY_KEY, terminal),
+        Session::Wayland => send_chord_wayland(COPY_KEY, terminal),
+    }
+}
+
+/// Refocus the captured window and send the paste chord appropriate for it
+pub fn paste() -> Result<(), String> {
+    let target = PREVIOUS_WINDOW.lock().map_err(|e| e.to_string())?.take();
+    let session = detect_session();
+
+    let class = match &target {
+        Some(target) => target.class.clone(),
+        // Nothing captured (e.g. pasting from the queue shortcut): use the focused window
+        None => focused_window_class(session),
+    };
+
+    if let Some(window) = target.as_ref().and_then(|t| t.window.as_ref()) {
+        activate(window)?;
+    }
+
+    // Give the window manager a moment to move keyboard focus
+    thread::sleep(Duration::from_millis(if session == Session::X11 { 50 } else { 150 }));
+
+    let terminal = is_terminal(class.as_deref());
+    println!(
+        "🎯 Pasting into {} window (class: {})",
+        if terminal { "terminal" } else { "regular" },
+        class.as_deref().unwrap_or("unknown")
+    );
+
+    match session {
+        Session::X11 => send_chord_x11(PASTE_KEY, terminal),
+        Session::Wayland => send_chord_wayland(PASTE_KEY, terminal),
+    }
+}
@@ -5,17 +5,49 @@ mod database;
 mod models;
 mod commands;
 mod search;
+mod secrets;
+mod smart_folders;
+mod sync;
+mod sync_crypto;
 mod auth;
+mod backup;
+mod classifier;
+mod diff;
+mod encryption;
+mod expansion;
 mod file_storage;
+mod hotkeys;
+mod importers;
+mod jetbrains_templates;
+mod launcher;
+mod library_archive;
+#[cfg(target_os = "linux")]
+mod linux_paste;
+mod markdown_vault;
+mod paste_queue;
+mod retention;
+mod tags;
+mod templates;
+mod transforms;
+mod trash;
+mod vscode_snippets;
 
 use std::sync::Mutex;
 
 pub struct AppState {
     db: Mutex<database::Database>,
     search_engine: Mutex<Option<search::SearchEngine>>,
+    paste_queue: Mutex<paste_queue::PasteQueue>,
+    hotkeys: Mutex<hotkeys::HotkeyRegistry>,
+    expansion: Mutex<expansion::ExpansionState>,
+    secrets: Mutex<secrets::SecretSession>,
 }
 
 fn main() {
+    // Without a key from the keyring, an encrypted library opens locked
+    if let Err(e) = encryption::load_key() {
+        eprintln!("Failed to load the encryption key: {:#}", e);
+    }
     let db = database::Database::new().expect("Failed to initialize database");
 
     tauri::Builder::default()
@@ -28,6 +60,19 @@ fn main() {
         .manage(AppState {
             db: Mutex::new(db),
             search_engine: Mutex::new(None),
+            paste_queue: Mutex::new(paste_queue::PasteQueue::new()),
+            hotkeys: Mutex::new(hotkeys::HotkeyRegistry::default()),
+            expansion: Mutex::new(expansion::ExpansionState::default()),
+            secrets: Mutex::new(secrets::SecretSession::default()),
+        })
+        .setup(|app| {
+            retention::spawn_clipboard_pruner(app.handle().clone());
+            expansion::init(app.handle().clone());
+            trash::spawn_trash_purger(app.handle().clone());
+            markdown_vault::spawn_vault_sync(app.handle().clone());
+            backup::spawn_backup_scheduler(app.handle().clone());
+            secrets::spawn_relocker(app.handle().clone());
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::create_snippet,
@@ -35,6 +80,11 @@ fn main() {
             commands::get_snippet,
             commands::update_snippet,
             commands::delete_snippet,
+            commands::get_snippet_revisions,
+            commands::diff_snippet_revisions,
+            commands::restore_snippet_revision,
+            commands::get_revision_retention,
+            commands::set_revision_retention,
             commands::search_snippets,
             commands::semantic_search,
             commands::download_model,
@@ -44,8 +94,14 @@ fn main() {
             commands::create_folder,
             commands::get_all_folders,
             commands::get_folder,
+            commands::get_smart_folder_counts,
             commands::update_folder,
             commands::delete_folder,
+            commands::move_folder,
+            commands::reorder_folders,
+            commands::create_smart_folder,
+            commands::update_smart_folder,
+            commands::preview_smart_folder,
             commands::get_snippets_by_folder,
             commands::update_snippet_folder,
             commands::organize_snippets,
@@ -55,21 +111,99 @@ fn main() {
             commands::get_clipboard_entry,
             commands::delete_clipboard_entry,
             commands::clear_clipboard_history,
+            commands::pin_clipboard_entry,
+            commands::unpin_clipboard_entry,
+            commands::set_clipboard_entry_favorite,
+            commands::reorder_pinned_clipboard_entries,
             commands::update_clipboard_entry,
+            commands::classify_content,
+            commands::detect_language,
+            commands::list_transforms,
+            commands::transform_clipboard_entry,
+            commands::get_clipboard_retention,
+            commands::set_clipboard_retention,
+            commands::prune_clipboard_history,
             commands::show_clipboard_popup,
             commands::hide_clipboard_popup,
+            commands::show_snippet_launcher,
+            commands::hide_snippet_launcher,
+            commands::search_snippet_launcher,
+            commands::render_snippet,
+            commands::paste_snippet,
             commands::register_clipboard_hotkey,
+            commands::get_hotkey_bindings,
+            commands::set_hotkey_binding,
+            commands::reset_hotkey_bindings,
+            commands::get_expansion_settings,
+            commands::set_expansion_settings,
+            commands::set_expansion_paused,
             commands::capture_previous_app,
             commands::paste_to_cursor,
             commands::paste_as_plain_text,
+            commands::start_paste_queue,
+            commands::paste_next_in_queue,
+            commands::get_paste_queue,
+            commands::reset_paste_queue,
             commands::get_app_version,
             commands::upload_file,
             commands::download_file,
             commands::update_file,
             commands::delete_file,
+            commands::get_trash,
+            commands::restore_from_trash,
+            commands::delete_permanently,
+            commands::empty_trash,
+            commands::get_trash_settings,
+            commands::set_trash_settings,
             commands::get_all_files,
             commands::get_files_by_folder,
             commands::search_files,
+            commands::get_all_tags,
+            commands::rename_tag,
+            commands::merge_tags,
+            commands::delete_tag,
+            commands::set_tag_color,
+            commands::set_clipboard_entry_tags,
+            commands::export_library,
+            commands::import_library,
+            commands::import_vscode_snippets,
+            commands::export_vscode_snippets,
+            commands::import_jetbrains_templates,
+            commands::export_jetbrains_templates,
+            commands::get_vault_settings,
+            commands::set_vault_settings,
+            commands::sync_vault,
+            commands::list_importers,
+            commands::run_importer,
+            commands::get_backup_settings,
+            commands::set_backup_settings,
+            commands::list_backups,
+            commands::create_backup,
+            commands::verify_backup,
+            commands::restore_backup,
+            commands::delete_backup,
+            commands::get_encryption_status,
+            commands::enable_encryption,
+            commands::add_encryption_passphrase,
+            commands::retry_encryption_keyring,
+            commands::reset_locked_library,
+            commands::unlock_library,
+            commands::get_secret_vault_status,
+            commands::set_secret_password,
+            commands::unlock_secrets,
+            commands::lock_secrets,
+            commands::set_secret_lock_timeout,
+            commands::reveal_secret_snippet,
+            commands::get_sync_encryption_status,
+            commands::setup_sync_encryption,
+            commands::unlock_sync_encryption,
+            commands::recover_sync_encryption,
+            commands::change_sync_passphrase,
+            commands::rotate_sync_key,
+            commands::retire_old_sync_keys,
+            commands::sync_library,
+            commands::get_sync_status,
+            commands::resync_library,
             auth::store_user_token,
             auth::get_user_token,
             auth::clear_user_tokens,
@@ -0,0 +1,1010 @@
+use anyhow::{Context, Result};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::{Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::classifier;
+use crate::database::Database;
+use crate::expansion;
+use crate::models::{Folder, FolderKind, Snippet, VaultSyncReport};
+use crate::tags;
+use crate::AppState;
+
+// Vault layout: one `<title>.md` per snippet, in the subdirectory matching its
+// folder (`Work/Go/Range loop.md`). Each file is YAML front matter followed by
+// the description and the content in a fenced code block:
+//
+//   ---
+//   id: 12
+//   title: Range loop
+//   language: go
+//   tags: [loops]
+//   ---
+//
+//   Iterate over a slice.
+//
+//   ```go
+//   for i, v := range items {}
+//   ```
+//
+// Files without an `id` are new snippets. A file and its snippet are compared
+// with what was last synced (`vault_files`) to tell which side changed; when
+// both did, the snippet wins and the file's version is kept as a new snippet.
+
+/// How long file changes settle before syncing
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// How often the database is checked for changes to write out
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Added to the title of a snippet made from a file that was edited while its
+/// snippet also changed
+const CONFLICT_SUFFIX: &str = " (vault conflict)";
+
+/// When at least this many synced files are gone at once, and they are most
+/// of the vault, the vault looks unmounted or replaced rather than cleaned up,
+/// so nothing is trashed
+const MASS_DELETE_MIN_FILES: usize = 3;
+
+/// Held from scanning the vault until the scan is applied, so that a second
+/// sync never applies a scan taken before the first one wrote its files
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FrontMatter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(default, deserialize_with = "tag_list", skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Informational; the file's directory decides the folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trigger: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+}
+
+/// Tags may be written as a list or as a comma-separated string
+fn tag_list<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<String>, D::Error> {
+    Ok(match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::String(text) => tags::parse_tags(&text),
+        serde_yaml::Value::Sequence(items) => {
+            tags::normalize_tags(items.iter().filter_map(serde_yaml::Value::as_str))
+        }
+        _ => Vec::new(),
+    })
+}
+
+/// A snippet as read from its Markdown file
+#[derive(Debug)]
+struct Note {
+    id: Option<i64>,
+    title: String,
+    language: String,
+    description: Option<String>,
+    content: String,
+    tags: Vec<String>,
+    trigger: Option<String>,
+}
+
+/// A Markdown file found in the vault. Paths are relative, with `/` separators.
+struct VaultFile {
+    path: String,
+    text: String,
+    hash: String,
+}
+
+/// Everything read from the vault, before the database is touched
+#[derive(Default)]
+pub struct Scan {
+    files: Vec<VaultFile>,
+    dirs: Vec<String>,
+    /// Files that exist but couldn't be read; they are never overwritten
+    unreadable: HashSet<String>,
+    problems: Vec<String>,
+}
+
+/// Live manual folders, addressed by their directory in the vault
+struct Folders {
+    folders: Vec<Folder>,
+}
+
+impl Folders {
+    fn load(db: &Database) -> Result<Self> {
+        let folders = db
+            .get_all_folders(false)?
+            .into_iter()
+            .filter(|f| f.kind == FolderKind::Manual)
+            .collect();
+        Ok(Folders { folders })
+    }
+
+    fn get(&self, id: i64) -> Option<&Folder> {
+        self.folders.iter().find(|f| f.id == Some(id))
+    }
+
+    /// Directory of a folder, e.g. `Work/Go`; empty for no folder
+    fn path(&self, id: Option<i64>) -> String {
+        let mut names = Vec::new();
+        let mut current = id.and_then(|id| self.get(id));
+        while let Some(folder) = current {
+            // Guards against a corrupt parent cycle
+            if names.len() > self.folders.len() {
+                break;
+            }
+            names.push(file_name_part(&folder.name));
+            current = folder.parent_id.and_then(|id| self.get(id));
+        }
+        names.reverse();
+        names.join("/")
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.folders.iter().map(|f| self.path(f.id)).collect()
+    }
+
+    /// Folder for a directory, if it exists. `Some(None)` is the vault root.
+    fn find(&self, dir: &str) -> Option<Option<i64>> {
+        let mut parent = None;
+        for part in dir.split('/').filter(|p| !p.is_empty()) {
+            let folder = self
+                .folders
+                .iter()
+                .find(|f| f.parent_id == parent && file_name_part(&f.name) == part)?;
+            parent = folder.id;
+        }
+        Some(parent)
+    }
+
+    /// Folder for a directory, creating the missing ones
+    fn ensure(&mut self, db: &Database, dir: &str) -> Result<Option<i64>> {
+        let mut parent = None;
+        for part in dir.split('/').filter(|p| !p.is_empty()) {
+            let existing = self
+                .folders
+                .iter()
+                .find(|f| f.parent_id == parent && file_name_part(&f.name) == part)
+                .and_then(|f| f.id);
+            parent = match existing {
+                Some(id) => Some(id),
+                None => {
+                    let id = db.create_folder(part, None, parent)?;
+                    self.folders.extend(db.get_folder(id, false)?);
+                    Some(id)
+                }
+            };
+        }
+        Ok(parent)
+    }
+}
+
+/// Read every Markdown file in the vault at `root`. A missing root is an
+/// error rather than an empty vault: recreating it would trash every snippet.
+pub fn scan_vault(root: &Path) -> Result<Scan> {
+    if !root.is_dir() {
+        anyhow::bail!(
+            "The vault directory {} is missing. Reconnect its drive, or choose another directory",
+            root.display()
+        );
+    }
+
+    let mut scanned = Scan::default();
+    scan(root, "", &mut scanned)?;
+    Ok(scanned)
+}
+
+/// Mirror the library to and from the vault at `root`, as read by `scan_vault`
+pub fn sync(db: &Database, root: &Path, scanned: Scan) -> Result<VaultSyncReport> {
+    let Scan { files, dirs, mut unreadable, problems } = scanned;
+    let mut report = VaultSyncReport { conflicts: problems, ..Default::default() };
+    let mut folders = Folders::load(db)?;
+    let records = db.get_vault_records()?;
+    let previous_folders: HashSet<String> = db.get_vault_folders()?.into_iter().collect();
+
+    let notes: Vec<(&VaultFile, Result<Note>)> = files
+        .iter()
+        .map(|file| {
+            let stem = file.path.rsplit('/').next().unwrap_or_default().trim_end_matches(".md");
+            (file, parse_note(&file.text, stem))
+        })
+        .collect();
+
+    // Check for a vanished vault before changing anything
+    let present_ids: HashSet<i64> = notes.iter().filter_map(|(_, note)| note.as_ref().ok()?.id).collect();
+    let present_paths: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let missing = records
+        .iter()
+        .filter(|(id, (path, _))| {
+            !present_ids.contains(id) && !present_paths.contains(path.as_str()) && !unreadable.contains(path)
+        })
+        .count();
+    if missing >= MASS_DELETE_MIN_FILES && missing * 2 > records.len() {
+        anyhow::bail!(
+            "{} of the {} synced files are missing from the vault, so no snippets were trashed. \
+             If you deleted them on purpose, delete the snippets in the app; to start over, stop syncing the vault and choose it again",
+            missing,
+            records.len()
+        );
+    }
+
+    // Directories made in the vault since the last sync become folders
+    for dir in &dirs {
+        if !previous_folders.contains(dir) && folders.find(dir).is_none() {
+            folders.ensure(db, dir)?;
+        }
+    }
+
+    // Secret snippets aren't synced; one that became secret is removed from the vault
+    let snippets: HashMap<i64, Snippet> = db
+        .get_all_snippets()?
+        .into_iter()
+        .filter(|s| !s.secret)
+        .filter_map(|s| Some((s.id?, s)))
+        .collect();
+    let planned = planned_paths(snippets.values(), &folders);
+
+    // Snippets that haven't changed in the app since they were last synced
+    let mut unchanged = HashSet::new();
+    for (id, (path, hash)) in &records {
+        let Some(snippet) = snippets.get(id) else { continue };
+        let text = render(snippet, &folders.path(snippet.folder_id))?;
+        if planned.get(id) == Some(path) && sha256_hex(&text) == *hash {
+            unchanged.insert(*id);
+        }
+    }
+
+    // The database changes are made in one transaction, and the files
+    // written once it's committed. A snippet changed from its file is
+    // recorded as synced with that file right away, so a write that fails
+    // doesn't make the next sync see a conflict or a new file.
+    let tx = db.transaction()?;
+
+    // The file each snippet is currently stored in
+    let mut linked: HashMap<i64, String> = HashMap::new();
+    // Files of snippets deleted in the app
+    let mut removed: Vec<&str> = Vec::new();
+
+    for (file, note) in notes {
+        let note = match note {
+            Ok(note) => note,
+            Err(e) => {
+                report.conflicts.push(format!("{}: {:#}", file.path, e));
+                unreadable.insert(file.path.clone());
+                continue;
+            }
+        };
+        let dir = parent_dir(&file.path);
+        // A new file whose ID wasn't written into it, as the sync that made its snippet failed
+        let id = note
+            .id
+            .or_else(|| records.iter().find(|(_, (path, hash))| *path == file.path && *hash == file.hash).map(|(id, _)| *id))
+            .filter(|id| !linked.contains_key(id));
+
+        match (id, id.and_then(|id| records.get(&id)), id.and_then(|id| snippets.get(&id))) {
+            (Some(id), Some((path, hash)), Some(snippet)) => {
+                linked.insert(id, file.path.clone());
+                if file.hash == *hash && file.path == *path {
+                    continue;
+                }
+
+                let folder_id = folders.find(dir);
+                if folder_id.is_some_and(|folder_id| same_as(snippet, &note, folder_id)) {
+                    continue;
+                }
+                let folder_id = folders.ensure(db, dir)?;
+
+                if !unchanged.contains(&id) {
+                    keep_as_conflict(db, note, folder_id, &file.path, &mut report)?;
+                } else {
+                    update_from_note(db, snippet, note, folder_id, &mut report)?;
+                }
+                db.set_vault_record(id, &file.path, &file.hash)?;
+            }
+            // Never synced, e.g. an existing vault linked again: the same
+            // snippet if it has the same title
+            (Some(id), None, Some(snippet)) if snippet.title == note.title => {
+                linked.insert(id, file.path.clone());
+                let folder_id = folders.find(dir);
+                if !folder_id.is_some_and(|folder_id| same_as(snippet, &note, folder_id)) {
+                    let folder_id = folders.ensure(db, dir)?;
+                    keep_as_conflict(db, note, folder_id, &file.path, &mut report)?;
+                    db.set_vault_record(id, &file.path, &file.hash)?;
+                }
+            }
+            // The snippet was deleted in the app
+            (Some(_), Some((_, hash)), None) if file.hash == *hash => {
+                removed.push(&file.path);
+            }
+            _ => {
+                if id.is_some_and(|id| records.contains_key(&id)) {
+                    report.conflicts.push(format!(
+                        "{}: edited in the vault after its snippet was deleted; restored as a new snippet",
+                        file.path
+                    ));
+                }
+                let folder_id = folders.ensure(db, dir)?;
+                let id = create_from_note(db, note, folder_id, &mut report)?;
+                report.snippets_created += 1;
+                linked.insert(id, file.path.clone());
+                db.set_vault_record(id, &file.path, &file.hash)?;
+            }
+        }
+    }
+
+    // Files deleted in the vault move their snippet to the trash, unless the
+    // snippet changed since; then the file is written again below
+    for &id in records.keys() {
+        if !linked.contains_key(&id) && unchanged.contains(&id) {
+            db.trash_snippet(id)?;
+            report.snippets_trashed += 1;
+        }
+    }
+
+    let writes = plan_writes(db, &unreadable, &linked, &mut report)?;
+    let folders = Folders::load(db)?;
+    tx.commit()?;
+
+    for path in removed {
+        fs::remove_file(root.join(path)).with_context(|| format!("Failed to remove {}", path))?;
+        report.files_removed += 1;
+    }
+    write_snippets(db, root, &files, writes, &mut report)?;
+
+    // Folders: directories for every folder, and directories of folders that
+    // were deleted or renamed in the app removed once empty
+    let folder_paths: Vec<String> = folders.paths();
+    for path in &folder_paths {
+        fs::create_dir_all(root.join(path))?;
+    }
+    let mut stale: Vec<&String> = dirs
+        .iter()
+        .filter(|dir| previous_folders.contains(*dir) && !folder_paths.contains(dir))
+        .collect();
+    stale.sort_by_key(|dir| std::cmp::Reverse(dir.len()));
+    for dir in stale {
+        // Fails, and so keeps the directory, if anything else is in it
+        let _ = fs::remove_dir(root.join(dir));
+    }
+    db.set_vault_folders(&folder_paths)?;
+
+    Ok(report)
+}
+
+/// A snippet's file as the sync leaves it
+struct PlannedWrite {
+    id: i64,
+    path: String,
+    text: String,
+    hash: String,
+    /// The file it was stored in before it was renamed or moved
+    old_path: Option<String>,
+}
+
+/// The file of every snippet in the vault
+fn plan_writes(
+    db: &Database,
+    unreadable: &HashSet<String>,
+    linked: &HashMap<i64, String>,
+    report: &mut VaultSyncReport,
+) -> Result<Vec<PlannedWrite>> {
+    let snippets: Vec<Snippet> = db.get_all_snippets()?.into_iter().filter(|s| !s.secret).collect();
+    let folders = Folders::load(db)?;
+    let planned = planned_paths(snippets.iter(), &folders);
+    let planned_set: HashSet<&String> = planned.values().collect();
+
+    let mut writes = Vec::new();
+    for snippet in &snippets {
+        let Some(id) = snippet.id else { continue };
+        let path = &planned[&id];
+        if unreadable.contains(path) {
+            report.conflicts.push(format!("{}: couldn't be read, so \"{}\" was not written", path, snippet.title));
+            continue;
+        }
+
+        let text = render(snippet, &folders.path(snippet.folder_id))?;
+        writes.push(PlannedWrite {
+            id,
+            hash: sha256_hex(&text),
+            text,
+            path: path.clone(),
+            old_path: linked.get(&id).filter(|old| *old != path && !planned_set.contains(old)).cloned(),
+        });
+    }
+    Ok(writes)
+}
+
+/// Write every snippet whose file is missing or out of date, recording each
+/// as synced once it's written
+fn write_snippets(
+    db: &Database,
+    root: &Path,
+    files: &[VaultFile],
+    writes: Vec<PlannedWrite>,
+    report: &mut VaultSyncReport,
+) -> Result<()> {
+    let current: HashMap<&str, &str> = files.iter().map(|f| (f.path.as_str(), f.hash.as_str())).collect();
+    let live: HashSet<i64> = db.get_all_snippets()?.into_iter().filter(|s| !s.secret).filter_map(|s| s.id).collect();
+
+    for write in &writes {
+        if current.get(write.path.as_str()) != Some(&write.hash.as_str()) {
+            let full_path = root.join(&write.path);
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full_path, &write.text).with_context(|| format!("Failed to write {}", write.path))?;
+            report.files_written += 1;
+        }
+
+        // The snippet was renamed or moved: remove its old file
+        if let Some(old) = &write.old_path {
+            if fs::remove_file(root.join(old)).is_ok() {
+                report.files_removed += 1;
+            }
+        }
+
+        db.set_vault_record(write.id, &write.path, &write.hash)?;
+    }
+
+    // Records of snippets that were deleted, or whose files were removed above
+    for (id, _) in db.get_vault_records()? {
+        if !live.contains(&id) {
+            db.delete_vault_record(id)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn scan(root: &Path, dir: &str, scanned: &mut Scan) -> Result<()> {
+    let entries = fs::read_dir(root.join(dir)).with_context(|| format!("Failed to read {}", root.join(dir).display()))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Hidden entries (.git, .obsidian, .trash) aren't part of the vault
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = if dir.is_empty() { name.clone() } else { format!("{}/{}", dir, name) };
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            scanned.dirs.push(path.clone());
+            scan(root, &path, scanned)?;
+        } else if file_type.is_file() && name.ends_with(".md") {
+            match fs::read_to_string(entry.path()) {
+                Ok(text) => scanned.files.push(VaultFile { hash: sha256_hex(&text), path, text }),
+                Err(e) => {
+                    scanned.problems.push(format!("{}: {}", path, e));
+                    scanned.unreadable.insert(path);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Where each snippet's file belongs: `<folder>/<title>.md`, or
+/// `<title> (<id>).md` when an older snippet in the folder has the same title
+fn planned_paths<'a>(snippets: impl Iterator<Item = &'a Snippet>, folders: &Folders) -> HashMap<i64, String> {
+    let mut snippets: Vec<&Snippet> = snippets.filter(|s| s.id.is_some()).collect();
+    snippets.sort_by_key(|s| s.id);
+
+    let mut taken = HashSet::new();
+    let mut paths = HashMap::new();
+    for snippet in snippets {
+        let Some(id) = snippet.id else { continue };
+        let dir = folders.path(snippet.folder_id);
+        let join = |name: String| if dir.is_empty() { name } else { format!("{}/{}", dir, name) };
+
+        let title = file_name_part(&snippet.title);
+        let mut path = join(format!("{}.md", title));
+        // File systems may be case-insensitive
+        if !taken.insert(path.to_lowercase()) {
+            path = join(format!("{} ({}).md", title, id));
+            taken.insert(path.to_lowercase());
+        }
+        paths.insert(id, path);
+    }
+    paths
+}
+
+fn parse_note(text: &str, file_stem: &str) -> Result<Note> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let (front_matter, body) = split_front_matter(&text);
+    let front_matter: FrontMatter = match front_matter {
+        Some(yaml) if !yaml.trim().is_empty() => {
+            serde_yaml::from_str(yaml).context("Invalid front matter")?
+        }
+        _ => FrontMatter::default(),
+    };
+
+    let lines: Vec<&str> = body.lines().collect();
+    let opening = lines.iter().position(|line| fence(line).is_some());
+
+    let (description, info, content) = match opening {
+        Some(start) => {
+            let (marker, length, info) = fence(lines[start]).unwrap_or_default();
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| {
+                    let line = line.trim();
+                    line.len() >= length && line.chars().all(|c| c == marker)
+                })
+                .map(|i| start + 1 + i)
+                .unwrap_or(lines.len());
+
+            // Text after the code block is kept with the description
+            let before = lines[..start].join("\n");
+            let after = lines.get(end + 1..).map(|rest| rest.join("\n")).unwrap_or_default();
+            let description = [before.trim(), after.trim()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+
+            (description, info, lines[start + 1..end].join("\n"))
+        }
+        None => (String::new(), String::new(), body.trim().to_string()),
+    };
+
+    let language = front_matter
+        .language
+        .filter(|l| !l.trim().is_empty())
+        .or_else(|| info.split_whitespace().next().map(str::to_string))
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| l != "auto")
+        .unwrap_or_else(|| classifier::detect_language(&content));
+
+    Ok(Note {
+        id: front_matter.id,
+        title: front_matter
+            .title
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| file_stem.to_string()),
+        language,
+        description: (!description.is_empty()).then_some(description),
+        content,
+        tags: front_matter.tags,
+        trigger: front_matter.trigger.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+    })
+}
+
+/// `(yaml, body)` for text starting with a `---` front matter block
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return (None, text);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+/// An opening code fence: its character, length and info string
+fn fence(line: &str) -> Option<(char, usize, String)> {
+    let line = line.trim_start();
+    let marker = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let length = line.chars().take_while(|&c| c == marker).count();
+    (length >= 3).then(|| (marker, length, line[length..].trim().to_string()))
+}
+
+fn render(snippet: &Snippet, folder_path: &str) -> Result<String> {
+    let language = snippet.language.trim();
+    let front_matter = FrontMatter {
+        id: snippet.id,
+        title: Some(snippet.title.clone()),
+        language: Some(language.to_string()).filter(|l| !l.is_empty()),
+        tags: tags::parse_tags(snippet.tags.as_deref().unwrap_or_default()),
+        folder: Some(folder_path.to_string()).filter(|p| !p.is_empty()),
+        trigger: snippet.trigger.clone(),
+        created: Some(snippet.created_at.clone()),
+        updated: Some(snippet.updated_at.clone()),
+    };
+
+    let mut text = format!("---\n{}---\n\n", serde_yaml::to_string(&front_matter)?);
+    if let Some(description) = snippet.description.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        text.push_str(description);
+        text.push_str("\n\n");
+    }
+
+    // Longer than any run of backticks in the content
+    let longest_run = snippet
+        .content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat((longest_run + 1).max(3));
+    let info = if language.eq_ignore_ascii_case("auto") { "" } else { language };
+    text.push_str(&format!("{}{}\n{}\n{}\n", fence, info, snippet.content, fence));
+
+    Ok(text)
+}
+
+/// Whether the note says nothing the snippet doesn't already
+fn same_as(snippet: &Snippet, note: &Note, folder_id: Option<i64>) -> bool {
+    let snippet_tags = tags::parse_tags(snippet.tags.as_deref().unwrap_or_default());
+    snippet.title == note.title
+        && snippet.content == note.content
+        && snippet.language.eq_ignore_ascii_case(&note.language)
+        && snippet.description.as_deref().map(str::trim).filter(|d| !d.is_empty()) == note.description.as_deref()
+        && snippet_tags.len() == note.tags.len()
+        && snippet_tags.iter().zip(&note.tags).all(|(a, b)| a.eq_ignore_ascii_case(b))
+        && snippet.trigger == note.trigger
+        && snippet.folder_id == folder_id
+}
+
+/// The note's trigger, unless it's invalid or another snippet has it
+fn usable_trigger(db: &Database, note: &Note, id: Option<i64>, report: &mut VaultSyncReport) -> Result<Option<String>> {
+    let Some(trigger) = note.trigger.clone() else {
+        return Ok(None);
+    };
+
+    let length = trigger.chars().count();
+    let problem = if trigger.chars().any(char::is_whitespace) || !(2..=expansion::MAX_TRIGGER_LENGTH).contains(&length) {
+        Some("is not a valid trigger")
+    } else if db.find_snippet_by_trigger(&trigger)?.is_some_and(|other| Some(other) != id) {
+        Some("is already used by another snippet")
+    } else {
+        None
+    };
+
+    match problem {
+        Some(problem) => {
+            report.conflicts.push(format!("{}: '{}' {}; left out", note.title, trigger, problem));
+            Ok(None)
+        }
+        None => Ok(Some(trigger)),
+    }
+}
+
+fn create_from_note(db: &Database, note: Note, folder_id: Option<i64>, report: &mut VaultSyncReport) -> Result<i64> {
+    let trigger = usable_trigger(db, &note, None, report)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = db.create_snippet(&Snippet {
+        id: None,
+        title: note.title,
+        content: note.content,
+        language: note.language,
+        description: note.description,
+        tags: tags::format_tags(&note.tags),
+        folder_id,
+        created_at: now.clone(),
+        updated_at: now,
+        trigger,
+        deleted_at: None,
+        secret: false,
+    })?;
+    report.snippet_ids.push(id);
+    Ok(id)
+}
+
+fn update_from_note(
+    db: &Database,
+    snippet: &Snippet,
+    note: Note,
+    folder_id: Option<i64>,
+    report: &mut VaultSyncReport,
+) -> Result<()> {
+    let id = snippet.id.context("Snippet without an ID")?;
+    let trigger = usable_trigger(db, &note, Some(id), report)?;
+    db.update_snippet(
+        id,
+        &Snippet {
+            title: note.title,
+            content: note.content,
+            language: note.language,
+            description: note.description,
+            tags: tags::format_tags(&note.tags),
+            folder_id,
+            trigger,
+            ..snippet.clone()
+        },
+    )?;
+    report.snippets_updated += 1;
+    report.snippet_ids.push(id);
+    Ok(())
+}
+
+/// Both sides changed: the snippet keeps its version, and the file's becomes
+/// a new snippet next to it
+fn keep_as_conflict(
+    db: &Database,
+    mut note: Note,
+    folder_id: Option<i64>,
+    path: &str,
+    report: &mut VaultSyncReport,
+) -> Result<()> {
+    report.conflicts.push(format!(
+        "{}: changed in both the app and the vault; the vault's version was saved as \"{}{}\"",
+        path, note.title, CONFLICT_SUFFIX
+    ));
+    note.title.push_str(CONFLICT_SUFFIX);
+    note.trigger = None;
+    create_from_note(db, note, folder_id, report)?;
+    report.snippets_created += 1;
+    Ok(())
+}
+
+/// A title or folder name made safe to use as a file name
+fn file_name_part(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').trim_start_matches('.');
+    if cleaned.is_empty() {
+        "_".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default()
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Sync with the configured vault, if there is one. The vault is read
+/// without holding the database, which is only locked to apply the result.
+pub fn sync_now(state: &AppState) -> Result<Option<VaultSyncReport>, String> {
+    let _syncing = SYNC_LOCK.lock().map_err(|e| e.to_string())?;
+    let directory = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_vault_settings().map_err(|e| e.to_string())?.directory
+    };
+    let Some(directory) = directory else {
+        return Ok(None);
+    };
+
+    let failed = |e: anyhow::Error| format!("Failed to sync the Markdown vault: {:#}", e);
+    let scanned = scan_vault(Path::new(&directory)).map_err(failed)?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    // The vault may have been moved or turned off meanwhile
+    if db.get_vault_settings().map_err(|e| e.to_string())?.directory.as_ref() != Some(&directory) {
+        return Ok(None);
+    }
+    let report = sync(&db, Path::new(&directory), scanned).map_err(failed)?;
+    if report.snippets_created + report.snippets_updated + report.snippets_trashed > 0 {
+        crate::commands::invalidate_triggers(state);
+    }
+    crate::commands::embed_snippets(state, &db, &report.snippet_ids)?;
+
+    Ok(Some(report))
+}
+
+/// Watch `directory` for changes, sending them to `sender`
+fn watch(directory: &str, sender: mpsc::Sender<DebounceEventResult>) -> Option<Debouncer<RecommendedWatcher>> {
+    let result = new_debouncer(DEBOUNCE, sender).and_then(|mut debouncer| {
+        debouncer.watcher().watch(Path::new(directory), RecursiveMode::Recursive)?;
+        Ok(debouncer)
+    });
+
+    match result {
+        Ok(debouncer) => {
+            println!("📝 Watching Markdown vault {}", directory);
+            Some(debouncer)
+        }
+        Err(e) => {
+            eprintln!("Failed to watch Markdown vault {}: {}", directory, e);
+            None
+        }
+    }
+}
+
+/// Spawn the background thread that keeps the vault in sync: right after
+/// files change, and every few seconds for changes made in the app
+pub fn spawn_vault_sync(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let (sender, receiver) = mpsc::channel();
+        let mut watching: Option<(String, Option<Debouncer<RecommendedWatcher>>)> = None;
+
+        loop {
+            let state = app_handle.state::<AppState>();
+            let directory = state
+                .db
+                .lock()
+                .ok()
+                .and_then(|db| db.get_vault_settings().ok())
+                .and_then(|settings| settings.directory);
+
+            if watching.as_ref().map(|(watched, _)| watched) != directory.as_ref() {
+                watching = directory.clone().map(|d| {
+                    let debouncer = watch(&d, sender.clone());
+                    (d, debouncer)
+                });
+            }
+
+            match sync_now(&state) {
+                Ok(Some(report)) if report.has_changes() => {
+                    println!(
+                        "📝 Vault sync: {} created, {} updated, {} trashed, {} files written, {} removed, {} conflicts",
+                        report.snippets_created, report.snippets_updated, report.snippets_trashed,
+                        report.files_written, report.files_removed, report.conflicts.len()
+                    );
+                    let _ = app_handle.emit("vault-synced", &report);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("{}", e),
+            }
+
+            // Our own writes come back as events too; syncing again is a no-op
+            let _ = receiver.recv_timeout(POLL_INTERVAL);
+            while receiver.try_recv().is_ok() {}
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(title: &str) -> Snippet {
+        Snippet {
+            id: None,
+            title: title.to_string(),
+            description: None,
+            content: format!("echo {}", title),
+            language: "bash".to_string(),
+            tags: None,
+            folder_id: None,
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+            updated_at: "2026-01-01T00:00:00+00:00".to_string(),
+            trigger: None,
+            deleted_at: None,
+            secret: false,
+        }
+    }
+
+    fn temp_vault() -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("nobraindev-vault-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn split_front_matter_needs_a_closing_line() {
+        assert_eq!(split_front_matter("---\ntitle: A\n---\nbody"), (Some("title: A\n"), "body"));
+        assert_eq!(split_front_matter("---\ntitle: A\n...\n\nbody"), (Some("title: A\n"), "\nbody"));
+        assert_eq!(split_front_matter("---\n---\nbody"), (Some(""), "body"));
+        assert_eq!(split_front_matter("---\ntitle: A\nbody"), (None, "---\ntitle: A\nbody"));
+        assert_eq!(split_front_matter("body\n---\n"), (None, "body\n---\n"));
+    }
+
+    #[test]
+    fn fence_reads_marker_length_and_info() {
+        assert_eq!(fence("```go"), Some(('`', 3, "go".to_string())));
+        assert_eq!(fence("  ~~~~ rust title=x "), Some(('~', 4, "rust title=x".to_string())));
+        assert_eq!(fence("``not a fence"), None);
+        assert_eq!(fence("text ```"), None);
+    }
+
+    #[test]
+    fn parse_note_reads_front_matter_description_and_code() {
+        let text = "---\r\nid: 7\r\ntitle: Range loop\r\ntags: Loops, go\r\ntrigger: ' ;rl '\r\n---\r\n\r\n\
+                    Iterate over a slice.\r\n\r\n```go\r\nfor i := range items {}\r\n```\r\n\r\nSee also maps.\r\n";
+        let note = parse_note(text, "ignored").unwrap();
+        assert_eq!(note.id, Some(7));
+        assert_eq!(note.title, "Range loop");
+        assert_eq!(note.language, "go");
+        assert_eq!(note.description.as_deref(), Some("Iterate over a slice.\n\nSee also maps."));
+        assert_eq!(note.content, "for i := range items {}");
+        assert_eq!(note.tags, vec!["Loops", "go"]);
+        assert_eq!(note.trigger.as_deref(), Some(";rl"));
+    }
+
+    #[test]
+    fn parse_note_without_front_matter_uses_the_file_name() {
+        let note = parse_note("\u{feff}just some text\n", "My note").unwrap();
+        assert_eq!(note.id, None);
+        assert_eq!(note.title, "My note");
+        assert_eq!(note.content, "just some text");
+        assert_eq!(note.description, None);
+
+        // An unclosed block runs to the end of the file
+        let note = parse_note("~~~~python\nprint(1)\n```\n", "x").unwrap();
+        assert_eq!(note.language, "python");
+        assert_eq!(note.content, "print(1)\n```");
+
+        assert!(parse_note("---\ntitle: [unclosed\n---\n", "x").is_err());
+    }
+
+    #[test]
+    fn rendered_notes_parse_back_to_the_snippet() {
+        let mut snippet = snippet("Fences");
+        snippet.id = Some(3);
+        snippet.description = Some("Shows ``` in markdown".to_string());
+        snippet.content = "```md\n# heading\n```".to_string();
+        snippet.language = "markdown".to_string();
+        snippet.tags = Some("docs, md".to_string());
+        snippet.trigger = Some(";fence".to_string());
+
+        let text = render(&snippet, "Work/Docs").unwrap();
+        assert!(text.contains("folder: Work/Docs"));
+        let note = parse_note(&text, "other name").unwrap();
+        assert_eq!(note.id, Some(3));
+        assert_eq!(note.title, snippet.title);
+        assert_eq!(note.language, snippet.language);
+        assert_eq!(note.description, snippet.description);
+        assert_eq!(note.content, snippet.content);
+        assert_eq!(note.tags, vec!["docs", "md"]);
+        assert_eq!(note.trigger, snippet.trigger);
+        assert!(same_as(&snippet, &note, None));
+    }
+
+    #[test]
+    fn planned_paths_number_clashing_titles() {
+        let db = Database::open_in_memory().unwrap();
+        let work = db.create_folder("Work", None, None).unwrap();
+        let folders = Folders::load(&db).unwrap();
+
+        let snippets: Vec<Snippet> = [(2, "Loop", None), (1, "loop", None), (3, "Loop", Some(work)), (4, "a/b", None)]
+            .into_iter()
+            .map(|(id, title, folder_id)| Snippet { id: Some(id), folder_id, ..snippet(title) })
+            .collect();
+        let paths = planned_paths(snippets.iter(), &folders);
+        // The older snippet keeps the plain name, whatever the order given
+        assert_eq!(paths[&1], "loop.md");
+        assert_eq!(paths[&2], "Loop (2).md");
+        assert_eq!(paths[&3], "Work/Loop.md");
+        assert_eq!(paths[&4], "a_b.md");
+    }
+
+    #[test]
+    fn sync_refuses_to_trash_most_of_the_vault() {
+        let db = Database::open_in_memory().unwrap();
+        let root = temp_vault();
+        for title in ["One", "Two", "Three", "Four"] {
+            db.create_snippet(&snippet(title)).unwrap();
+        }
+        let report = sync(&db, &root, scan_vault(&root).unwrap()).unwrap();
+        assert_eq!(report.files_written, 4);
+
+        // Most files gone at once: nothing is trashed
+        for title in ["One", "Two", "Three"] {
+            fs::remove_file(root.join(format!("{}.md", title))).unwrap();
+        }
+        let error = sync(&db, &root, scan_vault(&root).unwrap()).unwrap_err();
+        assert!(error.to_string().contains("3 of the 4 synced files are missing"));
+        assert_eq!(db.get_all_snippets().unwrap().len(), 4);
+
+        // Put back as they were, then delete one on purpose: its snippet is trashed
+        for snippet in db.get_all_snippets().unwrap() {
+            fs::write(root.join(format!("{}.md", snippet.title)), render(&snippet, "").unwrap()).unwrap();
+        }
+        fs::remove_file(root.join("One.md")).unwrap();
+        let report = sync(&db, &root, scan_vault(&root).unwrap()).unwrap();
+        assert_eq!(report.snippets_trashed, 1);
+        let titles: Vec<String> = db.get_all_snippets().unwrap().into_iter().map(|s| s.title).collect();
+        assert!(!titles.contains(&"One".to_string()));
+        assert_eq!(titles.len(), 3);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
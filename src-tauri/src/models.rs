@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snippet {
@@ -7,10 +8,20 @@ pub struct Snippet {
     pub content: String,
     pub language: String,
     pub description: Option<String>,
+    /// Comma-separated tag names (kept in sync with the `tags` table)
     pub tags: Option<String>,
     pub folder_id: Option<i64>,
     pub created_at: String,
     pub updated_at: String,
+    /// Abbreviation that expands to this snippet when typed, e.g. `;k8slogs`
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// Set while the snippet is in the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    /// Content encrypted with the secret vault's password; see `secrets.rs`
+    #[serde(default)]
+    pub secret: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,10 +29,61 @@ pub struct Folder {
     pub id: Option<i64>,
     pub name: String,
     pub icon: Option<String>,
+    /// Containing folder, `None` for top-level folders
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    /// Position among sibling folders
+    #[serde(default)]
+    pub sort_order: i64,
+    #[serde(default)]
+    pub kind: FolderKind,
+    /// Saved search of a smart folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<SmartFolderQuery>,
+    /// Set when a smart folder's saved search can't be read; the folder shows
+    /// nothing until its search is replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_error: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Snippets in the folder, or in the folder and its subfolders when requested
     #[serde(skip_serializing_if = "Option::is_none")]
     pub snippet_count: Option<i64>,
+    /// Set while the folder is in the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+}
+
+/// Manual folders hold the snippets assigned to them; smart folders show
+/// whatever matches their saved search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderKind {
+    #[default]
+    Manual,
+    Smart,
+}
+
+/// Saved search behind a smart folder. Every condition that is set must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SmartFolderQuery {
+    /// Keyword search; `tag:name` words match tags exactly
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Exact tags, all required
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Any of these languages (case-insensitive)
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub updated_within_days: Option<i64>,
+    /// Search by meaning (needs the AI model)
+    #[serde(default)]
+    pub semantic_query: Option<String>,
+    /// Minimum similarity (0-1) for `semantic_query` matches
+    #[serde(default)]
+    pub min_score: Option<f32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +109,289 @@ pub struct ClipboardEntry {
     pub source: String,
     pub category: String,
     pub created_at: String,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Position among pinned entries (1-based), used for quick-paste slots
+    #[serde(default)]
+    pub pin_order: Option<i64>,
+    /// Kept by the user, independent of pinning
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Limits applied when pruning clipboard history. A `None` limit is not enforced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub max_entries: Option<i64>,
+    pub max_age_days: Option<i64>,
+    pub max_total_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardRetentionSettings {
+    pub enabled: bool,
+    pub prune_interval_minutes: u64,
+    /// Policy for every category without its own entry in `category_policies`
+    pub default_policy: RetentionPolicy,
+    #[serde(default)]
+    pub category_policies: HashMap<String, RetentionPolicy>,
+}
+
+impl Default for ClipboardRetentionSettings {
+    fn default() -> Self {
+        ClipboardRetentionSettings {
+            // Off until the user opts in, so upgrading never removes history
+            enabled: false,
+            prune_interval_minutes: 30,
+            default_policy: RetentionPolicy {
+                max_entries: Some(1000),
+                max_age_days: Some(90),
+                max_total_bytes: Some(50 * 1024 * 1024),
+            },
+            category_policies: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub removed: i64,
+    pub removed_bytes: i64,
+    pub removed_by_category: HashMap<String, i64>,
+    pub pruned_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasteQueueStatus {
+    pub active: bool,
+    /// Number of entries already pasted
+    pub position: usize,
+    pub total: usize,
+    pub entry_ids: Vec<i64>,
+    pub next_preview: Option<String>,
+    pub timeout_secs: u64,
+}
+
+/// How the UI should ask for a template variable's value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateVariableKind {
+    #[default]
+    Text,
+    /// Pick one of `choices`
+    Choice,
+    /// A directory path (e.g. the repository for `${GIT_BRANCH}`)
+    Directory,
+}
+
+/// A placeholder found in a snippet template: a named `${name}` variable or a
+/// VS Code style `$1` / `${1:default}` tab stop (named by its number)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub default: Option<String>,
+    #[serde(default)]
+    pub kind: TemplateVariableKind,
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+/// Result of expanding a snippet template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SnippetRender {
+    Rendered { content: String },
+    /// The caller should prompt for every listed variable and render again
+    /// with the collected values
+    NeedsInput { variables: Vec<TemplateVariable> },
+}
+
+/// A previous version of a snippet, saved whenever the snippet is updated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetRevision {
+    pub id: i64,
+    pub snippet_id: i64,
+    pub title: String,
+    pub content: String,
+    pub language: String,
+    pub description: Option<String>,
+    pub tags: Option<String>,
+    /// When this version was originally saved (the snippet's `updated_at` at the time)
+    pub saved_at: String,
+    /// When this version was replaced and moved into history
+    pub created_at: String,
+}
+
+/// How much snippet history to keep. A `None` limit is not enforced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionRetention {
+    /// Revisions kept per snippet
+    pub max_revisions: Option<i64>,
+    pub max_age_days: Option<i64>,
+}
+
+impl Default for RevisionRetention {
+    fn default() -> Self {
+        RevisionRetention {
+            max_revisions: Some(50),
+            max_age_days: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// One line of a line-level diff. Line numbers are 1-based.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+    /// Line number in the old version (absent for added lines)
+    pub old_line: Option<usize>,
+    /// Line number in the new version (absent for removed lines)
+    pub new_line: Option<usize>,
+}
+
+/// Difference between two versions of a snippet. A `None` revision ID means
+/// the current version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetDiff {
+    pub snippet_id: i64,
+    pub from_revision: Option<i64>,
+    pub to_revision: Option<i64>,
+    pub title_changed: bool,
+    pub lines: Vec<DiffLine>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Global actions that can be bound to a system-wide shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ClipboardPopup,
+    SnippetSearch,
+    PasteLastClip,
+    CaptureSelection,
+    ToggleExpansion,
+    /// Only registered while a paste queue is running
+    PasteQueueNext,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 6] = [
+        HotkeyAction::ClipboardPopup,
+        HotkeyAction::SnippetSearch,
+        HotkeyAction::PasteLastClip,
+        HotkeyAction::CaptureSelection,
+        HotkeyAction::ToggleExpansion,
+        HotkeyAction::PasteQueueNext,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyAction::ClipboardPopup => "Open clipboard popup",
+            HotkeyAction::SnippetSearch => "Quick snippet search",
+            HotkeyAction::PasteLastClip => "Paste last clip",
+            HotkeyAction::CaptureSelection => "Capture selection as snippet",
+            HotkeyAction::ToggleExpansion => "Pause/resume text expansion",
+            HotkeyAction::PasteQueueNext => "Paste next entry in the paste queue",
+        }
+    }
+
+    /// Out-of-the-box shortcut. macOS uses Cmd chords, everything else Ctrl+Alt
+    pub fn default_shortcut(&self) -> &'static str {
+        #[cfg(target_os = "macos")]
+        {
+            match self {
+                HotkeyAction::ClipboardPopup => "Cmd+Shift+C",
+                HotkeyAction::SnippetSearch => "Cmd+Shift+Space",
+                HotkeyAction::PasteLastClip => "Cmd+Option+Shift+V",
+                HotkeyAction::CaptureSelection => "Cmd+Option+S",
+                HotkeyAction::ToggleExpansion => "Cmd+Option+E",
+                HotkeyAction::PasteQueueNext => "Cmd+Option+V",
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            match self {
+                HotkeyAction::ClipboardPopup => "Ctrl+Alt+C",
+                HotkeyAction::SnippetSearch => "Ctrl+Alt+Space",
+                HotkeyAction::PasteLastClip => "Ctrl+Alt+Shift+V",
+                HotkeyAction::CaptureSelection => "Ctrl+Alt+S",
+                HotkeyAction::ToggleExpansion => "Ctrl+Alt+E",
+                HotkeyAction::PasteQueueNext => "Ctrl+Alt+V",
+            }
+        }
+    }
+}
+
+/// Persisted shortcut per action. `None` leaves the action unbound.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeySettings {
+    pub bindings: HashMap<HotkeyAction, Option<String>>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        HotkeySettings {
+            bindings: HotkeyAction::ALL
+                .iter()
+                .map(|action| (*action, Some(action.default_shortcut().to_string())))
+                .collect(),
+        }
+    }
+}
+
+/// A configured binding together with its registration state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    pub label: String,
+    pub shortcut: Option<String>,
+    /// Whether the shortcut is currently registered with the OS
+    pub active: bool,
+    /// Why registration failed, if it did
+    pub error: Option<String>,
+}
+
+/// Text expansion (typed snippet triggers) configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpansionSettings {
+    /// Global pause: triggers are ignored everywhere while set
+    pub paused: bool,
+    /// When non-empty, expand only in these apps (bundle IDs on macOS,
+    /// window classes on Linux, process names on Windows)
+    #[serde(default)]
+    pub allowed_apps: Vec<String>,
+    /// Never expand in these apps. Takes precedence over `allowed_apps`
+    #[serde(default)]
+    pub denied_apps: Vec<String>,
+}
+
+impl ExpansionSettings {
+    /// Whether triggers typed in `app` should expand. An unknown app is only
+    /// allowed when there is no allow list.
+    pub fn allows_app(&self, app: Option<&str>) -> bool {
+        let matches = |list: &[String], app: &str| list.iter().any(|a| a.trim().eq_ignore_ascii_case(app));
+
+        match app {
+            Some(app) => {
+                !matches(&self.denied_apps, app)
+                    && (self.allowed_apps.is_empty() || matches(&self.allowed_apps, app))
+            }
+            None => self.allowed_apps.is_empty(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,5 +408,370 @@ pub struct File {
     pub tags: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Set while the file is in the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemKind {
+    Snippet,
+    Folder,
+    File,
+}
+
+/// Everything currently in the trash, most recently deleted first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrashContents {
+    pub snippets: Vec<Snippet>,
+    /// `snippet_count` is the number of snippets that return with the folder
+    pub folders: Vec<Folder>,
+    pub files: Vec<File>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashSettings {
+    /// Items are permanently deleted this many days after being trashed.
+    /// `None` keeps them until the trash is emptied.
+    pub retention_days: Option<i64>,
 }
 
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings { retention_days: Some(30) }
+    }
+}
+
+/// A tag shared by snippets, files and clipboard entries. Counts leave out
+/// items in the trash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// `#rrggbb`
+    pub color: Option<String>,
+    pub snippet_count: i64,
+    pub file_count: i64,
+    pub clipboard_count: i64,
+    pub created_at: String,
+}
+
+/// Everything in a library export: live folders, snippets and files (metadata
+/// only; blobs travel next to it), clipboard history and tags
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryData {
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+    #[serde(default)]
+    pub files: Vec<File>,
+    #[serde(default)]
+    pub clipboard: Vec<ClipboardEntry>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+/// How an imported library is combined with the current one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Add to the current library, skipping items it already has
+    #[default]
+    Merge,
+    /// Delete the current library first, except secret snippets
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryItemKind {
+    Folder,
+    Snippet,
+    File,
+    ClipboardEntry,
+}
+
+/// An imported item that clashed with the current library, and what was done about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConflict {
+    pub kind: LibraryItemKind,
+    pub name: String,
+    pub resolution: String,
+}
+
+/// What an import added. Duplicates are items the library already had.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub folders: usize,
+    pub snippets: usize,
+    pub files: usize,
+    pub clipboard_entries: usize,
+    pub duplicates_skipped: usize,
+    pub conflicts: Vec<ImportConflict>,
+    /// Set on a preview: nothing was saved, the counts are what an import would add
+    #[serde(default)]
+    pub dry_run: bool,
+    /// New IDs of the imported snippets, for embedding them
+    #[serde(skip)]
+    pub snippet_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportReport {
+    pub folders: usize,
+    pub snippets: usize,
+    pub files: usize,
+    pub clipboard_entries: usize,
+    /// Files whose data was missing from storage and were left out
+    pub missing_files: Vec<String>,
+}
+
+/// Where snippets are mirrored as Markdown files. `None` turns the vault off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultSettings {
+    pub directory: Option<String>,
+}
+
+/// What one vault sync changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VaultSyncReport {
+    /// Snippets created from new Markdown files
+    pub snippets_created: usize,
+    /// Snippets updated from edited Markdown files
+    pub snippets_updated: usize,
+    /// Snippets moved to the trash because their file was deleted
+    pub snippets_trashed: usize,
+    pub files_written: usize,
+    pub files_removed: usize,
+    /// Edits made on both sides, files that couldn't be read, and the like
+    pub conflicts: Vec<String>,
+    /// Snippets created or updated, to be embedded
+    #[serde(skip)]
+    pub snippet_ids: Vec<i64>,
+}
+
+impl VaultSyncReport {
+    pub fn has_changes(&self) -> bool {
+        self.snippets_created + self.snippets_updated + self.snippets_trashed + self.files_written + self.files_removed > 0
+    }
+}
+
+/// Automatic local backups of the database and stored files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// How many scheduled backups to keep; older ones are deleted
+    pub keep: u32,
+    /// Where backups go, if not the app's data directory
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings { enabled: true, interval_hours: 24, keep: 7, directory: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    Scheduled,
+    Manual,
+    /// Taken automatically before a restore replaced the library
+    PreRestore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// The backup's directory name
+    pub id: String,
+    pub kind: BackupKind,
+    pub created_at: String,
+    pub app_version: String,
+    pub size_bytes: u64,
+    pub files: usize,
+    /// Stored files the database refers to that weren't there to back up
+    pub missing_files: Vec<String>,
+    /// Taken of an encrypted library
+    pub encrypted: bool,
+}
+
+/// Whether the library is encrypted at rest, and how its key is kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    /// The key is in the OS keyring
+    pub keyring: bool,
+    /// A passphrase can unlock the library, e.g. when the keyring is unavailable
+    pub passphrase: bool,
+    /// Encryption is enabled but the key hasn't been loaded yet
+    pub locked: bool,
+    /// False while a plaintext library is still being migrated
+    pub database_encrypted: bool,
+    /// Stored files that are still plaintext
+    pub plaintext_files: usize,
+    /// Backups taken before encryption that are still plaintext
+    pub plaintext_backups: usize,
+}
+
+/// The secret vault's key, wrapped with its master password. Stored in the
+/// settings table, never sent to the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretVaultConfig {
+    /// Argon2id salt for the password (base64)
+    pub salt: String,
+    /// The vault key encrypted with the password's key (base64)
+    pub wrapped_key: String,
+    /// Lock again after this many minutes without revealing or pasting a secret
+    pub lock_after_minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretVaultStatus {
+    /// A master password has been set
+    pub configured: bool,
+    pub unlocked: bool,
+    pub lock_after_minutes: u32,
+}
+
+/// End-to-end encrypted sync on this device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEncryptionStatus {
+    /// This device has the key bundle
+    pub configured: bool,
+    pub unlocked: bool,
+    /// Version of the sync key new data is encrypted with
+    pub key_version: Option<u32>,
+    /// Older sync keys still kept to read data encrypted before a rotation
+    pub retired_keys: usize,
+}
+
+/// A key bundle to upload for other devices. It's only ever ciphertext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncKeySetup {
+    pub bundle: String,
+    /// A new recovery key, shown to the user once
+    pub recovery_key: Option<String>,
+}
+
+/// Kinds of records that sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncRecordKind {
+    Snippet,
+    Folder,
+    ClipboardEntry,
+    File,
+}
+
+impl SyncRecordKind {
+    pub const ALL: [SyncRecordKind; 4] = [
+        SyncRecordKind::Folder,
+        SyncRecordKind::Snippet,
+        SyncRecordKind::ClipboardEntry,
+        SyncRecordKind::File,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncRecordKind::Snippet => "snippet",
+            SyncRecordKind::Folder => "folder",
+            SyncRecordKind::ClipboardEntry => "clipboard_entry",
+            SyncRecordKind::File => "file",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+}
+
+/// Where a file's contents were last synced to, and their hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncBlob {
+    pub key: String,
+    pub hash: String,
+}
+
+/// What this device knows about one synced record
+#[derive(Debug, Clone)]
+pub struct SyncRecord {
+    /// Stable ID shared by every device
+    pub uuid: String,
+    pub kind: SyncRecordKind,
+    /// Row ID in this device's table for `kind`
+    pub local_id: i64,
+    /// Hybrid logical clock of the current version, see `sync::clock`
+    pub hlc: String,
+    /// Number of edits made on each device that this version includes
+    pub versions: BTreeMap<String, u64>,
+    /// A tombstone: the record was deleted
+    pub deleted: bool,
+    /// This version hasn't been pushed yet
+    pub dirty: bool,
+    pub blob: Option<SyncBlob>,
+}
+
+/// Sync bookkeeping of this device, kept in the settings table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    pub device_id: String,
+    /// Latest hybrid logical clock value handed out or seen
+    pub clock: Option<String>,
+    /// Where the last pull stopped in the server's change log
+    pub cursor: Option<String>,
+    pub last_synced_at: Option<String>,
+    /// Sync key version of a full re-push (see `sync::resync`) that hasn't
+    /// finished yet
+    #[serde(default)]
+    pub resync_key_version: Option<u32>,
+    /// Sync key version of the last full re-push that finished without
+    /// pulling anything encrypted with an older key. Older keys can be retired
+    /// once it's the current version.
+    #[serde(default)]
+    pub resynced_key_version: Option<u32>,
+    /// The tables of the sync before the change log were imported (see
+    /// `sync::legacy`)
+    #[serde(default)]
+    pub legacy_imported: bool,
+}
+
+/// Where to sync to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncServer {
+    /// The `sync_changes` table and a storage bucket of a Supabase project
+    Supabase {
+        url: String,
+        anon_key: String,
+        access_token: String,
+        user_id: String,
+        user_email: String,
+    },
+}
+
+/// What one sync did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    /// Local versions that lost to a concurrent edit and were kept as copies
+    pub conflicts: usize,
+    /// Items copied over from the tables of the sync before the change log,
+    /// on the first sync with this version
+    pub imported: usize,
+    pub synced_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub device_id: String,
+    /// Local changes that haven't been pushed yet
+    pub pending: usize,
+    pub last_synced_at: Option<String>,
+}
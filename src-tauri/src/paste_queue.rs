@@ -0,0 +1,223 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::{ClipboardEntry, HotkeyAction, PasteQueueStatus};
+use crate::AppState;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const PREVIEW_LENGTH: usize = 80;
+
+/// Entries selected for sequential pasting, consumed front to back
+pub struct PasteQueue {
+    items: Vec<ClipboardEntry>,
+    position: usize,
+    timeout: Duration,
+    /// Bumped on every change so stale timeout tasks can tell they are outdated
+    generation: u64,
+    /// System clipboard text from before the queue started, restored when it ends
+    original_clipboard: Option<String>,
+}
+
+impl PasteQueue {
+    pub fn new() -> Self {
+        PasteQueue {
+            items: Vec::new(),
+            position: 0,
+            timeout: DEFAULT_TIMEOUT,
+            generation: 0,
+            original_clipboard: None,
+        }
+    }
+
+    pub fn start(&mut self, items: Vec<ClipboardEntry>, timeout: Option<Duration>, original_clipboard: Option<String>) -> u64 {
+        self.items = items;
+        self.position = 0;
+        self.timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
+        self.original_clipboard = original_clipboard;
+        self.generation += 1;
+        self.generation
+    }
+
+    /// The entry the next paste will use
+    pub fn next(&self) -> Option<&ClipboardEntry> {
+        self.items.get(self.position)
+    }
+
+    /// Move past the next entry once it was pasted
+    pub fn advance(&mut self) {
+        if self.is_active() {
+            self.position += 1;
+            self.generation += 1;
+        }
+    }
+
+    /// Empty the queue, returning the clipboard text to restore (if any)
+    pub fn reset(&mut self) -> Option<String> {
+        self.items.clear();
+        self.position = 0;
+        self.generation += 1;
+        self.original_clipboard.take()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.position < self.items.len()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn status(&self) -> PasteQueueStatus {
+        PasteQueueStatus {
+            active: self.is_active(),
+            position: self.position,
+            total: self.items.len(),
+            entry_ids: self.items.iter().filter_map(|e| e.id).collect(),
+            next_preview: self
+                .items
+                .get(self.position)
+                .map(|e| e.content.chars().take(PREVIEW_LENGTH).collect()),
+            timeout_secs: self.timeout.as_secs(),
+        }
+    }
+}
+
+impl Default for PasteQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn emit_status(app_handle: &AppHandle, status: &PasteQueueStatus) {
+    let _ = app_handle.emit("paste-queue-changed", status);
+}
+
+/// Load the selected entries (in the given order) and arm the queue shortcut
+pub fn start(app_handle: &AppHandle, entry_ids: &[i64], timeout: Option<Duration>) -> Result<PasteQueueStatus, String> {
+    let state = app_handle.state::<AppState>();
+
+    let items = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut items = Vec::with_capacity(entry_ids.len());
+        for id in entry_ids {
+            let entry = db.get_clipboard_entry(*id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Clipboard entry {} not found", id))?;
+            items.push(entry);
+        }
+        items
+    };
+
+    if items.is_empty() {
+        return Err("Select at least one clipboard entry to queue".to_string());
+    }
+
+    let original_clipboard = arboard::Clipboard::new()
+        .ok()
+        .and_then(|mut clipboard| clipboard.get_text().ok());
+
+    let (status, generation, timeout) = {
+        let mut queue = state.paste_queue.lock().map_err(|e| e.to_string())?;
+        // Keep the clipboard from before an earlier queue that was replaced mid-way
+        let original_clipboard = queue.reset().or(original_clipboard);
+        let generation = queue.start(items, timeout, original_clipboard);
+        (queue.status(), generation, queue.timeout())
+    };
+
+    match crate::hotkeys::arm(app_handle, HotkeyAction::PasteQueueNext) {
+        Ok(Some(shortcut)) => println!("📚 Paste queue started with {} entries ({})", status.total, shortcut),
+        Ok(None) => println!("📚 Paste queue started with {} entries (no shortcut set)", status.total),
+        Err(e) => {
+            let _ = reset(app_handle);
+            return Err(format!("Failed to register the paste queue shortcut: {}", e));
+        }
+    }
+    schedule_timeout(app_handle.clone(), generation, timeout);
+    emit_status(app_handle, &status);
+
+    Ok(status)
+}
+
+/// Paste the next queued entry into the focused app. The entry stays next in
+/// line if the paste fails.
+pub fn paste_next(app_handle: &AppHandle) -> Result<PasteQueueStatus, String> {
+    let state = app_handle.state::<AppState>();
+
+    let (entry, generation) = {
+        let queue = state.paste_queue.lock().map_err(|e| e.to_string())?;
+        let entry = queue.next().cloned().ok_or_else(|| "Paste queue is empty".to_string())?;
+        (entry, queue.generation())
+    };
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Failed to access clipboard: {}", e))?;
+    clipboard.set_text(&entry.content)
+        .map_err(|e| format!("Failed to set clipboard: {}", e))?;
+
+    crate::commands::paste_internal(false)?;
+
+    let (status, generation, timeout) = {
+        let mut queue = state.paste_queue.lock().map_err(|e| e.to_string())?;
+        // The queue may have been replaced or reset during the paste
+        if queue.generation() == generation {
+            queue.advance();
+        }
+        (queue.status(), queue.generation(), queue.timeout())
+    };
+
+    if status.active {
+        schedule_timeout(app_handle.clone(), generation, timeout);
+        emit_status(app_handle, &status);
+        Ok(status)
+    } else {
+        println!("✅ Paste queue finished");
+        reset(app_handle)
+    }
+}
+
+/// Cancel the queue, disarm its shortcut and restore the original clipboard
+pub fn reset(app_handle: &AppHandle) -> Result<PasteQueueStatus, String> {
+    let state = app_handle.state::<AppState>();
+
+    let (original_clipboard, status) = {
+        let mut queue = state.paste_queue.lock().map_err(|e| e.to_string())?;
+        (queue.reset(), queue.status())
+    };
+
+    crate::hotkeys::disarm(app_handle, HotkeyAction::PasteQueueNext)?;
+
+    if let Some(text) = original_clipboard {
+        // Let the last paste land before swapping the clipboard back
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(500));
+            if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                let _ = clipboard.set_text(text);
+            }
+        });
+    }
+
+    emit_status(app_handle, &status);
+    Ok(status)
+}
+
+/// Reset the queue if nothing happens to it within `timeout`
+fn schedule_timeout(app_handle: AppHandle, generation: u64, timeout: Duration) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(timeout).await;
+
+        let expired = {
+            let state = app_handle.state::<AppState>();
+            let queue = state.paste_queue.lock();
+            queue.is_ok_and(|q| q.generation() == generation && q.is_active())
+        };
+
+        if expired {
+            println!("⏱️  Paste queue timed out");
+            let _ = reset(&app_handle);
+        }
+    });
+}
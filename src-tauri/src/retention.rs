@@ -0,0 +1,52 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::PruneReport;
+use crate::AppState;
+
+/// How long to wait before re-reading settings when pruning is disabled
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Run the retention policy once and notify the frontend if anything was removed
+pub fn run_clipboard_pruning(app_handle: &AppHandle, include_pinned: bool) -> Result<PruneReport, String> {
+    let state = app_handle.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let settings = db.get_clipboard_retention().map_err(|e| e.to_string())?;
+    let report = db.prune_clipboard_history(&settings, include_pinned).map_err(|e| e.to_string())?;
+
+    if report.removed > 0 {
+        println!(
+            "🧹 Pruned {} clipboard entries ({} bytes)",
+            report.removed, report.removed_bytes
+        );
+        let _ = app_handle.emit("clipboard-pruned", &report);
+    }
+
+    Ok(report)
+}
+
+/// Spawn the background task that prunes clipboard history on the configured interval
+pub fn spawn_clipboard_pruner(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = {
+                let state = app_handle.state::<AppState>();
+                let db = state.db.lock().ok();
+                db.and_then(|db| db.get_clipboard_retention().ok())
+            };
+
+            let interval = match settings {
+                Some(settings) if settings.enabled => {
+                    if let Err(e) = run_clipboard_pruning(&app_handle, false) {
+                        eprintln!("Failed to prune clipboard history: {}", e);
+                    }
+                    Duration::from_secs(settings.prune_interval_minutes.max(1) * 60)
+                }
+                _ => IDLE_CHECK_INTERVAL,
+            };
+
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
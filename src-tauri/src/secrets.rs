@@ -0,0 +1,203 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::encryption;
+use crate::models::{SecretVaultConfig, SecretVaultStatus};
+use crate::AppState;
+
+// Secret snippets are encrypted with the vault key: random bytes wrapped with
+// a master password of their own, separate from the library's encryption.
+// Their `content` column holds the base64 nonce and ciphertext, and reading
+// a snippet returns `SECRET_MASK` in its place. The content is only ever
+// decrypted to reveal or paste it, while the vault is unlocked.
+
+/// Shown instead of a secret snippet's content
+pub const SECRET_MASK: &str = "••••••••";
+
+pub const DEFAULT_LOCK_AFTER_MINUTES: u32 = 5;
+
+/// How often the relocker checks for an idle vault
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The unlocked vault key, forgotten after `lock_after` without use. Key
+/// material is zeroed when the vault locks and when the session is dropped.
+#[derive(Default)]
+pub struct SecretSession {
+    key: Option<[u8; 32]>,
+    last_used: Option<Instant>,
+    lock_after: Duration,
+    /// HMACs of the secrets pasted while unlocked, which the clipboard
+    /// history refuses to record
+    pasted: HashSet<[u8; 32]>,
+    /// Random key of those HMACs, replaced each time the vault locks
+    pasted_key: Option<[u8; 32]>,
+}
+
+impl SecretSession {
+    pub fn unlock(&mut self, key: &[u8; 32], lock_after_minutes: u32) {
+        self.key = Some(*key);
+        self.last_used = Some(Instant::now());
+        self.set_lock_after(lock_after_minutes);
+    }
+
+    pub fn set_lock_after(&mut self, minutes: u32) {
+        self.lock_after = Duration::from_secs(u64::from(minutes.max(1)) * 60);
+    }
+
+    /// Returns whether the vault was unlocked
+    pub fn lock(&mut self) -> bool {
+        self.last_used = None;
+        self.pasted.clear();
+        self.pasted_key.zeroize();
+        let was_unlocked = self.key.is_some();
+        self.key.zeroize();
+        was_unlocked
+    }
+
+    /// Lock the vault if it's been idle too long. Returns whether it was
+    /// locked now.
+    pub fn lock_if_idle(&mut self) -> bool {
+        let idle = self.last_used.is_some_and(|used| used.elapsed() >= self.lock_after);
+        idle && self.lock()
+    }
+
+    pub fn is_unlocked(&mut self) -> bool {
+        self.lock_if_idle();
+        self.key.is_some()
+    }
+
+    /// The vault key; using it keeps the vault unlocked
+    fn key(&mut self) -> Result<Zeroizing<[u8; 32]>> {
+        self.lock_if_idle();
+        let key = Zeroizing::new(self.key.context("Unlock secret snippets first")?);
+        self.last_used = Some(Instant::now());
+        Ok(key)
+    }
+
+    /// Content as stored for a secret snippet
+    pub fn encrypt(&mut self, content: &str) -> Result<String> {
+        let key = self.key()?;
+        Ok(BASE64.encode(encryption::seal(&key, content.as_bytes())?))
+    }
+
+    pub fn decrypt(&mut self, stored: &str) -> Result<String> {
+        let key = self.key()?;
+        let content = BASE64
+            .decode(stored)
+            .ok()
+            .and_then(|sealed| encryption::open(&key, &sealed))
+            .context("The secret is corrupted or was encrypted with another key")?;
+        String::from_utf8(content).context("The secret isn't text")
+    }
+
+    fn paste_tag(key: &[u8; 32], content: &str) -> [u8; 32] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes a key of any length");
+        mac.update(content.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    pub fn remember_paste(&mut self, content: &str) {
+        let key = self.pasted_key.get_or_insert_with(encryption::random_bytes);
+        let tag = Self::paste_tag(key, content);
+        self.pasted.insert(tag);
+    }
+
+    pub fn was_pasted(&self, content: &str) -> bool {
+        self.pasted_key.as_ref().is_some_and(|key| self.pasted.contains(&Self::paste_tag(key, content)))
+    }
+}
+
+impl Drop for SecretSession {
+    fn drop(&mut self) {
+        self.lock();
+    }
+}
+
+pub fn status(config: Option<&SecretVaultConfig>, session: &mut SecretSession) -> SecretVaultStatus {
+    SecretVaultStatus {
+        configured: config.is_some(),
+        unlocked: session.is_unlocked(),
+        lock_after_minutes: config.map_or(DEFAULT_LOCK_AFTER_MINUTES, |c| c.lock_after_minutes),
+    }
+}
+
+/// Wrap `key` with `password`
+pub fn wrap_key(key: &[u8; 32], password: &str, lock_after_minutes: u32) -> Result<SecretVaultConfig> {
+    let salt: [u8; encryption::SALT_LENGTH] = encryption::random_bytes();
+    let wrapped_key = encryption::seal(&encryption::passphrase_key(password, &salt)?, key)?;
+    Ok(SecretVaultConfig {
+        salt: BASE64.encode(salt),
+        wrapped_key: BASE64.encode(wrapped_key),
+        lock_after_minutes,
+    })
+}
+
+/// A new vault key and its config
+pub fn create_vault(password: &str) -> Result<(SecretVaultConfig, Zeroizing<[u8; 32]>)> {
+    let key = Zeroizing::new(encryption::random_bytes());
+    Ok((wrap_key(&key, password, DEFAULT_LOCK_AFTER_MINUTES)?, key))
+}
+
+pub fn unwrap_key(config: &SecretVaultConfig, password: &str) -> Result<Zeroizing<[u8; 32]>> {
+    let salt = BASE64.decode(&config.salt).context("The secret vault's salt is invalid")?;
+    let wrapped_key = BASE64.decode(&config.wrapped_key).context("The secret vault's key is invalid")?;
+    let password_key = Zeroizing::new(encryption::passphrase_key(password, &salt)?);
+    encryption::open(&password_key, &wrapped_key)
+        .map(Zeroizing::new)
+        .and_then(|key| key.as_slice().try_into().ok().map(Zeroizing::new))
+        .context("Wrong password")
+}
+
+/// Spawn the background thread that locks the vault when it's been idle
+pub fn spawn_relocker(app_handle: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        let state = app_handle.state::<AppState>();
+        let locked = state.secrets.lock().is_ok_and(|mut session| session.lock_if_idle());
+        if locked {
+            println!("🔒 Locked secret snippets after inactivity");
+            let _ = app_handle.emit("secrets-locked", ());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasted_secrets_are_forgotten_when_the_vault_locks() {
+        let mut session = SecretSession::default();
+        session.unlock(&[7; 32], DEFAULT_LOCK_AFTER_MINUTES);
+        session.remember_paste("hunter2");
+        assert!(session.was_pasted("hunter2"));
+        assert!(!session.was_pasted("hunter3"));
+
+        assert!(session.lock());
+        assert!(!session.was_pasted("hunter2"));
+        assert!(!session.is_unlocked());
+    }
+
+    #[test]
+    fn secrets_round_trip_while_unlocked() {
+        let mut session = SecretSession::default();
+        assert!(session.encrypt("token").is_err());
+
+        let (config, key) = create_vault("correct horse").unwrap();
+        assert!(unwrap_key(&config, "wrong horse").is_err());
+        session.unlock(&unwrap_key(&config, "correct horse").unwrap(), config.lock_after_minutes);
+        assert_eq!(*unwrap_key(&config, "correct horse").unwrap(), *key);
+
+        let stored = session.encrypt("token").unwrap();
+        assert_eq!(session.decrypt(&stored).unwrap(), "token");
+        session.lock();
+        assert!(session.decrypt(&stored).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::database::Database;
+use crate::models::{SearchResult, SmartFolderQuery};
+use crate::search::SearchEngine;
+
+/// Similarity required for semantic matches when the query doesn't set one
+pub const DEFAULT_MIN_SCORE: f32 = 0.5;
+
+/// Clean up a query before it is saved
+pub fn normalize(mut query: SmartFolderQuery) -> Result<SmartFolderQuery, String> {
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    query.text = non_empty(query.text);
+    query.semantic_query = non_empty(query.semantic_query);
+    query.tags = crate::tags::normalize_tags(query.tags.iter().map(String::as_str));
+    query.languages = query
+        .languages
+        .iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    if let Some(days) = query.updated_within_days {
+        if days < 1 {
+            return Err("The update window must be at least one day".to_string());
+        }
+    }
+    if let Some(score) = query.min_score {
+        if !(0.0..=1.0).contains(&score) {
+            return Err("Minimum similarity must be between 0 and 1".to_string());
+        }
+    }
+
+    let has_condition = query.text.is_some()
+        || query.semantic_query.is_some()
+        || !query.tags.is_empty()
+        || !query.languages.is_empty()
+        || query.updated_within_days.is_some();
+    if !has_condition {
+        return Err("A smart folder needs at least one condition".to_string());
+    }
+
+    Ok(query)
+}
+
+/// Snippets matching a smart folder query. Semantic queries are ranked by
+/// similarity; everything else is most recently updated first.
+pub fn evaluate(db: &Database, engine: Option<&SearchEngine>, query: &SmartFolderQuery) -> Result<Vec<SearchResult>> {
+    let mut snippets = if query.text.is_some() || !query.tags.is_empty() {
+        db.search_snippets(query.text.as_deref().unwrap_or_default(), &query.tags)?
+    } else {
+        db.get_all_snippets()?
+    };
+
+    if !query.languages.is_empty() {
+        snippets.retain(|s| query.languages.iter().any(|l| l.eq_ignore_ascii_case(&s.language)));
+    }
+
+    if let Some(days) = query.updated_within_days {
+        let cutoff = Utc::now() - Duration::days(days);
+        snippets.retain(|s| {
+            DateTime::parse_from_rfc3339(&s.updated_at)
+                .map(|updated| updated >= cutoff)
+                .unwrap_or(false)
+        });
+    }
+
+    let Some(semantic_query) = query.semantic_query.as_deref() else {
+        return Ok(snippets
+            .into_iter()
+            .map(|snippet| SearchResult { snippet, score: 1.0, highlight: None })
+            .collect());
+    };
+
+    let Some(engine) = engine else {
+        anyhow::bail!("Load the AI model to use smart folders that search by meaning");
+    };
+
+    let min_score = query.min_score.unwrap_or(DEFAULT_MIN_SCORE);
+    let embeddings = db.get_all_embeddings()?;
+    let mut results = engine.semantic_search(semantic_query, &snippets, &embeddings)?;
+    results.retain(|r| r.score >= min_score);
+
+    Ok(results)
+}
+
+/// Live snippet count, or `None` for searches by meaning, which are too slow
+/// to run whenever folders are listed
+pub fn count(db: &Database, query: &SmartFolderQuery) -> Option<i64> {
+    if query.semantic_query.is_some() {
+        return None;
+    }
+
+    match evaluate(db, None, query) {
+        Ok(results) => Some(results.len() as i64),
+        Err(e) => {
+            eprintln!("Failed to evaluate smart folder: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,745 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::database::Database;
+use crate::file_storage::FileStorageManager;
+use crate::models::{SyncBlob, SyncRecord, SyncRecordKind, SyncReport, SyncServer, SyncState, SyncStatus};
+use crate::sync_crypto;
+
+// Incremental sync of snippets, folders, clipboard history and files.
+//
+// Every record gets a UUID shared by all devices. Triggers note local writes
+// in a journal (see `Database::migrate_sync_journal`); before each push or
+// pull the journal becomes new versions of those records. A version carries
+// a hybrid logical clock timestamp and a version vector: how many edits from
+// each device it includes. Deleted records leave a tombstone version.
+//
+// The server only keeps an ordered log of encrypted versions. A pulled
+// version that includes everything this device has replaces it. One made
+// alongside a local version is a conflict: the later timestamp wins on every
+// device, and the device whose edits lost keeps them as a conflicted copy.
+//
+// Secret snippets aren't synced. Making a snippet secret doesn't delete it
+// elsewhere either: other devices keep the version from before.
+
+mod clock;
+mod legacy;
+#[cfg(test)]
+mod memory;
+mod supabase;
+mod transport;
+
+pub use supabase::SupabaseTransport;
+pub use transport::{Change, Transport};
+
+use clock::Clock;
+
+/// Changes per pull or push request
+const PAGE_SIZE: usize = 200;
+/// Folder ID columns, and the keys the folder's UUID is synced as
+const FOLDER_REFERENCES: &[(&str, &str)] = &[
+    ("folder_id", "folder"),
+    ("parent_id", "parent"),
+    ("trashed_folder_id", "trashed_folder"),
+];
+const CONFLICT_SUFFIX: &str = " (conflicted copy)";
+
+fn lock(db: &Mutex<Database>) -> Result<MutexGuard<'_, Database>> {
+    db.lock().map_err(|e| anyhow!(e.to_string()))
+}
+
+/// The sync state, with a device ID picked the first time
+fn load_state(db: &Database) -> Result<SyncState> {
+    let mut state = db.get_sync_state()?;
+    if state.device_id.is_empty() {
+        state.device_id = uuid::Uuid::new_v4().simple().to_string();
+        db.set_sync_state(&state)?;
+    }
+    Ok(state)
+}
+
+pub fn status(db: &Database) -> Result<SyncStatus> {
+    let state = load_state(db)?;
+    Ok(SyncStatus {
+        device_id: state.device_id,
+        pending: db.count_pending_sync_changes()?,
+        last_synced_at: state.last_synced_at,
+    })
+}
+
+/// Push the whole library and its files again on the next sync, e.g. after
+/// the sync key was rotated. Once that sync finishes, older keys can be
+/// retired (see `SyncState::resynced_key_version`).
+pub fn resync(db: &Database) -> Result<()> {
+    let mut state = load_state(db)?;
+    state.resync_key_version = Some(sync_crypto::current_key_version()?);
+    let tx = db.transaction()?;
+    db.forget_sync_blobs()?;
+    db.journal_library_for_sync()?;
+    db.set_sync_state(&state)?;
+    tx.commit()
+}
+
+/// Sync with `server`, bringing over what the sync before the change log
+/// left there the first time
+pub fn sync_with(db: &Mutex<Database>, server: &SyncServer) -> Result<SyncReport> {
+    match server {
+        SyncServer::Supabase { url, anon_key, access_token, user_id, user_email } => {
+            let mut transport = SupabaseTransport::new(url, anon_key, access_token, user_id, user_email);
+            let mut report = sync(db, &mut transport)?;
+            // After a pull, so what another device already brought over is skipped
+            report.imported = legacy::import_once(db, &transport)?;
+            if report.imported > 0 {
+                let more = sync(db, &mut transport)?;
+                report.pushed += more.pushed;
+                report.pulled += more.pulled;
+                report.conflicts += more.conflicts;
+                report.synced_at = more.synced_at;
+            }
+            Ok(report)
+        }
+    }
+}
+
+/// Pull what other devices pushed, then push local changes. The database is
+/// only locked between requests.
+pub fn sync(db: &Mutex<Database>, transport: &mut impl Transport) -> Result<SyncReport> {
+    run(db, transport, FileStorageManager::new()?)
+}
+
+/// `sync`, with file contents kept in `files`
+fn run(db: &Mutex<Database>, transport: &mut impl Transport, files: FileStorageManager) -> Result<SyncReport> {
+    if !sync_crypto::status().unlocked {
+        anyhow::bail!("Unlock encrypted sync first");
+    }
+
+    let mut session = Session::start(&*lock(db)?, files)?;
+    let mut report = SyncReport::default();
+    session.pull(db, transport, &mut report)?;
+    session.push(db, transport, &mut report)?;
+    // Everything was pushed with this key version
+    if let Some(version) = session.state.resync_key_version.take() {
+        session.state.resynced_key_version = Some(version);
+    }
+
+    report.synced_at = Utc::now().to_rfc3339();
+    session.state.last_synced_at = Some(report.synced_at.clone());
+    session.save(&*lock(db)?)?;
+
+    println!(
+        "🔄 Synced: {} pushed, {} pulled, {} conflicted copies",
+        report.pushed, report.pulled, report.conflicts
+    );
+    Ok(report)
+}
+
+/// Is every edit in `other` also in `versions`?
+fn includes(versions: &BTreeMap<String, u64>, other: &BTreeMap<String, u64>) -> bool {
+    other.iter().all(|(device, count)| versions.get(device).copied().unwrap_or_default() >= *count)
+}
+
+/// A secret snippet, which reads as missing without being deleted
+fn withheld(db: &Database, kind: SyncRecordKind, local_id: i64) -> Result<bool> {
+    Ok(kind == SyncRecordKind::Snippet && db.get_secret_content(local_id)?.is_some())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Associated data for a change's payload: its record and version
+fn change_aad(uuid: &str, hlc: &str) -> String {
+    format!("{}@{}", uuid, hlc)
+}
+
+/// `name.ext` becomes `name (conflicted copy).ext`
+fn conflicted_file_name(filename: &str) -> String {
+    let path = Path::new(filename);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => {
+            format!("{}{}.{}", stem.to_string_lossy(), CONFLICT_SUFFIX, extension.to_string_lossy())
+        }
+        _ => format!("{}{}", filename, CONFLICT_SUFFIX),
+    }
+}
+
+struct Session {
+    state: SyncState,
+    clock: Clock,
+    files: FileStorageManager,
+    /// The sync key version new data is encrypted with
+    key_version: u32,
+}
+
+impl Session {
+    fn start(db: &Database, files: FileStorageManager) -> Result<Self> {
+        let state = load_state(db)?;
+        let clock = Clock::new(&state.device_id, state.clock.as_deref());
+        Ok(Session { state, clock, files, key_version: sync_crypto::current_key_version()? })
+    }
+
+    fn save(&mut self, db: &Database) -> Result<()> {
+        self.state.clock = Some(self.clock.last());
+        db.set_sync_state(&self.state)
+    }
+
+    fn device(&self) -> &str {
+        &self.state.device_id
+    }
+
+    /// Pulled data encrypted with an older sync key stays on the server, so
+    /// the older keys are still needed: the library has to be pushed again
+    /// in full before they can be retired
+    fn note_key_version(&mut self, version: Option<u32>) {
+        if version.is_some_and(|version| version < self.key_version) {
+            self.state.resync_key_version = None;
+            self.state.resynced_key_version = None;
+        }
+    }
+
+    /// Turn journaled writes into new versions of their records
+    fn record_local_changes(&mut self, db: &Database) -> Result<()> {
+        for (kind, local_id) in db.drain_sync_journal()? {
+            let exists = db.read_sync_row(kind, local_id)?.is_some();
+            if !exists && withheld(db, kind, local_id)? {
+                continue;
+            }
+            let mut record = match db.get_sync_record_for(kind, local_id)? {
+                Some(record) if record.deleted && !exists => continue,
+                Some(record) => record,
+                None if !exists => continue,
+                None => SyncRecord {
+                    uuid: uuid::Uuid::new_v4().to_string(),
+                    kind,
+                    local_id,
+                    hlc: String::new(),
+                    versions: BTreeMap::new(),
+                    deleted: false,
+                    dirty: false,
+                    blob: None,
+                },
+            };
+
+            *record.versions.entry(self.state.device_id.clone()).or_default() += 1;
+            record.hlc = self.clock.tick();
+            record.deleted = !exists;
+            record.dirty = true;
+            db.save_sync_record(&record)?;
+        }
+        Ok(())
+    }
+
+    fn pull(&mut self, db: &Mutex<Database>, transport: &mut impl Transport, report: &mut SyncReport) -> Result<()> {
+        loop {
+            let batch = transport.pull(self.state.cursor.as_deref(), PAGE_SIZE)?;
+
+            let mut incoming = Vec::new();
+            for change in batch.changes {
+                self.clock.observe(&change.hlc);
+                if change.device == self.device() {
+                    continue;
+                }
+                let payload = match &change.payload {
+                    Some(encrypted) => {
+                        self.note_key_version(sync_crypto::change_key_version(encrypted));
+                        let json = sync_crypto::decrypt_change(&change_aad(&change.uuid, &change.hlc), encrypted)?;
+                        Some(serde_json::from_slice::<Map<String, Value>>(&json).context("A synced change is invalid")?)
+                    }
+                    None => None,
+                };
+                incoming.push((change, payload));
+            }
+            let blobs = self.fetch_blobs(db, transport, &incoming)?;
+
+            let db = lock(db)?;
+            // Local writes since the last page have to be versions before
+            // they're compared with the server's
+            self.record_local_changes(&db)?;
+            db.set_sync_applying(true)?;
+            let applied = incoming
+                .into_iter()
+                .try_for_each(|(change, payload)| self.apply(&db, change, payload, &blobs, report));
+            db.set_sync_applying(false)?;
+            applied?;
+
+            self.state.cursor = batch.cursor;
+            self.save(&db)?;
+            if !batch.more {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Download the file contents of a page of changes that this device
+    /// doesn't have yet, before the database is locked
+    fn fetch_blobs(
+        &mut self,
+        db: &Mutex<Database>,
+        transport: &mut impl Transport,
+        incoming: &[(Change, Option<Map<String, Value>>)],
+    ) -> Result<HashMap<String, Vec<u8>>> {
+        let mut blobs = HashMap::new();
+        for (change, payload) in incoming {
+            let Some(blob) = payload
+                .as_ref()
+                .and_then(|payload| payload.get("blob"))
+                .and_then(|blob| serde_json::from_value::<SyncBlob>(blob.clone()).ok())
+            else {
+                continue;
+            };
+            let local = lock(db)?.get_sync_record(&change.uuid)?;
+            let have = local.is_some_and(|local| !local.deleted && !local.dirty && local.blob.as_ref() == Some(&blob));
+            if have || blobs.contains_key(&blob.key) {
+                continue;
+            }
+            let encrypted = transport.get_blob(&blob.key)?;
+            self.note_key_version(sync_crypto::file_key_version(&encrypted));
+            let data = sync_crypto::decrypt_file(&blob.key, &encrypted)?;
+            if sha256_hex(&data) != blob.hash {
+                anyhow::bail!("The synced file {} doesn't match its checksum", blob.key);
+            }
+            blobs.insert(blob.key, data);
+        }
+        Ok(blobs)
+    }
+
+    /// Apply a version pulled from another device
+    fn apply(
+        &mut self,
+        db: &Database,
+        change: Change,
+        payload: Option<Map<String, Value>>,
+        blobs: &HashMap<String, Vec<u8>>,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let local = db.get_sync_record(&change.uuid)?;
+        let mut record = match local {
+            Some(local) => {
+                if local.kind != change.kind || includes(&local.versions, &change.versions) {
+                    return Ok(());
+                }
+                // A secret snippet here stays as it is
+                if !local.deleted && withheld(db, local.kind, local.local_id)? {
+                    return Ok(());
+                }
+                let concurrent = !includes(&change.versions, &local.versions);
+                if concurrent && change.hlc < local.hlc {
+                    // The local version wins; the other device keeps its edits as a copy
+                    return Ok(());
+                }
+                let own_edits = |versions: &BTreeMap<String, u64>| versions.get(self.device()).copied().unwrap_or_default();
+                if concurrent && !local.deleted && own_edits(&local.versions) > own_edits(&change.versions) {
+                    self.keep_conflicted_copy(db, &local)?;
+                    report.conflicts += 1;
+                }
+                local
+            }
+            // Nothing to delete
+            None if change.deleted => return Ok(()),
+            None => SyncRecord {
+                uuid: change.uuid.clone(),
+                kind: change.kind,
+                local_id: 0,
+                hlc: String::new(),
+                versions: BTreeMap::new(),
+                deleted: true,
+                dirty: false,
+                blob: None,
+            },
+        };
+
+        if change.deleted {
+            if !record.deleted {
+                if let Some(path) = db.delete_sync_row(record.kind, record.local_id)? {
+                    self.files.delete_file(&path)?;
+                }
+            }
+            record.deleted = true;
+        } else {
+            let mut row = payload.context("A synced change has no contents")?;
+            let existing = (!record.deleted).then_some(record.local_id);
+            let mut replaced_path = None;
+
+            if let Some(blob) = row.remove("blob") {
+                let blob: SyncBlob = serde_json::from_value(blob).context("A synced file is invalid")?;
+                let current_path = existing
+                    .and_then(|id| db.read_sync_row(SyncRecordKind::File, id).ok().flatten())
+                    .and_then(|current| current.get("storage_path").and_then(Value::as_str).map(String::from));
+                let path = match blobs.get(&blob.key) {
+                    Some(data) => {
+                        let filename = row.get("filename").and_then(Value::as_str).unwrap_or("file");
+                        replaced_path = current_path;
+                        self.files.save_file(filename, data)?.to_string_lossy().to_string()
+                    }
+                    None => current_path.context("The contents of a synced file are missing")?,
+                };
+                row.insert("storage_path".to_string(), path.into());
+                record.blob = Some(blob);
+            }
+
+            self.resolve_folder_uuids(db, &mut row)?;
+            record.local_id = db.write_sync_row(record.kind, existing, &row)?;
+            record.deleted = false;
+            if let Some(path) = replaced_path {
+                self.files.delete_file(&path)?;
+            }
+        }
+
+        record.hlc = change.hlc;
+        record.versions = change.versions;
+        record.dirty = false;
+        db.save_sync_record(&record)?;
+        report.pulled += 1;
+        Ok(())
+    }
+
+    /// Keep the local version of a record that lost a conflict as a new
+    /// record, which is pushed like any other local change
+    fn keep_conflicted_copy(&self, db: &Database, local: &SyncRecord) -> Result<()> {
+        let Some(mut row) = db.read_sync_row(local.kind, local.local_id)? else {
+            return Ok(());
+        };
+        let rename = |row: &mut Map<String, Value>, field: &str, rename: &dyn Fn(&str) -> String| {
+            if let Some(Value::String(value)) = row.get_mut(field) {
+                *value = rename(value);
+            }
+        };
+        match local.kind {
+            SyncRecordKind::Snippet => {
+                rename(&mut row, "title", &|title| format!("{}{}", title, CONFLICT_SUFFIX));
+                // Triggers are unique
+                row.insert("trigger_keyword".to_string(), Value::Null);
+            }
+            SyncRecordKind::Folder => rename(&mut row, "name", &|name| format!("{}{}", name, CONFLICT_SUFFIX)),
+            SyncRecordKind::File => {
+                rename(&mut row, "filename", &conflicted_file_name);
+                let path = row.get("storage_path").and_then(Value::as_str).unwrap_or_default();
+                let filename = row.get("filename").and_then(Value::as_str).unwrap_or("file");
+                let copy = self.files.save_file(filename, &self.files.read_file(path)?)?;
+                row.insert("storage_path".to_string(), copy.to_string_lossy().to_string().into());
+            }
+            SyncRecordKind::ClipboardEntry => {}
+        }
+
+        let id = db.write_sync_row(local.kind, None, &row)?;
+        db.journal_sync_change(local.kind, id)?;
+        println!("⚠️ Kept a conflicted copy of {} {}", local.kind.as_str(), local.uuid);
+        Ok(())
+    }
+
+    fn push(&mut self, db: &Mutex<Database>, transport: &mut impl Transport, report: &mut SyncReport) -> Result<()> {
+        let mut pushed: HashSet<String> = HashSet::new();
+        loop {
+            let mut outgoing = Vec::new();
+            {
+                let db = lock(db)?;
+                self.record_local_changes(&db)?;
+                self.save(&db)?;
+                for record in db.get_dirty_sync_records()? {
+                    if outgoing.len() == PAGE_SIZE {
+                        break;
+                    }
+                    // Edited, then made secret before it was pushed
+                    if pushed.contains(&record.uuid) || (!record.deleted && withheld(&db, record.kind, record.local_id)?) {
+                        continue;
+                    }
+                    outgoing.push(self.outgoing(&db, &record)?);
+                }
+            }
+            if outgoing.is_empty() {
+                return Ok(());
+            }
+
+            for (_, blob, upload) in &outgoing {
+                if let (Some(blob), Some(data)) = (blob, upload) {
+                    transport.put_blob(&blob.key, data)?;
+                }
+            }
+            let changes: Vec<Change> = outgoing.iter().map(|(change, _, _)| change.clone()).collect();
+            transport.push(&changes, self.state.cursor.as_deref())?;
+
+            let db = lock(db)?;
+            for (change, blob, _) in &outgoing {
+                db.mark_sync_record_pushed(&change.uuid, &change.hlc, blob.as_ref())?;
+                pushed.insert(change.uuid.clone());
+            }
+            report.pushed += outgoing.len();
+        }
+    }
+
+    /// The change to push for a dirty record, with the file contents to
+    /// upload first if they changed since they were last synced
+    fn outgoing(&self, db: &Database, record: &SyncRecord) -> Result<(Change, Option<SyncBlob>, Option<Vec<u8>>)> {
+        let mut change = Change {
+            uuid: record.uuid.clone(),
+            kind: record.kind,
+            hlc: record.hlc.clone(),
+            versions: record.versions.clone(),
+            device: self.state.device_id.clone(),
+            deleted: true,
+            payload: None,
+        };
+        let row = if record.deleted { None } else { db.read_sync_row(record.kind, record.local_id)? };
+        // Deleted since it was journaled; the journal has it again
+        let Some(mut row) = row else {
+            return Ok((change, None, None));
+        };
+
+        let mut blob = None;
+        let mut upload = None;
+        if record.kind == SyncRecordKind::File {
+            let path = row.remove("storage_path").and_then(|path| path.as_str().map(String::from)).unwrap_or_default();
+            let data = self.files.read_file(&path)?;
+            let hash = sha256_hex(&data);
+            let synced = match &record.blob {
+                Some(synced) if synced.hash == hash => synced.clone(),
+                _ => {
+                    let key = format!("{}/{}", record.uuid, record.hlc);
+                    upload = Some(sync_crypto::encrypt_file(&key, &data)?);
+                    SyncBlob { key, hash }
+                }
+            };
+            row.insert("blob".to_string(), serde_json::to_value(&synced)?);
+            blob = Some(synced);
+        }
+
+        for (column, key) in FOLDER_REFERENCES {
+            let Some(id) = row.remove(*column) else { continue };
+            let uuid = match id.as_i64() {
+                Some(id) => db
+                    .get_sync_record_for(SyncRecordKind::Folder, id)?
+                    .filter(|folder| !folder.deleted)
+                    .map(|folder| folder.uuid),
+                None => None,
+            };
+            row.insert(key.to_string(), uuid.into());
+        }
+
+        let json = serde_json::to_vec(&row)?;
+        change.deleted = false;
+        change.payload = Some(sync_crypto::encrypt_change(&change_aad(&record.uuid, &record.hlc), &json)?);
+        Ok((change, blob, upload))
+    }
+
+    /// Replace the folder UUIDs of a pulled record with local folder IDs.
+    /// Folders this device doesn't have leave the record uncategorized.
+    fn resolve_folder_uuids(&self, db: &Database, row: &mut Map<String, Value>) -> Result<()> {
+        for (column, key) in FOLDER_REFERENCES {
+            let Some(uuid) = row.remove(*key) else { continue };
+            let id = match uuid.as_str() {
+                Some(uuid) => db
+                    .get_sync_record(uuid)?
+                    .filter(|folder| folder.kind == SyncRecordKind::Folder && !folder.deleted)
+                    .map(|folder| folder.local_id),
+                None => None,
+            };
+            row.insert(column.to_string(), id.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::memory::MemoryServer;
+    use super::*;
+    use crate::models::{File, Snippet};
+    use std::path::PathBuf;
+
+    struct Device {
+        db: Mutex<Database>,
+        files: PathBuf,
+    }
+
+    impl Device {
+        fn new() -> Self {
+            sync_crypto::unlock_for_tests();
+            Device {
+                db: Mutex::new(Database::open_in_memory().unwrap()),
+                files: std::env::temp_dir().join(format!("nobraindev-sync-{}", uuid::Uuid::new_v4().simple())),
+            }
+        }
+
+        fn storage(&self) -> FileStorageManager {
+            FileStorageManager::in_dir(self.files.clone()).unwrap()
+        }
+
+        fn sync(&self, server: &MemoryServer) -> Result<SyncReport> {
+            run(&self.db, &mut server.clone(), self.storage())
+        }
+
+        fn db(&self) -> MutexGuard<'_, Database> {
+            self.db.lock().unwrap()
+        }
+
+        /// Titles and contents of the live snippets, sorted
+        fn snippets(&self) -> Vec<(String, String)> {
+            let mut snippets: Vec<(String, String)> =
+                self.db().get_all_snippets().unwrap().into_iter().map(|s| (s.title, s.content)).collect();
+            snippets.sort();
+            snippets
+        }
+    }
+
+    impl Drop for Device {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.files);
+        }
+    }
+
+    fn snippet(title: &str, content: &str) -> Snippet {
+        let now = Utc::now().to_rfc3339();
+        Snippet {
+            id: None,
+            title: title.to_string(),
+            content: content.to_string(),
+            language: "text".to_string(),
+            description: None,
+            tags: None,
+            folder_id: None,
+            created_at: now.clone(),
+            updated_at: now,
+            trigger: None,
+            deleted_at: None,
+            secret: false,
+        }
+    }
+
+    fn pair(text: &str, other: &str) -> (String, String) {
+        (text.to_string(), other.to_string())
+    }
+
+    #[test]
+    fn concurrent_edits_keep_the_losing_one_as_a_conflicted_copy() {
+        let server = MemoryServer::default();
+        let (a, b) = (Device::new(), Device::new());
+        let id = a.db().create_snippet(&snippet("Deploy", "v1")).unwrap();
+        a.sync(&server).unwrap();
+        b.sync(&server).unwrap();
+        let b_id = b.db().get_all_snippets().unwrap()[0].id.unwrap();
+
+        a.db().update_snippet(id, &snippet("Deploy", "from a")).unwrap();
+        b.db().update_snippet(b_id, &snippet("Deploy", "from b")).unwrap();
+        a.sync(&server).unwrap();
+        // B's version is made last, so it wins
+        assert_eq!(b.sync(&server).unwrap().conflicts, 0);
+        assert_eq!(a.sync(&server).unwrap().conflicts, 1);
+        b.sync(&server).unwrap();
+
+        let expected = vec![pair("Deploy", "from b"), pair("Deploy (conflicted copy)", "from a")];
+        assert_eq!(a.snippets(), expected);
+        assert_eq!(b.snippets(), expected);
+    }
+
+    #[test]
+    fn deletions_reach_other_devices() {
+        let server = MemoryServer::default();
+        let (a, b) = (Device::new(), Device::new());
+        let id = a.db().create_snippet(&snippet("Old", "gone soon")).unwrap();
+        a.db().create_snippet(&snippet("Kept", "stays")).unwrap();
+        a.sync(&server).unwrap();
+        b.sync(&server).unwrap();
+        assert_eq!(b.snippets().len(), 2);
+
+        a.db().purge_snippet(id).unwrap();
+        a.sync(&server).unwrap();
+        assert_eq!(b.sync(&server).unwrap().pulled, 1);
+        assert_eq!(b.snippets(), vec![pair("Kept", "stays")]);
+
+        // A device that syncs for the first time skips the tombstone
+        let c = Device::new();
+        c.sync(&server).unwrap();
+        assert_eq!(c.snippets(), vec![pair("Kept", "stays")]);
+    }
+
+    #[test]
+    fn secret_snippets_stay_as_they_were_on_other_devices() {
+        let server = MemoryServer::default();
+        let (a, b) = (Device::new(), Device::new());
+        let id = a.db().create_snippet(&snippet("Token", "abc")).unwrap();
+        a.sync(&server).unwrap();
+        b.sync(&server).unwrap();
+
+        let secret = Snippet { secret: true, ..snippet("Token", "sealed") };
+        a.db().update_snippet(id, &secret).unwrap();
+        assert_eq!(status(&a.db()).unwrap().pending, 0);
+        assert_eq!(a.sync(&server).unwrap().pushed, 0);
+        b.sync(&server).unwrap();
+        assert_eq!(b.snippets(), vec![pair("Token", "abc")]);
+
+        // Edits from elsewhere don't touch the secret
+        let b_id = b.db().get_all_snippets().unwrap()[0].id.unwrap();
+        b.db().update_snippet(b_id, &snippet("Token", "def")).unwrap();
+        b.sync(&server).unwrap();
+        a.sync(&server).unwrap();
+        assert_eq!(a.db().get_secret_content(id).unwrap().as_deref(), Some("sealed"));
+    }
+
+    #[test]
+    fn pulls_continue_from_the_cursor_page_by_page() {
+        let server = MemoryServer::default();
+        let (a, b) = (Device::new(), Device::new());
+        let count = PAGE_SIZE * 2 + 5;
+        for i in 0..count {
+            a.db().save_clipboard_entry(&format!("entry {}", i), "test", "text", &Utc::now().to_rfc3339()).unwrap();
+        }
+        assert_eq!(a.sync(&server).unwrap().pushed, count);
+
+        assert_eq!(b.sync(&server).unwrap().pulled, count);
+        assert_eq!(b.db().get_clipboard_history(i64::MAX, false).unwrap().len(), count);
+        assert_eq!(b.db().get_sync_state().unwrap().cursor, Some(count.to_string()));
+
+        a.db().save_clipboard_entry("one more", "test", "text", &Utc::now().to_rfc3339()).unwrap();
+        a.sync(&server).unwrap();
+        assert_eq!(b.sync(&server).unwrap().pulled, 1);
+        assert_eq!(b.sync(&server).unwrap().pulled, 0);
+    }
+
+    #[test]
+    fn files_round_trip_through_encrypted_blobs() {
+        let server = MemoryServer::default();
+        let (a, b) = (Device::new(), Device::new());
+        let data = b"plain file contents".to_vec();
+        let path = a.storage().save_file("notes.txt", &data).unwrap();
+        let now = Utc::now().to_rfc3339();
+        let id = a
+            .db()
+            .create_file(&File {
+                id: None,
+                filename: "notes.txt".to_string(),
+                file_type: "text".to_string(),
+                file_size: data.len() as i64,
+                folder_id: None,
+                storage_path: path.to_string_lossy().to_string(),
+                cloud_storage_path: None,
+                mime_type: Some("text/plain".to_string()),
+                description: None,
+                tags: None,
+                created_at: now.clone(),
+                updated_at: now,
+                deleted_at: None,
+            })
+            .unwrap();
+        a.sync(&server).unwrap();
+
+        let blob = a.db().get_sync_record_for(SyncRecordKind::File, id).unwrap().unwrap().blob.unwrap();
+        let uploaded = server.clone().get_blob(&blob.key).unwrap();
+        assert!(!uploaded.windows(data.len()).any(|window| window == data.as_slice()));
+
+        b.sync(&server).unwrap();
+        let file = b.db().get_all_files().unwrap().remove(0);
+        assert_eq!(file.filename, "notes.txt");
+        assert_eq!(b.storage().read_file(&file.storage_path).unwrap(), data);
+
+        // A blob swapped on the server isn't accepted
+        let other = sync_crypto::encrypt_file(&blob.key, b"something else").unwrap();
+        server.clone().put_blob(&blob.key, &other).unwrap();
+        let error = Device::new().sync(&server).unwrap_err();
+        assert!(error.to_string().contains("checksum"), "{}", error);
+    }
+}
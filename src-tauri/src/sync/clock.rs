@@ -0,0 +1,60 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A hybrid logical clock. Timestamps are wall-clock milliseconds plus a
+/// counter, so they keep increasing when the wall clock stalls or goes back,
+/// and never fall behind a timestamp seen from another device. The device ID
+/// at the end breaks ties, and the fixed width makes them compare correctly
+/// as strings: `000001718000000000-000000-<device>`.
+pub struct Clock {
+    device: String,
+    wall: u64,
+    counter: u32,
+}
+
+impl Clock {
+    /// Continue from `last`, the latest timestamp this device handed out or saw
+    pub fn new(device: &str, last: Option<&str>) -> Self {
+        let (wall, counter) = last.and_then(parse).unwrap_or((0, 0));
+        Clock { device: device.to_string(), wall, counter }
+    }
+
+    /// A timestamp for a local change, later than any before it
+    pub fn tick(&mut self) -> String {
+        let now = now_millis();
+        if now > self.wall {
+            self.wall = now;
+            self.counter = 0;
+        } else {
+            self.counter += 1;
+        }
+        self.last()
+    }
+
+    /// Move past a timestamp from another device
+    pub fn observe(&mut self, remote: &str) {
+        if let Some((wall, counter)) = parse(remote) {
+            if (wall, counter) > (self.wall, self.counter) {
+                self.wall = wall;
+                self.counter = counter;
+            }
+        }
+    }
+
+    pub fn last(&self) -> String {
+        format!("{:018}-{:06}-{}", self.wall, self.counter, self.device)
+    }
+}
+
+fn parse(timestamp: &str) -> Option<(u64, u32)> {
+    let mut parts = timestamp.splitn(3, '-');
+    let wall = parts.next()?.parse().ok()?;
+    let counter = parts.next()?.parse().ok()?;
+    Some((wall, counter))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
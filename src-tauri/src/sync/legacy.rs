@@ -0,0 +1,142 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use super::{load_state, lock, SupabaseTransport};
+use crate::database::Database;
+use crate::file_storage::FileStorageManager;
+use crate::models::{ClipboardEntry, File, Folder, ImportMode, LibraryData, Snippet, SyncRecordKind};
+use crate::sync_crypto;
+
+// Before the change log, sync copied every item to a table of its own
+// (`snippets`, `folders`, `clipboard_history` and `files`, by `user_email`
+// and the pushing device's `local_id`), with field-encrypted contents, and
+// file contents to the `user-files` bucket. On the first sync with the change
+// log, once it has pulled, those rows are merged into the library like an
+// imported archive: items the library already has, also from another device
+// that did this first, are skipped. The import is journaled like any local
+// write, so the following push puts it in the change log for other devices.
+// The old tables are left as they are.
+
+/// Import the old sync tables, once. Returns how many items were added.
+pub fn import_once(db: &Mutex<Database>, server: &SupabaseTransport) -> Result<usize> {
+    if load_state(&*lock(db)?)?.legacy_imported {
+        return Ok(0);
+    }
+    if !sync_crypto::status().unlocked {
+        anyhow::bail!("Unlock encrypted sync first");
+    }
+
+    let folders: Vec<Folder> = read_table(server, "folders", SyncRecordKind::Folder)?;
+    let mut snippets: Vec<Snippet> = read_table(server, "snippets", SyncRecordKind::Snippet)?;
+    let clipboard: Vec<ClipboardEntry> = read_table(server, "clipboard_history", SyncRecordKind::ClipboardEntry)?;
+    let mut files = Vec::new();
+    for row in server.legacy_rows("files")? {
+        let path = row.get("cloud_storage_path").and_then(Value::as_str).map(String::from);
+        if let Some(file) = parse_row::<File>(row, SyncRecordKind::File)? {
+            files.push((file, path));
+        }
+    }
+
+    // File contents are downloaded before the database is locked, and
+    // removed again if the import fails
+    let storage = FileStorageManager::new()?;
+    let mut blob_paths: HashMap<i64, String> = HashMap::new();
+    let result = store_files(server, &storage, &mut files, &mut blob_paths).and_then(|_| {
+        let db = lock(db)?;
+        // A snippet that was made secret since it was last synced stays secret
+        let secret_titles: HashSet<String> =
+            db.get_all_snippets()?.into_iter().filter(|s| s.secret).map(|s| s.title).collect();
+        snippets.retain(|snippet| !secret_titles.contains(&snippet.title));
+
+        let data = LibraryData {
+            folders,
+            snippets,
+            files: files.into_iter().map(|(file, _)| file).collect(),
+            clipboard,
+            tags: Vec::new(),
+        };
+        let (report, unused_blobs) = db.import_library(&data, ImportMode::Merge, &blob_paths)?;
+
+        let mut state = load_state(&db)?;
+        state.legacy_imported = true;
+        db.set_sync_state(&state)?;
+        Ok((report, unused_blobs))
+    });
+
+    match result {
+        Ok((report, unused_blobs)) => {
+            for storage_path in unused_blobs {
+                if let Err(e) = storage.delete_file(&storage_path) {
+                    eprintln!("Failed to delete file {} from storage: {}", storage_path, e);
+                }
+            }
+            let imported = report.snippets + report.folders + report.files + report.clipboard_entries;
+            println!(
+                "📦 Imported {} items from the old sync tables ({} duplicates skipped)",
+                imported, report.duplicates_skipped
+            );
+            Ok(imported)
+        }
+        Err(e) => {
+            for storage_path in blob_paths.values() {
+                let _ = storage.delete_file(storage_path);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn read_table<T: DeserializeOwned>(server: &SupabaseTransport, table: &str, kind: SyncRecordKind) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    for row in server.legacy_rows(table)? {
+        items.extend(parse_row(row, kind)?);
+    }
+    Ok(items)
+}
+
+/// An item from a decrypted row, with its `local_id` as the ID the
+/// library import maps folders by. Rows that don't make an item are skipped.
+fn parse_row<T: DeserializeOwned>(mut row: Map<String, Value>, kind: SyncRecordKind) -> Result<Option<T>> {
+    sync_crypto::decrypt_legacy_row(kind, &mut row)?;
+    let id = row.remove("local_id").unwrap_or(Value::Null);
+    row.insert("id".to_string(), id);
+    if kind == SyncRecordKind::File {
+        // Set once the contents are downloaded
+        row.insert("storage_path".to_string(), Value::String(String::new()));
+        row.insert("cloud_storage_path".to_string(), Value::Null);
+        row.entry("file_size").or_insert(0.into());
+    }
+
+    match serde_json::from_value(Value::Object(row)) {
+        Ok(item) => Ok(Some(item)),
+        Err(e) => {
+            println!("⚠️ Skipped a {} from the old sync tables: {}", kind.as_str(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// Download and store the contents of each file. Files whose contents are
+/// gone are left out of `blob_paths`, which the import reports.
+fn store_files(
+    server: &SupabaseTransport,
+    storage: &FileStorageManager,
+    files: &mut [(File, Option<String>)],
+    blob_paths: &mut HashMap<i64, String>,
+) -> Result<()> {
+    for (file, path) in files.iter_mut() {
+        let (Some(id), Some(path)) = (file.id, path.as_deref()) else { continue };
+        let Some(encrypted) = server.legacy_file(path)? else { continue };
+        let data = sync_crypto::decrypt_legacy_file(&encrypted)?;
+
+        // A random prefix keeps files with the same name from overwriting each other
+        let stored_name = format!("{}_{}", uuid::Uuid::new_v4().simple(), file.filename);
+        let stored_path = storage.save_file(&stored_name, &data)?;
+        blob_paths.insert(id, stored_path.to_string_lossy().to_string());
+        file.file_size = data.len() as i64;
+    }
+    Ok(())
+}
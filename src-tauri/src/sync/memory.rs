@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::transport::{Change, PullBatch, Transport};
+
+/// A sync server that lives in this process. Devices share it by cloning.
+/// It behaves like the real one: an ordered change log, compacted on push,
+/// and a blob store.
+#[derive(Clone, Default)]
+pub struct MemoryServer {
+    state: Arc<Mutex<ServerState>>,
+}
+
+#[derive(Default)]
+struct ServerState {
+    /// Sequence numbers and changes, oldest first
+    log: Vec<(u64, Change)>,
+    next_seq: u64,
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryServer {
+    fn state(&self) -> Result<std::sync::MutexGuard<'_, ServerState>> {
+        self.state.lock().map_err(|e| anyhow!(e.to_string()))
+    }
+}
+
+fn parse_cursor(cursor: Option<&str>) -> Result<u64> {
+    cursor.map_or(Ok(0), |cursor| cursor.parse().context("Invalid sync cursor"))
+}
+
+impl Transport for MemoryServer {
+    fn pull(&mut self, cursor: Option<&str>, limit: usize) -> Result<PullBatch> {
+        let after = parse_cursor(cursor)?;
+        let state = self.state()?;
+        let mut pending = state.log.iter().filter(|(seq, _)| *seq > after);
+        let page: Vec<&(u64, Change)> = pending.by_ref().take(limit).collect();
+
+        Ok(PullBatch {
+            cursor: page.last().map(|(seq, _)| seq.to_string()).or(cursor.map(String::from)),
+            more: pending.next().is_some(),
+            changes: page.into_iter().map(|(_, change)| change.clone()).collect(),
+        })
+    }
+
+    fn push(&mut self, changes: &[Change], seen: Option<&str>) -> Result<()> {
+        let seen = parse_cursor(seen)?;
+        let mut state = self.state()?;
+        let pushed: HashSet<&str> = changes.iter().map(|change| change.uuid.as_str()).collect();
+        state.log.retain(|(seq, change)| *seq > seen || !pushed.contains(change.uuid.as_str()));
+
+        for change in changes {
+            state.next_seq += 1;
+            let seq = state.next_seq;
+            state.log.push((seq, change.clone()));
+        }
+        Ok(())
+    }
+
+    fn put_blob(&mut self, key: &str, data: &[u8]) -> Result<()> {
+        self.state()?.blobs.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn get_blob(&mut self, key: &str) -> Result<Vec<u8>> {
+        self.state()?.blobs.get(key).cloned().with_context(|| format!("Blob {} not found", key))
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::transport::{Change, PullBatch, Transport};
+
+// Sync through a Supabase project. Changes go in the `sync_changes` table,
+// with row level security limiting each user to their own rows, and file
+// contents in the `user-files` bucket under `<user id>/sync/`. The tables and
+// policies are in `supabase/migrations` at the root of the repository.
+
+const STORAGE_BUCKET: &str = "user-files";
+/// Records per delete request, to keep URLs short
+const COMPACT_CHUNK: usize = 50;
+/// Rows per request when reading the old sync tables
+const LEGACY_PAGE: usize = 500;
+
+pub struct SupabaseTransport {
+    client: Client,
+    url: String,
+    anon_key: String,
+    access_token: String,
+    user_id: String,
+    user_email: String,
+}
+
+#[derive(Deserialize)]
+struct Row {
+    seq: i64,
+    #[serde(flatten)]
+    change: Change,
+}
+
+#[derive(Serialize)]
+struct NewRow<'a> {
+    user_email: &'a str,
+    #[serde(flatten)]
+    change: &'a Change,
+}
+
+impl SupabaseTransport {
+    pub fn new(url: &str, anon_key: &str, access_token: &str, user_id: &str, user_email: &str) -> Self {
+        SupabaseTransport {
+            client: Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            anon_key: anon_key.to_string(),
+            access_token: access_token.to_string(),
+            user_id: user_id.to_string(),
+            user_email: user_email.to_string(),
+        }
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header("apikey", &self.anon_key)
+            .bearer_auth(&self.access_token)
+    }
+
+    fn blob_url(&self, key: &str) -> String {
+        format!("{}/storage/v1/object/{}/{}/sync/{}", self.url, STORAGE_BUCKET, self.user_id, key)
+    }
+
+    /// The user's rows of a table the sync before the change log used (see
+    /// `sync::legacy`). A table the project never had reads as empty.
+    pub fn legacy_rows(&self, table: &str) -> Result<Vec<Map<String, Value>>> {
+        let mut rows = Vec::new();
+        loop {
+            let response = self
+                .authorized(self.client.get(format!("{}/rest/v1/{}", self.url, table)))
+                .query(&[
+                    ("select", "*".to_string()),
+                    ("user_email", format!("eq.{}", self.user_email)),
+                    ("order", "id.asc".to_string()),
+                    ("limit", LEGACY_PAGE.to_string()),
+                    ("offset", rows.len().to_string()),
+                ])
+                .send()
+                .context("Failed to reach the sync server")?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(rows);
+            }
+            let page: Vec<Map<String, Value>> = check(response)?
+                .json()
+                .with_context(|| format!("Unexpected rows in {} on the sync server", table))?;
+            let done = page.len() < LEGACY_PAGE;
+            rows.extend(page);
+            if done {
+                return Ok(rows);
+            }
+        }
+    }
+
+    /// A file the sync before the change log uploaded, still encrypted.
+    /// `None` if it's gone from the bucket.
+    pub fn legacy_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let url = format!("{}/storage/v1/object/authenticated/{}/{}", self.url, STORAGE_BUCKET, path);
+        let response = self
+            .authorized(self.client.get(url))
+            .send()
+            .context("Failed to download a file from the sync server")?;
+        // Storage answers 400 for objects that don't exist
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST) {
+            return Ok(None);
+        }
+        Ok(Some(check(response)?.bytes()?.to_vec()))
+    }
+}
+
+/// The response, or an error with what the server said
+fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().unwrap_or_default();
+    anyhow::bail!("The sync server returned {}: {}", status, body)
+}
+
+impl Transport for SupabaseTransport {
+    fn pull(&mut self, cursor: Option<&str>, limit: usize) -> Result<PullBatch> {
+        let after = cursor.unwrap_or("0");
+        let response = self
+            .authorized(self.client.get(format!("{}/rest/v1/sync_changes", self.url)))
+            .query(&[
+                ("select", "seq,uuid,kind,hlc,versions,device,deleted,payload".to_string()),
+                ("user_email", format!("eq.{}", self.user_email)),
+                ("seq", format!("gt.{}", after)),
+                ("order", "seq.asc".to_string()),
+                // One extra row tells whether there's another page
+                ("limit", (limit + 1).to_string()),
+            ])
+            .send()
+            .context("Failed to reach the sync server")?;
+        let mut rows: Vec<Row> = check(response)?.json().context("Unexpected changes from the sync server")?;
+
+        let more = rows.len() > limit;
+        rows.truncate(limit);
+        Ok(PullBatch {
+            cursor: rows.last().map(|row| row.seq.to_string()).or(cursor.map(String::from)),
+            more,
+            changes: rows.into_iter().map(|row| row.change).collect(),
+        })
+    }
+
+    fn push(&mut self, changes: &[Change], seen: Option<&str>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let rows: Vec<NewRow> = changes
+            .iter()
+            .map(|change| NewRow { user_email: &self.user_email, change })
+            .collect();
+        let response = self
+            .authorized(self.client.post(format!("{}/rest/v1/sync_changes", self.url)))
+            .header("Prefer", "return=minimal")
+            .json(&rows)
+            .send()
+            .context("Failed to reach the sync server")?;
+        check(response)?;
+
+        // The new rows are after `seen`, so only superseded ones are removed
+        let Some(seen) = seen else { return Ok(()) };
+        for chunk in changes.chunks(COMPACT_CHUNK) {
+            let uuids: Vec<&str> = chunk.iter().map(|change| change.uuid.as_str()).collect();
+            let response = self
+                .authorized(self.client.delete(format!("{}/rest/v1/sync_changes", self.url)))
+                .query(&[
+                    ("user_email", format!("eq.{}", self.user_email)),
+                    ("seq", format!("lte.{}", seen)),
+                    ("uuid", format!("in.({})", uuids.join(","))),
+                ])
+                .send()
+                .context("Failed to reach the sync server")?;
+            check(response)?;
+        }
+        Ok(())
+    }
+
+    fn put_blob(&mut self, key: &str, data: &[u8]) -> Result<()> {
+        let response = self
+            .authorized(self.client.post(self.blob_url(key)))
+            .header("Content-Type", "application/octet-stream")
+            .header("x-upsert", "true")
+            .body(data.to_vec())
+            .send()
+            .context("Failed to upload a file to the sync server")?;
+        check(response)?;
+        Ok(())
+    }
+
+    fn get_blob(&mut self, key: &str) -> Result<Vec<u8>> {
+        let url = format!(
+            "{}/storage/v1/object/authenticated/{}/{}/sync/{}",
+            self.url, STORAGE_BUCKET, self.user_id, key
+        );
+        let response = self
+            .authorized(self.client.get(url))
+            .send()
+            .context("Failed to download a file from the sync server")?;
+        Ok(check(response)?.bytes()?.to_vec())
+    }
+}
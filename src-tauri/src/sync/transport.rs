@@ -0,0 +1,50 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::SyncRecordKind;
+
+/// One version of a record, as the server stores it. Only `payload` says
+/// anything about the record's contents, and it's encrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub uuid: String,
+    pub kind: SyncRecordKind,
+    /// Hybrid logical clock of this version; the later one wins a conflict
+    pub hlc: String,
+    /// Edits per device this version includes, to tell whether it replaces
+    /// the version another device has or was made alongside it
+    pub versions: BTreeMap<String, u64>,
+    /// The device that made this version
+    pub device: String,
+    pub deleted: bool,
+    /// The record's JSON, encrypted with `sync_crypto::encrypt_change`.
+    /// `None` for deletions.
+    pub payload: Option<String>,
+}
+
+/// A page of the server's change log
+pub struct PullBatch {
+    pub changes: Vec<Change>,
+    /// Where the next pull continues from
+    pub cursor: Option<String>,
+    /// There are more changes after this page
+    pub more: bool,
+}
+
+/// How changes get to and from the server. The server keeps an ordered log
+/// of changes and a store of file contents (blobs); it never needs to
+/// understand either.
+pub trait Transport {
+    /// Changes pushed after `cursor` (from the start with `None`), in the
+    /// order the server received them
+    fn pull(&mut self, cursor: Option<&str>, limit: usize) -> Result<PullBatch>;
+
+    /// Append changes to the log. Earlier changes to the same records, up to
+    /// `seen` (the cursor of the last pull), are superseded and may be dropped.
+    fn push(&mut self, changes: &[Change], seen: Option<&str>) -> Result<()>;
+
+    fn put_blob(&mut self, key: &str, data: &[u8]) -> Result<()>;
+
+    fn get_blob(&mut self, key: &str) -> Result<Vec<u8>>;
+}
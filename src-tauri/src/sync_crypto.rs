@@ -0,0 +1,556 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use crate::auth;
+use crate::encryption::{self, SALT_LENGTH};
+use crate::models::{SyncEncryptionStatus, SyncKeySetup, SyncRecordKind};
+
+// Everything sync sends to the server is encrypted on this device first.
+//
+// Records and files are encrypted with a sync key: 32 random bytes with a
+// version, so the key can be rotated while data encrypted with older ones
+// stays readable. The sync keys are sealed with a master key, which is
+// wrapped twice: with a key derived from the user's passphrase (Argon2id),
+// and with a random recovery key the user writes down. That key bundle is
+// only ciphertext, so it's uploaded for the user's other devices, and kept in
+// the keychain like the other user tokens. The unwrapped keys stay in the
+// keychain until sign out.
+
+/// Keychain entry of the key bundle
+pub const BUNDLE_TOKEN: &str = "sync_key_bundle";
+/// Keychain entry of the unwrapped keys
+pub const KEYS_TOKEN: &str = "sync_keys";
+
+/// Start of every encrypted change, followed by the key version and the
+/// base64 nonce and ciphertext
+const CHANGE_PREFIX: &str = "nbde1:";
+/// Start of every encrypted file, followed by the key version (big endian),
+/// the nonce and the ciphertext. The magic, version and blob key are the
+/// associated data.
+const FILE_MAGIC: &[u8] = b"NBDSYNC1";
+const NONCE_LENGTH: usize = 24;
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    version: u32,
+    /// Argon2id salt for the passphrase (base64)
+    salt: String,
+    /// The master key sealed with the passphrase's key (base64)
+    passphrase_wrapped: String,
+    /// The master key sealed with the recovery key (base64)
+    recovery_wrapped: String,
+    /// The `KeyRing` as JSON, sealed with the master key (base64)
+    keys: String,
+}
+
+/// The sync keys (base64) by version
+#[derive(Clone, Serialize, Deserialize)]
+struct KeyRing {
+    current: u32,
+    keys: BTreeMap<u32, String>,
+}
+
+/// What this device keeps once the bundle is unlocked
+#[derive(Clone, Serialize, Deserialize)]
+struct Unlocked {
+    /// base64
+    master: String,
+    ring: KeyRing,
+}
+
+static UNLOCKED: RwLock<Option<Unlocked>> = RwLock::new(None);
+
+fn decode_key(text: &str) -> Result<[u8; 32]> {
+    BASE64
+        .decode(text)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("A sync key is invalid")
+}
+
+fn seal(key: &[u8; 32], data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let nonce: [u8; NONCE_LENGTH] = encryption::random_bytes();
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: data, aad })
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn open(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .ok()
+}
+
+/// The recovery key as the user writes it down: hex in groups of 8
+fn format_recovery_key(key: &[u8; 32]) -> String {
+    let hex: String = key.iter().map(|b| format!("{:02X}", b)).collect();
+    hex.as_bytes()
+        .chunks(8)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn parse_recovery_key(text: &str) -> Result<[u8; 32]> {
+    let hex: Vec<u8> = text.bytes().filter(|b| b.is_ascii_alphanumeric()).collect();
+    // Two digits for each byte, so a missing or extra digit isn't read as one
+    let bytes: Option<Vec<u8>> = (hex.len() == 64)
+        .then(|| {
+            hex.chunks(2)
+                .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+                .collect()
+        })
+        .flatten();
+    bytes
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("That isn't a recovery key")
+}
+
+fn parse_bundle(text: &str) -> Result<Bundle> {
+    let bundle: Bundle = serde_json::from_str(text).context("The sync key bundle is invalid")?;
+    if bundle.version != BUNDLE_VERSION {
+        anyhow::bail!("The sync key bundle is from a newer version of the app");
+    }
+    Ok(bundle)
+}
+
+fn wrap_for_passphrase(master: &[u8; 32], passphrase: &str) -> Result<(String, String)> {
+    let salt: [u8; SALT_LENGTH] = encryption::random_bytes();
+    let wrapped = seal(&encryption::passphrase_key(passphrase, &salt)?, master, b"passphrase")?;
+    Ok((BASE64.encode(salt), BASE64.encode(wrapped)))
+}
+
+fn seal_ring(master: &[u8; 32], ring: &KeyRing) -> Result<String> {
+    Ok(BASE64.encode(seal(master, &serde_json::to_vec(ring)?, b"keys")?))
+}
+
+/// Unseal the bundle's keys with its master key
+fn open_ring(bundle: &Bundle, master: [u8; 32]) -> Result<Unlocked> {
+    let sealed = BASE64.decode(&bundle.keys).context("The sync key bundle is invalid")?;
+    let ring = open(&master, &sealed, b"keys").context("The sync key bundle is corrupted")?;
+    Ok(Unlocked {
+        master: BASE64.encode(master),
+        ring: serde_json::from_slice(&ring).context("The sync key bundle is corrupted")?,
+    })
+}
+
+fn unlock_with_passphrase(bundle: &Bundle, passphrase: &str) -> Result<Unlocked> {
+    let salt = BASE64.decode(&bundle.salt).context("The sync key bundle is invalid")?;
+    let wrapped = BASE64.decode(&bundle.passphrase_wrapped).context("The sync key bundle is invalid")?;
+    let master: [u8; 32] = open(&encryption::passphrase_key(passphrase, &salt)?, &wrapped, b"passphrase")
+        .and_then(|key| key.try_into().ok())
+        .context("Wrong passphrase")?;
+    open_ring(bundle, master)
+}
+
+fn unlock_with_recovery_key(bundle: &Bundle, recovery_key: &str) -> Result<Unlocked> {
+    let wrapped = BASE64.decode(&bundle.recovery_wrapped).context("The sync key bundle is invalid")?;
+    let master: [u8; 32] = open(&parse_recovery_key(recovery_key)?, &wrapped, b"recovery")
+        .and_then(|key| key.try_into().ok())
+        .context("Wrong recovery key")?;
+    open_ring(bundle, master)
+}
+
+/// A bundle for `ring` with a new master key and recovery key
+fn new_bundle(ring: KeyRing, passphrase: &str) -> Result<(String, String, Unlocked)> {
+    let master: [u8; 32] = encryption::random_bytes();
+    let recovery_key: [u8; 32] = encryption::random_bytes();
+    let (salt, passphrase_wrapped) = wrap_for_passphrase(&master, passphrase)?;
+
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        salt,
+        passphrase_wrapped,
+        recovery_wrapped: BASE64.encode(seal(&recovery_key, &master, b"recovery")?),
+        keys: seal_ring(&master, &ring)?,
+    };
+    let unlocked = Unlocked { master: BASE64.encode(master), ring };
+    Ok((serde_json::to_string(&bundle)?, format_recovery_key(&recovery_key), unlocked))
+}
+
+/// `ring` with a new sync key, which new data is encrypted with
+fn add_key(mut ring: KeyRing) -> KeyRing {
+    let version = ring.keys.keys().max().copied().unwrap_or(0) + 1;
+    let key: [u8; 32] = encryption::random_bytes();
+    ring.keys.insert(version, BASE64.encode(key));
+    ring.current = version;
+    ring
+}
+
+/// `ring` with only its current key; see `retire_old_keys`
+fn retire_keys(mut ring: KeyRing, resynced: Option<u32>) -> Result<KeyRing> {
+    let current = ring.current;
+    if ring.keys.len() > 1 && resynced != Some(current) {
+        anyhow::bail!("Encrypt everything again with the current sync key and sync it before retiring the older keys");
+    }
+    ring.keys.retain(|version, _| *version == current);
+    Ok(ring)
+}
+
+/// Keep the bundle and its keys on this device
+fn remember(bundle: &str, unlocked: Unlocked) -> Result<()> {
+    auth::set_token(BUNDLE_TOKEN, bundle).map_err(|e| anyhow!(e))?;
+    auth::set_token(KEYS_TOKEN, &serde_json::to_string(&unlocked)?).map_err(|e| anyhow!(e))?;
+    *UNLOCKED.write().map_err(|e| anyhow!(e.to_string()))? = Some(unlocked);
+    Ok(())
+}
+
+fn local_bundle() -> Result<Bundle> {
+    let text = auth::get_token(BUNDLE_TOKEN).map_err(|_| anyhow!("Encrypted sync isn't set up on this device"))?;
+    parse_bundle(&text)
+}
+
+/// The unwrapped keys, loaded from the keychain the first time
+fn unlocked() -> Result<Unlocked> {
+    if let Some(unlocked) = UNLOCKED.read().map_err(|e| anyhow!(e.to_string()))?.clone() {
+        return Ok(unlocked);
+    }
+    let text = auth::get_token(KEYS_TOKEN).map_err(|_| anyhow!("Unlock encrypted sync first"))?;
+    let unlocked: Unlocked = serde_json::from_str(&text).context("The stored sync keys are invalid")?;
+    *UNLOCKED.write().map_err(|e| anyhow!(e.to_string()))? = Some(unlocked.clone());
+    Ok(unlocked)
+}
+
+/// Forget the unwrapped keys, e.g. on sign out
+pub fn forget_keys() {
+    if let Ok(mut unlocked) = UNLOCKED.write() {
+        *unlocked = None;
+    }
+}
+
+pub fn status() -> SyncEncryptionStatus {
+    let unlocked = unlocked().ok();
+    SyncEncryptionStatus {
+        configured: auth::get_token(BUNDLE_TOKEN).is_ok(),
+        unlocked: unlocked.is_some(),
+        key_version: unlocked.as_ref().map(|u| u.ring.current),
+        retired_keys: unlocked.map_or(0, |u| u.ring.keys.len().saturating_sub(1)),
+    }
+}
+
+/// Unlock with a throwaway key, as the keychain isn't there in tests
+#[cfg(test)]
+pub(crate) fn unlock_for_tests() {
+    let mut unlocked = UNLOCKED.write().unwrap();
+    if unlocked.is_none() {
+        let key: [u8; 32] = encryption::random_bytes();
+        let master: [u8; 32] = encryption::random_bytes();
+        let ring = KeyRing { current: 1, keys: BTreeMap::from([(1, BASE64.encode(key))]) };
+        *unlocked = Some(Unlocked { master: BASE64.encode(master), ring });
+    }
+}
+
+/// Create the sync keys, for a user who hasn't got any on the server yet
+pub fn setup(passphrase: &str) -> Result<SyncKeySetup> {
+    if auth::get_token(BUNDLE_TOKEN).is_ok() {
+        anyhow::bail!("Encrypted sync is already set up on this device");
+    }
+    let key: [u8; 32] = encryption::random_bytes();
+    let ring = KeyRing { current: 1, keys: BTreeMap::from([(1, BASE64.encode(key))]) };
+
+    let (bundle, recovery_key, unlocked) = new_bundle(ring, passphrase)?;
+    remember(&bundle, unlocked)?;
+    println!("🔐 Created the sync encryption keys");
+    Ok(SyncKeySetup { bundle, recovery_key: Some(recovery_key) })
+}
+
+/// Unlock the bundle from the server (or this device) with the passphrase
+pub fn unlock(bundle: &str, passphrase: &str) -> Result<()> {
+    let unlocked = unlock_with_passphrase(&parse_bundle(bundle)?, passphrase)?;
+    remember(bundle, unlocked)?;
+    println!("🔐 Unlocked encrypted sync");
+    Ok(())
+}
+
+/// Unlock the bundle with the recovery key when the passphrase is lost. The
+/// recovery key is used up: the new bundle has a new one.
+pub fn recover(bundle: &str, recovery_key: &str, new_passphrase: &str) -> Result<SyncKeySetup> {
+    let ring = unlock_with_recovery_key(&parse_bundle(bundle)?, recovery_key)?.ring;
+    let (bundle, recovery_key, unlocked) = new_bundle(ring, new_passphrase)?;
+    remember(&bundle, unlocked)?;
+    println!("🔐 Recovered encrypted sync with the recovery key");
+    Ok(SyncKeySetup { bundle, recovery_key: Some(recovery_key) })
+}
+
+/// Wrap the master key with a new passphrase. The recovery key still works.
+pub fn change_passphrase(current: &str, new_passphrase: &str) -> Result<SyncKeySetup> {
+    let mut bundle = local_bundle()?;
+    let unlocked = unlock_with_passphrase(&bundle, current)?;
+    (bundle.salt, bundle.passphrase_wrapped) = wrap_for_passphrase(&decode_key(&unlocked.master)?, new_passphrase)?;
+
+    let bundle = serde_json::to_string(&bundle)?;
+    remember(&bundle, unlocked)?;
+    Ok(SyncKeySetup { bundle, recovery_key: None })
+}
+
+/// Add a new sync key that new data is encrypted with, under a new master
+/// key and recovery key. Older keys are kept until `retire_old_keys`, once
+/// everything has been re-encrypted.
+pub fn rotate(passphrase: &str) -> Result<SyncKeySetup> {
+    let ring = add_key(unlock_with_passphrase(&local_bundle()?, passphrase)?.ring);
+    let version = ring.current;
+
+    let (bundle, recovery_key, unlocked) = new_bundle(ring, passphrase)?;
+    remember(&bundle, unlocked)?;
+    println!("🔐 Rotated the sync key to version {}", version);
+    Ok(SyncKeySetup { bundle, recovery_key: Some(recovery_key) })
+}
+
+/// The version of the sync key new data is encrypted with
+pub fn current_key_version() -> Result<u32> {
+    Ok(unlocked()?.ring.current)
+}
+
+/// Drop all but the current sync key. `resynced` is the key version of the
+/// last full re-push, which has to be the current one: until then the server
+/// may still have data only the older keys can read.
+pub fn retire_old_keys(resynced: Option<u32>) -> Result<SyncKeySetup> {
+    let mut bundle = local_bundle()?;
+    let mut unlocked = unlocked()?;
+    unlocked.ring = retire_keys(unlocked.ring, resynced)?;
+    let current = unlocked.ring.current;
+    bundle.keys = seal_ring(&decode_key(&unlocked.master)?, &unlocked.ring)?;
+
+    let bundle = serde_json::to_string(&bundle)?;
+    remember(&bundle, unlocked)?;
+    println!("🔐 Retired the sync keys before version {}", current);
+    Ok(SyncKeySetup { bundle, recovery_key: None })
+}
+
+/// Encrypt the payload of a change before it's pushed. `aad` names the
+/// record and version it belongs to, so the server can't move it elsewhere.
+pub fn encrypt_change(aad: &str, payload: &[u8]) -> Result<String> {
+    encrypt_with(&unlocked()?.ring, aad, payload)
+}
+
+fn encrypt_with(ring: &KeyRing, aad: &str, payload: &[u8]) -> Result<String> {
+    let version = ring.current;
+    let key = decode_key(ring.keys.get(&version).context("The current sync key is missing")?)?;
+    let sealed = seal(&key, payload, aad.as_bytes())?;
+    Ok(format!("{}{}:{}", CHANGE_PREFIX, version, BASE64.encode(sealed)))
+}
+
+/// The sync key version a change was encrypted with
+pub fn change_key_version(encrypted: &str) -> Option<u32> {
+    encrypted.strip_prefix(CHANGE_PREFIX)?.split_once(':')?.0.parse().ok()
+}
+
+/// The sync key version a file was encrypted with
+pub fn file_key_version(data: &[u8]) -> Option<u32> {
+    let version = data.strip_prefix(FILE_MAGIC)?.get(..4)?;
+    Some(u32::from_be_bytes(version.try_into().ok()?))
+}
+
+/// Associated data of a file: which key version and blob it belongs to
+fn file_aad(version: u32, blob_key: &str) -> Vec<u8> {
+    [FILE_MAGIC, &version.to_be_bytes(), blob_key.as_bytes()].concat()
+}
+
+/// Decrypt the payload of a pulled change, see `encrypt_change`
+pub fn decrypt_change(aad: &str, encrypted: &str) -> Result<Vec<u8>> {
+    decrypt_with(&unlocked()?.ring, aad, encrypted)
+}
+
+fn decrypt_with(ring: &KeyRing, aad: &str, encrypted: &str) -> Result<Vec<u8>> {
+    let (version, sealed) = encrypted
+        .strip_prefix(CHANGE_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .context("A synced change isn't encrypted")?;
+    let key = version
+        .parse::<u32>()
+        .ok()
+        .and_then(|version| ring.keys.get(&version))
+        .with_context(|| format!("This device doesn't have sync key version {}", version))?;
+    BASE64
+        .decode(sealed)
+        .ok()
+        .and_then(|sealed| open(&decode_key(key).ok()?, &sealed, aad.as_bytes()))
+        .context("A synced change is corrupted or was encrypted with another key")
+}
+
+/// Encrypt a file's contents before uploading them as `blob_key`, so the
+/// server can't hand them out as another file
+pub fn encrypt_file(blob_key: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let unlocked = unlocked()?;
+    let version = unlocked.ring.current;
+    let key = decode_key(unlocked.ring.keys.get(&version).context("The current sync key is missing")?)?;
+    Ok([FILE_MAGIC, &version.to_be_bytes(), &seal(&key, data, &file_aad(version, blob_key))?].concat())
+}
+
+/// Decrypt the file downloaded as `blob_key`. Once encrypted sync is set
+/// up, a file that isn't encrypted is refused.
+pub fn decrypt_file(blob_key: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let Some(rest) = data.strip_prefix(FILE_MAGIC) else {
+        if unlocked().is_ok() || auth::get_token(BUNDLE_TOKEN).is_ok() {
+            anyhow::bail!("A synced file isn't encrypted");
+        }
+        return Ok(data.to_vec());
+    };
+    if rest.len() < 4 {
+        anyhow::bail!("The file is corrupted");
+    }
+    let (version, sealed) = rest.split_at(4);
+    let version = u32::from_be_bytes(version.try_into()?);
+
+    let unlocked = unlocked()?;
+    let key = unlocked
+        .ring
+        .keys
+        .get(&version)
+        .with_context(|| format!("This device doesn't have sync key version {}", version))?;
+    open(&decode_key(key)?, sealed, &file_aad(version, blob_key))
+        .context("The file is corrupted, was encrypted with another key or belongs to another record")
+}
+
+/// Fields of each kind of record that the sync before the change log
+/// encrypted one by one
+fn legacy_fields(kind: SyncRecordKind) -> &'static [&'static str] {
+    match kind {
+        SyncRecordKind::Snippet => &["title", "language", "tags", "description", "content"],
+        SyncRecordKind::Folder => &["name", "icon"],
+        SyncRecordKind::ClipboardEntry => &["content", "source", "category"],
+        SyncRecordKind::File => &["filename", "file_type", "mime_type", "description", "tags"],
+    }
+}
+
+/// Decrypt a row of the old sync tables (see `sync::legacy`). Each field was
+/// bound to its record's kind, field name and `local_id`. Fields synced
+/// before encryption was set up are plaintext, and are left as they are.
+pub fn decrypt_legacy_row(kind: SyncRecordKind, row: &mut Map<String, Value>) -> Result<()> {
+    let id = row.get("local_id").map(|id| id.to_string()).unwrap_or_default();
+
+    for field in legacy_fields(kind) {
+        let Some(encrypted) = row.get(*field).and_then(Value::as_str).and_then(|text| text.strip_prefix(CHANGE_PREFIX)) else {
+            continue;
+        };
+        let (version, sealed) = encrypted.split_once(':').context("An encrypted field is invalid")?;
+        let unlocked = unlocked()?;
+        let key = version
+            .parse::<u32>()
+            .ok()
+            .and_then(|version| unlocked.ring.keys.get(&version))
+            .with_context(|| format!("This device doesn't have sync key version {}", version))?;
+        let aad = format!("{}.{}.{}", kind.as_str(), field, id);
+        let text = BASE64
+            .decode(sealed)
+            .ok()
+            .and_then(|sealed| open(&decode_key(key).ok()?, &sealed, aad.as_bytes()))
+            .and_then(|text| String::from_utf8(text).ok())
+            .with_context(|| format!("The {} of a synced {} is corrupted", field, kind.as_str()))?;
+        row.insert(field.to_string(), Value::String(text));
+    }
+    Ok(())
+}
+
+/// Decrypt a file uploaded by the sync before the change log, which only
+/// bound files to the key version. Files uploaded before encryption was set
+/// up are returned as they are.
+pub fn decrypt_legacy_file(data: &[u8]) -> Result<Vec<u8>> {
+    let Some(rest) = data.strip_prefix(FILE_MAGIC) else { return Ok(data.to_vec()) };
+    if rest.len() < 4 {
+        anyhow::bail!("The file is corrupted");
+    }
+    let (version, sealed) = rest.split_at(4);
+    let version = u32::from_be_bytes(version.try_into()?);
+
+    let unlocked = unlocked()?;
+    let key = unlocked
+        .ring
+        .keys
+        .get(&version)
+        .with_context(|| format!("This device doesn't have sync key version {}", version))?;
+    open(&decode_key(key)?, sealed, FILE_MAGIC).context("The file is corrupted or was encrypted with another key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring() -> KeyRing {
+        let key: [u8; 32] = encryption::random_bytes();
+        KeyRing { current: 1, keys: BTreeMap::from([(1, BASE64.encode(key))]) }
+    }
+
+    #[test]
+    fn recovery_keys_read_back_as_written() {
+        let key: [u8; 32] = encryption::random_bytes();
+        let written = format_recovery_key(&key);
+        assert_eq!(written.len(), 71);
+        assert_eq!(written.split('-').count(), 8);
+        assert_eq!(parse_recovery_key(&written).unwrap(), key);
+        // However it's typed back in
+        assert_eq!(parse_recovery_key(&format!(" {} ", written.to_lowercase().replace('-', " "))).unwrap(), key);
+
+        assert!(parse_recovery_key(&written[..70]).is_err());
+        assert!(parse_recovery_key(&format!("{}0", written)).is_err());
+        assert!(parse_recovery_key(&format!("G{}", &written[1..])).is_err());
+        assert!(parse_recovery_key("not a key").is_err());
+    }
+
+    #[test]
+    fn bundle_unlocks_with_the_passphrase_or_the_recovery_key() {
+        let (bundle, recovery_key, created) = new_bundle(ring(), "correct horse").unwrap();
+        let bundle = parse_bundle(&bundle).unwrap();
+
+        let unlocked = unlock_with_passphrase(&bundle, "correct horse").unwrap();
+        assert_eq!(unlocked.master, created.master);
+        assert_eq!(unlocked.ring.keys, created.ring.keys);
+        let recovered = unlock_with_recovery_key(&bundle, &recovery_key).unwrap();
+        assert_eq!(recovered.master, created.master);
+        assert_eq!(recovered.ring.keys, created.ring.keys);
+
+        let wrong_passphrase = unlock_with_passphrase(&bundle, "wrong horse").err().unwrap();
+        assert_eq!(wrong_passphrase.to_string(), "Wrong passphrase");
+        let other_key = format_recovery_key(&encryption::random_bytes());
+        let wrong_key = unlock_with_recovery_key(&bundle, &other_key).err().unwrap();
+        assert_eq!(wrong_key.to_string(), "Wrong recovery key");
+        assert!(parse_bundle(r#"{"version": 2}"#).is_err());
+    }
+
+    #[test]
+    fn rotated_keys_still_read_older_changes() {
+        let old = ring();
+        let before = encrypt_with(&old, "snippet:1", b"before").unwrap();
+
+        let rotated = add_key(old.clone());
+        assert_eq!(rotated.current, 2);
+        let after = encrypt_with(&rotated, "snippet:1", b"after").unwrap();
+        assert_eq!(change_key_version(&before), Some(1));
+        assert_eq!(change_key_version(&after), Some(2));
+
+        assert_eq!(decrypt_with(&rotated, "snippet:1", &before).unwrap(), b"before");
+        assert_eq!(decrypt_with(&rotated, "snippet:1", &after).unwrap(), b"after");
+        // Bound to the record it was encrypted for
+        assert!(decrypt_with(&rotated, "snippet:2", &before).is_err());
+        // Devices without the new key can't read what it encrypted
+        assert!(decrypt_with(&old, "snippet:1", &after).is_err());
+    }
+
+    #[test]
+    fn old_keys_are_retired_only_after_a_full_repush() {
+        let rotated = add_key(ring());
+        let before = encrypt_with(&rotated, "file:1", b"data").unwrap();
+
+        assert!(retire_keys(rotated.clone(), None).is_err());
+        assert!(retire_keys(rotated.clone(), Some(1)).is_err());
+        let retired = retire_keys(rotated, Some(2)).unwrap();
+        assert_eq!(retired.current, 2);
+        assert_eq!(retired.keys.keys().copied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(decrypt_with(&retired, "file:1", &before).unwrap(), b"data");
+
+        // With a single key there's nothing to re-encrypt
+        assert!(retire_keys(ring(), None).is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+/// Longest tag name accepted
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// Split a comma-separated tag string into tag names. Names are trimmed, a
+/// leading `#` is dropped, and repeats (ignoring case) keep their first spelling.
+pub fn parse_tags(tags: &str) -> Vec<String> {
+    normalize_tags(tags.split(','))
+}
+
+/// Clean up a list of tag names the same way `parse_tags` does
+pub fn normalize_tags<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+
+    for name in names {
+        let Some(name) = normalize_tag(name) else { continue };
+        if !result.iter().any(|existing| existing.eq_ignore_ascii_case(&name)) {
+            result.push(name);
+        }
+    }
+
+    result
+}
+
+/// A single cleaned-up tag name, or `None` if nothing usable is left
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let name = name.trim().trim_start_matches('#').trim();
+    let name: String = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if name.is_empty() || name.contains(',') {
+        return None;
+    }
+
+    Some(name.chars().take(MAX_TAG_LENGTH).collect())
+}
+
+/// The stored form of a tag list (`None` when there are no tags)
+pub fn format_tags(names: &[String]) -> Option<String> {
+    if names.is_empty() {
+        None
+    } else {
+        Some(names.join(", "))
+    }
+}
+
+/// Pull `tag:name` filters out of a search query. Returns the remaining
+/// free-text query and the tag names.
+pub fn split_tag_filters(query: &str) -> (String, Vec<String>) {
+    let mut text = Vec::new();
+    let mut tags = Vec::new();
+
+    for word in query.split_whitespace() {
+        match word.strip_prefix("tag:") {
+            Some(tag) => tags.push(tag),
+            None => text.push(word),
+        }
+    }
+
+    (text.join(" "), normalize_tags(tags))
+}
+
+/// Tag colors are stored as `#rrggbb`
+pub fn is_valid_color(color: &str) -> bool {
+    color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::models::Snippet;
+
+    #[test]
+    fn parse_tags_normalizes_names() {
+        assert_eq!(parse_tags(" rust ,#docker,  # k8s  ,"), ["rust", "docker", "k8s"]);
+        assert_eq!(parse_tags("big   data,\tnew\nline"), ["big data", "new line"]);
+        assert!(parse_tags("").is_empty());
+        assert!(parse_tags(" , ,#, ").is_empty());
+
+        let long = "x".repeat(MAX_TAG_LENGTH + 10);
+        assert_eq!(parse_tags(&long)[0].len(), MAX_TAG_LENGTH);
+    }
+
+    #[test]
+    fn parse_tags_drops_duplicates_keeping_first_spelling() {
+        assert_eq!(parse_tags("Rust, rust, RUST, #rust"), ["Rust"]);
+        assert_eq!(parse_tags("a, b, A, c, B"), ["a", "b", "c"]);
+        assert_eq!(parse_tags("big data, big  DATA"), ["big data"]);
+    }
+
+    #[test]
+    fn normalize_tag_rejects_unusable_names() {
+        assert_eq!(normalize_tag("  #go "), Some("go".to_string()));
+        assert_eq!(normalize_tag("##"), None);
+        assert_eq!(normalize_tag("a,b"), None);
+    }
+
+    #[test]
+    fn format_tags_round_trips() {
+        let names = parse_tags("one, two");
+        assert_eq!(format_tags(&names).as_deref(), Some("one, two"));
+        assert_eq!(parse_tags(&format_tags(&names).unwrap()), names);
+        assert_eq!(format_tags(&[]), None);
+    }
+
+    #[test]
+    fn tag_filters_are_split_from_the_query() {
+        let (text, tags) = split_tag_filters("deploy tag:k8s  script tag:#K8S tag:helm");
+        assert_eq!(text, "deploy script");
+        assert_eq!(tags, ["k8s", "helm"]);
+    }
+
+    #[test]
+    fn colors() {
+        assert!(is_valid_color("#a0B1c2"));
+        assert!(!is_valid_color("a0b1c2"));
+        assert!(!is_valid_color("#a0b1c"));
+        assert!(!is_valid_color("#a0b1cg"));
+    }
+
+    fn snippet(db: &Database, title: &str, tags: &str) -> i64 {
+        let now = chrono::Utc::now().to_rfc3339();
+        db.create_snippet(&Snippet {
+            id: None,
+            title: title.to_string(),
+            content: title.to_string(),
+            language: "text".to_string(),
+            description: None,
+            tags: Some(tags.to_string()),
+            folder_id: None,
+            created_at: now.clone(),
+            updated_at: now,
+            trigger: None,
+            deleted_at: None,
+            secret: false,
+        })
+        .unwrap()
+    }
+
+    fn tag_id(db: &Database, name: &str) -> i64 {
+        db.get_all_tags().unwrap().into_iter().find(|t| t.name == name).unwrap().id
+    }
+
+    #[test]
+    fn merge_moves_every_use_to_the_target() {
+        let db = Database::open_in_memory().unwrap();
+        let one = snippet(&db, "one", "js, web");
+        let two = snippet(&db, "two", "javascript, js");
+        let three = snippet(&db, "three", "ecmascript");
+
+        let target = tag_id(&db, "javascript");
+        db.merge_tags(&[tag_id(&db, "js"), tag_id(&db, "ecmascript"), target], target).unwrap();
+
+        let tags: Vec<_> = db.get_all_tags().unwrap().into_iter().map(|t| (t.name, t.snippet_count)).collect();
+        assert_eq!(tags, [("javascript".to_string(), 3), ("web".to_string(), 1)]);
+
+        let tags_of = |id| db.get_snippet(id).unwrap().unwrap().tags;
+        assert_eq!(tags_of(one).as_deref(), Some("javascript, web"));
+        // Already had the target: no duplicate, and it keeps its place
+        assert_eq!(tags_of(two).as_deref(), Some("javascript"));
+        assert_eq!(tags_of(three).as_deref(), Some("javascript"));
+    }
+
+    #[test]
+    fn merge_into_a_missing_tag_fails() {
+        let db = Database::open_in_memory().unwrap();
+        snippet(&db, "one", "a");
+        assert!(db.merge_tags(&[tag_id(&db, "a")], 9_999).is_err());
+        assert_eq!(db.get_all_tags().unwrap().len(), 1);
+    }
+}
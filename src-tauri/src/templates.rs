@@ -0,0 +1,617 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::{SnippetRender, TemplateVariable, TemplateVariableKind};
+
+// Template syntax (VS Code compatible where the two overlap):
+//
+//   ${name}  ${name:default}  ${name|one,two,three|}   named variables
+//   $1  ${1}  ${1:default}  ${1|one,two|}  $0           tab stops ($0 is the final cursor)
+//   $UUID  ${DATE:%d.%m.%Y}  ${GIT_BRANCH:~/src/app}    built-ins
+//
+// Defaults may contain further placeholders. `\$`, `\}` and `\\` are literal.
+// A bare `$word` that is not a built-in (e.g. `$HOME` in a shell snippet) is
+// left untouched.
+
+const BUILTINS: &[&str] = &[
+    "CLIPBOARD",
+    "UUID",
+    "DATE",
+    "TIME",
+    "DATETIME",
+    "CURRENT_YEAR",
+    "CURRENT_YEAR_SHORT",
+    "CURRENT_MONTH",
+    "CURRENT_MONTH_NAME",
+    "CURRENT_MONTH_NAME_SHORT",
+    "CURRENT_DATE",
+    "CURRENT_DAY_NAME",
+    "CURRENT_DAY_NAME_SHORT",
+    "CURRENT_HOUR",
+    "CURRENT_MINUTE",
+    "CURRENT_SECOND",
+    "CURRENT_SECONDS_UNIX",
+    "GIT_BRANCH",
+];
+
+/// Built-in whose argument must be supplied by the user when left out
+const GIT_BRANCH: &str = "GIT_BRANCH";
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Placeholder {
+        name: String,
+        default: Option<Vec<Node>>,
+        choices: Vec<String>,
+    },
+    Builtin {
+        name: String,
+        arg: Option<String>,
+    },
+    /// `$0`: where the cursor ends up; renders as nothing
+    Cursor,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Self {
+        Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    /// Parse until the end of input, or until an unmatched `}` when inside a default
+    fn parse_nodes(&mut self, in_default: bool) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' if matches!(self.peek_at(1), Some('$' | '}' | '\\')) => {
+                    text.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                '}' if in_default => break,
+                '$' => match self.parse_dollar() {
+                    Some(node) => {
+                        if !text.is_empty() {
+                            nodes.push(Node::Text(std::mem::take(&mut text)));
+                        }
+                        nodes.push(node);
+                    }
+                    None => {
+                        text.push('$');
+                        self.pos += 1;
+                    }
+                },
+                _ => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        nodes
+    }
+
+    fn read_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn read_name(&mut self) -> String {
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.read_while(|c| c.is_ascii_digit())
+        } else if self.peek().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') {
+            self.read_while(|c| c.is_ascii_alphanumeric() || c == '_')
+        } else {
+            String::new()
+        }
+    }
+
+    /// Parse a placeholder starting at `$`. On anything malformed the position
+    /// is restored and `None` returned so the `$` is kept as text.
+    fn parse_dollar(&mut self) -> Option<Node> {
+        let start = self.pos;
+        self.pos += 1;
+
+        if self.peek() != Some('{') {
+            let name = self.read_name();
+            let node = if name == "0" {
+                Some(Node::Cursor)
+            } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+                Some(placeholder(name, None, Vec::new()))
+            } else if BUILTINS.contains(&name.as_str()) {
+                Some(Node::Builtin { name, arg: None })
+            } else {
+                None
+            };
+
+            if node.is_none() {
+                self.pos = start;
+            }
+            return node;
+        }
+
+        self.pos += 1;
+        let name = self.read_name();
+        if name.is_empty() {
+            self.pos = start;
+            return None;
+        }
+        let is_builtin = BUILTINS.contains(&name.as_str());
+
+        let node = match self.peek() {
+            Some('}') => {
+                self.pos += 1;
+                Some(if name == "0" {
+                    Node::Cursor
+                } else if is_builtin {
+                    Node::Builtin { name, arg: None }
+                } else {
+                    placeholder(name, None, Vec::new())
+                })
+            }
+            Some(':') if is_builtin => {
+                self.pos += 1;
+                let arg = self.read_while(|c| c != '}');
+                (self.peek() == Some('}')).then(|| {
+                    self.pos += 1;
+                    Node::Builtin { name, arg: Some(arg) }
+                })
+            }
+            Some(':') => {
+                self.pos += 1;
+                let default = self.parse_nodes(true);
+                (self.peek() == Some('}')).then(|| {
+                    self.pos += 1;
+                    if name == "0" {
+                        Node::Cursor
+                    } else {
+                        placeholder(name, Some(default), Vec::new())
+                    }
+                })
+            }
+            Some('|') => {
+                self.pos += 1;
+                self.parse_choices().map(|choices| placeholder(name, None, choices))
+            }
+            _ => None,
+        };
+
+        if node.is_none() {
+            self.pos = start;
+        }
+        node
+    }
+
+    /// Parse `one,two|}` (after the opening `|`); `\,` and `\|` are literal
+    fn parse_choices(&mut self) -> Option<Vec<String>> {
+        let mut choices = Vec::new();
+        let mut current = String::new();
+
+        loop {
+            match self.peek()? {
+                '\\' if matches!(self.peek_at(1), Some(',' | '|' | '\\')) => {
+                    current.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                ',' => {
+                    choices.push(std::mem::take(&mut current));
+                    self.pos += 1;
+                }
+                '|' if self.peek_at(1) == Some('}') => {
+                    choices.push(current);
+                    self.pos += 2;
+                    return Some(choices);
+                }
+                c => {
+                    current.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn placeholder(name: String, default: Option<Vec<Node>>, choices: Vec<String>) -> Node {
+    Node::Placeholder { name, default, choices }
+}
+
+fn parse(content: &str) -> Vec<Node> {
+    Parser::new(content).parse_nodes(false)
+}
+
+/// Text of a default with nested placeholders replaced by their own defaults
+fn default_text(nodes: &[Node]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Text(text) => text.clone(),
+            Node::Placeholder { default: Some(default), .. } => default_text(default),
+            Node::Placeholder { choices, .. } => choices.first().cloned().unwrap_or_default(),
+            Node::Builtin { name, arg } => format!("${{{}{}}}", name, arg.as_ref().map(|a| format!(":{}", a)).unwrap_or_default()),
+            Node::Cursor => String::new(),
+        })
+        .collect()
+}
+
+fn collect_variables(nodes: &[Node], variables: &mut Vec<TemplateVariable>) {
+    for node in nodes {
+        let variable = match node {
+            Node::Placeholder { name, default, choices } => {
+                if let Some(default) = default {
+                    collect_variables(default, variables);
+                }
+                TemplateVariable {
+                    name: name.clone(),
+                    default: default
+                        .as_deref()
+                        .map(default_text)
+                        .or_else(|| choices.first().cloned()),
+                    kind: if choices.is_empty() {
+                        TemplateVariableKind::Text
+                    } else {
+                        TemplateVariableKind::Choice
+                    },
+                    choices: choices.clone(),
+                }
+            }
+            Node::Builtin { name, arg: None } if name == GIT_BRANCH => TemplateVariable {
+                name: name.clone(),
+                default: None,
+                kind: TemplateVariableKind::Directory,
+                choices: Vec::new(),
+            },
+            _ => continue,
+        };
+
+        match variables.iter_mut().find(|v| v.name == variable.name) {
+            // Mirrored occurrences: the first one with a default or choices wins
+            Some(existing) => {
+                if existing.default.is_none() {
+                    existing.default = variable.default;
+                }
+                if existing.choices.is_empty() && !variable.choices.is_empty() {
+                    existing.kind = variable.kind;
+                    existing.choices = variable.choices;
+                }
+            }
+            None => variables.push(variable),
+        }
+    }
+}
+
+/// Named variables in order of appearance, followed by tab stops in numeric order
+fn ordered(variables: Vec<TemplateVariable>) -> Vec<TemplateVariable> {
+    let tab_stop = |v: &TemplateVariable| v.name.parse::<u32>().ok();
+    let (mut tab_stops, mut named): (Vec<_>, Vec<_>) = variables.into_iter().partition(|v| tab_stop(v).is_some());
+    tab_stops.sort_by_key(|v| tab_stop(v));
+    named.extend(tab_stops);
+    named
+}
+
+/// A piece of a template, for converting to other snippet formats
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    /// A named variable or tab stop. Defaults are flattened to text.
+    Variable { name: String, default: Option<String>, choices: Vec<String> },
+    Builtin { name: String, arg: Option<String> },
+    Cursor,
+}
+
+/// Split a template into literal text and placeholders
+pub fn parts(content: &str) -> Vec<TemplatePart> {
+    parse(content)
+        .into_iter()
+        .map(|node| match node {
+            Node::Text(text) => TemplatePart::Text(text),
+            Node::Placeholder { name, default, choices } => TemplatePart::Variable {
+                name,
+                default: default.as_deref().map(default_text),
+                choices,
+            },
+            Node::Builtin { name, arg } => TemplatePart::Builtin { name, arg },
+            Node::Cursor => TemplatePart::Cursor,
+        })
+        .collect()
+}
+
+/// Whether `$name` is a built-in variable
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.contains(&name)
+}
+
+/// Escape literal text so it renders as itself, inside a default when
+/// `in_default` is set. A `$` is only escaped where it would start a placeholder.
+pub fn escape(text: &str, in_default: bool) -> String {
+    let mut parser = Parser::new(text);
+    let mut escaped = String::with_capacity(text.len());
+
+    while let Some(c) = parser.peek() {
+        let start = parser.pos;
+        match c {
+            '\\' if matches!(parser.peek_at(1), Some('$' | '}' | '\\')) => escaped.push_str("\\\\"),
+            '}' if in_default => escaped.push_str("\\}"),
+            '$' if parser.parse_dollar().is_some() => escaped.push_str("\\$"),
+            _ => escaped.push(c),
+        }
+        parser.pos = start + 1;
+    }
+
+    escaped
+}
+
+/// Source text of a variable placeholder, the inverse of `TemplatePart::Variable`
+pub fn variable_source(name: &str, default: Option<&str>, choices: &[String]) -> String {
+    if !choices.is_empty() {
+        let choices: Vec<String> = choices
+            .iter()
+            .map(|c| c.replace('\\', "\\\\").replace(',', "\\,").replace('|', "\\|"))
+            .collect();
+        return format!("${{{}|{}|}}", name, choices.join(","));
+    }
+
+    match default {
+        Some(default) => format!("${{{}:{}}}", name, escape(default, true)),
+        None => format!("${{{}}}", name),
+    }
+}
+
+fn git_branch(dir: &str) -> Result<String> {
+    let dir = match dir.strip_prefix("~") {
+        Some(rest) => dirs::home_dir()
+            .ok_or_else(|| anyhow!("Could not find home directory"))?
+            .join(rest.trim_start_matches(['/', '\\'])),
+        None => Path::new(dir).to_path_buf(),
+    };
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&dir)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{} is not a git repository", dir.display()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn builtin(name: &str, arg: Option<&str>) -> Result<String> {
+    let now = chrono::Local::now();
+    let format = |fmt: &str| now.format(fmt).to_string();
+
+    Ok(match name {
+        "CLIPBOARD" => arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_text())
+            .unwrap_or_default(),
+        "UUID" => uuid::Uuid::new_v4().to_string(),
+        "DATE" => format(arg.unwrap_or("%Y-%m-%d")),
+        "TIME" => format(arg.unwrap_or("%H:%M:%S")),
+        "DATETIME" => match arg {
+            Some(fmt) => format(fmt),
+            None => now.to_rfc3339(),
+        },
+        "CURRENT_YEAR" => format("%Y"),
+        "CURRENT_YEAR_SHORT" => format("%y"),
+        "CURRENT_MONTH" => format("%m"),
+        "CURRENT_MONTH_NAME" => format("%B"),
+        "CURRENT_MONTH_NAME_SHORT" => format("%b"),
+        "CURRENT_DATE" => format("%d"),
+        "CURRENT_DAY_NAME" => format("%A"),
+        "CURRENT_DAY_NAME_SHORT" => format("%a"),
+        "CURRENT_HOUR" => format("%H"),
+        "CURRENT_MINUTE" => format("%M"),
+        "CURRENT_SECOND" => format("%S"),
+        "CURRENT_SECONDS_UNIX" => now.timestamp().to_string(),
+        GIT_BRANCH => git_branch(arg.ok_or_else(|| anyhow!("GIT_BRANCH needs a directory"))?)?,
+        _ => return Err(anyhow!("Unknown built-in variable: {}", name)),
+    })
+}
+
+/// `shared` holds each variable's default, used by mirrored occurrences that
+/// have none of their own
+fn render_nodes(
+    nodes: &[Node],
+    values: &HashMap<String, String>,
+    shared: &HashMap<&str, &str>,
+    output: &mut String,
+) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Placeholder { name, default, choices } => {
+                if let Some(value) = values.get(name) {
+                    output.push_str(value);
+                } else if let Some(default) = default {
+                    render_nodes(default, values, shared, output)?;
+                } else if let Some(first) = choices.first() {
+                    output.push_str(first);
+                } else if let Some(default) = shared.get(name.as_str()) {
+                    output.push_str(default);
+                }
+            }
+            Node::Builtin { name, arg } => {
+                // A GIT_BRANCH without a directory takes the one the user picked
+                let arg = arg.as_deref().or_else(|| values.get(name).map(String::as_str));
+                output.push_str(&builtin(name, arg)?);
+            }
+            Node::Cursor => {}
+        }
+    }
+    Ok(())
+}
+
+/// Expand a template. With `values` set to `None`, templates that have any
+/// variables return `NeedsInput` so the user can review them first. With
+/// values, anything missing falls back to its default, and only variables
+/// without one still need input.
+pub fn render(content: &str, values: Option<&HashMap<String, String>>) -> Result<SnippetRender> {
+    let nodes = parse(content);
+    let mut variables = Vec::new();
+    collect_variables(&nodes, &mut variables);
+
+    let needs_input = match values {
+        None => !variables.is_empty(),
+        Some(values) => variables
+            .iter()
+            .any(|v| !values.contains_key(&v.name) && v.default.is_none()),
+    };
+
+    if needs_input {
+        return Ok(SnippetRender::NeedsInput {
+            variables: ordered(variables),
+        });
+    }
+
+    let shared = variables
+        .iter()
+        .filter_map(|v| Some((v.name.as_str(), v.default.as_deref()?)))
+        .collect();
+    let mut output = String::with_capacity(content.len());
+    render_nodes(&nodes, values.unwrap_or(&HashMap::new()), &shared, &mut output)?;
+
+    Ok(SnippetRender::Rendered { content: output })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(content: &str, values: &[(&str, &str)]) -> String {
+        let values: HashMap<String, String> = values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        match render(content, Some(&values)).unwrap() {
+            SnippetRender::Rendered { content } => content,
+            SnippetRender::NeedsInput { variables } => panic!("unexpected input request for {:?}", variables),
+        }
+    }
+
+    fn variables(content: &str) -> Vec<TemplateVariable> {
+        let mut variables = Vec::new();
+        collect_variables(&parse(content), &mut variables);
+        ordered(variables)
+    }
+
+    fn names(content: &str) -> Vec<String> {
+        variables(content).into_iter().map(|v| v.name).collect()
+    }
+
+    #[test]
+    fn default_values_fill_missing_variables() {
+        assert_eq!(rendered("${name:World}", &[]), "World");
+        assert_eq!(rendered("${name:World}", &[("name", "Ada")]), "Ada");
+        assert_eq!(rendered("${1:foo} ${2|a,b|}$0", &[]), "foo a");
+        assert_eq!(rendered("${empty:}", &[]), "");
+    }
+
+    #[test]
+    fn nested_defaults() {
+        let content = "${greeting:Hello ${name:World}}!";
+        assert_eq!(rendered(content, &[]), "Hello World!");
+        assert_eq!(rendered(content, &[("name", "Ada")]), "Hello Ada!");
+        assert_eq!(rendered(content, &[("greeting", "Bye")]), "Bye!");
+
+        let vars = variables(content);
+        assert_eq!(names(content), ["name", "greeting"]);
+        assert_eq!(vars[1].default.as_deref(), Some("Hello World"));
+    }
+
+    #[test]
+    fn mirrored_variables_share_the_first_default() {
+        let vars = variables("${x} ${x:one} ${x:two}");
+        assert_eq!(vars.len(), 1);
+        assert_eq!(vars[0].default.as_deref(), Some("one"));
+        assert_eq!(rendered("${x} ${x:one}", &[]), "one one");
+    }
+
+    #[test]
+    fn choices() {
+        let vars = variables("${env|dev,prod|} ${sep|a\\,b,c\\|d|}");
+        assert_eq!(vars[0].kind, TemplateVariableKind::Choice);
+        assert_eq!(vars[0].choices, ["dev", "prod"]);
+        assert_eq!(vars[1].choices, ["a,b", "c|d"]);
+        assert_eq!(rendered("${env|dev,prod|}", &[]), "dev");
+    }
+
+    #[test]
+    fn named_variables_come_before_tab_stops() {
+        assert_eq!(names("$2 ${name} $1 ${10}"), ["name", "1", "2", "10"]);
+    }
+
+    #[test]
+    fn escapes_are_literal() {
+        assert_eq!(rendered("\\${name} costs \\$5", &[]), "${name} costs $5");
+        assert_eq!(rendered("a\\\\b", &[]), "a\\b");
+        assert_eq!(rendered("${a:x\\}y}", &[]), "x}y");
+        // Backslashes before anything else are kept as typed
+        assert_eq!(rendered("C:\\temp\\n", &[]), "C:\\temp\\n");
+        assert!(variables("\\${name}").is_empty());
+    }
+
+    #[test]
+    fn malformed_placeholders_are_kept_as_text() {
+        for content in ["${name", "${}", "${name:abc", "${x|a,b}", "${-x}", "$", "${", "echo $HOME"] {
+            assert_eq!(rendered(content, &[]), content, "{content:?}");
+            assert!(variables(content).is_empty(), "{content:?}");
+        }
+        // Text after a malformed placeholder still parses
+        assert_eq!(rendered("${oops ${name:ok}", &[]), "${oops ok");
+    }
+
+    #[test]
+    fn missing_values_need_input() {
+        assert!(matches!(render("${name}", None).unwrap(), SnippetRender::NeedsInput { .. }));
+        assert!(matches!(render("${name}", Some(&HashMap::new())).unwrap(), SnippetRender::NeedsInput { .. }));
+        // Defaults are shown for review when no values are given at all
+        assert!(matches!(render("${name:x}", None).unwrap(), SnippetRender::NeedsInput { .. }));
+        assert!(matches!(render("plain text", None).unwrap(), SnippetRender::Rendered { .. }));
+    }
+
+    #[test]
+    fn builtins() {
+        assert_eq!(rendered("$UUID", &[]).len(), 36);
+        assert_eq!(rendered("${DATE:%Y}", &[]), chrono::Local::now().format("%Y").to_string());
+        assert!(variables("$UUID ${DATE:%Y}").is_empty());
+
+        let vars = variables("${GIT_BRANCH}");
+        assert_eq!(vars[0].kind, TemplateVariableKind::Directory);
+    }
+
+    #[test]
+    fn variable_source_round_trips() {
+        for default in ["plain", "a}b", "costs $5", "${not_a_var}", "back\\slash"] {
+            let source = variable_source("v", Some(default), &[]);
+            assert_eq!(rendered(&source, &[]), default, "{source:?}");
+        }
+
+        let choices = vec!["a,b".to_string(), "c|d".to_string()];
+        let source = variable_source("v", None, &choices);
+        assert_eq!(variables(&source)[0].choices, choices);
+    }
+}
@@ -0,0 +1,454 @@
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// A named text transformation that can be chained with others
+pub struct Transform {
+    pub name: &'static str,
+    pub description: &'static str,
+    apply: fn(&str) -> Result<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// Either a single transform name or a list applied left to right.
+/// A single name may also chain steps with `|`, e.g. `"trim|json_pretty"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TransformPipeline {
+    Single(String),
+    Chain(Vec<String>),
+}
+
+/// What to do with the transformed text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformOutput {
+    /// Only return the result
+    #[default]
+    Preview,
+    /// Paste into the previous app without touching clipboard history
+    Paste,
+    PastePlain,
+    /// Store the result as a new clipboard history entry
+    Save,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformResult {
+    pub content: String,
+    pub entry_id: Option<i64>,
+}
+
+impl TransformPipeline {
+    fn steps(&self) -> Vec<&str> {
+        match self {
+            TransformPipeline::Single(name) => name.split('|').map(str::trim).collect(),
+            TransformPipeline::Chain(names) => names.iter().map(|n| n.trim()).collect(),
+        }
+    }
+}
+
+const TRANSFORMS: &[Transform] = &[
+    Transform { name: "json_pretty", description: "Pretty-print JSON", apply: json_pretty },
+    Transform { name: "json_minify", description: "Minify JSON", apply: json_minify },
+    Transform { name: "base64_encode", description: "Base64 encode", apply: base64_encode },
+    Transform { name: "base64_decode", description: "Base64 decode", apply: base64_decode },
+    Transform { name: "url_encode", description: "Percent-encode for URLs", apply: url_encode },
+    Transform { name: "url_decode", description: "Decode percent-encoding", apply: url_decode },
+    Transform { name: "uppercase", description: "UPPER CASE", apply: uppercase },
+    Transform { name: "lowercase", description: "lower case", apply: lowercase },
+    Transform { name: "title_case", description: "Title Case", apply: title_case },
+    Transform { name: "camel_case", description: "camelCase", apply: camel_case },
+    Transform { name: "pascal_case", description: "PascalCase", apply: pascal_case },
+    Transform { name: "snake_case", description: "snake_case", apply: snake_case },
+    Transform { name: "kebab_case", description: "kebab-case", apply: kebab_case },
+    Transform { name: "constant_case", description: "CONSTANT_CASE", apply: constant_case },
+    Transform { name: "trim", description: "Trim surrounding and trailing whitespace", apply: trim },
+    Transform { name: "shell_escape", description: "Quote as a single shell argument", apply: shell_escape },
+    Transform { name: "json_escape", description: "Escape as a JSON string literal", apply: json_escape },
+    Transform { name: "sql_in_list", description: "Convert lines to a SQL IN (...) list", apply: sql_in_list },
+    Transform { name: "sort_lines", description: "Sort lines alphabetically", apply: sort_lines },
+    Transform { name: "dedupe_lines", description: "Remove duplicate lines, keeping the first", apply: dedupe_lines },
+];
+
+pub fn list_transforms() -> Vec<TransformInfo> {
+    TRANSFORMS
+        .iter()
+        .map(|t| TransformInfo {
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+        })
+        .collect()
+}
+
+fn find_transform(name: &str) -> Option<&'static Transform> {
+    TRANSFORMS.iter().find(|t| t.name == name)
+}
+
+/// Apply every step of the pipeline in order
+pub fn apply_pipeline(input: &str, pipeline: &TransformPipeline) -> Result<String> {
+    let steps = pipeline.steps();
+    if steps.iter().all(|s| s.is_empty()) {
+        return Err(anyhow!("No transform given"));
+    }
+
+    let mut output = input.to_string();
+    for name in steps.into_iter().filter(|s| !s.is_empty()) {
+        let transform = find_transform(name).ok_or_else(|| anyhow!("Unknown transform: {}", name))?;
+        output = (transform.apply)(&output).with_context(|| format!("Transform '{}' failed", name))?;
+    }
+
+    Ok(output)
+}
+
+fn json_pretty(input: &str) -> Result<String> {
+    let value: serde_json::Value = serde_json::from_str(input.trim()).context("Input is not valid JSON")?;
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+fn json_minify(input: &str) -> Result<String> {
+    let value: serde_json::Value = serde_json::from_str(input.trim()).context("Input is not valid JSON")?;
+    Ok(serde_json::to_string(&value)?)
+}
+
+fn base64_encode(input: &str) -> Result<String> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(input.as_bytes()))
+}
+
+fn base64_decode(input: &str) -> Result<String> {
+    let cleaned: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    // Accept both standard and URL-safe alphabets, with or without padding
+    let normalized = cleaned.replace('-', "+").replace('_', "/");
+    let normalized = normalized.trim_end_matches('=');
+    let bytes = base64::engine::general_purpose::STANDARD_NO_PAD
+        .decode(normalized)
+        .context("Input is not valid base64")?;
+    String::from_utf8(bytes).context("Decoded data is not valid UTF-8 text")
+}
+
+fn url_encode(input: &str) -> Result<String> {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    Ok(encoded)
+}
+
+/// Malformed escapes like `%zz` and a trailing `%` are kept as they are. If
+/// the escapes don't decode to UTF-8 text, the input is returned unchanged.
+fn url_decode(input: &str) -> Result<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3])?;
+                decoded.push(u8::from_str_radix(hex, 16)?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(String::from_utf8(decoded).unwrap_or_else(|_| input.to_string()))
+}
+
+fn uppercase(input: &str) -> Result<String> {
+    Ok(input.to_uppercase())
+}
+
+fn lowercase(input: &str) -> Result<String> {
+    Ok(input.to_lowercase())
+}
+
+fn title_case(input: &str) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut at_word_start = true;
+
+    for c in input.chars() {
+        if c.is_alphanumeric() || c == '\'' {
+            if at_word_start {
+                output.extend(c.to_uppercase());
+            } else {
+                output.extend(c.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            output.push(c);
+            at_word_start = true;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Split identifiers and phrases into lowercase words, so `fooBar`, `foo_bar`,
+/// `foo-bar` and `Foo Bar` all become `["foo", "bar"]`
+fn split_words(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = input.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        let starts_new_word = c.is_uppercase()
+            && !current.is_empty()
+            && (prev.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+                || next.is_some_and(|n| n.is_lowercase()));
+
+        if starts_new_word {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn camel_case(input: &str) -> Result<String> {
+    let words = split_words(input);
+    Ok(words
+        .iter()
+        .enumerate()
+        .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+        .collect())
+}
+
+fn pascal_case(input: &str) -> Result<String> {
+    Ok(split_words(input).iter().map(|w| capitalize(w)).collect())
+}
+
+fn snake_case(input: &str) -> Result<String> {
+    Ok(split_words(input).join("_"))
+}
+
+fn kebab_case(input: &str) -> Result<String> {
+    Ok(split_words(input).join("-"))
+}
+
+fn constant_case(input: &str) -> Result<String> {
+    Ok(split_words(input).join("_").to_uppercase())
+}
+
+fn trim(input: &str) -> Result<String> {
+    Ok(input
+        .trim()
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn shell_escape(input: &str) -> Result<String> {
+    Ok(format!("'{}'", input.replace('\'', r"'\''")))
+}
+
+fn json_escape(input: &str) -> Result<String> {
+    Ok(serde_json::to_string(input)?)
+}
+
+fn sql_in_list(input: &str) -> Result<String> {
+    let values: Vec<&str> = input
+        .split(['\n', ','])
+        .map(|v| v.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .filter(|v| !v.is_empty())
+        .collect();
+
+    if values.is_empty() {
+        return Err(anyhow!("No values to convert"));
+    }
+
+    // Leave the list unquoted only when every value is numeric
+    let all_numeric = values.iter().all(|v| v.parse::<f64>().is_ok());
+    let items: Vec<String> = values
+        .iter()
+        .map(|v| {
+            if all_numeric {
+                v.to_string()
+            } else {
+                format!("'{}'", v.replace('\'', "''"))
+            }
+        })
+        .collect();
+
+    Ok(format!("({})", items.join(", ")))
+}
+
+fn sort_lines(input: &str) -> Result<String> {
+    let mut lines: Vec<&str> = input.lines().collect();
+    lines.sort_by_key(|l| l.to_lowercase());
+    Ok(lines.join("\n"))
+}
+
+fn dedupe_lines(input: &str) -> Result<String> {
+    let mut seen = std::collections::HashSet::new();
+    Ok(input
+        .lines()
+        .filter(|l| seen.insert(*l))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(name: &str, input: &str) -> String {
+        apply_pipeline(input, &TransformPipeline::Single(name.to_string())).unwrap()
+    }
+
+    #[test]
+    fn every_transform_is_tested() {
+        // Keep in sync with the tests below when adding a transform
+        assert_eq!(list_transforms().len(), 20);
+    }
+
+    #[test]
+    fn json_pretty_and_minify_round_trip() {
+        let minified = r#"{"a":[1,2,{"b":null}],"c":"d"}"#;
+        let pretty = run("json_pretty", minified);
+        assert!(pretty.contains("\n  \"a\": ["));
+        assert_eq!(run("json_minify", &pretty), minified);
+        assert_eq!(run("json_pretty", &pretty), pretty);
+        assert!(apply_pipeline("{oops", &TransformPipeline::Single("json_pretty".into())).is_err());
+        assert!(apply_pipeline("{oops", &TransformPipeline::Single("json_minify".into())).is_err());
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        for input in ["", "hello", "héllo wörld ✓", "line\nbreak"] {
+            assert_eq!(run("base64_decode", &run("base64_encode", input)), input);
+        }
+        assert_eq!(run("base64_encode", "hi?"), "aGk/");
+        // URL-safe alphabet, missing padding and wrapped lines are accepted
+        assert_eq!(run("base64_decode", "aGk_"), "hi?");
+        assert_eq!(run("base64_decode", "aGV sbG8"), "hello");
+    }
+
+    #[test]
+    fn url_encoding_round_trips() {
+        for input in ["", "a b&c=d/e?f", "100% sure", "ünïcødé ✓", "safe-_.~"] {
+            assert_eq!(run("url_decode", &run("url_encode", input)), input);
+        }
+        assert_eq!(run("url_encode", "a b"), "a%20b");
+        assert_eq!(run("url_decode", "a+b%2Bc"), "a b+c");
+    }
+
+    #[test]
+    fn url_decode_keeps_malformed_escapes() {
+        assert_eq!(run("url_decode", "100%"), "100%");
+        assert_eq!(run("url_decode", "50%zz off"), "50%zz off");
+        assert_eq!(run("url_decode", "%4"), "%4");
+        assert_eq!(run("url_decode", "%é1"), "%é1");
+        assert_eq!(run("url_decode", "%FF%41"), "%FF%41");
+    }
+
+    #[test]
+    fn letter_case_round_trips() {
+        assert_eq!(run("uppercase", "Hello, wörld"), "HELLO, WÖRLD");
+        assert_eq!(run("lowercase", "Hello, WÖRLD"), "hello, wörld");
+        assert_eq!(run("lowercase", &run("uppercase", "mixed Case")), "mixed case");
+        assert_eq!(run("title_case", "the QUICK brown fox's tail"), "The Quick Brown Fox's Tail");
+        assert_eq!(run("title_case", &run("title_case", "a b")), "A B");
+    }
+
+    #[test]
+    fn identifier_cases_round_trip() {
+        let words = "parse HTTPResponse code2 value";
+        let snake = run("snake_case", words);
+        assert_eq!(snake, "parse_http_response_code2_value");
+        assert_eq!(run("camel_case", &snake), "parseHttpResponseCode2Value");
+        assert_eq!(run("pascal_case", &snake), "ParseHttpResponseCode2Value");
+        assert_eq!(run("kebab_case", &snake), "parse-http-response-code2-value");
+        assert_eq!(run("constant_case", &snake), "PARSE_HTTP_RESPONSE_CODE2_VALUE");
+
+        for name in ["camel_case", "pascal_case", "kebab_case", "constant_case"] {
+            assert_eq!(run("snake_case", &run(name, &snake)), snake, "{}", name);
+        }
+    }
+
+    #[test]
+    fn trim_is_idempotent() {
+        let trimmed = run("trim", "  \n  first  \nsecond\t\n\n");
+        assert_eq!(trimmed, "first\nsecond");
+        assert_eq!(run("trim", &trimmed), trimmed);
+    }
+
+    #[test]
+    fn shell_escape_quotes_one_argument() {
+        assert_eq!(run("shell_escape", "it's here"), r"'it'\''s here'");
+        assert_eq!(run("shell_escape", ""), "''");
+    }
+
+    #[test]
+    fn json_escape_round_trips() {
+        for input in ["", "quote \" and \\ backslash", "tab\tnew\nline", "ünïcødé"] {
+            let escaped = run("json_escape", input);
+            assert_eq!(serde_json::from_str::<String>(&escaped).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn sql_in_list_quotes_unless_numeric() {
+        assert_eq!(run("sql_in_list", "1\n2, 3\n"), "(1, 2, 3)");
+        assert_eq!(run("sql_in_list", "apple\n'pear'\nO'Brien"), "('apple', 'pear', 'O''Brien')");
+        assert!(apply_pipeline(" \n,", &TransformPipeline::Single("sql_in_list".into())).is_err());
+    }
+
+    #[test]
+    fn sort_and_dedupe_lines() {
+        assert_eq!(run("sort_lines", "pear\nApple\nbanana"), "Apple\nbanana\npear");
+        assert_eq!(run("dedupe_lines", "b\na\nb\na\nc"), "b\na\nc");
+        let sorted = run("sort_lines", "b\na\nb");
+        assert_eq!(run("sort_lines", &sorted), sorted);
+    }
+
+    #[test]
+    fn pipelines_chain_steps_in_order() {
+        assert_eq!(run("trim|uppercase|url_encode", "  a b "), "A%20B");
+        let chain = TransformPipeline::Chain(vec!["base64_encode".into(), "base64_decode".into()]);
+        assert_eq!(apply_pipeline("same", &chain).unwrap(), "same");
+        assert!(apply_pipeline("x", &TransformPipeline::Single("nope".into())).is_err());
+        assert!(apply_pipeline("x", &TransformPipeline::Single(" | ".into())).is_err());
+    }
+}
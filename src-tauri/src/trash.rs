@@ -0,0 +1,78 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::database::Database;
+use crate::file_storage::FileStorageManager;
+use crate::models::TrashItemKind;
+use crate::AppState;
+
+/// How often expired trash is looked for
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Permanently delete one trashed item. File blobs are removed only after
+/// their row is gone, so a failed purge never leaves a row without its data.
+pub fn purge_item(db: &Database, kind: TrashItemKind, id: i64) -> Result<(), String> {
+    match kind {
+        TrashItemKind::Snippet => db.purge_snippet(id).map_err(|e| e.to_string()),
+        TrashItemKind::Folder => db.purge_folder(id).map_err(|e| e.to_string()),
+        TrashItemKind::File => {
+            let file = db.get_file(id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "File not found".to_string())?;
+
+            db.purge_file(id).map_err(|e| e.to_string())?;
+
+            let storage_manager = FileStorageManager::new()
+                .map_err(|e| format!("Failed to initialize storage: {}", e))?;
+            if let Err(e) = storage_manager.delete_file(&file.storage_path) {
+                eprintln!("Failed to delete file {} from storage: {}", file.storage_path, e);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Permanently delete trashed items older than `cutoff` (RFC 3339), or
+/// everything in the trash with `None`. Returns how many were deleted.
+pub fn purge(db: &Database, cutoff: Option<&str>) -> Result<usize, String> {
+    let items = db.get_trashed_items(cutoff).map_err(|e| e.to_string())?;
+
+    for (kind, id) in &items {
+        purge_item(db, *kind, *id)?;
+    }
+
+    Ok(items.len())
+}
+
+/// Apply the trash retention period once
+pub fn purge_expired(app_handle: &AppHandle) -> Result<usize, String> {
+    let state = app_handle.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let settings = db.get_trash_settings().map_err(|e| e.to_string())?;
+    let Some(retention_days) = settings.retention_days else {
+        return Ok(0);
+    };
+
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(retention_days.max(0))).to_rfc3339();
+    let purged = purge(&db, Some(&cutoff))?;
+
+    if purged > 0 {
+        println!("🗑️  Permanently deleted {} expired trash items", purged);
+        let _ = app_handle.emit("trash-purged", purged);
+    }
+
+    Ok(purged)
+}
+
+/// Spawn the background task that empties expired trash
+pub fn spawn_trash_purger(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = purge_expired(&app_handle) {
+                eprintln!("Failed to purge trash: {}", e);
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
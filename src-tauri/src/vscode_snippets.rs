@@ -0,0 +1,281 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::models::Snippet;
+use crate::tags;
+
+/// VS Code language IDs that NoBrainDev names differently, as (VS Code, ours)
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("shellscript", "bash"),
+    ("javascriptreact", "javascript"),
+    ("typescriptreact", "typescript"),
+];
+
+/// One entry of a VS Code snippets file. `prefix` and `body` may be a string
+/// or an array of strings.
+#[derive(Debug, Serialize, Deserialize)]
+struct VsCodeSnippet {
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    prefix: Value,
+    body: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Comma-separated language IDs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+}
+
+/// An entry that couldn't be imported, by name with the reason
+pub type SkippedEntry = (String, String);
+
+/// Whether `path` looks like a VS Code snippets file
+pub fn is_snippets_file(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("json") | Some("code-snippets"))
+}
+
+/// Parse a VS Code snippets file into unsaved snippets. Language-specific
+/// files (`<language>.json`) give every snippet that language; global
+/// `.code-snippets` files use each snippet's first `scope`, or leave the
+/// language as `auto` to be detected. The first prefix becomes the trigger
+/// and any further prefixes become tags.
+pub fn parse(text: &str, file_name: &str) -> Result<(Vec<Snippet>, Vec<SkippedEntry>)> {
+    let entries: Map<String, Value> = serde_json::from_str(&strip_jsonc(text))
+        .context("Not a VS Code snippets file")?;
+
+    let path = Path::new(file_name);
+    let file_language = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => path.file_stem().and_then(|s| s.to_str()).map(from_vscode_language),
+        _ => None,
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut snippets = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (name, value) in entries {
+        let entry: VsCodeSnippet = match serde_json::from_value(value) {
+            Ok(entry) => entry,
+            Err(e) => {
+                skipped.push((name, format!("Not a snippet: {}", e)));
+                continue;
+            }
+        };
+
+        let Some(body) = string_list(&entry.body) else {
+            skipped.push((name, "The body must be a string or a list of lines".to_string()));
+            continue;
+        };
+
+        let language = file_language.clone().unwrap_or_else(|| {
+            entry
+                .scope
+                .as_deref()
+                .and_then(|scope| scope.split(',').map(str::trim).find(|s| !s.is_empty()))
+                .map(from_vscode_language)
+                .unwrap_or_else(|| "auto".to_string())
+        });
+        let mut prefixes = string_list(&entry.prefix).unwrap_or_default().into_iter();
+        let trigger = prefixes.next().map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+        let tags: Vec<String> = prefixes.collect();
+
+        snippets.push(Snippet {
+            id: None,
+            title: name,
+            content: body.join("\n"),
+            language,
+            description: entry.description.filter(|d| !d.trim().is_empty()),
+            tags: tags::format_tags(&tags::normalize_tags(tags.iter().map(String::as_str))),
+            folder_id: None,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            trigger,
+            deleted_at: None,
+            secret: false,
+        });
+    }
+
+    Ok((snippets, skipped))
+}
+
+/// Render snippets as VS Code snippet files, one `<language>.json` per
+/// language. Entries already in `existing` (file name to its contents) are
+/// kept unless a snippet of the same name replaces them.
+///
+/// The first prefix is the snippet's trigger, or a slug of its title when it
+/// has none, followed by its tags; `parse` reads them back the same way.
+pub fn export(snippets: &[Snippet], existing: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>> {
+    // Entries per file, and the names this export has written to it
+    let mut files: BTreeMap<String, (Map<String, Value>, HashSet<String>)> = BTreeMap::new();
+
+    for snippet in snippets {
+        let file_name = format!("{}.json", to_vscode_language(&snippet.language));
+        if !files.contains_key(&file_name) {
+            let current = match existing.get(&file_name) {
+                Some(text) => serde_json::from_str(&strip_jsonc(text))
+                    .with_context(|| format!("{} is not a VS Code snippets file", file_name))?,
+                None => Map::new(),
+            };
+            files.insert(file_name.clone(), (current, HashSet::new()));
+        }
+        let (entries, written) = files.get_mut(&file_name).expect("inserted above");
+
+        let mut prefixes = vec![snippet.trigger.clone().unwrap_or_else(|| slug(&snippet.title))];
+        prefixes.extend(tags::parse_tags(snippet.tags.as_deref().unwrap_or_default()));
+
+        let entry = VsCodeSnippet {
+            prefix: if prefixes.len() == 1 { Value::from(prefixes[0].clone()) } else { Value::from(prefixes) },
+            body: Value::from(snippet.content.lines().collect::<Vec<_>>()),
+            description: snippet.description.clone().filter(|d| !d.trim().is_empty()),
+            scope: None,
+        };
+
+        // Two snippets with the same title in one export keep both
+        let mut name = snippet.title.clone();
+        let mut copy = 2;
+        while written.contains(&name) {
+            name = format!("{} ({})", snippet.title, copy);
+            copy += 1;
+        }
+        entries.insert(name.clone(), serde_json::to_value(entry)?);
+        written.insert(name);
+    }
+
+    files
+        .into_iter()
+        .map(|(name, (entries, _))| Ok((name, serde_json::to_string_pretty(&entries)? + "\n")))
+        .collect()
+}
+
+fn from_vscode_language(id: &str) -> String {
+    let id = id.trim().to_lowercase();
+    LANGUAGE_ALIASES
+        .iter()
+        .find(|(vscode, _)| *vscode == id)
+        .map(|(_, ours)| ours.to_string())
+        .unwrap_or(id)
+}
+
+fn to_vscode_language(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    match language.as_str() {
+        "" | "auto" | "general" => "plaintext".to_string(),
+        "bash" | "sh" | "zsh" => "shellscript".to_string(),
+        // The other aliases fold React variants into their base language,
+        // which is a VS Code language itself
+        _ => language,
+    }
+}
+
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(s) => Some(vec![s.clone()]),
+        Value::Array(items) => items.iter().map(|item| item.as_str().map(str::to_string)).collect(),
+        _ => None,
+    }
+}
+
+/// A prefix made from a title: lowercase words joined by dashes
+pub(crate) fn slug(title: &str) -> String {
+    let slug = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        "snippet".to_string()
+    } else {
+        slug
+    }
+}
+
+/// VS Code snippet files are JSON with comments and trailing commas
+fn strip_jsonc(text: &str) -> String {
+    let text = text.trim_start_matches('\u{feff}');
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    let mut in_string = false;
+
+    while let Some((i, c)) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next().map(|(_, escaped)| escaped)),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if text[i..].starts_with("//") || text[i..].starts_with("/*") => {
+                // Skip to the end of the comment
+                let rest = skip_whitespace_and_comments(&text[i..]);
+                let end = text.len() - rest.len();
+                while chars.next_if(|&(j, _)| j < end).is_some() {}
+                out.push(' ');
+            }
+            // A trailing comma is dropped when only whitespace and comments
+            // remain before the closing bracket
+            ',' if matches!(skip_whitespace_and_comments(&text[i + 1..]).chars().next(), Some('}') | Some(']')) => {}
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// `text` without leading whitespace and comments
+fn skip_whitespace_and_comments(text: &str) -> &str {
+    let mut rest = text.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix("//") {
+            rest = after.find('\n').map_or("", |i| &after[i..]).trim_start();
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.find("*/").map_or("", |i| &after[i + 2..]).trim_start();
+        } else {
+            return rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_prefix_is_the_trigger() {
+        let text = r#"{ "Log": { "prefix": ["clg", "log", "debug"], "body": "console.log($1)" } }"#;
+        let (snippets, skipped) = parse(text, "javascript.json").unwrap();
+
+        assert!(skipped.is_empty());
+        assert_eq!(snippets[0].trigger.as_deref(), Some("clg"));
+        assert_eq!(snippets[0].tags.as_deref(), Some("log, debug"));
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let (snippets, _) = parse(
+            r#"{ "Log": { "prefix": ["clg", "log"], "body": ["a", "b"], "description": "Log it" } }"#,
+            "javascript.json",
+        )
+        .unwrap();
+
+        let files = export(&snippets, &BTreeMap::new()).unwrap();
+        let (again, _) = parse(&files["javascript.json"], "javascript.json").unwrap();
+
+        assert_eq!(again[0].title, "Log");
+        assert_eq!(again[0].trigger.as_deref(), Some("clg"));
+        assert_eq!(again[0].tags.as_deref(), Some("log"));
+        assert_eq!(again[0].content, "a\nb");
+        assert_eq!(again[0].description.as_deref(), Some("Log it"));
+    }
+}
@@ -0,0 +1,72 @@
+-- Tables and policies for sync (see src-tauri/src/sync.rs). Each user only
+-- sees their own rows: the email in their access token has to match.
+
+-- The change log: encrypted versions of records, in the order they were pushed
+create table if not exists public.sync_changes (
+    seq bigint generated always as identity primary key,
+    user_email text not null,
+    uuid text not null,
+    kind text not null,
+    hlc text not null,
+    versions jsonb not null,
+    device text not null,
+    deleted boolean not null,
+    payload text
+);
+
+create index if not exists sync_changes_user_seq on public.sync_changes (user_email, seq);
+create index if not exists sync_changes_user_uuid on public.sync_changes (user_email, uuid);
+
+alter table public.sync_changes enable row level security;
+
+-- Versions are only ever added, and superseded ones removed
+drop policy if exists "Users read their sync changes" on public.sync_changes;
+create policy "Users read their sync changes" on public.sync_changes
+    for select to authenticated
+    using (user_email = auth.jwt() ->> 'email');
+
+drop policy if exists "Users add sync changes" on public.sync_changes;
+create policy "Users add sync changes" on public.sync_changes
+    for insert to authenticated
+    with check (user_email = auth.jwt() ->> 'email');
+
+drop policy if exists "Users remove their sync changes" on public.sync_changes;
+create policy "Users remove their sync changes" on public.sync_changes
+    for delete to authenticated
+    using (user_email = auth.jwt() ->> 'email');
+
+-- Each user's sync key bundle, sealed with their passphrase and recovery key
+create table if not exists public.sync_keys (
+    user_email text primary key,
+    bundle text not null,
+    updated_at timestamptz not null default now()
+);
+
+alter table public.sync_keys enable row level security;
+
+drop policy if exists "Users manage their sync keys" on public.sync_keys;
+create policy "Users manage their sync keys" on public.sync_keys
+    for all to authenticated
+    using (user_email = auth.jwt() ->> 'email')
+    with check (user_email = auth.jwt() ->> 'email');
+
+-- Encrypted file contents, under `<user id>/` in the `user-files` bucket
+insert into storage.buckets (id, name, public)
+values ('user-files', 'user-files', false)
+on conflict (id) do nothing;
+
+drop policy if exists "Users read their files" on storage.objects;
+create policy "Users read their files" on storage.objects
+    for select to authenticated
+    using (bucket_id = 'user-files' and (storage.foldername(name))[1] = auth.uid()::text);
+
+drop policy if exists "Users upload their files" on storage.objects;
+create policy "Users upload their files" on storage.objects
+    for insert to authenticated
+    with check (bucket_id = 'user-files' and (storage.foldername(name))[1] = auth.uid()::text);
+
+drop policy if exists "Users replace their files" on storage.objects;
+create policy "Users replace their files" on storage.objects
+    for update to authenticated
+    using (bucket_id = 'user-files' and (storage.foldername(name))[1] = auth.uid()::text)
+    with check (bucket_id = 'user-files' and (storage.foldername(name))[1] = auth.uid()::text);
@@ -4,14 +4,18 @@ import { check } from '@tauri-apps/plugin-updater'
 import { relaunch } from '@tauri-apps/plugin-process'
 import { listen } from '@tauri-apps/api/event'
 import { open } from '@tauri-apps/plugin-shell'
-import { Plus, Search, FileCode, FileText, X, Edit, Trash2, Copy, Save, Brain, Download, Sparkles, CheckCircle, AlertCircle, Info, PanelLeftClose, PanelLeft, Keyboard, Code, Braces, RefreshCw, Cloud, User, List, LayoutList } from 'lucide-react'
+import { Plus, Search, FileCode, FileText, X, Edit, Trash2, Copy, Save, Brain, Download, Sparkles, CheckCircle, AlertCircle, Info, PanelLeftClose, PanelLeft, Keyboard, Code, Braces, RefreshCw, Cloud, User, List, LayoutList, History, Lock } from 'lucide-react'
 import { useSupabaseAuth } from '@/lib/supabase-auth'
 import { syncService } from '@/lib/sync'
+import { folderPath, splitTagFilters, hasAllTags } from '@/lib/utils'
 import { Button } from '@/components/ui/button'
 import { Badge } from '@/components/ui/badge'
 import { SearchBar } from '@/components/ui/searchbar'
 import { SearchModal } from '@/components/ui/searchmodal'
 import { ConfirmDialog } from '@/components/ui/confirmdialog'
+import { RevisionHistory } from '@/components/ui/revisionhistory'
+import { TagManager } from '@/components/ui/tagmanager'
+import { SmartFolderDialog } from '@/components/ui/smartfolderdialog'
 import { MenuSidebar } from '@/components/ui/menusidebar'
 import { SnippetsPanel } from '@/components/ui/snippetspanel'
 import { FilesPanel } from '@/components/ui/filespanel'
@@ -19,11 +23,17 @@ import { FilePreview } from '@/components/ui/filepreview'
 import { OrganizeModal } from '@/components/ui/organizemodal'
 import { AccountPanel } from '@/components/ui/accountpanel'
 import { AccountMainView } from '@/components/ui/accountmainview'
+import { TrashMainView } from '@/components/ui/trashmainview'
 import { ClipboardPanel } from '@/components/ui/clipboardpanel'
 import { ClipboardMainView } from '@/components/ui/clipboardmainview'
+import { UnlockScreen } from '@/components/ui/unlockscreen'
+import { SecretGate } from '@/components/ui/secretgate'
 import { TiptapEditor } from '@/components/ui/tiptap-editor'
 import './tiptap.css'
 
+// What the backend returns in place of a secret snippet's content
+const SECRET_MASK = '••••••••'
+
 function App() {
   const { user } = useSupabaseAuth()
   const isSignedIn = !!user
@@ -58,6 +68,13 @@ function App() {
   const [language, setLanguage] = useState('javascript')
   const [tags, setTags] = useState('')
   const [description, setDescription] = useState('')
+  // Text expansion trigger; saved on blur rather than by auto-save
+  const [trigger, setTrigger] = useState('')
+  // Whether the current secret snippet's content has been decrypted
+  const [secretRevealed, setSecretRevealed] = useState(false)
+  const [showHistory, setShowHistory] = useState(false)
+  const [showTagManager, setShowTagManager] = useState(false)
+  const [smartFolderDialog, setSmartFolderDialog] = useState({ isOpen: false, folder: null })
   const [content, setContent] = useState('')
   
   // Debounce timeout for auto-save
@@ -130,6 +147,9 @@ function App() {
   const [availableUpdate, setAvailableUpdate] = useState(null)
   const [isDownloadingUpdate, setIsDownloadingUpdate] = useState(false)
 
+  // Encryption at rest: the library may need its passphrase first
+  const [encryptionStatus, setEncryptionStatus] = useState(null)
+
   // Footer sync state
   const [isFooterSyncing, setIsFooterSyncing] = useState(false)
   const [syncApproval, setSyncApproval] = useState(null)
@@ -182,7 +202,7 @@ function App() {
           setIsFooterSyncing(false)
           return
         }
-        result = await syncService.syncFilesAll(email, user?.id)
+        result = await syncService.syncFilesAll(email)
         setHasUnsyncedFiles(false)
       } else if (activeMenu === 'account') {
         // Sync all (account menu syncs everything)
@@ -203,6 +223,8 @@ function App() {
       let errorMessage = 'Sync failed. Please try again.'
       if (error.message === 'SYNC_NOT_APPROVED') {
         errorMessage = 'Sync access not approved yet. Please wait for approval.'
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        errorMessage = 'Set up or unlock encrypted sync in Account first.'
       } else if (error.message === 'Supabase not configured') {
         errorMessage = 'Cloud sync not configured'
       } else if (error.message.includes('fetch') || error.message.includes('network')) {
@@ -227,6 +249,30 @@ function App() {
     setTimeout(() => setToast(null), 3000)
   }
 
+  // Check whether the library is locked
+  useEffect(() => {
+    invoke('get_encryption_status').then(setEncryptionStatus).catch(console.error)
+  }, [])
+
+  const handleUnlocked = async () => {
+    setEncryptionStatus(await invoke('get_encryption_status'))
+    await loadSnippets()
+    await loadFolders()
+  }
+
+  // Hide a revealed secret again once secret snippets lock
+  useEffect(() => {
+    let unlisten
+    listen('secrets-locked', () => setSecretRevealed(false)).then(fn => { unlisten = fn })
+    return () => unlisten?.()
+  }, [])
+
+  useEffect(() => {
+    if (secretRevealed || !currentSnippet?.secret || currentSnippet.content === SECRET_MASK) return
+    setCurrentSnippet({ ...currentSnippet, content: SECRET_MASK })
+    setContent(SECRET_MASK)
+  }, [secretRevealed, currentSnippet])
+
   // Load snippets and check model status on mount
   useEffect(() => {
     loadSnippets()
@@ -234,7 +280,7 @@ function App() {
     checkModelStatus()
     checkForUpdates()
 
-    // Register global clipboard hotkey
+    // Register global hotkeys
     invoke('register_clipboard_hotkey').catch(console.error)
   }, [])
 
@@ -264,6 +310,19 @@ function App() {
     }
   }, [])
 
+  // Reload when the Markdown vault sync changed the library
+  useEffect(() => {
+    let unlistenFn
+    listen('vault-synced', async (event) => {
+      console.log('🗂️ Vault synced', event.payload)
+      await loadSnippets()
+      await loadFolders()
+      setHasUnsyncedChanges(true)
+    }).then(fn => { unlistenFn = fn })
+
+    return () => unlistenFn?.()
+  }, [])
+
   // Always show all snippets in sidebar (no filtering)
   useEffect(() => {
     setFilteredSnippets(snippets)
@@ -477,10 +536,30 @@ function App() {
     }
   }
 
+  // Smart folder counts follow the snippets, so refresh them after any change
+  useEffect(() => {
+    if (folders.some(f => f.kind === 'smart')) {
+      loadSmartFolderCounts()
+    }
+  }, [snippets])
+
+  // Counted after the folders are shown so listing them never waits on a search
+  const loadSmartFolderCounts = async () => {
+    try {
+      const counts = await invoke('get_smart_folder_counts')
+      setFolders(prev => prev.map(f => f.kind === 'smart' ? { ...f, snippet_count: counts[f.id] ?? null } : f))
+    } catch (error) {
+      console.error('Failed to count smart folders:', error)
+    }
+  }
+
   const loadFolders = async () => {
     try {
       const data = await invoke('get_all_folders')
       setFolders(data)
+      if (data.some(f => f.kind === 'smart')) {
+        loadSmartFolderCounts()
+      }
 
       // Also load files for badge counts
       if (activeMenu === 'files') {
@@ -517,9 +596,9 @@ function App() {
     }
   }
 
-  const handleCreateFolder = async (name) => {
+  const handleCreateFolder = async (name, parentId = null) => {
     try {
-      await invoke('create_folder', { name })
+      await invoke('create_folder', { name, parentId })
       await loadFolders()
       // Reload snippets to ensure counts are up to date
       await loadSnippets()
@@ -533,7 +612,7 @@ function App() {
       }
     } catch (error) {
       console.error('Failed to create folder:', error)
-      showToast('Failed to create folder', 'error')
+      showToast(typeof error === 'string' ? error : 'Failed to create folder', 'error')
     }
   }
 
@@ -546,7 +625,7 @@ function App() {
         // Reload all snippets
         await loadSnippets()
       }
-      showToast('Folder deleted', 'success')
+      showToast('Folder moved to trash', 'success')
     } catch (error) {
       console.error('Failed to delete folder:', error)
       showToast('Failed to delete folder', 'error')
@@ -568,7 +647,32 @@ function App() {
       showToast('Folder updated', 'success')
     } catch (error) {
       console.error('Failed to update folder:', error)
-      showToast('Failed to update folder', 'error')
+      showToast(typeof error === 'string' ? error : 'Failed to update folder', 'error')
+    }
+  }
+
+  const handleMoveFolder = async (folderId, parentId) => {
+    try {
+      await invoke('move_folder', { id: folderId, parentId })
+      await loadFolders()
+      // The selected folder's view includes subfolders, so its contents may have changed
+      if (selectedFolderId !== null && selectedFolderId !== 'uncategorized') {
+        await handleFolderSelect(selectedFolderId)
+      }
+      showToast('Folder moved', 'success')
+    } catch (error) {
+      console.error('Failed to move folder:', error)
+      showToast(typeof error === 'string' ? error : 'Failed to move folder', 'error')
+    }
+  }
+
+  const handleReorderFolders = async (parentId, folderIds) => {
+    try {
+      await invoke('reorder_folders', { parentId, folderIds })
+      await loadFolders()
+    } catch (error) {
+      console.error('Failed to reorder folders:', error)
+      showToast('Failed to reorder folders', 'error')
     }
   }
 
@@ -577,7 +681,10 @@ function App() {
 
     // Load files when switching to files menu
     if (menu === 'files') {
-      await loadFiles(selectedFolderId)
+      // Smart folders only hold snippets, so fall back to all files
+      const folderId = folders.find(f => f.id === selectedFolderId)?.kind === 'smart' ? null : selectedFolderId
+      setSelectedFolderId(folderId)
+      await loadFiles(folderId)
     }
   }
 
@@ -602,8 +709,8 @@ function App() {
         // Uncategorized - pass null to get_snippets_by_folder (which returns snippets with folder_id IS NULL)
         data = await invoke('get_snippets_by_folder', { folderId: null })
       } else {
-        // Specific folder - pass the folder ID
-        data = await invoke('get_snippets_by_folder', { folderId })
+        // Specific folder - pass the folder ID, including its subfolders
+        data = await invoke('get_snippets_by_folder', { folderId, includeDescendants: true })
       }
 
       setFilteredSnippets(data)
@@ -630,8 +737,8 @@ function App() {
           // Uncategorized - pass null to get_snippets_by_folder
           data = await invoke('get_snippets_by_folder', { folderId: null })
         } else {
-          // Specific folder - pass the folder ID
-          data = await invoke('get_snippets_by_folder', { folderId: selectedFolderId })
+          // Specific folder - pass the folder ID, including its subfolders
+          data = await invoke('get_snippets_by_folder', { folderId: selectedFolderId, includeDescendants: true })
         }
 
         setFilteredSnippets(data)
@@ -720,6 +827,20 @@ function App() {
     }
   }
 
+  // Substring match on the text, plus exact matches for `tag:name` words
+  const filterSnippetsByKeyword = (query) => {
+    const { text, tags: tagFilters } = splitTagFilters(query)
+    const queryLower = text.toLowerCase()
+    return snippets.filter(snippet =>
+      hasAllTags(snippet, tagFilters) && (
+        snippet.title.toLowerCase().includes(queryLower) ||
+        snippet.language.toLowerCase().includes(queryLower) ||
+        snippet.content.toLowerCase().includes(queryLower) ||
+        (snippet.tags && snippet.tags.toLowerCase().includes(queryLower))
+      )
+    )
+  }
+
   const handleSearch = async (query) => {
     if (!query.trim()) {
       setSearchResults(snippets)
@@ -741,25 +862,11 @@ function App() {
         setSearchResults(results.map(r => r.snippet))
       } else {
         console.log('📝 Using keyword search...')
-        const queryLower = query.toLowerCase()
-        const filtered = snippets.filter(snippet =>
-          snippet.title.toLowerCase().includes(queryLower) ||
-          snippet.language.toLowerCase().includes(queryLower) ||
-          snippet.content.toLowerCase().includes(queryLower) ||
-          (snippet.tags && snippet.tags.toLowerCase().includes(queryLower))
-        )
-        setSearchResults(filtered)
+        setSearchResults(filterSnippetsByKeyword(query))
       }
     } catch (error) {
       console.error('Search failed:', error)
-      const queryLower = query.toLowerCase()
-      const filtered = snippets.filter(snippet =>
-        snippet.title.toLowerCase().includes(queryLower) ||
-        snippet.language.toLowerCase().includes(queryLower) ||
-        snippet.content.toLowerCase().includes(queryLower) ||
-        (snippet.tags && snippet.tags.toLowerCase().includes(queryLower))
-      )
-      setSearchResults(filtered)
+      setSearchResults(filterSnippetsByKeyword(query))
     }
   }
 
@@ -781,6 +888,7 @@ function App() {
     setLanguage('javascript')
     setTags('')
     setDescription('')
+    setTrigger('')
     setContent('')
 
     // Immediately create the snippet
@@ -850,7 +958,66 @@ function App() {
     setLanguage(snippet.language)
     setTags(snippet.tags || '')
     setDescription(snippet.description || '')
+    setTrigger(snippet.trigger || '')
     setContent(snippet.content)
+    setSecretRevealed(false)
+  }
+
+  const handleToggleSecret = async () => {
+    const makeSecret = !currentSnippet.secret
+    try {
+      if (makeSecret) {
+        const vault = await invoke('get_secret_vault_status')
+        if (!vault.configured || !vault.unlocked) {
+          showToast(vault.configured ? 'Unlock secret snippets in Account first' : 'Set a password for secret snippets in Account first', 'error')
+          return
+        }
+      } else if (!secretRevealed) {
+        showToast('Reveal the secret before making it a normal snippet', 'error')
+        return
+      }
+
+      // Secret snippets can't be expanded by a trigger
+      const updated = { ...currentSnippet, content, secret: makeSecret, trigger: makeSecret ? null : currentSnippet.trigger }
+      await invoke('update_snippet', { id: currentSnippet.id, snippet: updated })
+
+      setCurrentSnippet(updated)
+      setSecretRevealed(true)
+      if (makeSecret) setTrigger('')
+      setHasUnsyncedChanges(true)
+      await loadSnippets()
+
+      showToast(
+        makeSecret
+          ? 'Snippet is now secret. It no longer syncs; your other devices keep the version they already have.'
+          : 'Snippet is no longer secret',
+        'success'
+      )
+    } catch (error) {
+      console.error('Failed to change secret snippet:', error)
+      showToast(typeof error === 'string' ? error : 'Failed to change secret snippet', 'error')
+    }
+  }
+
+  const handleSaveTrigger = async () => {
+    if (!currentSnippet || trigger.trim() === (currentSnippet.trigger || '')) return
+
+    try {
+      await invoke('update_snippet', {
+        id: currentSnippet.id,
+        snippet: { ...currentSnippet, trigger: trigger.trim() || null }
+      })
+
+      setCurrentSnippet({ ...currentSnippet, trigger: trigger.trim() || null })
+      setHasUnsyncedChanges(true)
+      await loadSnippets()
+
+      showToast(trigger.trim() ? `Type ${trigger.trim()} anywhere to insert this snippet` : 'Trigger removed', 'success')
+    } catch (error) {
+      console.error('Failed to update trigger:', error)
+      showToast(typeof error === 'string' ? error : 'Failed to update trigger', 'error')
+      setTrigger(currentSnippet.trigger || '')
+    }
   }
 
   const handleSaveSnippet = async (silent = false) => {
@@ -878,6 +1045,8 @@ function App() {
       description: description.trim() || null,
       content: content.trim(),
       folder_id: currentSnippet?.folder_id || selectedFolderId || null,
+      trigger: currentSnippet?.trigger || null,
+      secret: currentSnippet?.secret || false,
       created_at: '',
       updated_at: ''
     }
@@ -993,27 +1162,15 @@ function App() {
       title: hasUnsavedChanges ? 'Unsaved Changes' : 'Delete Snippet',
       message: hasUnsavedChanges
         ? `You have unsaved changes in "${snippet.title}". Are you sure you want to delete it? These changes will be lost.`
-        : `Are you sure you want to delete "${snippet.title}"? This will be deleted from both local and cloud storage.`,
+        : `Are you sure you want to delete "${snippet.title}"? It will be moved to the trash.`,
       onConfirm: async () => {
         setIsDeletingSnippet(true)
         setDeleteError(null)
         try {
-          // Delete locally first
           await invoke('delete_snippet', { id: snippet.id })
 
-          // Delete from cloud if user is signed in
-          if (isSignedIn && user?.email) {
-            try {
-              await syncService.deleteFromCloud(user.email, snippet.id)
-              showToast('Snippet deleted from local and cloud', 'success')
-            } catch (cloudError) {
-              console.warn('Failed to delete from cloud:', cloudError)
-              setDeleteError('Deleted locally but failed to sync deletion to cloud')
-              showToast('Deleted locally (cloud sync failed)', 'warning')
-            }
-          } else {
-            showToast('Snippet deleted locally', 'success')
-          }
+          // The deletion reaches other devices on the next sync
+          showToast('Snippet moved to trash', 'success')
 
           // Mark as having unsynced changes
           setHasUnsyncedChanges(true)
@@ -1026,6 +1183,7 @@ function App() {
             setLanguage('javascript')
             setTags('')
             setDescription('')
+            setTrigger('')
           }
         } catch (error) {
           console.error('Failed to delete snippet:', error)
@@ -1052,24 +1210,13 @@ function App() {
     setConfirmDialog({
       isOpen: true,
       title: 'Delete File',
-      message: `Are you sure you want to delete "${file.filename}"? This will be deleted from both local and cloud storage.`,
+      message: `Are you sure you want to delete "${file.filename}"? It will be moved to the trash.`,
       onConfirm: async () => {
         try {
-          // Delete locally first
           await invoke('delete_file', { id: file.id })
 
-          // Delete from cloud if user is signed in
-          if (isSignedIn && user?.email) {
-            try {
-              await syncService.deleteFileFromCloud(user.email, user.id, file.id)
-              showToast('File deleted from local and cloud', 'success')
-            } catch (cloudError) {
-              console.warn('Failed to delete from cloud:', cloudError)
-              showToast('Deleted locally (cloud sync failed)', 'warning')
-            }
-          } else {
-            showToast('File deleted locally', 'success')
-          }
+          // The deletion reaches other devices on the next sync
+          showToast('File moved to trash', 'success')
 
           // Mark as having unsynced changes
           setHasUnsyncedFiles(true)
@@ -1089,6 +1236,10 @@ function App() {
     })
   }
 
+  if (encryptionStatus?.locked) {
+    return <UnlockScreen canUnlock={encryptionStatus.passphrase} onUnlocked={handleUnlocked} />
+  }
+
   return (
     <div className="flex flex-col h-screen bg-background overflow-hidden">
       {/* Search Bar - Below Native Title Bar */}
@@ -1175,6 +1326,11 @@ function App() {
           onCreateFolder={handleCreateFolder}
           onUpdateFolder={handleUpdateFolder}
           onDeleteFolder={handleDeleteFolder}
+          onMoveFolder={handleMoveFolder}
+          onReorderFolders={handleReorderFolders}
+          onCreateSmartFolder={() => setSmartFolderDialog({ isOpen: true, folder: null })}
+          onEditSmartFolder={(folder) => setSmartFolderDialog({ isOpen: true, folder })}
+          onManageTags={() => setShowTagManager(true)}
           snippets={snippets}
           files={allFiles}
         />
@@ -1182,9 +1338,9 @@ function App() {
         {/* Resizable Content Sidebar */}
         <div
           className={`border-r flex flex-col transition-all duration-300 ${
-            sidebarCollapsed || activeMenu === 'clipboard' || activeMenu === 'account' ? 'w-0 overflow-hidden' : ''
+            sidebarCollapsed || activeMenu === 'clipboard' || activeMenu === 'account' || activeMenu === 'trash' ? 'w-0 overflow-hidden' : ''
           }`}
-          style={{ width: (sidebarCollapsed || activeMenu === 'clipboard' || activeMenu === 'account') ? 0 : `${sidebarWidth}px` }}
+          style={{ width: (sidebarCollapsed || activeMenu === 'clipboard' || activeMenu === 'account' || activeMenu === 'trash') ? 0 : `${sidebarWidth}px` }}
         >
           {activeMenu === 'snippets' && (
             <SnippetsPanel
@@ -1327,9 +1483,9 @@ function App() {
                     className="h-7 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
                   >
                     <option value="none">Uncategorized</option>
-                    {folders.map((folder) => (
+                    {folders.filter(f => f.kind !== 'smart').map((folder) => (
                       <option key={folder.id} value={folder.id}>
-                        {folder.icon || '📁'} {folder.name}
+                        {folder.icon || '📁'} {folderPath(folders, folder)}
                       </option>
                     ))}
                   </select>
@@ -1412,6 +1568,25 @@ function App() {
                 onSyncStart={() => {
                   // Optionally handle sync start
                 }}
+                folders={folders}
+                onLibraryImported={async () => {
+                  await loadSnippets()
+                  await loadFolders()
+                  await loadFiles(selectedFolderId)
+                  setHasUnsyncedChanges(true)
+                }}
+              />
+            </div>
+          ) : activeMenu === 'trash' ? (
+            <div className="flex-1 overflow-hidden">
+              <TrashMainView
+                onRestored={async () => {
+                  await loadFolders()
+                  await loadSnippets()
+                  await loadFiles(selectedFolderId)
+                  setHasUnsyncedChanges(true)
+                  showToast('Restored from trash', 'success')
+                }}
               />
             </div>
           ) : (
@@ -1449,6 +1624,8 @@ function App() {
                             description: currentSnippet.description || null,
                             content: currentSnippet.content,
                             folder_id: newFolderId,
+                            trigger: currentSnippet.trigger || null,
+                            secret: currentSnippet.secret || false,
                             created_at: currentSnippet.created_at,
                             updated_at: currentSnippet.updated_at
                           }
@@ -1471,15 +1648,34 @@ function App() {
                       className="h-7 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
                     >
                       <option value="none">Uncategorized</option>
-                      {folders.map((folder) => (
+                      {folders.filter(f => f.kind !== 'smart').map((folder) => (
                         <option key={folder.id} value={folder.id}>
-                          {folder.icon || '📁'} {folder.name}
+                          {folder.icon || '📁'} {folderPath(folders, folder)}
                         </option>
                       ))}
                     </select>
                   </div>
                 )}
 
+                {/* Text expansion trigger - Only for current snippet */}
+                {currentSnippet && !currentSnippet.secret && (
+                  <div className="flex items-center gap-2">
+                    <span className="text-[10px] text-muted-foreground">Trigger:</span>
+                    <input
+                      type="text"
+                      value={trigger}
+                      onChange={(e) => setTrigger(e.target.value)}
+                      onBlur={handleSaveTrigger}
+                      onKeyDown={(e) => {
+                        if (e.key === 'Enter') e.currentTarget.blur()
+                      }}
+                      placeholder=";abbrev"
+                      title="Type this anywhere to insert the snippet"
+                      className="h-7 w-28 px-2 text-[10px] font-mono bg-background border border-border rounded-md focus:outline-none focus:ring-1 focus:ring-purple-500"
+                    />
+                  </div>
+                )}
+
                 {/* Auto-save indicator */}
                 {currentSnippet || (title && content) ? (
                   <div className="flex items-center gap-2 px-2 py-1 rounded-md bg-muted/50">
@@ -1501,9 +1697,25 @@ function App() {
                 {/* Action Buttons - Only for current snippet */}
                 {currentSnippet ? (
                   <div className="flex items-center gap-1.5">
-                    <Button variant="ghost" size="sm" className="h-7 px-2 text-[10px]" onClick={() => handleCopyCode(currentSnippet.content)}>
-                      <Copy className="h-3 w-3 mr-1" />
-                      Copy
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      className={`h-7 px-2 text-[10px] ${currentSnippet.secret ? 'text-purple-500' : ''}`}
+                      onClick={handleToggleSecret}
+                      title={currentSnippet.secret ? 'Make this a normal snippet' : 'Encrypt with the secret snippets password'}
+                    >
+                      <Lock className="h-3 w-3 mr-1" />
+                      Secret
+                    </Button>
+                    {!currentSnippet.secret && (
+                      <Button variant="ghost" size="sm" className="h-7 px-2 text-[10px]" onClick={() => handleCopyCode(currentSnippet.content)}>
+                        <Copy className="h-3 w-3 mr-1" />
+                        Copy
+                      </Button>
+                    )}
+                    <Button variant="ghost" size="sm" className="h-7 px-2 text-[10px]" onClick={() => setShowHistory(true)}>
+                      <History className="h-3 w-3 mr-1" />
+                      History
                     </Button>
                     <Button variant="ghost" size="sm" className="h-7 px-2 text-[10px] text-destructive hover:text-destructive" onClick={() => handleDeleteSnippet(currentSnippet)}>
                       <Trash2 className="h-3 w-3 mr-1" />
@@ -1531,6 +1743,15 @@ function App() {
                     </p>
                   </div>
                 </div>
+              ) : currentSnippet?.secret && !secretRevealed ? (
+                <SecretGate
+                  snippetId={currentSnippet.id}
+                  onRevealed={(revealed) => {
+                    setCurrentSnippet({ ...currentSnippet, content: revealed })
+                    setContent(revealed)
+                    setSecretRevealed(true)
+                  }}
+                />
               ) : (
                 // Always Edit Mode
                 <div className="flex-1 flex flex-col overflow-hidden">
@@ -1738,11 +1959,59 @@ function App() {
         isOpen={showOrganizeModal}
         onClose={() => setShowOrganizeModal(false)}
         snippets={snippets.filter(s => !s.folder_id)}
-        folders={folders}
+        folders={folders.filter(f => f.kind !== 'smart')}
         onOrganize={handleOrganizeSnippets}
       />
 
       {/* Confirmation Dialog */}
+      <RevisionHistory
+        isOpen={showHistory}
+        snippet={currentSnippet}
+        onClose={() => setShowHistory(false)}
+        onRestored={async (restored) => {
+          handleEditSnippet(restored)
+          lastSavedStateRef.current = {
+            id: restored.id,
+            title: restored.title,
+            content: restored.content,
+            language: restored.language,
+            tags: restored.tags || '',
+            description: restored.description || ''
+          }
+          setHasUnsyncedChanges(true)
+          await loadSnippets()
+          showToast('Snippet restored', 'success')
+        }}
+      />
+
+      <TagManager
+        isOpen={showTagManager}
+        onClose={() => setShowTagManager(false)}
+        onSelectTag={(tag) => {
+          setShowTagManager(false)
+          setSearchQuery(`tag:${tag.name}`)
+          setIsSearchModalOpen(true)
+        }}
+        onTagsChanged={async () => {
+          await loadSnippets()
+          await loadFiles(selectedFolderId)
+          setHasUnsyncedChanges(true)
+        }}
+      />
+
+      <SmartFolderDialog
+        isOpen={smartFolderDialog.isOpen}
+        folder={smartFolderDialog.folder}
+        onClose={() => setSmartFolderDialog({ isOpen: false, folder: null })}
+        onSaved={async () => {
+          await loadFolders()
+          if (smartFolderDialog.folder && selectedFolderId === smartFolderDialog.folder.id) {
+            await handleFolderSelect(selectedFolderId)
+          }
+          showToast(smartFolderDialog.folder ? 'Smart folder updated' : 'Smart folder created', 'success')
+        }}
+      />
+
       <ConfirmDialog
         isOpen={confirmDialog.isOpen}
         onClose={() => {
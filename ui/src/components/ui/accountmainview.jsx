@@ -4,11 +4,19 @@ import { useState, useEffect } from 'react'
 import { useSupabaseAuth } from '@/lib/supabase-auth'
 import { syncService } from '@/lib/sync'
 import { isSupabaseConfigured } from '@/lib/supabase'
+import { LibraryPanel } from '@/components/ui/librarypanel'
+import { VaultPanel } from '@/components/ui/vaultpanel'
+import { BackupPanel } from '@/components/ui/backuppanel'
+import { EncryptionPanel } from '@/components/ui/encryptionpanel'
+import { SecretsPanel } from '@/components/ui/secretspanel'
+import { SyncEncryptionPanel } from '@/components/ui/syncencryptionpanel'
 
 export function AccountMainView({
   hasUnsyncedChanges,
   onSyncComplete,
   onSyncStart,
+  onLibraryImported,
+  folders,
   lastSyncTime: externalLastSyncTime
 }) {
   const { user, signInWithGoogle, signOut } = useSupabaseAuth()
@@ -134,6 +142,8 @@ export function AccountMainView({
 
       if (error.message === 'SYNC_NOT_APPROVED') {
         setError('Sync access not approved yet. Please wait for approval.')
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        setError('Set up or unlock encrypted sync in Account first.')
       } else {
         setSyncStatus({
           type: 'error',
@@ -326,6 +336,8 @@ export function AccountMainView({
                         Unsynced changes
                       </p>
                     )}
+
+                    <SyncEncryptionPanel userEmail={user.email} />
                   </div>
                 )}
               </div>
@@ -344,6 +356,27 @@ export function AccountMainView({
               </div>
             </div>
           )}
+
+          {/* Export/import, available without an account */}
+          <div className="pt-3 border-t">
+            <LibraryPanel folders={folders} onImported={onLibraryImported} />
+          </div>
+
+          <div className="pt-3 border-t">
+            <VaultPanel onSynced={onLibraryImported} />
+          </div>
+
+          <div className="pt-3 border-t">
+            <BackupPanel onRestored={onLibraryImported} />
+          </div>
+
+          <div className="pt-3 border-t">
+            <EncryptionPanel />
+          </div>
+
+          <div className="pt-3 border-t">
+            <SecretsPanel />
+          </div>
         </div>
       </div>
 
@@ -114,6 +114,9 @@ export function AccountPanel({
       } else {
         message = `↑ ${result.pushed} up, ↓ ${result.pulled} down`
       }
+      if (result.imported > 0) {
+        message += ` (${result.imported} item${result.imported > 1 ? 's' : ''} brought over from the previous sync)`
+      }
 
       setSyncStatus({
         type: 'success',
@@ -128,6 +131,8 @@ export function AccountPanel({
 
       if (error.message === 'SYNC_NOT_APPROVED') {
         setError('Sync access not approved yet. Please wait for approval.')
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        setError('Set up or unlock encrypted sync in Account first.')
       } else {
         setSyncStatus({
           type: 'error',
@@ -0,0 +1,245 @@
+import { useEffect, useState } from 'react'
+import { HardDrive, FolderOpen, AlertCircle, CheckCircle, RefreshCw, RotateCcw, ShieldCheck, Trash2 } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { ask, open } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+
+const INTERVALS = [
+  { hours: 6, label: 'Every 6 hours' },
+  { hours: 12, label: 'Every 12 hours' },
+  { hours: 24, label: 'Daily' },
+  { hours: 168, label: 'Weekly' },
+]
+
+const KIND_LABELS = { scheduled: 'Scheduled', manual: 'Manual', pre_restore: 'Before restore' }
+
+function formatSize(bytes) {
+  if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+}
+
+/**
+ * BackupPanel - scheduled local backups of the database and stored files,
+ * with rotation. Each backup can be verified and restored; a restore backs up
+ * the current library first.
+ */
+export function BackupPanel({ onRestored }) {
+  const [settings, setSettings] = useState(null)
+  const [backups, setBackups] = useState([])
+  const [busy, setBusy] = useState(null)
+  const [message, setMessage] = useState(null)
+  const [error, setError] = useState(null)
+
+  async function loadBackups() {
+    try {
+      setBackups(await invoke('list_backups'))
+    } catch (err) {
+      console.error('Failed to list backups:', err)
+    }
+  }
+
+  useEffect(() => {
+    invoke('get_backup_settings').then(setSettings).catch(console.error)
+    loadBackups()
+
+    let unlistenFn
+    listen('backup-created', () => loadBackups()).then(fn => { unlistenFn = fn })
+    return () => unlistenFn?.()
+  }, [])
+
+  async function run(key, action, failure) {
+    setBusy(key)
+    setError(null)
+    setMessage(null)
+    try {
+      await action()
+    } catch (err) {
+      console.error(`${failure}:`, err)
+      setError(typeof err === 'string' ? err : failure)
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  function saveSettings(changes) {
+    const next = { ...settings, ...changes }
+    run('settings', async () => {
+      await invoke('set_backup_settings', { settings: next })
+      setSettings(next)
+      await loadBackups()
+    }, 'Failed to save backup settings')
+  }
+
+  async function handleChooseDirectory() {
+    const directory = await open({ directory: true, title: 'Choose a backup folder' })
+    if (!directory || Array.isArray(directory)) return
+    saveSettings({ directory })
+  }
+
+  function handleBackupNow() {
+    run('create', async () => {
+      const info = await invoke('create_backup')
+      setMessage(`Backed up ${info.files} files (${formatSize(info.size_bytes)})`)
+      await loadBackups()
+    }, 'Backup failed')
+  }
+
+  function handleVerify(id) {
+    run(`verify-${id}`, async () => {
+      await invoke('verify_backup', { id })
+      setMessage('The backup is intact')
+    }, 'The backup could not be verified')
+  }
+
+  async function handleRestore(backup) {
+    const confirmed = await ask(
+      `Your library is replaced with the backup from ${new Date(backup.created_at).toLocaleString()}. ` +
+        'It is backed up first, so this can be undone.',
+      { title: 'Restore backup?', kind: 'warning', okLabel: 'Restore' }
+    )
+    if (!confirmed) return
+
+    run(`restore-${backup.id}`, async () => {
+      await invoke('restore_backup', { id: backup.id })
+      setMessage('Backup restored')
+      await loadBackups()
+      onRestored?.()
+    }, 'Restore failed')
+  }
+
+  async function handleDelete(backup) {
+    const confirmed = await ask('This backup is deleted permanently.', { title: 'Delete backup?', kind: 'warning', okLabel: 'Delete' })
+    if (!confirmed) return
+
+    run(`delete-${backup.id}`, async () => {
+      await invoke('delete_backup', { id: backup.id })
+      await loadBackups()
+    }, 'Failed to delete the backup')
+  }
+
+  if (!settings) return null
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center gap-2">
+        <HardDrive className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Backups</span>
+      </div>
+
+      <div className="flex items-center gap-2">
+        <label className="flex items-center gap-1.5 text-[10px] cursor-pointer">
+          <input
+            type="checkbox"
+            checked={settings.enabled}
+            onChange={(e) => saveSettings({ enabled: e.target.checked })}
+            disabled={busy !== null}
+          />
+          Automatic
+        </label>
+        <select
+          value={settings.interval_hours}
+          onChange={(e) => saveSettings({ interval_hours: Number(e.target.value) })}
+          disabled={busy !== null || !settings.enabled}
+          className="flex-1 min-w-0 h-7 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
+        >
+          {INTERVALS.some(i => i.hours === settings.interval_hours) ? null : (
+            <option value={settings.interval_hours}>Every {settings.interval_hours} hours</option>
+          )}
+          {INTERVALS.map(i => <option key={i.hours} value={i.hours}>{i.label}</option>)}
+        </select>
+      </div>
+
+      <div className="flex items-center gap-2 text-[10px] text-muted-foreground">
+        <span>Keep</span>
+        <input
+          type="number"
+          min={1}
+          max={100}
+          value={settings.keep}
+          onChange={(e) => {
+            const keep = Number(e.target.value)
+            if (keep >= 1) saveSettings({ keep })
+          }}
+          disabled={busy !== null}
+          className="w-12 h-7 px-1.5 bg-background border border-border rounded-md text-foreground"
+        />
+        <span>scheduled backups</span>
+      </div>
+
+      <div className="flex items-center gap-1">
+        <p className="flex-1 text-[10px] text-muted-foreground truncate" title={settings.directory || 'App data folder'}>
+          {settings.directory || 'App data folder'}
+        </p>
+        <button
+          onClick={handleChooseDirectory}
+          disabled={busy !== null}
+          className="p-0.5 text-muted-foreground hover:text-foreground"
+          title="Choose backup folder"
+        >
+          <FolderOpen className="h-3 w-3" />
+        </button>
+      </div>
+
+      <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleBackupNow} disabled={busy !== null}>
+        {busy === 'create' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <HardDrive className="h-3 w-3 mr-1" />}
+        Back Up Now
+      </Button>
+
+      {backups.length > 0 && (
+        <ul className="max-h-40 overflow-y-auto space-y-1">
+          {backups.map(backup => (
+            <li key={backup.id} className="flex items-center gap-1 text-[10px]">
+              <div className="flex-1 min-w-0">
+                <p className="truncate">{new Date(backup.created_at).toLocaleString()}</p>
+                <p className="text-[9px] text-muted-foreground truncate">
+                  {KIND_LABELS[backup.kind] ?? backup.kind} • {formatSize(backup.size_bytes)} • {backup.files} files
+                  {backup.missing_files.length > 0 && ` • ${backup.missing_files.length} missing`}
+                  {backup.encrypted && ' • Encrypted'}
+                </p>
+              </div>
+              <button
+                onClick={() => handleVerify(backup.id)}
+                disabled={busy !== null}
+                className="p-0.5 text-muted-foreground hover:text-foreground"
+                title="Verify"
+              >
+                {busy === `verify-${backup.id}` ? <RefreshCw className="h-3 w-3 animate-spin" /> : <ShieldCheck className="h-3 w-3" />}
+              </button>
+              <button
+                onClick={() => handleRestore(backup)}
+                disabled={busy !== null}
+                className="p-0.5 text-muted-foreground hover:text-foreground"
+                title="Restore"
+              >
+                {busy === `restore-${backup.id}` ? <RefreshCw className="h-3 w-3 animate-spin" /> : <RotateCcw className="h-3 w-3" />}
+              </button>
+              <button
+                onClick={() => handleDelete(backup)}
+                disabled={busy !== null}
+                className="p-0.5 text-muted-foreground hover:text-destructive"
+                title="Delete"
+              >
+                <Trash2 className="h-3 w-3" />
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+
+      {message && (
+        <div className="p-2.5 bg-green-500/10 border border-green-500/20 rounded-lg flex items-start gap-2">
+          <CheckCircle className="h-3.5 w-3.5 text-green-500 mt-0.5 shrink-0" />
+          <p className="text-[10px] text-green-600">{message}</p>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -135,20 +135,8 @@ export const ClipboardMainView = forwardRef(({ onConvertToSnippet, onClipboardCh
     setError(null)
 
     try {
-      // Delete locally first
       await clipboardService.deleteClipboardEntry(entryId)
 
-      // Delete from cloud if user is signed in
-      if (isSignedIn && user?.email && syncApproval?.approved) {
-        try {
-          const { syncService } = await import('@/lib/sync')
-          await syncService.deleteClipboardFromCloud(user.email, entryId)
-        } catch (cloudError) {
-          console.warn('Failed to delete from cloud:', cloudError)
-          setError('Deleted locally but failed to sync deletion to cloud')
-        }
-      }
-
       // Mark as having unsynced changes
       onClipboardChanged?.()
 
@@ -193,14 +181,14 @@ export const ClipboardMainView = forwardRef(({ onConvertToSnippet, onClipboardCh
         'python': 'python',
         'html': 'html',
         'css': 'css',
-        'code': 'text',
+        'code': 'auto',
         'url': 'text',
         'general': 'text',
       }
 
       await clipboardService.convertToSnippet(entry.id, {
         title,
-        language: languageMap[entry.category?.toLowerCase()] || 'text',
+        language: languageMap[entry.category?.toLowerCase()] || 'auto',
         content: entry.content,
         tags: [],
         description: `Converted from clipboard (${entry.category})`,
@@ -219,25 +207,8 @@ export const ClipboardMainView = forwardRef(({ onConvertToSnippet, onClipboardCh
     setError(null)
 
     try {
-      // Clear local storage first
       await clipboardService.clearClipboardHistory()
 
-      // Clear from cloud if user is signed in
-      if (isSignedIn && user?.email && syncApproval?.approved) {
-        try {
-          const { supabase } = await import('@/lib/supabase')
-          const { error } = await supabase
-            .from('clipboard_history')
-            .delete()
-            .eq('user_email', user.email)
-
-          if (error) throw error
-        } catch (cloudError) {
-          console.warn('Failed to clear from cloud:', cloudError)
-          setError('Cleared locally but failed to sync deletion to cloud')
-        }
-      }
-
       // Mark as having unsynced changes
       onClipboardChanged?.()
 
@@ -304,6 +275,8 @@ export const ClipboardMainView = forwardRef(({ onConvertToSnippet, onClipboardCh
       let errorMessage
       if (error.message === 'SYNC_NOT_APPROVED') {
         errorMessage = 'Sync access not approved yet. Please wait for approval.'
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        errorMessage = 'Set up or unlock encrypted sync in Account first.'
       } else if (error.message === 'Supabase not configured') {
         errorMessage = 'Cloud sync not configured'
       } else if (error.message.includes('fetch') || error.message.includes('network')) {
@@ -641,7 +614,7 @@ export const ClipboardMainView = forwardRef(({ onConvertToSnippet, onClipboardCh
                         Quick Scan
                       </p>
                       <p className="text-[11px] text-muted-foreground mb-2">
-                        Press <kbd className="px-1.5 py-0.5 rounded bg-background border text-[10px] font-mono">⌘⇧C</kbd> or <kbd className="px-1.5 py-0.5 rounded bg-background border text-[10px] font-mono">Ctrl+Shift+C</kbd> to scan clipboard
+                        Press <kbd className="px-1.5 py-0.5 rounded bg-background border text-[10px] font-mono">⌘⇧C</kbd> or <kbd className="px-1.5 py-0.5 rounded bg-background border text-[10px] font-mono">Ctrl+Alt+C</kbd> to scan clipboard
                       </p>
                     </div>
                   </div>
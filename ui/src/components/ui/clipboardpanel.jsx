@@ -119,20 +119,8 @@ export const ClipboardPanel = forwardRef(({ onConvertToSnippet, onClipboardChang
     setError(null)
 
     try {
-      // Delete locally first
       await clipboardService.deleteClipboardEntry(entryId)
-      
-      // Delete from cloud if user is signed in
-      if (isSignedIn && user?.email && syncApproval?.approved) {
-        try {
-          const { syncService } = await import('@/lib/sync')
-          await syncService.deleteClipboardFromCloud(user.email, entryId)
-        } catch (cloudError) {
-          console.warn('Failed to delete from cloud:', cloudError)
-          setError('Deleted locally but failed to sync deletion to cloud')
-        }
-      }
-      
+
       // Mark as having unsynced changes
       onClipboardChanged?.()
       
@@ -178,14 +166,14 @@ export const ClipboardPanel = forwardRef(({ onConvertToSnippet, onClipboardChang
         'python': 'python',
         'html': 'html',
         'css': 'css',
-        'code': 'text',
+        'code': 'auto',
         'url': 'text',
         'general': 'text',
       }
 
       await clipboardService.convertToSnippet(selectedEntry.id, {
         title,
-        language: languageMap[selectedEntry.category?.toLowerCase()] || 'text',
+        language: languageMap[selectedEntry.category?.toLowerCase()] || 'auto',
         content: selectedEntry.content,
         tags: [],
         description: `Converted from clipboard (${selectedEntry.category})`,
@@ -207,25 +195,8 @@ export const ClipboardPanel = forwardRef(({ onConvertToSnippet, onClipboardChang
     setError(null)
 
     try {
-      // Clear local storage first
       await clipboardService.clearClipboardHistory()
-      
-      // Clear from cloud if user is signed in
-      if (isSignedIn && user?.email && syncApproval?.approved) {
-        try {
-          const { supabase } = await import('@/lib/supabase')
-          const { error } = await supabase
-            .from('clipboard_history')
-            .delete()
-            .eq('user_email', user.email)
-          
-          if (error) throw error
-        } catch (cloudError) {
-          console.warn('Failed to clear from cloud:', cloudError)
-          setError('Cleared locally but failed to sync deletion to cloud')
-        }
-      }
-      
+
       // Mark as having unsynced changes
       onClipboardChanged?.()
       
@@ -287,6 +258,8 @@ export const ClipboardPanel = forwardRef(({ onConvertToSnippet, onClipboardChang
       let errorMessage
       if (error.message === 'SYNC_NOT_APPROVED') {
         errorMessage = 'Sync access not approved yet. Please wait for approval.'
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        errorMessage = 'Set up or unlock encrypted sync in Account first.'
       } else if (error.message === 'Supabase not configured') {
         errorMessage = 'Cloud sync not configured'
       } else if (error.message.includes('fetch') || error.message.includes('network')) {
@@ -376,7 +349,7 @@ export const ClipboardPanel = forwardRef(({ onConvertToSnippet, onClipboardChang
                 Open Clipboard Popup
               </p>
               <p className="text-[10px] text-muted-foreground mb-2">
-                Press <kbd className="px-1.5 py-0.5 rounded bg-background border text-[9px] font-mono">⌘⇧C</kbd> or <kbd className="px-1.5 py-0.5 rounded bg-background border text-[9px] font-mono">Ctrl+Shift+C</kbd> to open clipboard in the center panel
+                Press <kbd className="px-1.5 py-0.5 rounded bg-background border text-[9px] font-mono">⌘⇧C</kbd> or <kbd className="px-1.5 py-0.5 rounded bg-background border text-[9px] font-mono">Ctrl+Alt+C</kbd> to open clipboard in the center panel
               </p>
               <Button
                 size="sm"
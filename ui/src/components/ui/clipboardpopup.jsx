@@ -1,12 +1,15 @@
 import { useState, useEffect, useRef } from 'react'
-import { Search, FilePlus, Clock, Check, ChevronDown, Clipboard } from 'lucide-react'
+import { Search, FilePlus, Clock, Check, ChevronDown, Clipboard, Pin, Star, ListPlus } from 'lucide-react'
 import { ClipboardService } from '@/lib/clipboard'
+import { hotkeyService } from '@/lib/hotkeys'
 import { Button } from '@/components/ui/button'
 import { listen, emit } from '@tauri-apps/api/event'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { invoke } from '@tauri-apps/api/core'
 
 const PAGE_SIZE = 20
+// Quick slots paste with Cmd on macOS and Ctrl elsewhere
+const SLOT_MODIFIER = navigator.platform.toUpperCase().indexOf('MAC') >= 0 ? '⌘' : 'Ctrl+'
 
 /**
  * ClipboardPopup - A Clipy-like clipboard manager popup
@@ -21,6 +24,9 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
   const [page, setPage] = useState(1)
   const [hasMore, setHasMore] = useState(false)
   const [copiedEntryId, setCopiedEntryId] = useState(null)
+  const [favoritesOnly, setFavoritesOnly] = useState(false)
+  // Entries picked for the paste queue, in the order they'll be pasted
+  const [queuedIds, setQueuedIds] = useState([])
   const popupRef = useRef(null)
   const inputRef = useRef(null)
   const isLoadingRef = useRef(false)
@@ -57,6 +63,7 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
       setEntries([]) // Clear entries when closed
       setAllEntries([]) // Clear all entries when closed
       setCopiedEntryId(null)
+      setQueuedIds([])
       setShowConvertModal(false)
       setSelectedEntry(null)
       isLoadingRef.current = false // Reset loading flag
@@ -125,6 +132,7 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
   // Update displayed entries when page, search query, or all entries change
   useEffect(() => {
     const filtered = allEntries.filter(entry => {
+      if (favoritesOnly && !entry.favorite) return false
       if (!searchQuery) return true
       const query = searchQuery.toLowerCase()
       return (
@@ -141,7 +149,7 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
 
     setEntries(paginatedEntries)
     setHasMore(filtered.length > end)
-  }, [page, allEntries, searchQuery])
+  }, [page, allEntries, searchQuery, favoritesOnly])
 
   // Filter entries based on search
   const filteredEntries = entries
@@ -168,6 +176,17 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
       // Don't handle other shortcuts when modal is open
       if (showConvertModal) return
 
+      // Cmd/Ctrl+1-9 - paste the pinned entry in that quick slot
+      if ((e.metaKey || e.ctrlKey) && e.key >= '1' && e.key <= '9') {
+        const pinnedEntry = allEntries.filter(entry => entry.pinned)[Number(e.key) - 1]
+        if (pinnedEntry) {
+          e.preventDefault()
+          e.stopPropagation()
+          pasteToClipboard(pinnedEntry, false)
+        }
+        return
+      }
+
       // Arrow down
       if (e.key === 'ArrowDown') {
         e.preventDefault()
@@ -205,7 +224,7 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
 
     window.addEventListener('keydown', handleKeyDown, true)
     return () => window.removeEventListener('keydown', handleKeyDown, true)
-  }, [isOpen, showConvertModal, selectedIndex, filteredEntries])
+  }, [isOpen, showConvertModal, selectedIndex, filteredEntries, allEntries])
 
   // Scroll selected item into view
   useEffect(() => {
@@ -329,6 +348,54 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
     }
   }
 
+  async function togglePinned(entry) {
+    try {
+      await clipboardService.setPinned(entry.id, !entry.pinned)
+      await loadClipboardHistory()
+    } catch (error) {
+      console.error('Failed to toggle pin:', error)
+      if (showToast) showToast('Failed to update pin', 'error')
+    }
+  }
+
+  function toggleQueued(entry) {
+    setQueuedIds(prev =>
+      prev.includes(entry.id) ? prev.filter(id => id !== entry.id) : [...prev, entry.id]
+    )
+  }
+
+  // Queue the picked entries; each press of the queue shortcut then pastes the next one
+  async function startQueue() {
+    try {
+      const status = await clipboardService.startPasteQueue(queuedIds)
+      const bindings = await hotkeyService.getBindings()
+      const shortcut = bindings.find(binding => binding.action === 'paste_queue_next')?.shortcut
+      if (showToast) {
+        showToast(
+          shortcut
+            ? `Queued ${status.total} entries. Press ${shortcut} to paste each one`
+            : `Queued ${status.total} entries. Set a paste queue shortcut to paste them`,
+          'success'
+        )
+      }
+      setQueuedIds([])
+      await getCurrentWindow().hide()
+    } catch (error) {
+      console.error('Failed to start paste queue:', error)
+      if (showToast) showToast(typeof error === 'string' ? error : 'Failed to start paste queue', 'error')
+    }
+  }
+
+  async function toggleFavorite(entry) {
+    try {
+      await clipboardService.setFavorite(entry.id, !entry.favorite)
+      await loadClipboardHistory()
+    } catch (error) {
+      console.error('Failed to toggle favorite:', error)
+      if (showToast) showToast('Failed to update favorite', 'error')
+    }
+  }
+
   function handleConvertClick(entry) {
     console.log('🔄 Convert button clicked for entry:', entry)
     console.log('Current state - showConvertModal:', showConvertModal, 'selectedEntry:', selectedEntry)
@@ -361,14 +428,14 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
         'python': 'python',
         'html': 'html',
         'css': 'css',
-        'code': 'text',
+        'code': 'auto',
         'url': 'text',
         'general': 'text',
       }
 
       const snippetData = {
         title,
-        language: languageMap[selectedEntry.category.toLowerCase()] || 'text',
+        language: languageMap[selectedEntry.category.toLowerCase()] || 'auto',
         content: selectedEntry.content,
         tags: [],
         description: `Converted from clipboard (${selectedEntry.category})`,
@@ -451,8 +518,21 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
             value={searchQuery}
             onChange={(e) => setSearchQuery(e.target.value)}
             placeholder="Search clipboard history..."
-            className="w-full pl-10 pr-4 py-3 bg-background border-0 focus:outline-none focus:ring-0"
+            className="w-full pl-10 pr-12 py-3 bg-background border-0 focus:outline-none focus:ring-0"
           />
+          <button
+            onClick={() => {
+              setFavoritesOnly(!favoritesOnly)
+              setPage(1)
+              setSelectedIndex(0)
+            }}
+            className={`absolute right-3 top-1/2 -translate-y-1/2 p-1 rounded transition-colors ${
+              favoritesOnly ? 'text-yellow-500' : 'text-muted-foreground hover:text-foreground'
+            }`}
+            title={favoritesOnly ? 'Show all entries' : 'Show favorites only'}
+          >
+            <Star className={`w-4 h-4 ${favoritesOnly ? 'fill-current' : ''}`} />
+          </button>
         </div>
 
         {/* Entries List */}
@@ -465,7 +545,7 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
             <div className="flex flex-col items-center justify-center py-12 text-muted-foreground">
               <Clock className="w-8 h-8 mb-2 opacity-50" />
               <div className="text-sm">
-                {searchQuery ? 'No matching clipboard entries' : 'No clipboard history yet'}
+                {searchQuery ? 'No matching clipboard entries' : favoritesOnly ? 'No favorites yet' : 'No clipboard history yet'}
               </div>
             </div>
           ) : (
@@ -480,6 +560,11 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
                   onClick={(e) => {
                     e.preventDefault()
                     setSelectedIndex(index)
+                    // Cmd/Ctrl+click picks entries for the paste queue
+                    if (e.metaKey || e.ctrlKey) {
+                      toggleQueued(entry)
+                      return
+                    }
                     // Use setTimeout to ensure state updates before copy
                     setTimeout(() => copyToClipboard(entry), 0)
                   }}
@@ -494,6 +579,30 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
                         <span>{getTimeAgo(entry.created_at)}</span>
                         <span>•</span>
                         <span className="capitalize">{entry.category}</span>
+                        {entry.pinned && (
+                          <>
+                            <span>•</span>
+                            <span className="flex items-center gap-1 text-primary">
+                              <Pin className="w-3 h-3" />
+                              {entry.pin_order && entry.pin_order <= 9 ? `${SLOT_MODIFIER}${entry.pin_order}` : 'Pinned'}
+                            </span>
+                          </>
+                        )}
+                        {queuedIds.includes(entry.id) && (
+                          <>
+                            <span>•</span>
+                            <span className="flex items-center gap-1 text-primary">
+                              <ListPlus className="w-3 h-3" />
+                              #{queuedIds.indexOf(entry.id) + 1}
+                            </span>
+                          </>
+                        )}
+                        {entry.favorite && (
+                          <>
+                            <span>•</span>
+                            <Star className="w-3 h-3 text-yellow-500 fill-current" />
+                          </>
+                        )}
                         {copiedEntryId === entry.id && (
                           <>
                             <span>•</span>
@@ -512,6 +621,51 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
                     {/* Show paste buttons only on selected card */}
                     {index === selectedIndex && (
                       <div className="flex items-center gap-1.5 flex-shrink-0 ml-2">
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            togglePinned(entry)
+                          }}
+                          onMouseDown={(e) => {
+                            e.stopPropagation()
+                          }}
+                          className={`flex items-center gap-1 px-2 py-1 rounded transition-colors font-medium text-[10px] shadow-sm border border-border/50 ${
+                            entry.pinned ? 'bg-primary/10 text-primary' : 'bg-muted text-muted-foreground hover:bg-muted/70 hover:text-foreground'
+                          }`}
+                          title={entry.pinned ? 'Unpin' : 'Pin to a quick slot'}
+                        >
+                          <Pin className="w-3 h-3" />
+                        </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            toggleFavorite(entry)
+                          }}
+                          onMouseDown={(e) => {
+                            e.stopPropagation()
+                          }}
+                          className={`flex items-center gap-1 px-2 py-1 rounded transition-colors font-medium text-[10px] shadow-sm border border-border/50 ${
+                            entry.favorite ? 'bg-yellow-500/10 text-yellow-500' : 'bg-muted text-muted-foreground hover:bg-muted/70 hover:text-foreground'
+                          }`}
+                          title={entry.favorite ? 'Remove from favorites' : 'Add to favorites'}
+                        >
+                          <Star className={`w-3 h-3 ${entry.favorite ? 'fill-current' : ''}`} />
+                        </button>
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            toggleQueued(entry)
+                          }}
+                          onMouseDown={(e) => {
+                            e.stopPropagation()
+                          }}
+                          className={`flex items-center gap-1 px-2 py-1 rounded transition-colors font-medium text-[10px] shadow-sm border border-border/50 ${
+                            queuedIds.includes(entry.id) ? 'bg-primary/10 text-primary' : 'bg-muted text-muted-foreground hover:bg-muted/70 hover:text-foreground'
+                          }`}
+                          title={queuedIds.includes(entry.id) ? 'Remove from the paste queue' : 'Add to the paste queue (⌘-click)'}
+                        >
+                          <ListPlus className="w-3 h-3" />
+                        </button>
                         <button
                           onClick={(e) => {
                             e.stopPropagation()
@@ -563,6 +717,28 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
           )}
         </div>
 
+        {/* Paste queue selection */}
+        {queuedIds.length > 0 && (
+          <div className="flex items-center justify-between gap-2 px-3 py-2 border-t border-border/50 bg-primary/5 text-xs">
+            <span>{queuedIds.length} selected for the paste queue</span>
+            <div className="flex items-center gap-1.5">
+              <button
+                onClick={() => setQueuedIds([])}
+                className="px-2 py-1 rounded text-[10px] text-muted-foreground hover:text-foreground"
+              >
+                Clear
+              </button>
+              <button
+                onClick={startQueue}
+                className="flex items-center gap-1 bg-primary text-primary-foreground hover:bg-primary/90 px-2 py-1 rounded transition-colors font-medium text-[10px] shadow-sm"
+              >
+                <ListPlus className="w-3 h-3" />
+                Queue these
+              </button>
+            </div>
+          </div>
+        )}
+
         {/* Footer */}
         <div className="p-2 border-t border-border/50 text-[11px] text-muted-foreground bg-muted/30">
           <div className="flex flex-col sm:flex-row items-center justify-center gap-1.5 sm:gap-3 text-center">
@@ -573,6 +749,10 @@ export function ClipboardPopup({ isOpen, onClose, showToast, onConvertToSnippet
               <span className="text-border/30">•</span>
               <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Shift+Enter</kbd> Plain
               <span className="text-border/30">•</span>
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">⌘1-9</kbd> Pinned
+              <span className="text-border/30">•</span>
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">⌘-click</kbd> Queue
+              <span className="text-border/30">•</span>
               <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Esc</kbd> Close
             </span>
             {copiedEntryId && <span className="text-green-500">✓ Copied!</span>}
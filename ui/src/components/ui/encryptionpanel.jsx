@@ -0,0 +1,197 @@
+import { useEffect, useState } from 'react'
+import { Lock, ShieldCheck, AlertCircle, RefreshCw } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { ask } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+
+const MIN_PASSPHRASE_LENGTH = 8
+
+/**
+ * EncryptionPanel - encrypts the database and stored files at rest. The key
+ * is kept in the OS keyring, with an optional passphrase that unlocks the
+ * library when the keyring can't.
+ */
+export function EncryptionPanel() {
+  const [status, setStatus] = useState(null)
+  const [useKeyring, setUseKeyring] = useState(true)
+  const [passphrase, setPassphrase] = useState('')
+  const [confirmation, setConfirmation] = useState('')
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    invoke('get_encryption_status').then(setStatus).catch(console.error)
+  }, [])
+
+  const incomplete = status?.enabled &&
+    (!status.database_encrypted || status.plaintext_files > 0 || status.plaintext_backups > 0)
+
+  async function handleEnable() {
+    setError(null)
+    if (!incomplete) {
+      if (!useKeyring && !passphrase) {
+        setError('Choose a passphrase, or keep the key in the system keychain')
+        return
+      }
+      if (passphrase && passphrase.length < MIN_PASSPHRASE_LENGTH) {
+        setError(`Use a passphrase of at least ${MIN_PASSPHRASE_LENGTH} characters`)
+        return
+      }
+      if (passphrase !== confirmation) {
+        setError('The passphrases don\'t match')
+        return
+      }
+
+      const confirmed = await ask(
+        'The database and stored files are encrypted in place. ' +
+          (passphrase ? 'If you forget the passphrase and lose the keychain entry, the library can\'t be recovered. ' : '') +
+          'Existing backups are encrypted too.',
+        { title: 'Encrypt library?', kind: 'warning', okLabel: 'Encrypt' }
+      )
+      if (!confirmed) return
+    }
+
+    setBusy(true)
+    try {
+      setStatus(await invoke('enable_encryption', { useKeyring, passphrase: passphrase || null }))
+      setPassphrase('')
+      setConfirmation('')
+    } catch (err) {
+      console.error('Failed to encrypt the library:', err)
+      setError(typeof err === 'string' ? err : 'Failed to encrypt the library')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function handleAddPassphrase() {
+    setError(null)
+    if (passphrase.length < MIN_PASSPHRASE_LENGTH) {
+      setError(`Use a passphrase of at least ${MIN_PASSPHRASE_LENGTH} characters`)
+      return
+    }
+    if (passphrase !== confirmation) {
+      setError('The passphrases don\'t match')
+      return
+    }
+
+    setBusy(true)
+    try {
+      setStatus(await invoke('add_encryption_passphrase', { passphrase }))
+      setPassphrase('')
+      setConfirmation('')
+    } catch (err) {
+      console.error('Failed to add a passphrase:', err)
+      setError(typeof err === 'string' ? err : 'Failed to add a passphrase')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  if (!status) return null
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center gap-2">
+        <Lock className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Encryption</span>
+      </div>
+
+      {status.enabled && !incomplete && (
+        <div className="flex items-start gap-2 text-[10px]">
+          <ShieldCheck className="h-3.5 w-3.5 text-green-500 mt-0.5 shrink-0" />
+          <p className="text-muted-foreground">
+            Encrypted at rest. The key is kept
+            {status.keyring && ' in the system keychain'}
+            {status.keyring && status.passphrase && ' and'}
+            {status.passphrase && ' behind your passphrase'}.
+          </p>
+        </div>
+      )}
+
+      {incomplete && (
+        <p className="text-[10px] text-muted-foreground">
+          Encryption was interrupted
+          {status.plaintext_files > 0 && ` (${status.plaintext_files} files left)`}
+          {status.plaintext_backups > 0 && ` (${status.plaintext_backups} backups left)`}. Finish it to encrypt the rest of the library.
+        </p>
+      )}
+
+      {status.enabled && !status.locked && status.keyring && !status.passphrase && (
+        <>
+          <p className="text-[10px] text-muted-foreground">
+            Add a passphrase to unlock the library if the system keychain loses the key.
+          </p>
+          <input
+            type="password"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            placeholder="Passphrase"
+            disabled={busy}
+            className="w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md"
+          />
+          {passphrase && (
+            <>
+              <input
+                type="password"
+                value={confirmation}
+                onChange={(e) => setConfirmation(e.target.value)}
+                placeholder="Confirm passphrase"
+                disabled={busy}
+                className="w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md"
+              />
+              <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleAddPassphrase} disabled={busy}>
+                {busy && <RefreshCw className="h-3 w-3 mr-1 animate-spin" />}
+                Add Passphrase
+              </Button>
+            </>
+          )}
+        </>
+      )}
+
+      {!status.enabled && (
+        <>
+          <p className="text-[10px] text-muted-foreground">
+            Encrypt the database and stored files on this device.
+          </p>
+          <label className="flex items-center gap-1.5 text-[10px] cursor-pointer">
+            <input type="checkbox" checked={useKeyring} onChange={(e) => setUseKeyring(e.target.checked)} disabled={busy} />
+            Keep the key in the system keychain
+          </label>
+          <input
+            type="password"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            placeholder={useKeyring ? 'Passphrase (optional fallback)' : 'Passphrase'}
+            disabled={busy}
+            className="w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md"
+          />
+          {passphrase && (
+            <input
+              type="password"
+              value={confirmation}
+              onChange={(e) => setConfirmation(e.target.value)}
+              placeholder="Confirm passphrase"
+              disabled={busy}
+              className="w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md"
+            />
+          )}
+        </>
+      )}
+
+      {(!status.enabled || incomplete) && (
+        <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleEnable} disabled={busy}>
+          {busy ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Lock className="h-3 w-3 mr-1" />}
+          {incomplete ? 'Finish Encrypting' : 'Encrypt Library'}
+        </Button>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+    </div>
+  )
+}
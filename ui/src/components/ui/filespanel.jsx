@@ -253,7 +253,7 @@ export function FilesPanel({
     onSyncStart?.()
 
     try {
-      const result = await syncService.syncFilesAll(email, user?.id)
+      const result = await syncService.syncFilesAll(email)
 
       setSyncStatus({
         type: 'success',
@@ -275,6 +275,8 @@ export function FilesPanel({
       let errorMessage
       if (error.message === 'SYNC_NOT_APPROVED') {
         errorMessage = 'Sync access not approved yet. Please wait for approval.'
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        errorMessage = 'Set up or unlock encrypted sync in Account first.'
       } else if (error.message === 'Supabase not configured') {
         errorMessage = 'Cloud sync not configured'
       } else {
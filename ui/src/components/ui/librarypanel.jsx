@@ -0,0 +1,401 @@
+import { useEffect, useState } from 'react'
+import { Archive, Code, Download, Upload, AlertCircle, CheckCircle, RefreshCw, PackageOpen } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { ask, open, save } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+import { folderPath } from '@/lib/utils'
+
+const ARCHIVE_FILTERS = [{ name: 'NoBrainDev library', extensions: ['zip'] }]
+const VSCODE_FILTERS = [{ name: 'VS Code snippets', extensions: ['json', 'code-snippets'] }]
+const JETBRAINS_FILTERS = [{ name: 'JetBrains live templates', extensions: ['xml'] }]
+
+function plural(count, singular, pluralForm = `${singular}s`) {
+  return `${count} ${count === 1 ? singular : pluralForm}`
+}
+
+function describe(verb, report) {
+  return `${verb} ${plural(report.snippets, 'snippet')}, ${plural(report.folders, 'folder')}, ` +
+    `${plural(report.files, 'file')} and ${plural(report.clipboard_entries, 'clipboard entry', 'clipboard entries')}`
+}
+
+/**
+ * LibraryPanel - export the whole library to a portable archive and import it
+ * on another machine. Importing merges by default; replace wipes the library first.
+ * Snippets can also be moved to and from VS Code snippet files and JetBrains
+ * live templates, per folder, and brought over from other snippet managers and
+ * clipboard tools after previewing what would be added.
+ */
+export function LibraryPanel({ folders = [], onImported }) {
+  const [busy, setBusy] = useState(null)
+  const [mode, setMode] = useState('merge')
+  const [editorFolderId, setEditorFolderId] = useState('')
+  // Smart folders can be exported but not imported into
+  const editorFolderIsSmart = folders.some(f => String(f.id) === editorFolderId && f.kind === 'smart')
+  const [result, setResult] = useState(null)
+  const [error, setError] = useState(null)
+  const [importers, setImporters] = useState([])
+  const [importerId, setImporterId] = useState('')
+  // Dry-run report waiting to be confirmed, with what it was run on
+  const [preview, setPreview] = useState(null)
+  const importer = importers.find(i => i.id === importerId)
+
+  useEffect(() => {
+    invoke('list_importers')
+      .then(list => {
+        setImporters(list)
+        setImporterId(list[0]?.id ?? '')
+      })
+      .catch(console.error)
+  }, [])
+
+  async function handleExport() {
+    const path = await save({
+      title: 'Export library',
+      defaultPath: `nobraindev-library-${new Date().toISOString().slice(0, 10)}.zip`,
+      filters: ARCHIVE_FILTERS,
+    })
+    if (!path) return
+
+    setBusy('export')
+    setError(null)
+    setResult(null)
+    try {
+      const report = await invoke('export_library', { path })
+      setResult({
+        summary: describe('Exported', report),
+        notes: report.missing_files.map(name => `${name}: file data missing, left out`),
+      })
+    } catch (err) {
+      console.error('Failed to export library:', err)
+      setError(typeof err === 'string' ? err : 'Failed to export library')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleImport() {
+    const path = await open({ multiple: false, title: 'Import library', filters: ARCHIVE_FILTERS })
+    if (!path || Array.isArray(path)) return
+
+    if (mode === 'replace') {
+      const confirmed = await ask(
+        'Everything currently in your library, including the trash, is deleted before importing. Secret snippets are kept, as archives never include them.',
+        { title: 'Replace library?', kind: 'warning', okLabel: 'Replace' }
+      )
+      if (!confirmed) return
+    }
+
+    setBusy('import')
+    setError(null)
+    setResult(null)
+    try {
+      const report = await invoke('import_library', { path, mode })
+      setResult({
+        summary: describe('Imported', report) +
+          (report.duplicates_skipped ? ` • ${report.duplicates_skipped} already here` : ''),
+        notes: report.conflicts.map(c => `${c.name}: ${c.resolution}`),
+      })
+      onImported?.()
+    } catch (err) {
+      console.error('Failed to import library:', err)
+      setError(typeof err === 'string' ? err : 'Failed to import library')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleVscodeImport() {
+    const paths = await open({ multiple: true, title: 'Import VS Code snippets', filters: VSCODE_FILTERS })
+    if (!paths || paths.length === 0) return
+
+    setBusy('vscode-import')
+    setError(null)
+    setResult(null)
+    try {
+      const folderId = editorFolderId === '' ? null : Number(editorFolderId)
+      const report = await invoke('import_vscode_snippets', { paths: [].concat(paths), folderId })
+      setResult({
+        summary: `Imported ${plural(report.snippets, 'snippet')}` +
+          (report.duplicates_skipped ? ` • ${report.duplicates_skipped} already here` : ''),
+        notes: report.conflicts.map(c => `${c.name}: ${c.resolution}`),
+      })
+      onImported?.()
+    } catch (err) {
+      console.error('Failed to import VS Code snippets:', err)
+      setError(typeof err === 'string' ? err : 'Failed to import VS Code snippets')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleVscodeExport() {
+    const directory = await open({ directory: true, title: 'Export to a VS Code snippets folder' })
+    if (!directory || Array.isArray(directory)) return
+
+    setBusy('vscode-export')
+    setError(null)
+    setResult(null)
+    try {
+      const folderId = editorFolderId === '' ? null : Number(editorFolderId)
+      const written = await invoke('export_vscode_snippets', { folderId, directory })
+      setResult({
+        summary: `Wrote ${plural(written.length, 'snippet file')}`,
+        notes: written,
+      })
+    } catch (err) {
+      console.error('Failed to export VS Code snippets:', err)
+      setError(typeof err === 'string' ? err : 'Failed to export VS Code snippets')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleJetbrainsImport() {
+    const paths = await open({ multiple: true, title: 'Import JetBrains live templates', filters: JETBRAINS_FILTERS })
+    if (!paths || paths.length === 0) return
+
+    setBusy('jetbrains-import')
+    setError(null)
+    setResult(null)
+    try {
+      const report = await invoke('import_jetbrains_templates', { paths: [].concat(paths) })
+      setResult({
+        summary: `Imported ${plural(report.snippets, 'template')} into ${plural(report.folders, 'new folder')}` +
+          (report.duplicates_skipped ? ` • ${report.duplicates_skipped} already here` : ''),
+        notes: report.conflicts.map(c => `${c.name}: ${c.resolution}`),
+      })
+      onImported?.()
+    } catch (err) {
+      console.error('Failed to import live templates:', err)
+      setError(typeof err === 'string' ? err : 'Failed to import live templates')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleJetbrainsExport() {
+    const directory = await open({ directory: true, title: 'Export to a JetBrains templates folder' })
+    if (!directory || Array.isArray(directory)) return
+
+    setBusy('jetbrains-export')
+    setError(null)
+    setResult(null)
+    try {
+      const folderId = editorFolderId === '' ? null : Number(editorFolderId)
+      const written = await invoke('export_jetbrains_templates', { folderId, directory })
+      setResult({
+        summary: `Wrote ${plural(written.length, 'template group')}`,
+        notes: written,
+      })
+    } catch (err) {
+      console.error('Failed to export live templates:', err)
+      setError(typeof err === 'string' ? err : 'Failed to export live templates')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  function importSummary(report) {
+    const counts = importer?.target === 'clipboard'
+      ? plural(report.clipboard_entries, 'clipboard entry', 'clipboard entries')
+      : `${plural(report.snippets, 'snippet')} and ${plural(report.folders, 'folder')}`
+    return (report.dry_run ? `Would import ${counts}` : `Imported ${counts}`) +
+      (report.duplicates_skipped ? ` • ${report.duplicates_skipped} already here` : '')
+  }
+
+  async function handleImporterPreview() {
+    if (!importer) return
+    const paths = await open({
+      multiple: true,
+      title: `Import from ${importer.name}`,
+      filters: [{ name: importer.name, extensions: importer.extensions }],
+    })
+    if (!paths || paths.length === 0) return
+
+    const folderId = importer.target === 'snippets' && editorFolderId !== '' ? Number(editorFolderId) : null
+    const request = { importer: importer.id, paths: [].concat(paths), folderId }
+
+    setBusy('importer-preview')
+    setError(null)
+    setResult(null)
+    setPreview(null)
+    try {
+      const report = await invoke('run_importer', { ...request, dryRun: true })
+      setPreview({ request, report })
+    } catch (err) {
+      console.error('Failed to preview import:', err)
+      setError(typeof err === 'string' ? err : 'Failed to read the export')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  async function handleImporterRun() {
+    if (!preview) return
+
+    setBusy('importer-run')
+    setError(null)
+    try {
+      const report = await invoke('run_importer', { ...preview.request, dryRun: false })
+      setPreview(null)
+      setResult({
+        summary: importSummary(report),
+        notes: report.conflicts.map(c => `${c.name}: ${c.resolution}`),
+      })
+      onImported?.()
+    } catch (err) {
+      console.error('Failed to import:', err)
+      setError(typeof err === 'string' ? err : 'Failed to import')
+    } finally {
+      setBusy(null)
+    }
+  }
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center gap-2">
+        <Archive className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Library</span>
+      </div>
+
+      <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleExport} disabled={busy !== null}>
+        {busy === 'export' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Download className="h-3 w-3 mr-1" />}
+        Export Library
+      </Button>
+
+      <div className="flex items-center gap-2">
+        <select
+          value={mode}
+          onChange={(e) => setMode(e.target.value)}
+          className="h-8 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
+          title="Merge adds to your library; replace deletes it first"
+        >
+          <option value="merge">Merge</option>
+          <option value="replace">Replace</option>
+        </select>
+        <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleImport} disabled={busy !== null}>
+          {busy === 'import' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Upload className="h-3 w-3 mr-1" />}
+          Import Library
+        </Button>
+      </div>
+
+      <div className="flex items-center gap-2 pt-2">
+        <Code className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Editor Snippets</span>
+      </div>
+
+      <select
+        value={editorFolderId}
+        onChange={(e) => setEditorFolderId(e.target.value)}
+        className="w-full h-8 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
+        title="Exports take this folder's snippets; VS Code and other apps' imports go into it"
+      >
+        <option value="">No folder (all snippets)</option>
+        {folders.map(f => (
+          <option key={f.id} value={f.id}>{f.icon || '📁'} {folderPath(folders, f)}</option>
+        ))}
+      </select>
+
+      <div className="flex items-center gap-2">
+        <span className="w-16 text-[10px] text-muted-foreground">VS Code</span>
+        <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleVscodeImport} disabled={busy !== null || editorFolderIsSmart}>
+          {busy === 'vscode-import' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Upload className="h-3 w-3 mr-1" />}
+          Import
+        </Button>
+        <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleVscodeExport} disabled={busy !== null}>
+          {busy === 'vscode-export' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Download className="h-3 w-3 mr-1" />}
+          Export
+        </Button>
+      </div>
+
+      <div className="flex items-center gap-2">
+        <span className="w-16 text-[10px] text-muted-foreground" title="Each template group becomes a folder">JetBrains</span>
+        <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleJetbrainsImport} disabled={busy !== null}>
+          {busy === 'jetbrains-import' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Upload className="h-3 w-3 mr-1" />}
+          Import
+        </Button>
+        <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleJetbrainsExport} disabled={busy !== null}>
+          {busy === 'jetbrains-export' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Download className="h-3 w-3 mr-1" />}
+          Export
+        </Button>
+      </div>
+
+      <div className="flex items-center gap-2 pt-2">
+        <PackageOpen className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Other Apps</span>
+      </div>
+
+      <div className="flex items-center gap-2">
+        <select
+          value={importerId}
+          onChange={(e) => {
+            setImporterId(e.target.value)
+            setPreview(null)
+          }}
+          className="flex-1 min-w-0 h-8 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
+        >
+          {importers.map(i => (
+            <option key={i.id} value={i.id}>{i.name}{i.target === 'clipboard' ? ' (clipboard)' : ''}</option>
+          ))}
+        </select>
+        <Button
+          variant="outline"
+          className="text-xs"
+          size="sm"
+          onClick={handleImporterPreview}
+          disabled={busy !== null || !importer || (importer.target === 'snippets' && editorFolderIsSmart)}
+        >
+          {busy === 'importer-preview' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Upload className="h-3 w-3 mr-1" />}
+          Preview
+        </Button>
+      </div>
+      {importer && <p className="text-[9px] text-muted-foreground break-words">{importer.hint}</p>}
+
+      {preview && (
+        <div className="p-2.5 bg-muted/50 border border-border rounded-lg space-y-2">
+          <p className="text-[10px]">{importSummary(preview.report)}</p>
+          {preview.report.conflicts.length > 0 && (
+            <ul className="max-h-24 overflow-y-auto pl-5 list-disc text-[9px] text-muted-foreground">
+              {preview.report.conflicts.map((c, i) => <li key={i}>{c.name}: {c.resolution}</li>)}
+            </ul>
+          )}
+          <div className="flex items-center gap-2">
+            <Button
+              className="flex-1 text-xs"
+              size="sm"
+              onClick={handleImporterRun}
+              disabled={busy !== null || (preview.report.snippets + preview.report.clipboard_entries === 0)}
+            >
+              {busy === 'importer-run' ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <Upload className="h-3 w-3 mr-1" />}
+              Import
+            </Button>
+            <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={() => setPreview(null)} disabled={busy !== null}>
+              Cancel
+            </Button>
+          </div>
+        </div>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+
+      {result && (
+        <div className="p-2.5 bg-green-500/10 border border-green-500/20 rounded-lg space-y-1">
+          <div className="flex items-start gap-2">
+            <CheckCircle className="h-3.5 w-3.5 text-green-500 mt-0.5 shrink-0" />
+            <p className="text-[10px] text-green-600">{result.summary}</p>
+          </div>
+          {result.notes.length > 0 && (
+            <ul className="max-h-24 overflow-y-auto pl-5 list-disc text-[9px] text-muted-foreground">
+              {result.notes.map((note, i) => <li key={i}>{note}</li>)}
+            </ul>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
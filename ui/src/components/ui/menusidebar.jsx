@@ -1,6 +1,7 @@
-import { FileCode, FileText, User, Clipboard, Folder, FolderPlus, FolderOpen, X, Edit2 } from 'lucide-react'
+import { FileCode, FileText, User, Clipboard, Folder, FolderPlus, FolderOpen, FolderInput, X, Edit2, Trash2, ChevronRight, ChevronDown, ArrowUp, ArrowDown, Tag, FolderSearch, AlertTriangle } from 'lucide-react'
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { folderPath } from '@/lib/utils'
 
 // Common emojis for folder icons
 const FOLDER_EMOJIS = [
@@ -21,6 +22,11 @@ export function MenuSidebar({
   onCreateFolder,
   onUpdateFolder,
   onDeleteFolder,
+  onMoveFolder,
+  onReorderFolders,
+  onCreateSmartFolder,
+  onEditSmartFolder,
+  onManageTags,
   snippets,
   files
 }) {
@@ -28,6 +34,9 @@ export function MenuSidebar({
   const [newFolderName, setNewFolderName] = useState('')
   const [editingFolderId, setEditingFolderId] = useState(null)
   const [editingFolderName, setEditingFolderName] = useState('')
+  const [newFolderParentId, setNewFolderParentId] = useState(null)
+  const [movingFolderId, setMovingFolderId] = useState(null)
+  const [collapsedFolderIds, setCollapsedFolderIds] = useState(() => new Set())
   const [appVersion, setAppVersion] = useState('')
 
   // Load app version on mount
@@ -41,9 +50,50 @@ export function MenuSidebar({
     { id: 'snippets', icon: FileCode, label: 'Snippets', badge: null },
     { id: 'files', icon: FileText, label: 'Files', badge: null },
     { id: 'clipboard', icon: Clipboard, label: 'Clipboard', badge: null },
+    { id: 'trash', icon: Trash2, label: 'Trash', badge: null },
     { id: 'account', icon: User, label: 'Account', badge: 'DEV' }
   ]
 
+  // Folders are a flat list ordered by position; children are looked up by parent.
+  // Smart folders only hold snippets, so they're hidden in the files view.
+  const childFolders = (parentId) => folders.filter(f =>
+    (f.parent_id ?? null) === parentId && !(f.kind === 'smart' && activeMenu === 'files')
+  )
+
+  const getSubtreeIds = (folderId) => {
+    const ids = new Set([folderId])
+    const pending = [folderId]
+    while (pending.length > 0) {
+      for (const child of childFolders(pending.pop())) {
+        if (!ids.has(child.id)) {
+          ids.add(child.id)
+          pending.push(child.id)
+        }
+      }
+    }
+    return ids
+  }
+
+  // Tree rows in display order, skipping children of collapsed folders
+  const getVisibleRows = (parentId = null, depth = 0) =>
+    childFolders(parentId).flatMap(folder => {
+      const hasChildren = childFolders(folder.id).length > 0
+      const row = { folder, depth, hasChildren }
+      return collapsedFolderIds.has(folder.id) ? [row] : [row, ...getVisibleRows(folder.id, depth + 1)]
+    })
+
+  const toggleCollapsed = (folderId) => {
+    setCollapsedFolderIds(prev => {
+      const next = new Set(prev)
+      if (next.has(folderId)) {
+        next.delete(folderId)
+      } else {
+        next.add(folderId)
+      }
+      return next
+    })
+  }
+
   // Count items per folder (snippets or files based on active menu)
   const getFolderCount = (folderId) => {
     // Use files when in files menu, otherwise use snippets
@@ -55,6 +105,16 @@ export function MenuSidebar({
     if (folderId === 'uncategorized') {
       return items.filter(item => !item.folder_id).length
     }
+    // Smart folders are counted by the backend from their saved search
+    const folder = folders.find(f => f.id === folderId)
+    if (folder?.kind === 'smart') {
+      return folder.snippet_count ?? '–'
+    }
+    // Selecting a folder shows the snippets of its subfolders too; files stay per folder
+    if (activeMenu !== 'files') {
+      const subtree = getSubtreeIds(folderId)
+      return items.filter(item => subtree.has(item.folder_id)).length
+    }
     return items.filter(item => item.folder_id === folderId).length
   }
 
@@ -62,11 +122,30 @@ export function MenuSidebar({
     e.preventDefault()
     if (!newFolderName.trim()) return
 
-    await onCreateFolder(newFolderName.trim())
+    await onCreateFolder(newFolderName.trim(), newFolderParentId)
+    if (newFolderParentId !== null) {
+      setCollapsedFolderIds(prev => {
+        const next = new Set(prev)
+        next.delete(newFolderParentId)
+        return next
+      })
+    }
     setNewFolderName('')
+    setNewFolderParentId(null)
     setShowCreateFolder(false)
   }
 
+  const handleMoveWithinSiblings = async (folder, offset) => {
+    const siblings = childFolders(folder.parent_id ?? null).map(f => f.id)
+    const index = siblings.indexOf(folder.id)
+    const target = index + offset
+    if (target < 0 || target >= siblings.length) return
+
+    siblings.splice(index, 1)
+    siblings.splice(target, 0, folder.id)
+    await onReorderFolders(folder.parent_id ?? null, siblings)
+  }
+
   const handleUpdateFolder = async (folderId) => {
     if (!editingFolderName.trim()) return
 
@@ -142,13 +221,34 @@ export function MenuSidebar({
               <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">
                 {activeMenu === 'files' ? 'File Folders' : 'Folders'}
               </span>
-              <button
-                onClick={() => setShowCreateFolder(!showCreateFolder)}
-                className="p-1 hover:bg-accent rounded transition-colors"
-                title="Create folder"
-              >
-                <FolderPlus className="h-3.5 w-3.5 text-muted-foreground hover:text-foreground" />
-              </button>
+              <div className="flex items-center">
+                <button
+                  onClick={onManageTags}
+                  className="p-1 hover:bg-accent rounded transition-colors"
+                  title="Manage tags"
+                >
+                  <Tag className="h-3.5 w-3.5 text-muted-foreground hover:text-foreground" />
+                </button>
+                {activeMenu !== 'files' && (
+                  <button
+                    onClick={onCreateSmartFolder}
+                    className="p-1 hover:bg-accent rounded transition-colors"
+                    title="Create smart folder"
+                  >
+                    <FolderSearch className="h-3.5 w-3.5 text-muted-foreground hover:text-foreground" />
+                  </button>
+                )}
+                <button
+                  onClick={() => {
+                    setNewFolderParentId(null)
+                    setShowCreateFolder(!showCreateFolder)
+                  }}
+                  className="p-1 hover:bg-accent rounded transition-colors"
+                  title="Create folder"
+                >
+                  <FolderPlus className="h-3.5 w-3.5 text-muted-foreground hover:text-foreground" />
+                </button>
+              </div>
             </div>
 
             {/* Create Folder Input */}
@@ -158,7 +258,9 @@ export function MenuSidebar({
                   type="text"
                   value={newFolderName}
                   onChange={(e) => setNewFolderName(e.target.value)}
-                  placeholder="Folder name..."
+                  placeholder={newFolderParentId !== null
+                    ? `Subfolder of ${folders.find(f => f.id === newFolderParentId)?.name ?? ''}...`
+                    : 'Folder name...'}
                   className="w-full px-2 py-1 text-xs bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-purple-500"
                   autoFocus
                 />
@@ -207,9 +309,57 @@ export function MenuSidebar({
             </button>
 
             {/* Custom Folders */}
-            {folders.map((folder) => (
-              <div key={folder.id} className="group">
-                {editingFolderId === folder.id ? (
+            {getVisibleRows().map(({ folder, depth, hasChildren }) => (
+              <div key={folder.id} className="group" style={{ paddingLeft: depth * 12 }}>
+                {movingFolderId === folder.id ? (
+                  // Move mode - pick a new parent or shift among siblings
+                  <div className="flex items-center gap-1 px-2 py-1.5">
+                    <select
+                      value={folder.parent_id ?? ''}
+                      onChange={async (e) => {
+                        const parentId = e.target.value === '' ? null : Number(e.target.value)
+                        await onMoveFolder(folder.id, parentId)
+                        setMovingFolderId(null)
+                      }}
+                      className="flex-1 min-w-0 px-1 py-0.5 text-xs bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-purple-500"
+                      autoFocus
+                    >
+                      <option value="">Top level</option>
+                      {(() => {
+                        // A folder can't move into itself, its own subfolders or a smart folder
+                        const excluded = getSubtreeIds(folder.id)
+                        return folders
+                          .filter(f => !excluded.has(f.id) && f.kind !== 'smart')
+                          .map(f => (
+                            <option key={f.id} value={f.id}>{f.icon || '📁'} {folderPath(folders, f)}</option>
+                          ))
+                      })()}
+                    </select>
+                    <button
+                      type="button"
+                      onClick={() => handleMoveWithinSiblings(folder, -1)}
+                      className="p-0.5 hover:bg-accent rounded"
+                      title="Move up"
+                    >
+                      <ArrowUp className="h-3 w-3" />
+                    </button>
+                    <button
+                      type="button"
+                      onClick={() => handleMoveWithinSiblings(folder, 1)}
+                      className="p-0.5 hover:bg-accent rounded"
+                      title="Move down"
+                    >
+                      <ArrowDown className="h-3 w-3" />
+                    </button>
+                    <button
+                      type="button"
+                      onClick={() => setMovingFolderId(null)}
+                      className="p-0.5 hover:bg-destructive/20 rounded"
+                    >
+                      <X className="h-3 w-3 text-destructive" />
+                    </button>
+                  </div>
+                ) : editingFolderId === folder.id ? (
                   // Edit mode
                   <form onSubmit={(e) => { e.preventDefault(); handleUpdateFolder(folder.id) }} className="flex items-center gap-1 px-2 py-1.5">
                     <input
@@ -238,20 +388,47 @@ export function MenuSidebar({
                           : 'hover:bg-accent text-muted-foreground hover:text-foreground'
                       }`}
                     >
-                      {/* Folder icon - clickable to change emoji */}
-                      <span
-                        className="text-sm cursor-pointer hover:scale-125 transition-transform flex-shrink-0 leading-none"
-                        onClick={(e) => {
-                          e.stopPropagation()
-                          // Show emoji picker (for now, cycle through emojis)
-                          const currentIndex = FOLDER_EMOJIS.indexOf(folder.icon || '📁')
-                          const nextIndex = (currentIndex + 1) % FOLDER_EMOJIS.length
-                          handleEmojiSelect(folder.id, FOLDER_EMOJIS[nextIndex])
-                        }}
-                        title="Click to change icon"
-                      >
-                        {folder.icon || '📁'}
-                      </span>
+                      {/* Expand/collapse subfolders */}
+                      {hasChildren ? (
+                        <span
+                          className="-ml-1 flex-shrink-0 text-muted-foreground hover:text-foreground"
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            toggleCollapsed(folder.id)
+                          }}
+                        >
+                          {collapsedFolderIds.has(folder.id)
+                            ? <ChevronRight className="h-3 w-3" />
+                            : <ChevronDown className="h-3 w-3" />}
+                        </span>
+                      ) : (
+                        <span className="-ml-1 w-3 flex-shrink-0" />
+                      )}
+
+                      {/* Folder icon - clickable to change emoji; smart folders get a search icon */}
+                      {folder.kind === 'smart' ? (
+                        folder.query_error ? (
+                          <span title={folder.query_error} className="flex-shrink-0">
+                            <AlertTriangle className="h-3.5 w-3.5 text-amber-500" />
+                          </span>
+                        ) : (
+                          <FolderSearch className="h-3.5 w-3.5 flex-shrink-0 text-purple-500" />
+                        )
+                      ) : (
+                        <span
+                          className="text-sm cursor-pointer hover:scale-125 transition-transform flex-shrink-0 leading-none"
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            // Show emoji picker (for now, cycle through emojis)
+                            const currentIndex = FOLDER_EMOJIS.indexOf(folder.icon || '📁')
+                            const nextIndex = (currentIndex + 1) % FOLDER_EMOJIS.length
+                            handleEmojiSelect(folder.id, FOLDER_EMOJIS[nextIndex])
+                          }}
+                          title="Click to change icon"
+                        >
+                          {folder.icon || '📁'}
+                        </span>
+                      )}
 
                       {/* Folder name */}
                       <span className="text-xs flex-1 text-left truncate">
@@ -268,14 +445,43 @@ export function MenuSidebar({
 
                     {/* Edit/Delete buttons - absolutely positioned to not affect badge alignment */}
                     <div className="absolute right-10 top-1/2 -translate-y-1/2 flex items-center gap-0.5 opacity-0 group-hover:opacity-100 transition-opacity">
+                      {folder.kind !== 'smart' && (
+                        <button
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            setNewFolderParentId(folder.id)
+                            setShowCreateFolder(true)
+                          }}
+                          className="p-1 hover:bg-accent rounded transition-colors"
+                          title="New subfolder"
+                        >
+                          <FolderPlus className="h-3 w-3" />
+                        </button>
+                      )}
+
+                      <button
+                        onClick={(e) => {
+                          e.stopPropagation()
+                          setMovingFolderId(folder.id)
+                        }}
+                        className="p-1 hover:bg-accent rounded transition-colors"
+                        title="Move folder"
+                      >
+                        <FolderInput className="h-3 w-3" />
+                      </button>
+
                       <button
                         onClick={(e) => {
                           e.stopPropagation()
+                          if (folder.kind === 'smart') {
+                            onEditSmartFolder(folder)
+                            return
+                          }
                           setEditingFolderId(folder.id)
                           setEditingFolderName(folder.name)
                         }}
                         className="p-1 hover:bg-accent rounded transition-colors"
-                        title="Edit folder"
+                        title={folder.kind === 'smart' ? 'Edit smart folder' : 'Edit folder'}
                       >
                         <Edit2 className="h-3 w-3" />
                       </button>
@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react'
 import { Folder, Check, X } from 'lucide-react'
 import { Button } from '@/components/ui/button'
+import { folderPath } from '@/lib/utils'
 
 export function OrganizeModal({
   isOpen,
@@ -91,7 +92,7 @@ export function OrganizeModal({
                   <option value="uncategorized">Uncategorized</option>
                   {folders.map(folder => (
                     <option key={folder.id} value={folder.id}>
-                      {folder.name}
+                      {folderPath(folders, folder)}
                     </option>
                   ))}
                 </select>
@@ -0,0 +1,156 @@
+import { useState, useEffect } from 'react'
+import { History, X, RotateCcw } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { Button } from '@/components/ui/button'
+
+const LINE_CLASS = {
+  added: 'bg-green-500/10 text-green-700 dark:text-green-400',
+  removed: 'bg-red-500/10 text-red-700 dark:text-red-400',
+  unchanged: 'text-muted-foreground',
+}
+
+const LINE_PREFIX = { added: '+', removed: '-', unchanged: ' ' }
+
+/**
+ * RevisionHistory - previous versions of a snippet
+ * Shows a line diff of the selected revision against the current version
+ * and restores it (the current version is kept in history, so restores can be undone).
+ */
+export function RevisionHistory({ isOpen, snippet, onClose, onRestored }) {
+  const [revisions, setRevisions] = useState([])
+  const [selectedId, setSelectedId] = useState(null)
+  const [diff, setDiff] = useState(null)
+  const [isRestoring, setIsRestoring] = useState(false)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    if (!isOpen || !snippet) return
+
+    setDiff(null)
+    setError(null)
+    invoke('get_snippet_revisions', { snippetId: snippet.id })
+      .then(found => {
+        setRevisions(found)
+        setSelectedId(found[0]?.id ?? null)
+      })
+      .catch(err => {
+        console.error('Failed to load revisions:', err)
+        setError('Failed to load history')
+      })
+  }, [isOpen, snippet])
+
+  useEffect(() => {
+    if (!isOpen || !snippet || selectedId === null) return
+
+    invoke('diff_snippet_revisions', {
+      snippetId: snippet.id,
+      fromRevision: selectedId,
+      toRevision: null,
+    })
+      .then(setDiff)
+      .catch(err => {
+        console.error('Failed to diff revisions:', err)
+        setError('Failed to compare versions')
+      })
+  }, [isOpen, snippet, selectedId])
+
+  if (!isOpen || !snippet) return null
+
+  const handleRestore = async () => {
+    setIsRestoring(true)
+    try {
+      const restored = await invoke('restore_snippet_revision', { revisionId: selectedId })
+      onRestored?.(restored)
+      onClose()
+    } catch (err) {
+      console.error('Failed to restore revision:', err)
+      setError(typeof err === 'string' ? err : 'Failed to restore revision')
+    } finally {
+      setIsRestoring(false)
+    }
+  }
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      {/* Backdrop */}
+      <div className="absolute inset-0 bg-black/50" onClick={onClose} />
+
+      {/* Dialog */}
+      <div className="relative bg-background border border-border rounded-lg shadow-lg max-w-4xl w-full h-[70vh] mx-4 z-10 flex flex-col">
+        {/* Header */}
+        <div className="flex items-center justify-between p-4 border-b">
+          <div className="flex items-center gap-2">
+            <History className="h-4 w-4 text-primary" />
+            <h2 className="text-sm font-semibold">History: {snippet.title}</h2>
+          </div>
+          <button
+            onClick={onClose}
+            className="h-6 w-6 flex items-center justify-center hover:bg-accent rounded-sm transition-colors"
+          >
+            <X className="h-3.5 w-3.5" />
+          </button>
+        </div>
+
+        <div className="flex flex-1 overflow-hidden">
+          {/* Revision list */}
+          <div className="w-56 border-r overflow-y-auto">
+            {revisions.length === 0 ? (
+              <div className="p-4 text-xs text-muted-foreground">No earlier versions yet</div>
+            ) : (
+              revisions.map(revision => (
+                <button
+                  key={revision.id}
+                  onClick={() => setSelectedId(revision.id)}
+                  className={`w-full text-left px-3 py-2 border-b border-border/50 transition-colors ${
+                    revision.id === selectedId ? 'bg-accent' : 'hover:bg-accent/50'
+                  }`}
+                >
+                  <div className="text-xs font-medium truncate">{revision.title}</div>
+                  <div className="text-[10px] text-muted-foreground">
+                    {new Date(revision.saved_at).toLocaleString()}
+                  </div>
+                </button>
+              ))
+            )}
+          </div>
+
+          {/* Diff against the current version */}
+          <div className="flex-1 overflow-auto">
+            {diff && (
+              <>
+                <div className="px-3 py-2 border-b text-[10px] text-muted-foreground sticky top-0 bg-background">
+                  Changes since this version:
+                  <span className="text-green-600 dark:text-green-400 ml-1">+{diff.added}</span>
+                  <span className="text-red-600 dark:text-red-400 ml-1">-{diff.removed}</span>
+                  {diff.title_changed && <span className="ml-2">• title changed</span>}
+                </div>
+                <pre className="text-[11px] font-mono leading-relaxed">
+                  {diff.lines.map((line, index) => (
+                    <div key={index} className={`px-3 whitespace-pre-wrap ${LINE_CLASS[line.kind]}`}>
+                      {LINE_PREFIX[line.kind]} {line.text}
+                    </div>
+                  ))}
+                </pre>
+              </>
+            )}
+          </div>
+        </div>
+
+        {error && (
+          <div className="px-4 py-2 text-xs text-red-500 border-t">{error}</div>
+        )}
+
+        {/* Footer */}
+        <div className="flex items-center justify-end gap-2 p-4 border-t bg-muted/30">
+          <Button variant="outline" onClick={onClose}>
+            Close
+          </Button>
+          <Button onClick={handleRestore} disabled={selectedId === null || isRestoring}>
+            <RotateCcw className="h-3 w-3 mr-1" />
+            {isRestoring ? 'Restoring...' : 'Restore this version'}
+          </Button>
+        </div>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,72 @@
+import { useEffect, useState } from 'react'
+import { Lock, Eye, AlertCircle } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { Button } from '@/components/ui/button'
+
+/**
+ * SecretGate - stands in for the editor of a secret snippet until it's
+ * revealed, asking for the vault password when secrets are locked
+ */
+export function SecretGate({ snippetId, onRevealed }) {
+  const [unlocked, setUnlocked] = useState(false)
+  const [password, setPassword] = useState('')
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    invoke('get_secret_vault_status')
+      .then(status => setUnlocked(status.unlocked))
+      .catch(console.error)
+  }, [snippetId])
+
+  async function handleReveal(e) {
+    e.preventDefault()
+    setBusy(true)
+    setError(null)
+    try {
+      if (!unlocked) {
+        await invoke('unlock_secrets', { password })
+        setPassword('')
+      }
+      onRevealed(await invoke('reveal_secret_snippet', { id: snippetId }))
+    } catch (err) {
+      console.error('Failed to reveal secret snippet:', err)
+      setError(typeof err === 'string' ? err : 'Failed to reveal secret snippet')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  return (
+    <div className="flex-1 flex items-center justify-center">
+      <form onSubmit={handleReveal} className="w-64 space-y-2 text-center">
+        <Lock className="h-8 w-8 text-muted-foreground mx-auto" />
+        <h2 className="text-sm font-semibold">This snippet is secret</h2>
+        <p className="text-[10px] text-muted-foreground">
+          {unlocked ? 'Reveal it to view or edit it.' : 'Enter the password for secret snippets to reveal it.'}
+        </p>
+        {!unlocked && (
+          <input
+            type="password"
+            autoFocus
+            value={password}
+            onChange={(e) => setPassword(e.target.value)}
+            placeholder="Password"
+            disabled={busy}
+            className="w-full h-8 px-2 text-xs bg-background border border-border rounded-md"
+          />
+        )}
+        <Button type="submit" className="w-full text-xs" size="sm" disabled={busy || (!unlocked && !password)}>
+          <Eye className="h-3 w-3 mr-1" />
+          Reveal
+        </Button>
+        {error && (
+          <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2 text-left">
+            <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+            <p className="text-[10px] text-destructive">{error}</p>
+          </div>
+        )}
+      </form>
+    </div>
+  )
+}
@@ -0,0 +1,210 @@
+import { useEffect, useState } from 'react'
+import { KeyRound, Lock, Unlock, AlertCircle } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { Button } from '@/components/ui/button'
+
+const MIN_PASSWORD_LENGTH = 8
+const LOCK_AFTER_OPTIONS = [1, 5, 15, 30, 60]
+const FIELD_CLASS = 'w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md'
+
+/**
+ * SecretsPanel - the master password for secret snippets, which are
+ * encrypted separately from the rest of the library and lock again after
+ * being left alone
+ */
+export function SecretsPanel() {
+  const [status, setStatus] = useState(null)
+  const [currentPassword, setCurrentPassword] = useState('')
+  const [password, setPassword] = useState('')
+  const [confirmation, setConfirmation] = useState('')
+  const [changing, setChanging] = useState(false)
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    invoke('get_secret_vault_status').then(setStatus).catch(console.error)
+
+    let unlisten
+    listen('secrets-locked', () => {
+      setStatus(prev => prev && { ...prev, unlocked: false })
+    }).then(fn => { unlisten = fn })
+    return () => unlisten?.()
+  }, [])
+
+  async function run(action, fallback) {
+    setBusy(true)
+    setError(null)
+    try {
+      await action()
+    } catch (err) {
+      console.error(fallback, err)
+      setError(typeof err === 'string' ? err : fallback)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  function handleSetPassword() {
+    if (password.length < MIN_PASSWORD_LENGTH) {
+      setError(`Use a password of at least ${MIN_PASSWORD_LENGTH} characters`)
+      return
+    }
+    if (password !== confirmation) {
+      setError('The passwords don\'t match')
+      return
+    }
+    run(async () => {
+      setStatus(await invoke('set_secret_password', {
+        currentPassword: status.configured ? currentPassword : null,
+        password,
+      }))
+      setCurrentPassword('')
+      setPassword('')
+      setConfirmation('')
+      setChanging(false)
+    }, 'Failed to set the password')
+  }
+
+  function handleUnlock() {
+    run(async () => {
+      setStatus(await invoke('unlock_secrets', { password: currentPassword }))
+      setCurrentPassword('')
+    }, 'Failed to unlock secret snippets')
+  }
+
+  function handleLock() {
+    run(async () => {
+      await invoke('lock_secrets')
+      setStatus({ ...status, unlocked: false })
+    }, 'Failed to lock secret snippets')
+  }
+
+  function handleLockAfter(minutes) {
+    run(async () => {
+      setStatus(await invoke('set_secret_lock_timeout', { minutes }))
+    }, 'Failed to change when secrets lock')
+  }
+
+  if (!status) return null
+
+  const editingPassword = !status.configured || changing
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center gap-2">
+        <KeyRound className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Secret Snippets</span>
+      </div>
+
+      <p className="text-[10px] text-muted-foreground">
+        {status.configured
+          ? `Secrets are ${status.unlocked ? 'unlocked' : 'locked'}. They lock again after being left alone.`
+          : 'Set a password to keep tokens and connection strings as secret snippets, encrypted and hidden until you unlock them.'}
+      </p>
+      <p className="text-[10px] text-muted-foreground">
+        Secret snippets stay on this device. One that was synced before it became secret is left as it was on your other devices; delete it there to remove it.
+      </p>
+
+      {status.configured && !changing && (
+        <>
+          {status.unlocked ? (
+            <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleLock} disabled={busy}>
+              <Lock className="h-3 w-3 mr-1" />
+              Lock Now
+            </Button>
+          ) : (
+            <div className="flex gap-1.5">
+              <input
+                type="password"
+                value={currentPassword}
+                onChange={(e) => setCurrentPassword(e.target.value)}
+                onKeyDown={(e) => e.key === 'Enter' && handleUnlock()}
+                placeholder="Password"
+                disabled={busy}
+                className={FIELD_CLASS}
+              />
+              <Button variant="outline" className="h-7 text-xs" size="sm" onClick={handleUnlock} disabled={busy || !currentPassword}>
+                <Unlock className="h-3 w-3 mr-1" />
+                Unlock
+              </Button>
+            </div>
+          )}
+
+          <label className="flex items-center justify-between text-[10px]">
+            <span className="text-muted-foreground">Lock after</span>
+            <select
+              value={status.lock_after_minutes}
+              onChange={(e) => handleLockAfter(parseInt(e.target.value))}
+              disabled={busy}
+              className="h-6 px-1 text-[10px] bg-background border border-border rounded-md"
+            >
+              {[...new Set([...LOCK_AFTER_OPTIONS, status.lock_after_minutes])].sort((a, b) => a - b).map(minutes => (
+                <option key={minutes} value={minutes}>
+                  {minutes === 1 ? '1 minute' : `${minutes} minutes`}
+                </option>
+              ))}
+            </select>
+          </label>
+
+          <button
+            type="button"
+            onClick={() => { setChanging(true); setCurrentPassword('') }}
+            className="text-[10px] text-muted-foreground hover:text-foreground underline"
+          >
+            Change password
+          </button>
+        </>
+      )}
+
+      {editingPassword && (
+        <>
+          {status.configured && (
+            <input
+              type="password"
+              value={currentPassword}
+              onChange={(e) => setCurrentPassword(e.target.value)}
+              placeholder="Current password"
+              disabled={busy}
+              className={FIELD_CLASS}
+            />
+          )}
+          <input
+            type="password"
+            value={password}
+            onChange={(e) => setPassword(e.target.value)}
+            placeholder={status.configured ? 'New password' : 'Password'}
+            disabled={busy}
+            className={FIELD_CLASS}
+          />
+          <input
+            type="password"
+            value={confirmation}
+            onChange={(e) => setConfirmation(e.target.value)}
+            placeholder="Confirm password"
+            disabled={busy}
+            className={FIELD_CLASS}
+          />
+          <div className="flex gap-1.5">
+            {changing && (
+              <Button variant="ghost" className="flex-1 text-xs" size="sm" onClick={() => setChanging(false)} disabled={busy}>
+                Cancel
+              </Button>
+            )}
+            <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleSetPassword} disabled={busy || !password}>
+              <KeyRound className="h-3 w-3 mr-1" />
+              {status.configured ? 'Change Password' : 'Set Password'}
+            </Button>
+          </div>
+        </>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+    </div>
+  )
+}
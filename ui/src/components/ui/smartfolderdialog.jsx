@@ -0,0 +1,201 @@
+import { useState, useEffect } from 'react'
+import { FolderSearch, X } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { Button } from '@/components/ui/button'
+import { parseTags } from '@/lib/utils'
+
+const UPDATED_WITHIN_OPTIONS = [
+  { value: '', label: 'Any time' },
+  { value: 1, label: 'Last day' },
+  { value: 7, label: 'Last 7 days' },
+  { value: 30, label: 'Last 30 days' },
+  { value: 90, label: 'Last 90 days' },
+]
+
+const DEFAULT_MIN_SCORE = 0.5
+
+const inputClass = 'w-full px-2 py-1 text-xs bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-purple-500'
+
+/**
+ * SmartFolderDialog - create or edit a smart folder
+ * A smart folder is a saved search: its snippets are whatever currently matches
+ * the text, tag, language, date and meaning conditions below.
+ */
+export function SmartFolderDialog({ isOpen, folder, onClose, onSaved }) {
+  const [name, setName] = useState('')
+  const [text, setText] = useState('')
+  const [tags, setTags] = useState('')
+  const [languages, setLanguages] = useState('')
+  const [updatedWithinDays, setUpdatedWithinDays] = useState('')
+  const [semanticQuery, setSemanticQuery] = useState('')
+  const [minScore, setMinScore] = useState(DEFAULT_MIN_SCORE)
+  const [preview, setPreview] = useState(null)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    if (!isOpen) return
+    const query = folder?.query || {}
+    setName(folder?.name || '')
+    setText(query.text || '')
+    setTags((query.tags || []).join(', '))
+    setLanguages((query.languages || []).join(', '))
+    setUpdatedWithinDays(query.updated_within_days ?? '')
+    setSemanticQuery(query.semantic_query || '')
+    setMinScore(query.min_score ?? DEFAULT_MIN_SCORE)
+    setPreview(null)
+    setError(null)
+  }, [isOpen, folder])
+
+  const query = {
+    text: text.trim() || null,
+    tags: parseTags(tags),
+    languages: languages.split(',').map(l => l.trim()).filter(Boolean),
+    updated_within_days: updatedWithinDays === '' ? null : Number(updatedWithinDays),
+    semantic_query: semanticQuery.trim() || null,
+    min_score: semanticQuery.trim() ? minScore : null,
+  }
+  const queryKey = JSON.stringify(query)
+
+  // Debounced match count while the conditions are edited
+  useEffect(() => {
+    if (!isOpen) return
+    const timer = setTimeout(async () => {
+      try {
+        const results = await invoke('preview_smart_folder', { query })
+        setPreview({ count: results.length })
+      } catch (err) {
+        setPreview({ error: typeof err === 'string' ? err : 'Failed to preview smart folder' })
+      }
+    }, 400)
+    return () => clearTimeout(timer)
+  }, [isOpen, queryKey])
+
+  if (!isOpen) return null
+
+  async function handleSave(e) {
+    e.preventDefault()
+    if (!name.trim()) return
+
+    try {
+      setError(null)
+      if (folder) {
+        if (name.trim() !== folder.name) {
+          await invoke('update_folder', { id: folder.id, name: name.trim(), icon: folder.icon })
+        }
+        await invoke('update_smart_folder', { id: folder.id, query })
+      } else {
+        await invoke('create_smart_folder', { name: name.trim(), query })
+      }
+      onSaved?.()
+      onClose()
+    } catch (err) {
+      console.error('Failed to save smart folder:', err)
+      setError(typeof err === 'string' ? err : 'Failed to save smart folder')
+    }
+  }
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      {/* Backdrop */}
+      <div className="absolute inset-0 bg-black/50" onClick={onClose} />
+
+      {/* Dialog */}
+      <form
+        onSubmit={handleSave}
+        className="relative bg-background border border-border rounded-lg shadow-lg max-w-md w-full mx-4 z-10 flex flex-col"
+      >
+        {/* Header */}
+        <div className="flex items-center justify-between p-4 border-b">
+          <div className="flex items-center gap-2">
+            <FolderSearch className="h-4 w-4 text-primary" />
+            <h2 className="text-sm font-semibold">{folder ? 'Edit Smart Folder' : 'New Smart Folder'}</h2>
+          </div>
+          <button
+            type="button"
+            onClick={onClose}
+            className="h-6 w-6 flex items-center justify-center hover:bg-accent rounded-sm transition-colors"
+          >
+            <X className="h-3.5 w-3.5" />
+          </button>
+        </div>
+
+        {/* Conditions */}
+        <div className="p-4 space-y-3">
+          <label className="block space-y-1">
+            <span className="text-[10px] text-muted-foreground">Name</span>
+            <input type="text" value={name} onChange={(e) => setName(e.target.value)} className={inputClass} autoFocus />
+          </label>
+
+          <label className="block space-y-1">
+            <span className="text-[10px] text-muted-foreground">Contains text</span>
+            <input type="text" value={text} onChange={(e) => setText(e.target.value)} placeholder="Title, description or code" className={inputClass} />
+          </label>
+
+          <div className="grid grid-cols-2 gap-3">
+            <label className="block space-y-1">
+              <span className="text-[10px] text-muted-foreground">Has all tags</span>
+              <input type="text" value={tags} onChange={(e) => setTags(e.target.value)} placeholder="react, hooks" className={inputClass} />
+            </label>
+            <label className="block space-y-1">
+              <span className="text-[10px] text-muted-foreground">Language is any of</span>
+              <input type="text" value={languages} onChange={(e) => setLanguages(e.target.value)} placeholder="rust, go" className={inputClass} />
+            </label>
+          </div>
+
+          <label className="block space-y-1">
+            <span className="text-[10px] text-muted-foreground">Updated</span>
+            <select value={updatedWithinDays} onChange={(e) => setUpdatedWithinDays(e.target.value)} className={`${inputClass} cursor-pointer`}>
+              {UPDATED_WITHIN_OPTIONS.map(option => (
+                <option key={option.label} value={option.value}>{option.label}</option>
+              ))}
+            </select>
+          </label>
+
+          <label className="block space-y-1">
+            <span className="text-[10px] text-muted-foreground">Similar in meaning to (needs the AI model)</span>
+            <input type="text" value={semanticQuery} onChange={(e) => setSemanticQuery(e.target.value)} placeholder="retry an HTTP request with backoff" className={inputClass} />
+          </label>
+
+          {semanticQuery.trim() && (
+            <label className="flex items-center gap-2">
+              <span className="text-[10px] text-muted-foreground whitespace-nowrap">Minimum similarity</span>
+              <input
+                type="range"
+                min="0"
+                max="1"
+                step="0.05"
+                value={minScore}
+                onChange={(e) => setMinScore(Number(e.target.value))}
+                className="flex-1"
+              />
+              <span className="text-[10px] w-8 text-right">{Math.round(minScore * 100)}%</span>
+            </label>
+          )}
+        </div>
+
+        {error && (
+          <div className="px-4 py-2 text-xs text-red-500 border-t">{error}</div>
+        )}
+
+        {/* Footer */}
+        <div className="flex items-center justify-between gap-2 p-4 border-t bg-muted/30">
+          <span className="text-[10px] text-muted-foreground">
+            {preview?.error
+              ? preview.error
+              : preview
+                ? `${preview.count} matching snippet${preview.count === 1 ? '' : 's'}`
+                : 'Checking...'}
+          </span>
+          <div className="flex items-center gap-2">
+            <Button type="button" variant="outline" onClick={onClose}>
+              Cancel
+            </Button>
+            <Button type="submit" disabled={!name.trim()}>
+              {folder ? 'Save' : 'Create'}
+            </Button>
+          </div>
+        </div>
+      </form>
+    </div>
+  )
+}
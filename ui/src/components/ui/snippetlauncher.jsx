@@ -0,0 +1,408 @@
+import { useState, useEffect, useRef } from 'react'
+import { Search, Code, Clipboard, ArrowLeft, FolderOpen, Lock } from 'lucide-react'
+import { listen } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
+import { open } from '@tauri-apps/plugin-dialog'
+
+const RESULT_LIMIT = 20
+const SEARCH_DEBOUNCE_MS = 120
+const FIELD_CLASS = 'mt-1 w-full px-3 py-2 text-sm font-mono bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-primary'
+
+/**
+ * SnippetLauncher - quick-insert window for snippets
+ * Fuzzy + semantic search, then pastes the snippet into the previously focused app.
+ * Snippet templates with variables (named, tab stops, choices, GIT_BRANCH)
+ * ask for values before pasting, including ones opened by a typed text
+ * expansion trigger. Secret snippets ask for the vault password first when
+ * it's locked.
+ */
+export function SnippetLauncher({ onClose }) {
+  const [query, setQuery] = useState('')
+  const [results, setResults] = useState([])
+  const [selectedIndex, setSelectedIndex] = useState(0)
+  const [isLoading, setIsLoading] = useState(false)
+  const [error, setError] = useState(null)
+
+  // Template variable prompt state
+  const [pending, setPending] = useState(null) // { snippet, asPlainText, variables, unlock }
+  const [values, setValues] = useState({})
+  const [password, setPassword] = useState('')
+
+  const inputRef = useRef(null)
+  const firstFieldRef = useRef(null)
+  const searchIdRef = useRef(0)
+  const isChoosingDirectoryRef = useRef(false)
+
+  function reset() {
+    setQuery('')
+    setSelectedIndex(0)
+    setPending(null)
+    setValues({})
+    setPassword('')
+    setError(null)
+  }
+
+  async function runSearch(text) {
+    const searchId = ++searchIdRef.current
+    setIsLoading(true)
+    try {
+      const found = await invoke('search_snippet_launcher', { query: text, limit: RESULT_LIMIT })
+      // Ignore responses for queries the user already typed past
+      if (searchId === searchIdRef.current) {
+        setResults(found)
+        setSelectedIndex(0)
+      }
+    } catch (err) {
+      console.error('Failed to search snippets:', err)
+      setError('Failed to search snippets')
+    } finally {
+      if (searchId === searchIdRef.current) setIsLoading(false)
+    }
+  }
+
+  // Debounced search as the user types
+  useEffect(() => {
+    const timer = setTimeout(() => runSearch(query), SEARCH_DEBOUNCE_MS)
+    return () => clearTimeout(timer)
+  }, [query])
+
+  // Reset and refresh whenever the launcher hotkey is pressed
+  useEffect(() => {
+    let unlistenFn
+
+    const setupListener = async () => {
+      unlistenFn = await listen('snippet-launcher-triggered', () => {
+        reset()
+        runSearch('')
+        setTimeout(() => inputRef.current?.focus(), 50)
+      })
+    }
+
+    setupListener()
+
+    return () => {
+      if (unlistenFn) {
+        unlistenFn()
+      }
+    }
+  }, [])
+
+  // A typed text expansion trigger whose template needs values
+  useEffect(() => {
+    let unlistenFn
+
+    const setupListener = async () => {
+      unlistenFn = await listen('snippet-launcher-prompt', async (event) => {
+        try {
+          const snippet = await invoke('get_snippet', { id: event.payload })
+          if (snippet) pasteSnippet(snippet)
+        } catch (err) {
+          console.error('Failed to load snippet:', err)
+        }
+      })
+    }
+
+    setupListener()
+
+    return () => {
+      if (unlistenFn) {
+        unlistenFn()
+      }
+    }
+  }, [])
+
+  // Close when the window loses focus (click outside)
+  useEffect(() => {
+    const handleBlur = () => {
+      // The native folder picker takes focus; keep the launcher open behind it
+      if (!isChoosingDirectoryRef.current) onClose()
+    }
+    window.addEventListener('blur', handleBlur)
+    return () => window.removeEventListener('blur', handleBlur)
+  }, [onClose])
+
+  // Focus the first variable field when prompting
+  useEffect(() => {
+    if (pending) {
+      setTimeout(() => firstFieldRef.current?.focus(), 0)
+    } else {
+      inputRef.current?.focus()
+    }
+  }, [pending])
+
+  function setVariable(name, value) {
+    setValues(prev => ({ ...prev, [name]: value }))
+  }
+
+  async function chooseDirectory(name) {
+    isChoosingDirectoryRef.current = true
+    try {
+      const dir = await open({ directory: true, multiple: false })
+      if (dir) setVariable(name, dir)
+    } catch (err) {
+      console.error('Failed to choose directory:', err)
+    } finally {
+      isChoosingDirectoryRef.current = false
+    }
+  }
+
+  async function pasteSnippet(snippet, asPlainText = false, templateValues = null) {
+    try {
+      setError(null)
+      if (snippet.secret) {
+        const vault = await invoke('get_secret_vault_status')
+        if (!vault.unlocked) {
+          setPending({ snippet, asPlainText, variables: [], unlock: true })
+          return
+        }
+      }
+
+      const result = await invoke('paste_snippet', {
+        id: snippet.id,
+        values: templateValues,
+        asPlainText,
+      })
+
+      if (result.status === 'needs_input') {
+        const initial = {}
+        result.variables.forEach(v => {
+          initial[v.name] = templateValues?.[v.name] ?? v.default ?? ''
+        })
+        setValues(initial)
+        setPending({ snippet, asPlainText, variables: result.variables })
+        return
+      }
+
+      reset()
+    } catch (err) {
+      console.error('Failed to paste snippet:', err)
+      setError(typeof err === 'string' ? err : 'Failed to paste snippet')
+    }
+  }
+
+  // Keyboard navigation
+  useEffect(() => {
+    const handleKeyDown = (e) => {
+      if (e.key === 'Escape') {
+        e.preventDefault()
+        if (pending) {
+          setPending(null)
+        } else {
+          onClose()
+        }
+        return
+      }
+
+      // Variable form handles its own Enter via submit
+      if (pending) return
+
+      if (e.key === 'ArrowDown') {
+        e.preventDefault()
+        setSelectedIndex(prev => Math.min(prev + 1, results.length - 1))
+        return
+      }
+
+      if (e.key === 'ArrowUp') {
+        e.preventDefault()
+        setSelectedIndex(prev => Math.max(prev - 1, 0))
+        return
+      }
+
+      // Enter - paste, Shift+Enter - paste as plain text
+      if (e.key === 'Enter' && results.length > 0) {
+        e.preventDefault()
+        pasteSnippet(results[selectedIndex].snippet, e.shiftKey)
+      }
+    }
+
+    window.addEventListener('keydown', handleKeyDown, true)
+    return () => window.removeEventListener('keydown', handleKeyDown, true)
+  }, [pending, results, selectedIndex, onClose])
+
+  // Scroll selected item into view
+  useEffect(() => {
+    const selectedElement = document.getElementById(`launcher-result-${selectedIndex}`)
+    if (selectedElement) {
+      selectedElement.scrollIntoView({ block: 'nearest' })
+    }
+  }, [selectedIndex, results])
+
+  async function unlockAndPaste() {
+    try {
+      setError(null)
+      await invoke('unlock_secrets', { password })
+      setPassword('')
+      await pasteSnippet(pending.snippet, pending.asPlainText)
+    } catch (err) {
+      console.error('Failed to unlock secret snippets:', err)
+      setError(typeof err === 'string' ? err : 'Failed to unlock secret snippets')
+    }
+  }
+
+  function firstLine(text) {
+    return (text || '').split('\n').find(line => line.trim()) || ''
+  }
+
+  return (
+    <div className="fixed inset-0 bg-background border border-border rounded-b-xl shadow-2xl overflow-hidden flex flex-col">
+      {pending ? (
+        <form
+          className="flex flex-col flex-1 overflow-hidden"
+          onSubmit={(e) => {
+            e.preventDefault()
+            if (pending.unlock) {
+              unlockAndPaste()
+            } else {
+              pasteSnippet(pending.snippet, pending.asPlainText, values)
+            }
+          }}
+        >
+          <div className="flex items-center gap-2 px-4 py-3 border-b border-border/50 bg-muted/30">
+            <button
+              type="button"
+              onClick={() => setPending(null)}
+              className="text-muted-foreground hover:text-foreground"
+              title="Back (Esc)"
+            >
+              <ArrowLeft className="w-4 h-4" />
+            </button>
+            <span className="text-xs font-semibold truncate">{pending.snippet.title}</span>
+          </div>
+
+          <div className="flex-1 overflow-y-auto p-4 space-y-3">
+            {pending.unlock && (
+              <label className="block">
+                <span className="text-[11px] text-muted-foreground">Password for secret snippets</span>
+                <input
+                  type="password"
+                  autoFocus
+                  value={password}
+                  onChange={(e) => setPassword(e.target.value)}
+                  className={FIELD_CLASS}
+                />
+              </label>
+            )}
+            {pending.variables.map((variable, index) => (
+              <label key={variable.name} className="block">
+                <span className="text-[11px] font-mono text-muted-foreground">
+                  {variable.kind === 'directory' ? `${variable.name} (repository folder)` : variable.name}
+                </span>
+                {variable.kind === 'choice' ? (
+                  <select
+                    ref={index === 0 ? firstFieldRef : undefined}
+                    value={values[variable.name] ?? ''}
+                    onChange={(e) => setVariable(variable.name, e.target.value)}
+                    className={FIELD_CLASS}
+                  >
+                    {variable.choices.map(choice => (
+                      <option key={choice} value={choice}>{choice}</option>
+                    ))}
+                  </select>
+                ) : (
+                  <div className="flex gap-2">
+                    <input
+                      ref={index === 0 ? firstFieldRef : undefined}
+                      type="text"
+                      value={values[variable.name] ?? ''}
+                      placeholder={variable.default ?? ''}
+                      onChange={(e) => setVariable(variable.name, e.target.value)}
+                      className={FIELD_CLASS}
+                    />
+                    {variable.kind === 'directory' && (
+                      <button
+                        type="button"
+                        onClick={() => chooseDirectory(variable.name)}
+                        className="mt-1 flex items-center gap-1 bg-muted text-muted-foreground hover:bg-muted/70 hover:text-foreground px-2 rounded transition-colors text-[10px] border border-border/50"
+                        title="Choose folder"
+                      >
+                        <FolderOpen className="w-3 h-3" />
+                      </button>
+                    )}
+                  </div>
+                )}
+              </label>
+            ))}
+          </div>
+
+          <div className="flex items-center justify-between p-2 border-t border-border/50 text-[11px] text-muted-foreground bg-muted/30">
+            <span className="px-2">
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Enter</kbd> {pending.unlock ? 'Unlock and paste' : 'Paste'}
+            </span>
+            <button
+              type="submit"
+              className="flex items-center gap-1 bg-primary text-primary-foreground hover:bg-primary/90 px-2 py-1 rounded transition-colors font-medium text-[10px] shadow-sm"
+            >
+              <Clipboard className="w-3 h-3" />
+              <span>Paste</span>
+            </button>
+          </div>
+        </form>
+      ) : (
+        <>
+          {/* Search Input */}
+          <div className="relative border-b border-border/50">
+            <Search className="absolute left-3 h-4 w-4 text-muted-foreground top-1/2 -translate-y-1/2" />
+            <input
+              ref={inputRef}
+              type="text"
+              value={query}
+              onChange={(e) => setQuery(e.target.value)}
+              placeholder="Search snippets..."
+              className="w-full pl-10 pr-4 py-3 bg-background border-0 focus:outline-none focus:ring-0"
+            />
+          </div>
+
+          {/* Results */}
+          <div className="overflow-y-auto flex-1">
+            {results.length === 0 ? (
+              <div className="flex flex-col items-center justify-center py-12 text-muted-foreground">
+                <Code className="w-8 h-8 mb-2 opacity-50" />
+                <div className="text-sm">
+                  {isLoading ? 'Searching...' : query ? 'No matching snippets' : 'No snippets yet'}
+                </div>
+              </div>
+            ) : (
+              results.map(({ snippet }, index) => (
+                <div
+                  key={snippet.id}
+                  id={`launcher-result-${index}`}
+                  className={`px-4 py-2.5 border-b border-border/50 cursor-pointer transition-all ${
+                    index === selectedIndex ? 'bg-accent ring-1 ring-primary/20' : 'hover:bg-accent'
+                  }`}
+                  onMouseEnter={() => setSelectedIndex(index)}
+                  onClick={() => pasteSnippet(snippet, false)}
+                >
+                  <div className="flex items-center gap-2">
+                    {snippet.secret && <Lock className="w-3 h-3 text-muted-foreground shrink-0" />}
+                    <span className="text-sm font-medium truncate flex-1">{snippet.title}</span>
+                    <span className="text-[10px] text-muted-foreground uppercase">{snippet.language}</span>
+                  </div>
+                  <div className="text-xs font-mono text-muted-foreground truncate mt-0.5">
+                    {firstLine(snippet.content)}
+                  </div>
+                </div>
+              ))
+            )}
+          </div>
+
+          {/* Footer */}
+          <div className="p-2 border-t border-border/50 text-[11px] text-muted-foreground bg-muted/30">
+            <span className="flex items-center gap-1.5 flex-wrap justify-center">
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">↑↓</kbd> Navigate
+              <span className="text-border/30">•</span>
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Enter</kbd> Paste
+              <span className="text-border/30">•</span>
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Shift+Enter</kbd> Plain
+              <span className="text-border/30">•</span>
+              <kbd className="px-1.5 py-0.5 bg-background border border-border rounded text-[10px] font-mono">Esc</kbd> Close
+            </span>
+          </div>
+        </>
+      )}
+
+      {error && (
+        <div className="px-4 py-2 text-xs text-red-500 border-t border-border/50">{error}</div>
+      )}
+    </div>
+  )
+}
@@ -1,4 +1,4 @@
-import { Search, Plus, FileCode, Trash2, Copy, AlertCircle, RefreshCw, Cloud, CheckCircle, Loader2, ArrowUp, Shield, Clock, List, LayoutList } from 'lucide-react'
+import { Search, Plus, FileCode, Trash2, Copy, AlertCircle, RefreshCw, Cloud, CheckCircle, Loader2, ArrowUp, Shield, Clock, List, LayoutList, Lock } from 'lucide-react'
 import { Button } from '@/components/ui/button'
 import { Badge } from '@/components/ui/badge'
 import { useEffect, useRef, useState } from 'react'
@@ -103,6 +103,8 @@ export function SnippetsPanel({
       let errorMessage
       if (error.message === 'SYNC_NOT_APPROVED') {
         errorMessage = 'Sync access not approved yet. Please wait for approval.'
+      } else if (error.message === 'SYNC_ENCRYPTION_LOCKED') {
+        errorMessage = 'Set up or unlock encrypted sync in Account first.'
       } else if (error.message === 'Supabase not configured') {
         errorMessage = 'Cloud sync not configured'
       } else if (error.message.includes('fetch') || error.message.includes('network')) {
@@ -273,6 +275,7 @@ export function SnippetsPanel({
                   <div className="flex items-center gap-2">
                     <div className="flex-1 min-w-0">
                       <div className="flex items-center gap-2">
+                        {snippet.secret && <Lock className="h-2.5 w-2.5 text-muted-foreground shrink-0" />}
                         <h3 className="text-[10px] font-medium truncate">
                           {snippet.title}
                         </h3>
@@ -305,14 +308,16 @@ export function SnippetsPanel({
                       <span className="text-[9px] text-muted-foreground">
                         {new Date(snippet.updated_at).toLocaleDateString('en-US', { month: 'short', day: 'numeric' })}
                       </span>
-                      <Button
-                        size="sm"
-                        variant="ghost"
-                        className="h-5 w-5 p-0"
-                        onClick={(e) => handleCopySnippet(e, snippet.content)}
-                      >
-                        <Copy className="h-2.5 w-2.5" />
-                      </Button>
+                      {!snippet.secret && (
+                        <Button
+                          size="sm"
+                          variant="ghost"
+                          className="h-5 w-5 p-0"
+                          onClick={(e) => handleCopySnippet(e, snippet.content)}
+                        >
+                          <Copy className="h-2.5 w-2.5" />
+                        </Button>
+                      )}
                       <Button
                         size="sm"
                         variant="ghost"
@@ -330,6 +335,7 @@ export function SnippetsPanel({
                     <div className="flex items-start gap-2">
                       <div className="flex-1 min-w-0">
                         <div className="flex items-center gap-2 mb-1">
+                          {snippet.secret && <Lock className="h-3 w-3 text-muted-foreground shrink-0" />}
                           <h3 className="text-[11px] font-medium truncate">
                             {snippet.title}
                           </h3>
@@ -373,14 +379,16 @@ export function SnippetsPanel({
 
                     {/* Actions */}
                     <div className="flex gap-1 mt-2">
-                      <Button
-                        size="sm"
-                        variant="ghost"
-                        className="h-7 px-2 text-[9px]"
-                        onClick={(e) => handleCopySnippet(e, snippet.content)}
-                      >
-                        <Copy className="h-3 w-3" />
-                      </Button>
+                      {!snippet.secret && (
+                        <Button
+                          size="sm"
+                          variant="ghost"
+                          className="h-7 px-2 text-[9px]"
+                          onClick={(e) => handleCopySnippet(e, snippet.content)}
+                        >
+                          <Copy className="h-3 w-3" />
+                        </Button>
+                      )}
                       <Button
                         size="sm"
                         variant="ghost"
@@ -0,0 +1,296 @@
+import { useEffect, useState } from 'react'
+import { ShieldCheck, KeyRound, RefreshCw, AlertCircle } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { ask } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+import { syncService } from '@/lib/sync'
+
+const MIN_PASSPHRASE_LENGTH = 8
+const FIELD_CLASS = 'w-full h-7 px-2 text-[10px] bg-background border border-border rounded-md'
+
+/**
+ * SyncEncryptionPanel - end-to-end encryption for cloud sync. Sets up the
+ * sync passphrase, unlocks it on other devices, and handles passphrase
+ * changes, key rotation and recovery.
+ */
+export function SyncEncryptionPanel({ userEmail }) {
+  const [status, setStatus] = useState(null)
+  const [hasCloudKeys, setHasCloudKeys] = useState(false)
+  const [mode, setMode] = useState(null) // null, 'recover', 'change' or 'rotate'
+  const [passphrase, setPassphrase] = useState('')
+  const [newPassphrase, setNewPassphrase] = useState('')
+  const [confirmation, setConfirmation] = useState('')
+  const [recoveryKey, setRecoveryKey] = useState('')
+  const [shownRecoveryKey, setShownRecoveryKey] = useState(null)
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState(null)
+
+  async function refresh() {
+    setStatus(await invoke('get_sync_encryption_status'))
+    setHasCloudKeys(!!(await syncService.fetchKeyBundle(userEmail)))
+  }
+
+  useEffect(() => {
+    refresh().catch(console.error)
+  }, [userEmail])
+
+  function reset() {
+    setMode(null)
+    setPassphrase('')
+    setNewPassphrase('')
+    setConfirmation('')
+    setRecoveryKey('')
+  }
+
+  function checkNewPassphrase(value) {
+    if (value.length < MIN_PASSPHRASE_LENGTH) {
+      setError(`Use a passphrase of at least ${MIN_PASSPHRASE_LENGTH} characters`)
+      return false
+    }
+    if (value !== confirmation) {
+      setError('The passphrases don\'t match')
+      return false
+    }
+    return true
+  }
+
+  async function run(action, fallback) {
+    setBusy(true)
+    setError(null)
+    try {
+      await action()
+      reset()
+    } catch (err) {
+      console.error(fallback, err)
+      setError(typeof err === 'string' ? err : err?.message || fallback)
+    } finally {
+      await refresh().catch(console.error)
+      setBusy(false)
+    }
+  }
+
+  // Replace what's in the cloud with data encrypted with the current key
+  async function reencrypt() {
+    await syncService.reencryptAll(userEmail)
+  }
+
+  function handleSetup() {
+    if (!checkNewPassphrase(passphrase)) return
+    run(async () => {
+      const setup = await invoke('setup_sync_encryption', { passphrase })
+      await syncService.uploadKeyBundle(userEmail, setup.bundle)
+      setShownRecoveryKey(setup.recovery_key)
+      await reencrypt()
+    }, 'Failed to set up encrypted sync')
+  }
+
+  function handleUnlock() {
+    run(async () => {
+      const bundle = await syncService.fetchKeyBundle(userEmail)
+      await invoke('unlock_sync_encryption', { bundle, passphrase })
+      await reencrypt()
+    }, 'Failed to unlock encrypted sync')
+  }
+
+  function handleRecover() {
+    if (!checkNewPassphrase(newPassphrase)) return
+    run(async () => {
+      const bundle = await syncService.fetchKeyBundle(userEmail)
+      const setup = await invoke('recover_sync_encryption', { bundle, recoveryKey, newPassphrase })
+      await syncService.uploadKeyBundle(userEmail, setup.bundle)
+      setShownRecoveryKey(setup.recovery_key)
+    }, 'Failed to recover encrypted sync')
+  }
+
+  function handleChangePassphrase() {
+    if (!checkNewPassphrase(newPassphrase)) return
+    run(async () => {
+      const setup = await invoke('change_sync_passphrase', { currentPassphrase: passphrase, newPassphrase })
+      await syncService.uploadKeyBundle(userEmail, setup.bundle)
+    }, 'Failed to change the passphrase')
+  }
+
+  async function handleRotate() {
+    const confirmed = await ask(
+      'Everything in the cloud is encrypted again with a new key, and you get a new recovery key. ' +
+        'Other devices need your passphrase again.',
+      { title: 'Rotate sync key?', kind: 'warning', okLabel: 'Rotate' }
+    )
+    if (!confirmed) return
+
+    run(async () => {
+      const setup = await invoke('rotate_sync_key', { passphrase })
+      await syncService.uploadKeyBundle(userEmail, setup.bundle)
+      setShownRecoveryKey(setup.recovery_key)
+      await finishRotation()
+    }, 'Failed to rotate the sync key')
+  }
+
+  // Old keys are dropped once nothing in the cloud needs them
+  async function finishRotation() {
+    await reencrypt()
+    const retired = await invoke('retire_old_sync_keys')
+    await syncService.uploadKeyBundle(userEmail, retired.bundle)
+  }
+
+  if (!status) return null
+
+  const passphraseField = (placeholder) => (
+    <input
+      type="password"
+      value={passphrase}
+      onChange={(e) => setPassphrase(e.target.value)}
+      placeholder={placeholder}
+      disabled={busy}
+      className={FIELD_CLASS}
+    />
+  )
+
+  const newPassphraseFields = (
+    <>
+      <input
+        type="password"
+        value={newPassphrase}
+        onChange={(e) => setNewPassphrase(e.target.value)}
+        placeholder="New passphrase"
+        disabled={busy}
+        className={FIELD_CLASS}
+      />
+      <input
+        type="password"
+        value={confirmation}
+        onChange={(e) => setConfirmation(e.target.value)}
+        placeholder="Confirm passphrase"
+        disabled={busy}
+        className={FIELD_CLASS}
+      />
+    </>
+  )
+
+  const submit = (label, onClick, disabled) => (
+    <div className="flex gap-1.5">
+      {mode && (
+        <Button variant="ghost" className="flex-1 text-xs" size="sm" onClick={reset} disabled={busy}>
+          Cancel
+        </Button>
+      )}
+      <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={onClick} disabled={busy || disabled}>
+        {busy ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <KeyRound className="h-3 w-3 mr-1" />}
+        {label}
+      </Button>
+    </div>
+  )
+
+  return (
+    <div className="space-y-2">
+      {shownRecoveryKey && (
+        <div className="p-2.5 bg-yellow-500/10 border border-yellow-500/20 rounded-lg space-y-1.5">
+          <p className="text-[10px] font-medium text-yellow-700 dark:text-yellow-400">Write down your recovery key</p>
+          <p className="text-[10px] font-mono break-all select-all">{shownRecoveryKey}</p>
+          <p className="text-[9px] text-muted-foreground">
+            It's the only way back into your synced data if you forget the passphrase. It won't be shown again.
+          </p>
+          <Button variant="outline" className="w-full text-xs" size="sm" onClick={() => setShownRecoveryKey(null)}>
+            I've saved it
+          </Button>
+        </div>
+      )}
+
+      {status.unlocked ? (
+        <>
+          <div className="flex items-start gap-2 text-[10px]">
+            <ShieldCheck className="h-3.5 w-3.5 text-green-500 mt-0.5 shrink-0" />
+            <p className="text-muted-foreground">
+              End-to-end encrypted. The server only sees ciphertext.
+              {status.retired_keys > 0 && ' Re-encryption after a key rotation is unfinished.'}
+            </p>
+          </div>
+
+          {mode === 'change' && (
+            <>
+              {passphraseField('Current passphrase')}
+              {newPassphraseFields}
+              {submit('Change Passphrase', handleChangePassphrase, !passphrase || !newPassphrase)}
+            </>
+          )}
+
+          {mode === 'rotate' && (
+            <>
+              {passphraseField('Passphrase')}
+              {submit('Rotate Key', handleRotate, !passphrase)}
+            </>
+          )}
+
+          {!mode && (
+            <div className="flex gap-3">
+              <button type="button" onClick={() => setMode('change')} className="text-[10px] text-muted-foreground hover:text-foreground underline">
+                Change passphrase
+              </button>
+              <button type="button" onClick={() => setMode('rotate')} className="text-[10px] text-muted-foreground hover:text-foreground underline">
+                Rotate key
+              </button>
+              {status.retired_keys > 0 && (
+                <button
+                  type="button"
+                  onClick={() => run(finishRotation, 'Failed to finish the key rotation')}
+                  disabled={busy}
+                  className="text-[10px] text-muted-foreground hover:text-foreground underline"
+                >
+                  Finish re-encrypting
+                </button>
+              )}
+            </div>
+          )}
+        </>
+      ) : hasCloudKeys ? (
+        mode === 'recover' ? (
+          <>
+            <p className="text-[10px] text-muted-foreground">Enter your recovery key and choose a new passphrase.</p>
+            <input
+              type="text"
+              value={recoveryKey}
+              onChange={(e) => setRecoveryKey(e.target.value)}
+              placeholder="Recovery key"
+              disabled={busy}
+              className={`${FIELD_CLASS} font-mono`}
+            />
+            {newPassphraseFields}
+            {submit('Recover', handleRecover, !recoveryKey || !newPassphrase)}
+          </>
+        ) : (
+          <>
+            <p className="text-[10px] text-muted-foreground">Enter your sync passphrase to sync on this device.</p>
+            {passphraseField('Sync passphrase')}
+            {submit('Unlock Sync', handleUnlock, !passphrase)}
+            <button type="button" onClick={() => setMode('recover')} className="text-[10px] text-muted-foreground hover:text-foreground underline">
+              Forgot it? Use your recovery key
+            </button>
+          </>
+        )
+      ) : (
+        <>
+          <p className="text-[10px] text-muted-foreground">
+            Choose a sync passphrase. Everything is encrypted on this device before it's uploaded.
+          </p>
+          {passphraseField('Sync passphrase')}
+          <input
+            type="password"
+            value={confirmation}
+            onChange={(e) => setConfirmation(e.target.value)}
+            placeholder="Confirm passphrase"
+            disabled={busy}
+            className={FIELD_CLASS}
+          />
+          {submit('Set Up Encrypted Sync', handleSetup, !passphrase)}
+        </>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+    </div>
+  )
+}
@@ -0,0 +1,194 @@
+import { useState, useEffect } from 'react'
+import { Tag, X, Edit2, Merge, Trash2, Search } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { Button } from '@/components/ui/button'
+
+const TAG_COLORS = ['#ef4444', '#f97316', '#eab308', '#22c55e', '#06b6d4', '#3b82f6', '#8b5cf6', '#ec4899']
+
+/**
+ * TagManager - tags shared by snippets, files and clipboard entries
+ * Rename, recolor, merge and delete tags; selecting one searches for it.
+ */
+export function TagManager({ isOpen, onClose, onSelectTag, onTagsChanged }) {
+  const [tags, setTags] = useState([])
+  const [editingId, setEditingId] = useState(null)
+  const [editingName, setEditingName] = useState('')
+  const [mergingId, setMergingId] = useState(null)
+  const [error, setError] = useState(null)
+
+  async function loadTags() {
+    try {
+      setTags(await invoke('get_all_tags'))
+    } catch (err) {
+      console.error('Failed to load tags:', err)
+      setError('Failed to load tags')
+    }
+  }
+
+  useEffect(() => {
+    if (isOpen) {
+      setError(null)
+      setEditingId(null)
+      setMergingId(null)
+      loadTags()
+    }
+  }, [isOpen])
+
+  if (!isOpen) return null
+
+  // Run a tag change, then refresh the list and let the app reload tagged items
+  async function apply(command, args, failure) {
+    try {
+      setError(null)
+      await invoke(command, args)
+      await loadTags()
+      onTagsChanged?.()
+    } catch (err) {
+      console.error(`${failure}:`, err)
+      setError(typeof err === 'string' ? err : failure)
+    }
+  }
+
+  async function handleRename(tag) {
+    const name = editingName.trim()
+    setEditingId(null)
+    if (!name || name === tag.name) return
+    await apply('rename_tag', { id: tag.id, name }, 'Failed to rename tag')
+  }
+
+  async function handleMerge(source, targetId) {
+    setMergingId(null)
+    if (!targetId) return
+    await apply('merge_tags', { sourceIds: [source.id], targetId: Number(targetId) }, 'Failed to merge tags')
+  }
+
+  function usage(tag) {
+    const parts = []
+    if (tag.snippet_count) parts.push(`${tag.snippet_count} snippet${tag.snippet_count === 1 ? '' : 's'}`)
+    if (tag.file_count) parts.push(`${tag.file_count} file${tag.file_count === 1 ? '' : 's'}`)
+    if (tag.clipboard_count) parts.push(`${tag.clipboard_count} clip${tag.clipboard_count === 1 ? '' : 's'}`)
+    return parts.length > 0 ? parts.join(' • ') : 'Unused'
+  }
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center">
+      {/* Backdrop */}
+      <div className="absolute inset-0 bg-black/50" onClick={onClose} />
+
+      {/* Dialog */}
+      <div className="relative bg-background border border-border rounded-lg shadow-lg max-w-lg w-full max-h-[70vh] mx-4 z-10 flex flex-col">
+        {/* Header */}
+        <div className="flex items-center justify-between p-4 border-b">
+          <div className="flex items-center gap-2">
+            <Tag className="h-4 w-4 text-primary" />
+            <h2 className="text-sm font-semibold">Tags</h2>
+          </div>
+          <button
+            onClick={onClose}
+            className="h-6 w-6 flex items-center justify-center hover:bg-accent rounded-sm transition-colors"
+          >
+            <X className="h-3.5 w-3.5" />
+          </button>
+        </div>
+
+        {/* Tag list */}
+        <div className="flex-1 overflow-y-auto">
+          {tags.length === 0 ? (
+            <div className="p-4 text-xs text-muted-foreground">No tags yet</div>
+          ) : (
+            tags.map(tag => (
+              <div key={tag.id} className="group px-4 py-2 border-b border-border/50 flex items-center gap-2">
+                {/* Color - click to cycle */}
+                <button
+                  onClick={() => {
+                    const next = TAG_COLORS[(TAG_COLORS.indexOf(tag.color) + 1) % (TAG_COLORS.length + 1)] ?? null
+                    apply('set_tag_color', { id: tag.id, color: next }, 'Failed to set tag color')
+                  }}
+                  className="h-3 w-3 rounded-full border border-border flex-shrink-0"
+                  style={{ backgroundColor: tag.color || 'transparent' }}
+                  title="Change color"
+                />
+
+                <div className="flex-1 min-w-0">
+                  {editingId === tag.id ? (
+                    <form onSubmit={(e) => { e.preventDefault(); handleRename(tag) }}>
+                      <input
+                        type="text"
+                        value={editingName}
+                        onChange={(e) => setEditingName(e.target.value)}
+                        onBlur={() => handleRename(tag)}
+                        className="w-full px-1 py-0.5 text-xs bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-purple-500"
+                        autoFocus
+                      />
+                    </form>
+                  ) : mergingId === tag.id ? (
+                    <select
+                      defaultValue=""
+                      onChange={(e) => handleMerge(tag, e.target.value)}
+                      onBlur={() => setMergingId(null)}
+                      className="w-full px-1 py-0.5 text-xs bg-background border border-border rounded focus:outline-none focus:ring-1 focus:ring-purple-500"
+                      autoFocus
+                    >
+                      <option value="" disabled>Merge "{tag.name}" into...</option>
+                      {tags.filter(t => t.id !== tag.id).map(t => (
+                        <option key={t.id} value={t.id}>{t.name}</option>
+                      ))}
+                    </select>
+                  ) : (
+                    <>
+                      <div className="text-xs font-medium truncate">{tag.name}</div>
+                      <div className="text-[10px] text-muted-foreground">{usage(tag)}</div>
+                    </>
+                  )}
+                </div>
+
+                <div className="flex items-center gap-0.5 opacity-0 group-hover:opacity-100 transition-opacity">
+                  <button
+                    onClick={() => onSelectTag?.(tag)}
+                    className="p-1 hover:bg-accent rounded transition-colors"
+                    title="Search this tag"
+                  >
+                    <Search className="h-3 w-3" />
+                  </button>
+                  <button
+                    onClick={() => { setEditingId(tag.id); setEditingName(tag.name) }}
+                    className="p-1 hover:bg-accent rounded transition-colors"
+                    title="Rename"
+                  >
+                    <Edit2 className="h-3 w-3" />
+                  </button>
+                  <button
+                    onClick={() => setMergingId(tag.id)}
+                    className="p-1 hover:bg-accent rounded transition-colors"
+                    title="Merge into another tag"
+                    disabled={tags.length < 2}
+                  >
+                    <Merge className="h-3 w-3" />
+                  </button>
+                  <button
+                    onClick={() => apply('delete_tag', { id: tag.id }, 'Failed to delete tag')}
+                    className="p-1 hover:bg-destructive/20 rounded transition-colors"
+                    title="Remove tag from everything"
+                  >
+                    <Trash2 className="h-3 w-3 text-destructive" />
+                  </button>
+                </div>
+              </div>
+            ))
+          )}
+        </div>
+
+        {error && (
+          <div className="px-4 py-2 text-xs text-red-500 border-t">{error}</div>
+        )}
+
+        {/* Footer */}
+        <div className="flex items-center justify-end gap-2 p-4 border-t bg-muted/30">
+          <Button variant="outline" onClick={onClose}>
+            Close
+          </Button>
+        </div>
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,173 @@
+import { useState, useEffect } from 'react'
+import { Trash2, RotateCcw, FileCode, FileText, Folder, X } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { Button } from '@/components/ui/button'
+
+const RETENTION_OPTIONS = [
+  { value: 7, label: '7 days' },
+  { value: 30, label: '30 days' },
+  { value: 90, label: '90 days' },
+  { value: null, label: 'Never' },
+]
+
+/**
+ * TrashMainView - deleted snippets, folders and files
+ * Items can be restored (a folder brings its snippets and files back) or deleted
+ * permanently. Anything older than the retention period is purged automatically.
+ */
+export function TrashMainView({ onRestored }) {
+  const [trash, setTrash] = useState({ snippets: [], folders: [], files: [] })
+  const [retentionDays, setRetentionDays] = useState(30)
+  const [error, setError] = useState(null)
+
+  async function loadTrash() {
+    try {
+      setTrash(await invoke('get_trash'))
+    } catch (err) {
+      console.error('Failed to load trash:', err)
+      setError('Failed to load trash')
+    }
+  }
+
+  useEffect(() => {
+    loadTrash()
+    invoke('get_trash_settings')
+      .then(settings => setRetentionDays(settings.retention_days ?? null))
+      .catch(console.error)
+
+    let unlistenFn
+    listen('trash-purged', () => loadTrash()).then(fn => { unlistenFn = fn })
+    return () => unlistenFn?.()
+  }, [])
+
+  async function restore(kind, id) {
+    try {
+      await invoke('restore_from_trash', { kind, id })
+      await loadTrash()
+      onRestored?.(kind)
+    } catch (err) {
+      console.error('Failed to restore item:', err)
+      setError(typeof err === 'string' ? err : 'Failed to restore item')
+    }
+  }
+
+  async function deletePermanently(kind, id) {
+    try {
+      await invoke('delete_permanently', { kind, id })
+      await loadTrash()
+    } catch (err) {
+      console.error('Failed to delete item:', err)
+      setError(typeof err === 'string' ? err : 'Failed to delete item')
+    }
+  }
+
+  async function emptyTrash() {
+    try {
+      await invoke('empty_trash')
+      await loadTrash()
+    } catch (err) {
+      console.error('Failed to empty trash:', err)
+      setError('Failed to empty trash')
+    }
+  }
+
+  async function changeRetention(value) {
+    const days = value === 'never' ? null : Number(value)
+    setRetentionDays(days)
+    try {
+      await invoke('set_trash_settings', { settings: { retention_days: days } })
+      await loadTrash()
+    } catch (err) {
+      console.error('Failed to save trash settings:', err)
+      setError('Failed to save trash settings')
+    }
+  }
+
+  const items = [
+    ...trash.folders.map(f => ({
+      kind: 'folder', id: f.id, icon: Folder, name: `${f.icon || '📁'} ${f.name}`, deletedAt: f.deleted_at,
+      detail: f.snippet_count ? `${f.snippet_count} snippet${f.snippet_count === 1 ? '' : 's'} return with it` : null,
+    })),
+    ...trash.snippets.map(s => ({
+      kind: 'snippet', id: s.id, icon: FileCode, name: s.title, deletedAt: s.deleted_at, detail: s.language,
+    })),
+    ...trash.files.map(f => ({
+      kind: 'file', id: f.id, icon: FileText, name: f.filename, deletedAt: f.deleted_at, detail: f.file_type,
+    })),
+  ].sort((a, b) => (b.deletedAt || '').localeCompare(a.deletedAt || ''))
+
+  return (
+    <div className="h-full flex flex-col">
+      {/* Header */}
+      <div className="border-b px-4 py-3 flex items-center gap-3">
+        <Trash2 className="h-4 w-4 text-muted-foreground" />
+        <h2 className="text-sm font-semibold flex-1">Trash</h2>
+        <span className="text-[10px] text-muted-foreground">Delete permanently after</span>
+        <select
+          value={retentionDays ?? 'never'}
+          onChange={(e) => changeRetention(e.target.value)}
+          className="h-7 px-2 text-[10px] bg-background border border-border rounded-md hover:bg-accent focus:outline-none focus:ring-1 focus:ring-purple-500 cursor-pointer"
+        >
+          {RETENTION_OPTIONS.map(option => (
+            <option key={option.label} value={option.value ?? 'never'}>{option.label}</option>
+          ))}
+        </select>
+        <Button
+          variant="ghost"
+          size="sm"
+          className="h-7 px-2 text-[10px] text-destructive hover:text-destructive"
+          onClick={emptyTrash}
+          disabled={items.length === 0}
+        >
+          Empty trash
+        </Button>
+      </div>
+
+      {error && (
+        <div className="px-4 py-2 text-xs text-red-500 border-b flex items-center justify-between">
+          {error}
+          <button onClick={() => setError(null)}><X className="h-3 w-3" /></button>
+        </div>
+      )}
+
+      {/* Items */}
+      <div className="flex-1 overflow-y-auto">
+        {items.length === 0 ? (
+          <div className="flex flex-col items-center justify-center py-16 text-muted-foreground">
+            <Trash2 className="w-8 h-8 mb-2 opacity-50" />
+            <div className="text-sm">Trash is empty</div>
+          </div>
+        ) : (
+          items.map(item => {
+            const Icon = item.icon
+            return (
+              <div key={`${item.kind}-${item.id}`} className="px-4 py-2.5 border-b border-border/50 flex items-center gap-3 hover:bg-accent/50">
+                <Icon className="h-4 w-4 text-muted-foreground flex-shrink-0" />
+                <div className="flex-1 min-w-0">
+                  <div className="text-sm truncate">{item.name}</div>
+                  <div className="text-[10px] text-muted-foreground">
+                    Deleted {new Date(item.deletedAt).toLocaleString()}
+                    {item.detail && ` • ${item.detail}`}
+                  </div>
+                </div>
+                <Button variant="ghost" size="sm" className="h-7 px-2 text-[10px]" onClick={() => restore(item.kind, item.id)}>
+                  <RotateCcw className="h-3 w-3 mr-1" />
+                  Restore
+                </Button>
+                <Button
+                  variant="ghost"
+                  size="sm"
+                  className="h-7 px-2 text-[10px] text-destructive hover:text-destructive"
+                  onClick={() => deletePermanently(item.kind, item.id)}
+                >
+                  Delete forever
+                </Button>
+              </div>
+            )
+          })
+        )}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,119 @@
+import { useState } from 'react'
+import { Lock, AlertCircle, RefreshCw } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { ask, message } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+
+/**
+ * UnlockScreen - shown instead of the app while an encrypted library waits
+ * for its passphrase. Without one, the keychain can be tried again, or the
+ * library set aside to start an empty one.
+ */
+export function UnlockScreen({ canUnlock, onUnlocked }) {
+  const [passphrase, setPassphrase] = useState('')
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState(null)
+
+  async function handleUnlock(e) {
+    e.preventDefault()
+    if (!passphrase) return
+    setBusy(true)
+    setError(null)
+    try {
+      await invoke('unlock_library', { passphrase })
+      onUnlocked?.()
+    } catch (err) {
+      console.error('Failed to unlock the library:', err)
+      setError(typeof err === 'string' ? err : 'Failed to unlock the library')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function handleRetryKeychain() {
+    setBusy(true)
+    setError(null)
+    try {
+      await invoke('retry_encryption_keyring')
+      onUnlocked?.()
+    } catch (err) {
+      console.error('Failed to load the key from the keychain:', err)
+      setError(typeof err === 'string' ? err : 'Failed to load the key from the keychain')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function handleReset() {
+    const confirmed = await ask(
+      'The encrypted library is moved to a separate folder, not deleted, and an empty library is started. ' +
+        'It can only be opened again with its key.',
+      { title: 'Start an empty library?', kind: 'warning', okLabel: 'Start Over' }
+    )
+    if (!confirmed) return
+
+    setBusy(true)
+    setError(null)
+    try {
+      const aside = await invoke('reset_locked_library')
+      await message(`The encrypted library was moved to ${aside}`, { title: 'Library set aside' })
+      onUnlocked?.()
+    } catch (err) {
+      console.error('Failed to set the library aside:', err)
+      setError(typeof err === 'string' ? err : 'Failed to set the library aside')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  return (
+    <div className="flex h-screen items-center justify-center bg-background">
+      <form onSubmit={handleUnlock} className="w-72 space-y-3">
+        <div className="flex flex-col items-center gap-2 text-center">
+          <Lock className="h-8 w-8 text-purple-500" />
+          <h1 className="text-sm font-semibold">Your library is locked</h1>
+          <p className="text-xs text-muted-foreground">
+            {canUnlock
+              ? 'Enter your passphrase to unlock it.'
+              : 'The encryption key is missing from the system keychain, and the library has no passphrase.'}
+          </p>
+        </div>
+
+        {canUnlock && (
+          <>
+            <input
+              type="password"
+              autoFocus
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              placeholder="Passphrase"
+              disabled={busy}
+              className="w-full h-8 px-2 text-xs bg-background border border-border rounded-md"
+            />
+            <Button type="submit" className="w-full text-xs" size="sm" disabled={busy || !passphrase}>
+              {busy && <RefreshCw className="h-3 w-3 mr-1 animate-spin" />}
+              Unlock
+            </Button>
+          </>
+        )}
+
+        {!canUnlock && (
+          <Button type="button" className="w-full text-xs" size="sm" onClick={handleRetryKeychain} disabled={busy}>
+            {busy && <RefreshCw className="h-3 w-3 mr-1 animate-spin" />}
+            Try the Keychain Again
+          </Button>
+        )}
+        <Button type="button" variant="outline" className="w-full text-xs" size="sm" onClick={handleReset} disabled={busy}>
+          Start an Empty Library
+        </Button>
+
+        {error && (
+          <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+            <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+            <p className="text-[10px] text-destructive">{error}</p>
+          </div>
+        )}
+      </form>
+    </div>
+  )
+}
@@ -0,0 +1,144 @@
+import { useEffect, useState } from 'react'
+import { FolderSync, FolderOpen, AlertCircle, CheckCircle, RefreshCw, X } from 'lucide-react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { ask, open } from '@tauri-apps/plugin-dialog'
+import { Button } from '@/components/ui/button'
+
+function plural(count, singular, pluralForm = `${singular}s`) {
+  return `${count} ${count === 1 ? singular : pluralForm}`
+}
+
+function describe(report) {
+  const parts = []
+  if (report.snippets_created) parts.push(`${plural(report.snippets_created, 'snippet')} created`)
+  if (report.snippets_updated) parts.push(`${plural(report.snippets_updated, 'snippet')} updated`)
+  if (report.snippets_trashed) parts.push(`${plural(report.snippets_trashed, 'snippet')} trashed`)
+  if (report.files_written) parts.push(`${plural(report.files_written, 'note')} written`)
+  if (report.files_removed) parts.push(`${plural(report.files_removed, 'note')} removed`)
+  return parts.length > 0 ? parts.join(', ') : 'Everything is in sync'
+}
+
+/**
+ * VaultPanel - mirror the library to a directory of Markdown notes. Edits on
+ * either side are synced while the app runs; subdirectories are folders.
+ */
+export function VaultPanel({ onSynced }) {
+  const [directory, setDirectory] = useState(null)
+  const [busy, setBusy] = useState(false)
+  const [result, setResult] = useState(null)
+  const [error, setError] = useState(null)
+
+  useEffect(() => {
+    invoke('get_vault_settings')
+      .then(settings => setDirectory(settings.directory ?? null))
+      .catch(console.error)
+
+    let unlistenFn
+    listen('vault-synced', (event) => setResult(event.payload)).then(fn => { unlistenFn = fn })
+    return () => unlistenFn?.()
+  }, [])
+
+  async function run(action) {
+    setBusy(true)
+    setError(null)
+    try {
+      const report = await action()
+      setResult(report)
+      if (report) onSynced?.()
+    } catch (err) {
+      console.error('Vault sync failed:', err)
+      setError(typeof err === 'string' ? err : 'Vault sync failed')
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  async function handleChoose() {
+    const chosen = await open({ directory: true, title: 'Choose a Markdown vault folder' })
+    if (!chosen || Array.isArray(chosen)) return
+
+    setResult(null)
+    await run(async () => {
+      const report = await invoke('set_vault_settings', { settings: { directory: chosen } })
+      setDirectory(chosen)
+      return report
+    })
+  }
+
+  async function handleDisconnect() {
+    const confirmed = await ask(
+      'The notes stay where they are, but changes are no longer synced.',
+      { title: 'Stop syncing the vault?', kind: 'warning', okLabel: 'Stop Syncing' }
+    )
+    if (!confirmed) return
+
+    setResult(null)
+    await run(async () => {
+      await invoke('set_vault_settings', { settings: { directory: null } })
+      setDirectory(null)
+      return null
+    })
+  }
+
+  return (
+    <div className="space-y-2">
+      <div className="flex items-center gap-2">
+        <FolderSync className="h-3.5 w-3.5 text-muted-foreground" />
+        <span className="text-[10px] font-semibold text-muted-foreground uppercase tracking-wide">Markdown Vault</span>
+      </div>
+
+      {directory ? (
+        <>
+          <div className="flex items-center gap-1">
+            <p className="flex-1 text-[10px] text-muted-foreground truncate" title={directory}>{directory}</p>
+            <button
+              onClick={handleDisconnect}
+              disabled={busy}
+              className="p-0.5 text-muted-foreground hover:text-destructive"
+              title="Stop syncing"
+            >
+              <X className="h-3 w-3" />
+            </button>
+          </div>
+          <div className="flex items-center gap-2">
+            <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={() => run(() => invoke('sync_vault'))} disabled={busy}>
+              <RefreshCw className={`h-3 w-3 mr-1 ${busy ? 'animate-spin' : ''}`} />
+              Sync Now
+            </Button>
+            <Button variant="outline" className="flex-1 text-xs" size="sm" onClick={handleChoose} disabled={busy}>
+              <FolderOpen className="h-3 w-3 mr-1" />
+              Change
+            </Button>
+          </div>
+        </>
+      ) : (
+        <Button variant="outline" className="w-full text-xs" size="sm" onClick={handleChoose} disabled={busy}>
+          {busy ? <RefreshCw className="h-3 w-3 mr-1 animate-spin" /> : <FolderOpen className="h-3 w-3 mr-1" />}
+          Choose Vault Folder
+        </Button>
+      )}
+
+      {error && (
+        <div className="p-2.5 bg-destructive/10 border border-destructive/20 rounded-lg flex items-start gap-2">
+          <AlertCircle className="h-3.5 w-3.5 text-destructive mt-0.5 shrink-0" />
+          <p className="text-[10px] text-destructive">{error}</p>
+        </div>
+      )}
+
+      {result && (
+        <div className="p-2.5 bg-green-500/10 border border-green-500/20 rounded-lg space-y-1">
+          <div className="flex items-start gap-2">
+            <CheckCircle className="h-3.5 w-3.5 text-green-500 mt-0.5 shrink-0" />
+            <p className="text-[10px] text-green-600">{describe(result)}</p>
+          </div>
+          {result.conflicts.length > 0 && (
+            <ul className="max-h-24 overflow-y-auto pl-5 list-disc text-[9px] text-muted-foreground">
+              {result.conflicts.map((note, i) => <li key={i}>{note}</li>)}
+            </ul>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
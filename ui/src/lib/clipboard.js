@@ -19,7 +19,7 @@ export class ClipboardService {
       const entry = {
         content,
         source: metadata.source || 'system',
-        category: metadata.category || 'general',
+        category: metadata.category || null,
         created_at: new Date().toISOString(),
       }
 
@@ -38,11 +38,11 @@ export class ClipboardService {
   }
 
   /**
-   * Get clipboard history from local database
+   * Get clipboard history from local database, optionally only favorites
    */
-  async getClipboardHistory(limit = 100) {
+  async getClipboardHistory(limit = 100, favoritesOnly = false) {
     try {
-      const history = await invoke('get_clipboard_history', { limit })
+      const history = await invoke('get_clipboard_history', { limit, favoritesOnly })
       return history || []
     } catch (error) {
       console.error('Failed to get clipboard history:', error)
@@ -63,17 +63,67 @@ export class ClipboardService {
   }
 
   /**
-   * Clear all clipboard history
+   * Clear clipboard history (pinned and favorite entries are kept unless force is true)
    */
-  async clearClipboardHistory() {
+  async clearClipboardHistory(force = false) {
     try {
-      await invoke('clear_clipboard_history')
+      await invoke('clear_clipboard_history', { force })
     } catch (error) {
       console.error('Failed to clear clipboard history:', error)
       throw error
     }
   }
 
+  /**
+   * Pin or unpin a clipboard entry
+   */
+  async setPinned(entryId, pinned) {
+    try {
+      await invoke(pinned ? 'pin_clipboard_entry' : 'unpin_clipboard_entry', { id: entryId })
+    } catch (error) {
+      console.error('Failed to update pinned state:', error)
+      throw error
+    }
+  }
+
+  /**
+   * Mark or unmark a clipboard entry as a favorite
+   */
+  async setFavorite(entryId, favorite) {
+    try {
+      await invoke('set_clipboard_entry_favorite', { id: entryId, favorite })
+    } catch (error) {
+      console.error('Failed to update favorite state:', error)
+      throw error
+    }
+  }
+
+  /**
+   * Transform a clipboard entry. `transform` is a transform name, a `|`-separated
+   * chain or an array of names; `output` is 'preview', 'paste', 'paste_plain' or 'save'
+   */
+  async transformEntry(entryId, transform, output = 'preview') {
+    try {
+      return await invoke('transform_clipboard_entry', { id: entryId, transform, output })
+    } catch (error) {
+      console.error('Failed to transform clipboard entry:', error)
+      throw error
+    }
+  }
+
+  /**
+   * Queue entries for sequential pasting. Progress is reported through the
+   * `paste-queue-changed` event; the queue resets itself after `timeoutSecs` idle
+   */
+  async startPasteQueue(entryIds, timeoutSecs = 60) {
+    try {
+      return await invoke('start_paste_queue', { entryIds, timeoutSecs })
+    } catch (error) {
+      console.error('Failed to start paste queue:', error)
+      throw error
+    }
+  }
+
   /**
    * Convert clipboard entry to snippet
    */
@@ -136,7 +186,7 @@ export class ClipboardService {
     }
     
     const { syncService } = await import('./sync')
-    return await syncService.pushClipboardToCloud(userEmail)
+    return await syncService.syncClipboardAll(userEmail)
   }
 
   /**
@@ -148,7 +198,7 @@ export class ClipboardService {
     }
     
     const { syncService } = await import('./sync')
-    return await syncService.pullClipboardFromCloud(userEmail)
+    return await syncService.syncClipboardAll(userEmail)
   }
 
   /**
@@ -213,9 +263,9 @@ export class ClipboardService {
 
       console.log('💾 Saving new clipboard content...')
       // Save the new clipboard content
+      // Category is left to the backend classifier
       await this.saveClipboardEntry(clipboardText, {
         source: 'system',
-        category: this.categorizeContent(clipboardText),
       })
 
       console.log('✅ New clipboard content saved successfully')
@@ -225,28 +275,6 @@ export class ClipboardService {
       throw new Error('Failed to read system clipboard: ' + (error.message || 'Unknown error'))
     }
   }
-
-  /**
-   * Auto-categorize clipboard content
-   */
-  categorizeContent(content) {
-    if (content.startsWith('{') && content.endsWith('}')) {
-      return 'json'
-    }
-    if (content.startsWith('http://') || content.startsWith('https://')) {
-      return 'url'
-    }
-    if (content.includes('SELECT') || content.includes('INSERT') || content.includes('UPDATE')) {
-      return 'sql'
-    }
-    if (content.includes('{') || content.includes('}') || content.includes('function') || content.includes('const ')) {
-      return 'code'
-    }
-    if (content.includes('<') && content.includes('>')) {
-      return 'code'
-    }
-    return 'general'
-  }
 }
 
 // Export singleton instance
@@ -0,0 +1,45 @@
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+
+/**
+ * Text expansion settings for NoBrainDev
+ * Typing a snippet's trigger in any app replaces it with the snippet.
+ * App lists hold bundle IDs (macOS), window classes (Linux) or process names (Windows).
+ */
+export class ExpansionService {
+  /**
+   * Get { paused, allowed_apps, denied_apps }
+   */
+  async getSettings() {
+    try {
+      return await invoke('get_expansion_settings')
+    } catch (error) {
+      console.error('Failed to get text expansion settings:', error)
+      return { paused: false, allowed_apps: [], denied_apps: [] }
+    }
+  }
+
+  /**
+   * Save the pause flag and allow/deny lists
+   */
+  async setSettings(settings) {
+    return await invoke('set_expansion_settings', { settings })
+  }
+
+  /**
+   * Pause or resume expansion everywhere
+   */
+  async setPaused(paused) {
+    return await invoke('set_expansion_paused', { paused })
+  }
+
+  /**
+   * Called with the new settings whenever they change (including via the hotkey)
+   */
+  async onChange(callback) {
+    return await listen('text-expansion-changed', (event) => callback(event.payload))
+  }
+}
+
+// Export singleton instance
+export const expansionService = new ExpansionService()
@@ -0,0 +1,38 @@
+import { invoke } from '@tauri-apps/api/core'
+
+/**
+ * Global hotkey bindings for NoBrainDev
+ * Actions: clipboard_popup, snippet_search, paste_last_clip, capture_selection, toggle_expansion,
+ * paste_queue_next (only registered while a paste queue is running)
+ */
+export class HotkeyService {
+  /**
+   * Get every action with its shortcut and whether it is registered
+   */
+  async getBindings() {
+    try {
+      return await invoke('get_hotkey_bindings')
+    } catch (error) {
+      console.error('Failed to get hotkey bindings:', error)
+      return []
+    }
+  }
+
+  /**
+   * Rebind an action. Pass null to unbind it.
+   * Throws with a readable message when the shortcut conflicts or cannot be registered.
+   */
+  async setBinding(action, shortcut) {
+    return await invoke('set_hotkey_binding', { action, shortcut })
+  }
+
+  /**
+   * Restore the default shortcuts for this platform
+   */
+  async resetBindings() {
+    return await invoke('reset_hotkey_bindings')
+  }
+}
+
+// Export singleton instance
+export const hotkeyService = new HotkeyService()
@@ -2,7 +2,9 @@ import { supabase, isSupabaseConfigured } from './supabase'
 import { invoke } from '@tauri-apps/api/core'
 
 /**
- * Sync service for syncing local SQLite database with Supabase
+ * Sync service. Syncing itself happens in the Rust backend (see sync.rs),
+ * which pushes and pulls changes from the local journal; this only passes it
+ * the Supabase session and handles approval and sync keys.
  */
 export class SyncService {
   constructor() {
@@ -10,176 +12,6 @@ export class SyncService {
     this.lastSyncTime = null
   }
 
-  /**
-   * Push local snippets to Supabase
-   */
-  async pushToCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      // Get all local snippets
-      const localSnippets = await invoke('get_all_snippets')
-
-      if (!localSnippets || localSnippets.length === 0) {
-        console.log('No local snippets to sync')
-        return { pushed: 0, errors: 0 }
-      }
-
-      let pushed = 0
-      let errors = 0
-
-      // Push each snippet to Supabase
-      for (const snippet of localSnippets) {
-        try {
-          // Check if snippet already exists in cloud
-          const { data: existing, error: fetchError } = await supabase
-            .from('snippets')
-            .select('id, updated_at')
-            .eq('user_email', userEmail)
-            .eq('local_id', snippet.id)
-            .single()
-
-          if (fetchError && fetchError.code !== 'PGRST116') {
-            // PGRST116 = not found, which is okay
-            throw fetchError
-          }
-
-          const cloudSnippet = {
-            user_email: userEmail,
-            local_id: snippet.id,
-            title: snippet.title,
-            language: snippet.language,
-            tags: snippet.tags,
-            description: snippet.description,
-            content: snippet.content,
-            folder_id: snippet.folder_id,
-            created_at: snippet.created_at,
-            updated_at: snippet.updated_at
-          }
-
-          if (existing) {
-            // Update if local is newer
-            const localTime = new Date(snippet.updated_at).getTime()
-            const cloudTime = new Date(existing.updated_at).getTime()
-
-            if (localTime > cloudTime) {
-              const { error } = await supabase
-                .from('snippets')
-                .update(cloudSnippet)
-                .eq('id', existing.id)
-
-              if (error) throw error
-              pushed++
-            }
-          } else {
-            // Insert new snippet
-            const { error } = await supabase
-              .from('snippets')
-              .insert(cloudSnippet)
-
-            if (error) throw error
-            pushed++
-          }
-        } catch (error) {
-          console.error(`Failed to sync snippet ${snippet.id}:`, error)
-          errors++
-        }
-      }
-
-      this.lastSyncTime = new Date()
-      return { pushed, errors }
-    } catch (error) {
-      console.error('Push to cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Pull snippets from Supabase to local SQLite
-   */
-  async pullFromCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      // Get all cloud snippets for this user
-      const { data: cloudSnippets, error } = await supabase
-        .from('snippets')
-        .select('*')
-        .eq('user_email', userEmail)
-
-      if (error) throw error
-
-      if (!cloudSnippets || cloudSnippets.length === 0) {
-        console.log('No cloud snippets to sync')
-        return { pulled: 0, errors: 0 }
-      }
-
-      let pulled = 0
-      let errors = 0
-
-      // Get all local snippets to check for conflicts
-      const localSnippets = await invoke('get_all_snippets')
-      const localMap = new Map(localSnippets.map(s => [s.id, s]))
-
-      for (const cloudSnippet of cloudSnippets) {
-        try {
-          const localSnippet = localMap.get(cloudSnippet.local_id)
-
-          const snippet = {
-            title: cloudSnippet.title,
-            language: cloudSnippet.language,
-            tags: cloudSnippet.tags || '',
-            description: cloudSnippet.description || '',
-            content: cloudSnippet.content,
-            folder_id: cloudSnippet.folder_id,
-            created_at: cloudSnippet.created_at,
-            updated_at: cloudSnippet.updated_at
-          }
-
-          if (localSnippet) {
-            // Check which is newer
-            const localTime = new Date(localSnippet.updated_at).getTime()
-            const cloudTime = new Date(cloudSnippet.updated_at).getTime()
-
-            if (cloudTime > localTime) {
-              // Cloud is newer, update local
-              await invoke('update_snippet', {
-                id: cloudSnippet.local_id,
-                snippet
-              })
-              pulled++
-            }
-          } else {
-            // New snippet from cloud, create locally
-            await invoke('create_snippet', { snippet })
-            pulled++
-          }
-        } catch (error) {
-          console.error(`Failed to pull snippet ${cloudSnippet.id}:`, error)
-          errors++
-        }
-      }
-
-      this.lastSyncTime = new Date()
-      return { pulled, errors }
-    } catch (error) {
-      console.error('Pull from cloud failed:', error)
-      throw error
-    }
-  }
-
   /**
    * Check if user has sync approval and encryption status
    */
@@ -206,177 +38,6 @@ export class SyncService {
     }
   }
 
-  /**
-   * Push local folders to Supabase
-   */
-  async pushFoldersToCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      const localFolders = await invoke('get_all_folders')
-
-      if (!localFolders || localFolders.length === 0) {
-        console.log('No local folders to sync')
-        return { pushed: 0, errors: 0 }
-      }
-
-      let pushed = 0
-      let errors = 0
-
-      for (const folder of localFolders) {
-        try {
-          const { data: existing, error: fetchError } = await supabase
-            .from('folders')
-            .select('id, updated_at')
-            .eq('user_email', userEmail)
-            .eq('local_id', folder.id)
-            .single()
-
-          if (fetchError && fetchError.code !== 'PGRST116') {
-            throw fetchError
-          }
-
-          const cloudFolder = {
-            user_email: userEmail,
-            local_id: folder.id,
-            name: folder.name,
-            icon: folder.icon,
-            created_at: folder.created_at,
-            updated_at: folder.updated_at
-          }
-
-          if (existing) {
-            const localTime = new Date(folder.updated_at).getTime()
-            const cloudTime = new Date(existing.updated_at).getTime()
-
-            if (localTime > cloudTime) {
-              const { error } = await supabase
-                .from('folders')
-                .update(cloudFolder)
-                .eq('id', existing.id)
-
-              if (error) throw error
-              pushed++
-            }
-          } else {
-            const { error } = await supabase
-              .from('folders')
-              .insert(cloudFolder)
-
-            if (error) throw error
-            pushed++
-          }
-        } catch (error) {
-          console.error(`Failed to sync folder ${folder.id}:`, error)
-          errors++
-        }
-      }
-
-      return { pushed, errors }
-    } catch (error) {
-      console.error('Push folders to cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Pull folders from Supabase to local
-   */
-  async pullFoldersFromCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      const { data: cloudFolders, error } = await supabase
-        .from('folders')
-        .select('*')
-        .eq('user_email', userEmail)
-
-      if (error) throw error
-
-      if (!cloudFolders || cloudFolders.length === 0) {
-        console.log('No cloud folders to sync')
-        return { pulled: 0, errors: 0 }
-      }
-
-      let pulled = 0
-      let errors = 0
-
-      const localFolders = await invoke('get_all_folders')
-      const localMap = new Map(localFolders.map(f => [f.id, f]))
-
-      for (const cloudFolder of cloudFolders) {
-        try {
-          const localFolder = localMap.get(cloudFolder.local_id)
-
-          if (localFolder) {
-            const localTime = new Date(localFolder.updated_at).getTime()
-            const cloudTime = new Date(cloudFolder.updated_at).getTime()
-
-            if (cloudTime > localTime) {
-              await invoke('update_folder', {
-                id: cloudFolder.local_id,
-                name: cloudFolder.name,
-                icon: cloudFolder.icon
-              })
-              pulled++
-            }
-          } else {
-            // Create folder locally - we'll skip this for now as it requires special handling
-            console.log('New folder from cloud - needs special handling:', cloudFolder.name)
-          }
-        } catch (error) {
-          console.error(`Failed to pull folder ${cloudFolder.id}:`, error)
-          errors++
-        }
-      }
-
-      return { pulled, errors }
-    } catch (error) {
-      console.error('Pull folders from cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Delete snippet from cloud by local_id
-   */
-  async deleteFromCloud(userEmail, localId) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for deletion')
-    }
-
-    try {
-      const { error } = await supabase
-        .from('snippets')
-        .delete()
-        .eq('user_email', userEmail)
-        .eq('local_id', localId)
-
-      if (error) throw error
-
-      return { deleted: true, timestamp: new Date() }
-    } catch (error) {
-      console.error(`Failed to delete snippet ${localId} from cloud:`, error)
-      throw error
-    }
-  }
-
   /**
    * Request sync approval
    */
@@ -408,7 +69,29 @@ export class SyncService {
   }
 
   /**
-   * Full two-way sync
+   * Where the backend syncs to: the signed-in user's Supabase project
+   */
+  async getServer(userEmail) {
+    const { data: { session }, error } = await supabase.auth.getSession()
+    if (error) throw error
+    if (!session) {
+      throw new Error('Not signed in')
+    }
+
+    return {
+      kind: 'supabase',
+      url: import.meta.env.VITE_SUPABASE_URL,
+      anon_key: import.meta.env.VITE_SUPABASE_ANON_KEY,
+      access_token: session.access_token,
+      user_id: session.user.id,
+      user_email: userEmail
+    }
+  }
+
+  /**
+   * Full two-way sync of snippets, folders, clipboard history and files.
+   * Only changes since the last sync are pushed and pulled; when two devices
+   * edit the same item, the losing edit is kept as a conflicted copy.
    */
   async syncAll(userEmail) {
     if (this.isSyncing) {
@@ -421,700 +104,113 @@ export class SyncService {
     if (!approval || !approval.approved) {
       throw new Error('SYNC_NOT_APPROVED')
     }
+    await this.requireEncryption()
 
     this.isSyncing = true
 
     try {
       console.log('Starting sync for:', userEmail)
+      const server = await this.getServer(userEmail)
+      const report = await invoke('sync_library', { server })
+      console.log('Sync result:', report)
 
-      // Sync folders FIRST (since snippets depend on them)
-      const foldersPush = await this.pushFoldersToCloud(userEmail)
-      const foldersPull = await this.pullFoldersFromCloud(userEmail)
-      console.log('Folders sync result:', { push: foldersPush, pull: foldersPull })
-
-      // Then sync snippets
-      const pushResult = await this.pushToCloud(userEmail)
-      console.log('Push result:', pushResult)
-
-      const pullResult = await this.pullFromCloud(userEmail)
-      console.log('Pull result:', pullResult)
-
-      this.lastSyncTime = new Date()
+      this.lastSyncTime = new Date(report.synced_at)
 
       return {
-        foldersPushed: foldersPush.pushed,
-        foldersPulled: foldersPull.pulled,
-        pushed: pushResult.pushed,
-        pulled: pullResult.pulled,
-        errors: foldersPush.errors + foldersPull.errors + pushResult.errors + pullResult.errors,
+        pushed: report.pushed,
+        pulled: report.pulled,
+        conflicts: report.conflicts,
+        // Copied from the previous sync's tables, on the first sync
+        imported: report.imported,
         syncTime: this.lastSyncTime
       }
     } catch (error) {
       console.error('Sync failed:', error)
-      throw error
+      throw typeof error === 'string' ? new Error(error) : error
     } finally {
       this.isSyncing = false
     }
   }
 
   /**
-   * Push local clipboard history to Supabase
-   */
-  async pushClipboardToCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      // Get all local clipboard entries
-      const localClipboard = await invoke('get_clipboard_history', { limit: 1000 })
-
-      if (!localClipboard || localClipboard.length === 0) {
-        console.log('No local clipboard entries to sync')
-        return { pushed: 0, updated: 0, errors: 0 }
-      }
-
-      let pushed = 0
-      let updated = 0
-      let errors = 0
-
-      // Push each clipboard entry to Supabase
-      for (const entry of localClipboard) {
-        try {
-          // Check if entry already exists in cloud
-          const { data: existing, error: fetchError } = await supabase
-            .from('clipboard_history')
-            .select('id, updated_at')
-            .eq('user_email', userEmail)
-            .eq('local_id', entry.id)
-            .single()
-
-          if (fetchError && fetchError.code !== 'PGRST116') {
-            throw fetchError
-          }
-
-          const cloudEntry = {
-            user_email: userEmail,
-            local_id: entry.id,
-            content: entry.content,
-            source: entry.source || 'system',
-            category: entry.category || 'general',
-            created_at: entry.created_at,
-            updated_at: entry.updated_at || entry.created_at
-          }
-
-          if (existing) {
-            // Update if local is newer
-            const localTime = new Date(entry.updated_at || entry.created_at).getTime()
-            const cloudTime = new Date(existing.updated_at).getTime()
-
-            if (localTime > cloudTime) {
-              // Don't include user_email in update to avoid RLS ambiguity
-              const updateData = {
-                content: entry.content,
-                source: entry.source || 'system',
-                category: entry.category || 'general',
-                updated_at: entry.updated_at || entry.created_at
-              }
-              
-              const { error } = await supabase
-                .from('clipboard_history')
-                .update(updateData)
-                .eq('id', existing.id)
-
-              if (error) throw error
-              updated++
-            }
-          } else {
-            // Insert new entry
-            const { error } = await supabase
-              .from('clipboard_history')
-              .insert(cloudEntry)
-
-            if (error) throw error
-            pushed++
-          }
-        } catch (error) {
-          console.error(`Failed to sync clipboard entry ${entry.id}:`, error)
-          errors++
-        }
-      }
-
-      return { pushed, updated, errors }
-    } catch (error) {
-      console.error('Push clipboard to cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Pull clipboard history from Supabase to local SQLite
-   */
-  async pullClipboardFromCloud(userEmail) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      // Get all cloud clipboard entries for this user
-      const { data: cloudEntries, error } = await supabase
-        .from('clipboard_history')
-        .select('*')
-        .eq('user_email', userEmail)
-        .order('created_at', { ascending: false })
-
-      if (error) throw error
-
-      if (!cloudEntries || cloudEntries.length === 0) {
-        console.log('No cloud clipboard entries to sync')
-        return { pulled: 0, errors: 0 }
-      }
-
-      let pulled = 0
-      let errors = 0
-
-      // Get all local clipboard entries to check for conflicts
-      const localEntries = await invoke('get_clipboard_history', { limit: 10000 })
-      const localMap = new Map(localEntries.map(e => [e.id, e]))
-
-      for (const cloudEntry of cloudEntries) {
-        try {
-          const localEntry = localMap.get(cloudEntry.local_id)
-
-          if (localEntry) {
-            // Check which is newer
-            const localTime = new Date(localEntry.updated_at || localEntry.created_at).getTime()
-            const cloudTime = new Date(cloudEntry.updated_at).getTime()
-
-            if (cloudTime > localTime) {
-              // Cloud is newer, update local
-              await invoke('update_clipboard_entry', {
-                id: cloudEntry.local_id,
-                content: cloudEntry.content,
-                source: cloudEntry.source || 'system',
-                category: cloudEntry.category || 'general',
-                updatedAt: cloudEntry.updated_at
-              })
-              pulled++
-            }
-          } else {
-            // New entry from cloud, create locally
-            await invoke('save_clipboard_entry', {
-              content: cloudEntry.content,
-              source: cloudEntry.source || 'system',
-              category: cloudEntry.category || 'general',
-              createdAt: cloudEntry.created_at
-            })
-            pulled++
-          }
-        } catch (error) {
-          console.error(`Failed to pull clipboard entry ${cloudEntry.id}:`, error)
-          errors++
-        }
-      }
-
-      return { pulled, errors }
-    } catch (error) {
-      console.error('Pull clipboard from cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Full two-way sync for clipboard
+   * Clipboard history syncs with the rest of the library
    */
   async syncClipboardAll(userEmail) {
-    // Check approval first
-    const approval = await this.checkSyncApproval(userEmail)
-    if (!approval || !approval.approved) {
-      throw new Error('SYNC_NOT_APPROVED')
-    }
-
-    try {
-      console.log('Starting clipboard sync for:', userEmail)
-
-      // First push local changes
-      const pushResult = await this.pushClipboardToCloud(userEmail)
-      console.log('Clipboard push result:', pushResult)
-
-      // Then pull cloud changes
-      const pullResult = await this.pullClipboardFromCloud(userEmail)
-      console.log('Clipboard pull result:', pullResult)
-
-      return {
-        pushed: pushResult.pushed,
-        updated: pushResult.updated,
-        pulled: pullResult.pulled,
-        errors: pushResult.errors + pullResult.errors,
-        syncTime: new Date()
-      }
-    } catch (error) {
-      console.error('Clipboard sync failed:', error)
-      throw error
-    }
+    return await this.syncAll(userEmail)
   }
 
   /**
-   * Delete clipboard entry from cloud by local_id
+   * Files sync with the rest of the library
    */
-  async deleteClipboardFromCloud(userEmail, localId) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for deletion')
-    }
-
-    try {
-      const { error } = await supabase
-        .from('clipboard_history')
-        .delete()
-        .eq('user_email', userEmail)
-        .eq('local_id', localId)
-
-      if (error) throw error
-
-      return { deleted: true, timestamp: new Date() }
-    } catch (error) {
-      console.error(`Failed to delete clipboard entry ${localId} from cloud:`, error)
-      throw error
-    }
+  async syncFilesAll(userEmail) {
+    return await this.syncAll(userEmail)
   }
 
   /**
-   * Upload file to Supabase Storage
+   * Local changes waiting to be pushed
    */
-  async uploadFileToStorage(userId, fileData, fileName, mimeType) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    try {
-      console.log('📤 Uploading file to storage:', { userId, fileName, mimeType, fileSize: fileData.length })
-
-      // Validate file size (Supabase Storage limit is typically 50MB for free tier)
-      const MAX_FILE_SIZE = 50 * 1024 * 1024 // 50MB
-      if (fileData.length > MAX_FILE_SIZE) {
-        throw new Error(`File size ${(fileData.length / 1024 / 1024).toFixed(2)}MB exceeds maximum allowed size of 50MB`)
-      }
-
-      // Use user ID as folder name for better organization
-      const filePath = `${userId}/${fileName}`
-
-      console.log('📁 Storage path:', filePath)
-
-      // Ensure fileData is the correct format (Uint8Array or ArrayBuffer)
-      let uploadData = fileData
-      if (fileData instanceof ArrayBuffer) {
-        uploadData = new Uint8Array(fileData)
-      } else if (!Array.isArray(fileData) && !(fileData instanceof Uint8Array)) {
-        throw new Error('Invalid file data format. Expected Uint8Array or ArrayBuffer.')
-      }
-
-      const { data, error } = await supabase
-        .storage
-        .from('user-files')
-        .upload(filePath, uploadData, {
-          contentType: mimeType || 'application/octet-stream',
-          upsert: true,
-          cacheControl: '3600'
-        })
-
-      if (error) {
-        console.error('❌ Storage upload error:', error)
-        // Provide more helpful error messages
-        if (error.message?.includes('Payload too large')) {
-          throw new Error('File size exceeds Supabase Storage limit (max 50MB)')
-        } else if (error.message?.includes('Bucket not found')) {
-          throw new Error('Storage bucket "user-files" not found. Please create it in Supabase dashboard.')
-        } else if (error.message?.includes('Invalid key')) {
-          throw new Error('Invalid file path or bucket configuration.')
-        }
-        throw error
-      }
-
-      console.log('✅ File uploaded successfully:', filePath)
-      return filePath
-    } catch (error) {
-      console.error('❌ Failed to upload file to storage:', error)
-      throw new Error(`Failed to upload file: ${error.message}`)
-    }
+  async getSyncStatus() {
+    return await invoke('get_sync_status')
   }
 
   /**
-   * Download file from Supabase Storage
+   * Nothing is synced until this device has the sync keys, so the server
+   * only ever sees ciphertext
    */
-  async downloadFileFromStorage(userId, cloudPath) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    try {
-      console.log('📥 Downloading file from storage:', cloudPath)
-
-      const { data, error } = await supabase
-        .storage
-        .from('user-files')
-        .download(cloudPath)
-
-      if (error) {
-        console.error('❌ Storage download error:', error)
-        throw error
-      }
-
-      console.log('✅ File downloaded successfully:', cloudPath)
-      return data
-    } catch (error) {
-      console.error('❌ Failed to download file from storage:', error)
-      throw new Error(`Failed to download file: ${error.message}`)
+  async requireEncryption() {
+    const status = await invoke('get_sync_encryption_status')
+    if (!status.unlocked) {
+      throw new Error('SYNC_ENCRYPTION_LOCKED')
     }
   }
 
   /**
-   * Delete file from Supabase Storage
+   * Get the user's sync key bundle from Supabase. It's encrypted with their
+   * passphrase (and recovery key), so the server can't read it.
    */
-  async deleteFileFromStorage(userId, cloudPath) {
+  async fetchKeyBundle(userEmail) {
     if (!isSupabaseConfigured()) {
       throw new Error('Supabase not configured')
     }
 
-    try {
-      console.log('🗑️  Deleting file from storage:', cloudPath)
-
-      const { error } = await supabase
-        .storage
-        .from('user-files')
-        .remove([cloudPath])
-
-      if (error) {
-        console.error('❌ Storage delete error:', error)
-        throw error
-      }
+    const { data, error } = await supabase
+      .from('sync_keys')
+      .select('bundle')
+      .eq('user_email', userEmail)
+      .single()
 
-      console.log('✅ File deleted successfully:', cloudPath)
-      return { deleted: true }
-    } catch (error) {
-      console.error('❌ Failed to delete file from storage:', error)
-      throw new Error(`Failed to delete file: ${error.message}`)
-    }
+    if (error && error.code !== 'PGRST116') throw error
+    return data?.bundle || null
   }
 
   /**
-   * Push local files to Supabase
+   * Store the user's sync key bundle in Supabase for their other devices
    */
-  async pushFilesToCloud(userEmail, userId) {
+  async uploadKeyBundle(userEmail, bundle) {
     if (!isSupabaseConfigured()) {
       throw new Error('Supabase not configured')
     }
 
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      console.log('📤 Getting local files...')
-      const localFiles = await invoke('get_all_files')
-
-      if (!localFiles || localFiles.length === 0) {
-        console.log('✅ No local files to sync')
-        return { pushed: 0, errors: 0 }
-      }
-
-      console.log(`📦 Found ${localFiles.length} local files to sync`)
-
-      let pushed = 0
-      let errors = 0
-
-      for (const file of localFiles) {
-        try {
-          console.log(`📄 Processing file: ${file.filename} (ID: ${file.id})`)
-
-          // Get file data from local storage
-          const [fileName, fileData, mimeType] = await invoke('download_file', { id: file.id })
+    const { error } = await supabase
+      .from('sync_keys')
+      .upsert({
+        user_email: userEmail,
+        bundle,
+        updated_at: new Date().toISOString()
+      }, {
+        onConflict: 'user_email'
+      })
 
-          console.log(`📥 Retrieved file data: ${fileName}, size: ${fileData.length} bytes`)
-
-          // Convert array from Rust to Uint8Array for Supabase
-          const fileDataUint8Array = new Uint8Array(fileData)
-
-          // Upload to Supabase Storage
-          const cloudPath = await this.uploadFileToStorage(userId, fileDataUint8Array, fileName, mimeType)
-
-          // Check if file already exists in database
-          const { data: existing, error: fetchError } = await supabase
-            .from('files')
-            .select('id, updated_at, cloud_storage_path')
-            .eq('user_email', userEmail)
-            .eq('local_id', file.id)
-            .single()
-
-          if (fetchError && fetchError.code !== 'PGRST116') {
-            throw fetchError
-          }
-
-          const cloudFile = {
-            user_email: userEmail,
-            local_id: file.id,
-            filename: file.filename,
-            file_type: file.file_type,
-            file_size: file.file_size,
-            folder_id: file.folder_id,
-            cloud_storage_path: cloudPath,
-            mime_type: file.mime_type,
-            description: file.description,
-            tags: file.tags,
-            created_at: file.created_at,
-            updated_at: file.updated_at
-          }
-
-          if (existing) {
-            console.log(`🔄 File exists in cloud, checking if update needed...`)
-            const localTime = new Date(file.updated_at).getTime()
-            const cloudTime = new Date(existing.updated_at).getTime()
-
-            if (localTime > cloudTime) {
-              console.log(`⬆️  Updating file in cloud: ${file.filename}`)
-              const { error } = await supabase
-                .from('files')
-                .update(cloudFile)
-                .eq('id', existing.id)
-
-              if (error) throw error
-
-              // Update local database with cloud storage path
-              try {
-                await invoke('update_file_cloud_path', {
-                  id: file.id,
-                  cloudStoragePath: cloudPath
-                })
-                console.log(`✅ Updated local cloud path for: ${file.filename}`)
-              } catch (localUpdateError) {
-                console.warn(`⚠️  Failed to update local cloud path:`, localUpdateError)
-              }
-
-              pushed++
-              console.log(`✅ File updated: ${file.filename}`)
-            } else {
-              console.log(`⏭️  Skipping ${file.filename} (cloud is newer or same)`)
-            }
-          } else {
-            console.log(`➕ Inserting new file to cloud: ${file.filename}`)
-            const { error } = await supabase
-              .from('files')
-              .insert(cloudFile)
-
-            if (error) throw error
-
-            // Update local database with cloud storage path
-            try {
-              await invoke('update_file_cloud_path', {
-                id: file.id,
-                cloudStoragePath: cloudPath
-              })
-              console.log(`✅ Updated local cloud path for: ${file.filename}`)
-            } catch (localUpdateError) {
-              console.warn(`⚠️  Failed to update local cloud path:`, localUpdateError)
-            }
-
-            pushed++
-            console.log(`✅ File inserted: ${file.filename}`)
-          }
-        } catch (error) {
-          console.error(`❌ Failed to sync file ${file.id} (${file.filename}):`, error)
-          errors++
-        }
-      }
-
-      console.log(`📊 Push files complete: ${pushed} pushed, ${errors} errors`)
-      return { pushed, errors }
-    } catch (error) {
-      console.error('❌ Push files to cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Pull files from Supabase to local
-   */
-  async pullFilesFromCloud(userEmail, userId) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for sync')
-    }
-
-    try {
-      console.log('📥 Getting cloud files...')
-      const { data: cloudFiles, error } = await supabase
-        .from('files')
-        .select('*')
-        .eq('user_email', userEmail)
-
-      if (error) throw error
-
-      if (!cloudFiles || cloudFiles.length === 0) {
-        console.log('✅ No cloud files to sync')
-        return { pulled: 0, errors: 0 }
-      }
-
-      console.log(`📦 Found ${cloudFiles.length} cloud files to sync`)
-
-      let pulled = 0
-      let errors = 0
-
-      const localFiles = await invoke('get_all_files')
-      const localMap = new Map(localFiles.map(f => [f.id, f]))
-
-      for (const cloudFile of cloudFiles) {
-        try {
-          console.log(`📄 Processing cloud file: ${cloudFile.filename} (local_id: ${cloudFile.local_id})`)
-
-          const localFile = localMap.get(cloudFile.local_id)
-
-          if (!localFile) {
-            // New file from cloud - download file data
-            if (cloudFile.cloud_storage_path) {
-              console.log(`📥 Downloading new file from cloud: ${cloudFile.filename}`)
-              const fileData = await this.downloadFileFromStorage(userId, cloudFile.cloud_storage_path)
-
-              // Create file locally
-              const fileName = cloudFile.filename
-              const fileArrayBuffer = await fileData.arrayBuffer()
-              const fileBytes = new Uint8Array(fileArrayBuffer)
-
-              await invoke('upload_file', {
-                filename: fileName,
-                fileData: Array.from(fileBytes),
-                folderId: cloudFile.folder_id,
-                description: cloudFile.description,
-                tags: cloudFile.tags
-              })
-              pulled++
-              console.log(`✅ Downloaded and created: ${cloudFile.filename}`)
-            } else {
-              console.log(`⚠️  Skipping ${cloudFile.filename} (no cloud storage path)`)
-            }
-          } else {
-            // Check which is newer
-            const localTime = new Date(localFile.updated_at).getTime()
-            const cloudTime = new Date(cloudFile.updated_at).getTime()
-
-            if (cloudTime > localTime) {
-              console.log(`🔄 Updating metadata for: ${cloudFile.filename}`)
-              // Update metadata (we don't update the actual file content to avoid conflicts)
-              await invoke('update_file', {
-                id: cloudFile.local_id,
-                filename: cloudFile.filename,
-                description: cloudFile.description,
-                tags: cloudFile.tags,
-                folderId: cloudFile.folder_id
-              })
-              pulled++
-              console.log(`✅ Updated metadata: ${cloudFile.filename}`)
-            } else {
-              console.log(`⏭️  Skipping ${cloudFile.filename} (local is newer or same)`)
-            }
-          }
-        } catch (error) {
-          console.error(`❌ Failed to pull file ${cloudFile.id} (${cloudFile.filename}):`, error)
-          errors++
-        }
-      }
-
-      console.log(`📊 Pull files complete: ${pulled} pulled, ${errors} errors`)
-      return { pulled, errors }
-    } catch (error) {
-      console.error('❌ Pull files from cloud failed:', error)
-      throw error
-    }
-  }
-
-  /**
-   * Delete file from cloud by local_id
-   */
-  async deleteFileFromCloud(userEmail, userId, localId) {
-    if (!isSupabaseConfigured()) {
-      throw new Error('Supabase not configured')
-    }
-
-    if (!userEmail) {
-      throw new Error('User email required for deletion')
-    }
-
-    try {
-      // Get file record to get cloud storage path
-      const { data: fileRecord, error: fetchError } = await supabase
-        .from('files')
-        .select('cloud_storage_path')
-        .eq('user_email', userEmail)
-        .eq('local_id', localId)
-        .single()
-
-      if (fetchError) throw fetchError
-
-      // Delete from storage if path exists
-      if (fileRecord?.cloud_storage_path) {
-        await this.deleteFileFromStorage(userId, fileRecord.cloud_storage_path)
-      }
-
-      // Delete from database
-      const { error } = await supabase
-        .from('files')
-        .delete()
-        .eq('user_email', userEmail)
-        .eq('local_id', localId)
-
-      if (error) throw error
-
-      return { deleted: true, timestamp: new Date() }
-    } catch (error) {
-      console.error(`Failed to delete file ${localId} from cloud:`, error)
-      throw error
-    }
+    if (error) throw error
   }
 
   /**
-   * Full two-way sync for files
+   * Upload everything again with the current sync key. Used after setting up
+   * encryption and after rotating the key.
    */
-  async syncFilesAll(userEmail, userId) {
-    // Check approval first
-    const approval = await this.checkSyncApproval(userEmail)
-    if (!approval || !approval.approved) {
-      throw new Error('SYNC_NOT_APPROVED')
-    }
-
-    try {
-      console.log('Starting files sync for:', userEmail)
-
-      // First push local changes
-      const pushResult = await this.pushFilesToCloud(userEmail, userId)
-      console.log('Files push result:', pushResult)
-
-      // Then pull cloud changes
-      const pullResult = await this.pullFilesFromCloud(userEmail, userId)
-      console.log('Files pull result:', pullResult)
-
-      return {
-        pushed: pushResult.pushed,
-        pulled: pullResult.pulled,
-        errors: pushResult.errors + pullResult.errors,
-        syncTime: new Date()
-      }
-    } catch (error) {
-      console.error('Files sync failed:', error)
-      throw error
-    }
+  async reencryptAll(userEmail) {
+    await invoke('resync_library')
+    return await this.syncAll(userEmail)
   }
 
   /**
@@ -5,3 +5,35 @@ export function cn(...inputs) {
   return twMerge(clsx(inputs))
 }
 
+
+// "Infra / Kubernetes / Helm" - folder names repeat under different parents
+export function folderPath(folders, folder) {
+  const byId = new Map(folders.map(f => [f.id, f]))
+  const names = [folder.name]
+  const seen = new Set([folder.id])
+  let parent = byId.get(folder.parent_id)
+  while (parent && !seen.has(parent.id)) {
+    names.unshift(parent.name)
+    seen.add(parent.id)
+    parent = byId.get(parent.parent_id)
+  }
+  return names.join(' / ')
+}
+
+// Split `tag:name` words out of a search query (tags match exactly, ignoring case)
+export function splitTagFilters(query) {
+  const words = query.trim().split(/\s+/).filter(Boolean)
+  return {
+    text: words.filter(w => !w.startsWith('tag:')).join(' '),
+    tags: words.filter(w => w.startsWith('tag:')).map(w => w.slice(4).toLowerCase()).filter(Boolean),
+  }
+}
+
+export function parseTags(tags) {
+  return (tags || '').split(',').map(t => t.trim().replace(/^#/, '')).filter(Boolean)
+}
+
+export function hasAllTags(item, tags) {
+  const own = parseTags(item.tags).map(t => t.toLowerCase())
+  return tags.every(tag => own.includes(tag))
+}
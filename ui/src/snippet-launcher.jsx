@@ -0,0 +1,21 @@
+import React from 'react'
+import ReactDOM from 'react-dom/client'
+import { SnippetLauncher } from './components/ui/snippetlauncher'
+import './index.css'
+import './tiptap.css'
+import { invoke } from '@tauri-apps/api/core'
+
+// Mount the SnippetLauncher component
+ReactDOM.createRoot(document.getElementById('root')).render(
+  <React.StrictMode>
+    <SnippetLauncher
+      onClose={() => {
+        // Hide the launcher window when closed
+        invoke('hide_snippet_launcher').catch(console.error)
+      }}
+    />
+  </React.StrictMode>,
+)
+
+// Log when the launcher window is ready
+console.log('⚡ Snippet launcher window ready')